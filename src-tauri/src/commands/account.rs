@@ -1,12 +1,15 @@
 use crate::error::{AppError, AppResult};
 use crate::models;
 use crate::modules;
+use serde::Serialize;
 use tauri::AppHandle;
 use tauri::Emitter;
 
 #[tauri::command]
-pub async fn list_accounts() -> Result<Vec<models::Account>, String> {
-    modules::list_accounts()
+pub async fn list_accounts(
+    filter: Option<models::AccountFilter>,
+) -> Result<Vec<models::Account>, String> {
+    modules::list_accounts_filtered(filter.as_ref())
 }
 
 /// 从插件共享目录同步账号（credentials.json）
@@ -70,43 +73,88 @@ pub async fn set_current_account(app: tauri::AppHandle, account_id: String) -> R
     Ok(())
 }
 
+/// 刷新单个账号配额；timeout_ms 默认 15000，超时返回 `QUOTA_TIMEOUT:` 前缀的错误
 #[tauri::command]
-pub async fn fetch_account_quota(account_id: String) -> AppResult<models::QuotaData> {
+pub async fn fetch_account_quota(
+    account_id: String,
+    timeout_ms: Option<u64>,
+) -> AppResult<models::QuotaData> {
     let mut account = modules::load_account(&account_id).map_err(AppError::Account)?;
-    let quota = modules::fetch_quota_with_retry(&mut account, true).await?;
+    let timeout_ms = timeout_ms.unwrap_or(modules::quota::DEFAULT_QUOTA_TIMEOUT_MS);
+    let quota = modules::account::fetch_quota_with_timeout(&mut account, true, timeout_ms).await?;
     modules::update_account_quota(&account_id, quota.clone()).map_err(AppError::Account)?;
     Ok(quota)
 }
 
+/// 批量刷新所有账号配额，返回每个账号的刷新结果
+#[tauri::command]
+pub async fn refresh_all_quotas_detailed(
+    app: tauri::AppHandle,
+) -> Result<Vec<modules::account::AccountQuotaRefreshResult>, String> {
+    crate::modules::fault_injection::check("refresh")?;
+    let results = modules::account::refresh_all_quotas_detailed_logic().await?;
+    if let Err(e) = modules::account::run_quota_alert_if_needed() {
+        modules::logger::log_warn(&format!("[QuotaAlert] 预警检查失败: {}", e));
+    }
+    let _ = crate::modules::tray::update_tray_menu(&app);
+    Ok(results)
+}
+
+/// 仅刷新某个分组（账号标签）内的账号配额
+#[tauri::command]
+pub async fn refresh_group_quotas(
+    app: tauri::AppHandle,
+    group_id: String,
+) -> Result<modules::account::GroupRefreshSummary, String> {
+    crate::modules::fault_injection::check("refresh")?;
+    let summary = modules::account::refresh_group_quotas_logic(&group_id).await?;
+    if summary.success > 0 {
+        if let Err(e) = modules::account::run_quota_alert_if_needed() {
+            modules::logger::log_warn(&format!("[QuotaAlert] 预警检查失败: {}", e));
+        }
+        let _ = crate::modules::tray::update_tray_menu(&app);
+    }
+    Ok(summary)
+}
+
+#[tauri::command]
+pub fn cancel_group_quota_refresh(group_id: String) -> bool {
+    modules::account::cancel_group_quota_refresh(&group_id)
+}
+
 #[tauri::command]
 pub async fn refresh_all_quotas(
     app: tauri::AppHandle,
 ) -> Result<modules::account::RefreshStats, String> {
+    crate::modules::fault_injection::check("refresh")?;
+    let _guard = modules::quota_refresh_scheduler::try_begin_refresh()
+        .ok_or_else(|| "配额刷新正在进行中，请稍后再试".to_string())?;
     let result = modules::account::refresh_all_quotas_logic().await;
     if result.is_ok() {
-        let mut switched = false;
-        match modules::account::run_auto_switch_if_needed().await {
-            Ok(Some(account)) => {
-                modules::logger::log_info(&format!("[AutoSwitch] 自动切号完成: {}", account.email));
-                switched = true;
-            }
-            Ok(None) => {}
-            Err(e) => {
-                modules::logger::log_warn(&format!("[AutoSwitch] 自动切号执行失败: {}", e));
-            }
-        }
-        if !switched {
-            if let Err(e) = modules::account::run_quota_alert_if_needed() {
-                modules::logger::log_warn(&format!("[QuotaAlert] 预警检查失败: {}", e));
-            }
-        }
+        modules::account::run_post_batch_refresh_hooks().await;
         let _ = crate::modules::tray::update_tray_menu(&app);
     }
     result
 }
 
+/// 获取账号的配额历史快照（按时间正序，最多保留最近 90 条）
+#[tauri::command]
+pub async fn get_quota_history(
+    account_id: String,
+    limit: Option<usize>,
+) -> Result<Vec<modules::quota_cache::QuotaSnapshot>, String> {
+    modules::quota_cache::get_quota_history(&account_id, limit)
+}
+
+/// 扫描账号文件修复账号索引（先备份原索引，索引正常时不做修改）
+#[tauri::command]
+pub async fn repair_account_index() -> Result<modules::account::RepairReport, String> {
+    modules::account::repair_account_index()
+}
+
 #[tauri::command]
 pub async fn refresh_current_quota(app: tauri::AppHandle) -> Result<(), String> {
+    crate::modules::fault_injection::check("refresh")?;
     let Some(account) = modules::get_current_account().map_err(|e| e.to_string())? else {
         return Err("未找到当前账号".to_string());
     };
@@ -141,9 +189,41 @@ pub async fn refresh_current_quota(app: tauri::AppHandle) -> Result<(), String>
     Ok(())
 }
 
+/// 切换账号结果：账号信息 + 本次实际应用的指纹 ID（未应用时为空）
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SwitchAccountResult {
+    #[serde(flatten)]
+    pub account: models::Account,
+    pub applied_fingerprint_id: Option<String>,
+}
+
+/// 将账号绑定的指纹写入 storage.json，返回成功应用的指纹 ID
+fn apply_account_fingerprint(account: &models::Account) -> Option<String> {
+    let storage_path = modules::device::get_storage_path().ok()?;
+    let fp_id = account.fingerprint_id.as_ref()?;
+    let fingerprint = modules::fingerprint::get_fingerprint(fp_id).ok()?;
+    modules::logger::log_info(&format!(
+        "写入设备指纹: machineId={}, serviceMachineId={}",
+        fingerprint.profile.machine_id, fingerprint.profile.service_machine_id
+    ));
+    if let Err(e) = modules::device::write_profile(&storage_path, &fingerprint.profile) {
+        modules::logger::log_warn(&format!("写入设备指纹失败: {}", e));
+        return None;
+    }
+    let _ = modules::db::write_service_machine_id(&fingerprint.profile.service_machine_id);
+    // 更新当前应用的指纹ID
+    let _ = modules::fingerprint::set_current_fingerprint_id(fp_id);
+    Some(fp_id.clone())
+}
+
 /// 切换账号（完整流程：Token刷新 + 关闭程序 + 注入 + 指纹同步 + 重启）
 #[tauri::command]
-pub async fn switch_account(app: AppHandle, account_id: String) -> Result<models::Account, String> {
+pub async fn switch_account(
+    app: AppHandle,
+    account_id: String,
+) -> Result<SwitchAccountResult, String> {
+    crate::modules::fault_injection::check("switch")?;
     modules::logger::log_info(&format!("开始切换账号: {}", account_id));
 
     // 1. 加载并验证账号存在
@@ -165,23 +245,14 @@ pub async fn switch_account(app: AppHandle, account_id: String) -> Result<models
         modules::save_account(&account)?;
     }
 
-    // 3. 写入设备指纹到 storage.json
-    if let Ok(storage_path) = modules::device::get_storage_path() {
-        if let Some(ref fp_id) = account.fingerprint_id {
-            // 优先使用绑定的指纹
-            if let Ok(fingerprint) = modules::fingerprint::get_fingerprint(fp_id) {
-                modules::logger::log_info(&format!(
-                    "写入设备指纹: machineId={}, serviceMachineId={}",
-                    fingerprint.profile.machine_id, fingerprint.profile.service_machine_id
-                ));
-                let _ = modules::device::write_profile(&storage_path, &fingerprint.profile);
-                let _ =
-                    modules::db::write_service_machine_id(&fingerprint.profile.service_machine_id);
-                // 更新当前应用的指纹ID
-                let _ = modules::fingerprint::set_current_fingerprint_id(fp_id);
-            }
-        }
-    }
+    // 3. 写入设备指纹到 storage.json（可在设置中关闭）
+    let applied_fingerprint_id =
+        if modules::config::get_user_config().auto_apply_fingerprint_on_switch {
+            apply_account_fingerprint(&account)
+        } else {
+            modules::logger::log_info("已关闭切号自动应用指纹，跳过写入设备指纹");
+            None
+        };
 
     // 4. 更新工具内部状态
     modules::set_current_account_id(&account_id)?;
@@ -222,7 +293,7 @@ pub async fn switch_account(app: AppHandle, account_id: String) -> Result<models
         }
         Err(e) => {
             modules::logger::log_warn(&format!("Antigravity 启动失败: {}", e));
-            if e.starts_with("APP_PATH_NOT_FOUND:") {
+            if crate::modules::process::is_app_path_error(&e) {
                 let _ = app.emit(
                     "app:path_missing",
                     serde_json::json!({ "app": "antigravity", "retry": { "kind": "default" } }),
@@ -237,7 +308,10 @@ pub async fn switch_account(app: AppHandle, account_id: String) -> Result<models
     // 广播切换完成通知
     modules::websocket::broadcast_account_switched(&account.id, &account.email);
 
-    Ok(account)
+    Ok(SwitchAccountResult {
+        account,
+        applied_fingerprint_id,
+    })
 }
 
 #[tauri::command]
@@ -262,6 +336,27 @@ pub async fn update_account_tags(
     Ok(account)
 }
 
+#[tauri::command]
+pub async fn set_account_label(
+    account_id: String,
+    label: String,
+) -> Result<models::Account, String> {
+    let account = modules::set_account_label(&account_id, &label)?;
+    modules::websocket::broadcast_data_changed("account_label_updated");
+    Ok(account)
+}
+
+#[tauri::command]
+pub async fn bulk_update_account_tags(
+    account_ids: Vec<String>,
+    add_tags: Vec<String>,
+    remove_tags: Vec<String>,
+) -> Result<Vec<models::Account>, String> {
+    let accounts = modules::account::bulk_update_account_tags(&account_ids, add_tags, remove_tags)?;
+    modules::websocket::broadcast_data_changed("account_tags_updated");
+    Ok(accounts)
+}
+
 #[tauri::command]
 pub async fn get_bound_accounts(fingerprint_id: String) -> Result<Vec<models::Account>, String> {
     modules::fingerprint::get_bound_accounts(&fingerprint_id)
@@ -276,8 +371,7 @@ pub async fn sync_current_from_client() -> Result<Option<String>, String> {
 
     // 读取本地数据库中的 refresh_token
     let db_path = modules::db::get_db_path()?;
-    let conn =
-        rusqlite::Connection::open(&db_path).map_err(|e| format!("打开数据库失败: {}", e))?;
+    let conn = modules::db::open_readonly(&db_path)?;
 
     let state_data: Result<String, _> = conn.query_row(
         "SELECT value FROM ItemTable WHERE key = ?",
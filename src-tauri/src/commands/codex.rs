@@ -1,6 +1,9 @@
-use crate::models::codex::{CodexAccount, CodexQuota, CodexTokens};
+use crate::models::codex::{
+    CodexAccount, CodexAccountPage, CodexDeferredAccount, CodexQuota, CodexQuotaRefreshSummary,
+    CodexTokens,
+};
 use crate::modules::{
-    codex_account, codex_oauth, codex_quota, config, logger, opencode_auth, process,
+    codex_account, codex_oauth, codex_quota, config, logger, opencode_auth, process, websocket,
 };
 use tauri::AppHandle;
 #[cfg(target_os = "macos")]
@@ -12,6 +15,15 @@ pub fn list_codex_accounts() -> Result<Vec<CodexAccount>, String> {
     Ok(codex_account::list_accounts())
 }
 
+/// 分页列出 Codex 账号（未指定 offset / limit 时返回全部）
+#[tauri::command]
+pub fn list_codex_accounts_page(
+    offset: Option<usize>,
+    limit: Option<usize>,
+) -> Result<CodexAccountPage, String> {
+    Ok(codex_account::list_accounts_page(offset, limit))
+}
+
 /// 获取当前激活的 Codex 账号
 #[tauri::command]
 pub fn get_current_codex_account() -> Result<Option<CodexAccount>, String> {
@@ -24,6 +36,7 @@ pub async fn switch_codex_account(
     app: AppHandle,
     account_id: String,
 ) -> Result<CodexAccount, String> {
+    crate::modules::fault_injection::check("switch")?;
     let _ = codex_account::prepare_account_for_injection(&account_id).await?;
 
     // 切换账号（写入 auth.json）
@@ -86,7 +99,7 @@ pub async fn switch_codex_account(
                 Ok(_) => {}
                 Err(e) => {
                     logger::log_warn(&format!("Codex 启动失败: {}", e));
-                    if e.starts_with("APP_PATH_NOT_FOUND:") {
+                    if crate::modules::process::is_app_path_error(&e) {
                         let _ = app.emit(
                             "app:path_missing",
                             serde_json::json!({ "app": "codex", "retry": { "kind": "default" } }),
@@ -115,6 +128,12 @@ pub fn delete_codex_accounts(account_ids: Vec<String>) -> Result<(), String> {
     codex_account::remove_accounts(&account_ids)
 }
 
+/// 调整 Codex 账号顺序（需包含全部账号 ID）
+#[tauri::command]
+pub fn reorder_codex_accounts(ordered_ids: Vec<String>) -> Result<(), String> {
+    codex_account::reorder_accounts(&ordered_ids)
+}
+
 /// 从本地 auth.json 导入账号
 #[tauri::command]
 pub fn import_codex_from_local() -> Result<CodexAccount, String> {
@@ -136,6 +155,7 @@ pub fn export_codex_accounts(account_ids: Vec<String>) -> Result<String, String>
 /// 刷新单个账号配额
 #[tauri::command]
 pub async fn refresh_codex_quota(app: AppHandle, account_id: String) -> Result<CodexQuota, String> {
+    crate::modules::fault_injection::check("refresh")?;
     let result = codex_quota::refresh_account_quota(&account_id).await;
     if result.is_ok() {
         if let Err(e) = codex_account::run_quota_alert_if_needed() {
@@ -148,6 +168,7 @@ pub async fn refresh_codex_quota(app: AppHandle, account_id: String) -> Result<C
 
 #[tauri::command]
 pub async fn refresh_current_codex_quota(app: AppHandle) -> Result<(), String> {
+    crate::modules::fault_injection::check("refresh")?;
     let Some(account) = codex_account::get_current_account() else {
         return Err("未找到当前 Codex 账号".to_string());
     };
@@ -168,12 +189,29 @@ pub async fn refresh_current_codex_quota(app: AppHandle) -> Result<(), String> {
     }
 }
 
-/// 刷新所有账号配额
+/// 刷新所有账号配额（被限流推迟的账号单独列出，不计为失败）
 #[tauri::command]
-pub async fn refresh_all_codex_quotas(app: AppHandle) -> Result<i32, String> {
+pub async fn refresh_all_codex_quotas(app: AppHandle) -> Result<CodexQuotaRefreshSummary, String> {
+    crate::modules::fault_injection::check("refresh")?;
     let results = codex_quota::refresh_all_quotas().await?;
-    let success_count = results.iter().filter(|(_, r)| r.is_ok()).count();
-    if success_count > 0 {
+    let mut summary = CodexQuotaRefreshSummary {
+        success: 0,
+        failed: 0,
+        deferred: Vec::new(),
+    };
+    for (account_id, outcome) in &results {
+        match outcome {
+            codex_quota::BatchRefreshOutcome::Refreshed(Ok(_)) => summary.success += 1,
+            codex_quota::BatchRefreshOutcome::Refreshed(Err(_)) => summary.failed += 1,
+            codex_quota::BatchRefreshOutcome::Deferred(retry_after_secs) => {
+                summary.deferred.push(CodexDeferredAccount {
+                    account_id: account_id.clone(),
+                    retry_after_secs: *retry_after_secs,
+                });
+            }
+        }
+    }
+    if summary.success > 0 {
         if let Err(e) = codex_account::run_quota_alert_if_needed() {
             logger::log_warn(&format!(
                 "[QuotaAlert][Codex] 全量刷新后预警检查失败: {}",
@@ -182,7 +220,7 @@ pub async fn refresh_all_codex_quotas(app: AppHandle) -> Result<i32, String> {
         }
     }
     let _ = crate::modules::tray::update_tray_menu(&app);
-    Ok(success_count as i32)
+    Ok(summary)
 }
 
 async fn save_codex_oauth_tokens(tokens: CodexTokens) -> Result<CodexAccount, String> {
@@ -293,6 +331,17 @@ pub async fn update_codex_account_tags(
     codex_account::update_account_tags(&account_id, tags)
 }
 
+/// 设置 Codex 账号显示名称（空字符串清除）
+#[tauri::command]
+pub async fn set_codex_account_label(
+    account_id: String,
+    label: String,
+) -> Result<CodexAccount, String> {
+    let account = codex_account::set_account_label(&account_id, &label)?;
+    websocket::broadcast_data_changed("codex_account_label_updated");
+    Ok(account)
+}
+
 #[tauri::command]
 pub fn is_codex_oauth_port_in_use() -> Result<bool, String> {
     let port = codex_oauth::get_callback_port();
@@ -1,6 +1,8 @@
+use std::collections::HashMap;
 use std::path::Path;
+use tauri::{AppHandle, Emitter};
 
-use crate::models::{DefaultInstanceSettings, InstanceProfileView};
+use crate::models::{DefaultInstanceSettings, InstanceProfile, InstanceProfileView};
 use crate::modules;
 
 const DEFAULT_INSTANCE_ID: &str = "__default__";
@@ -22,6 +24,24 @@ fn resolve_local_account_id() -> Option<String> {
     Some(account.id)
 }
 
+/// 显式绑定优先；未绑定且开启“跟随当前账号”时使用当前选中的 Codex 账号
+fn resolve_instance_account_id(instance: &InstanceProfile) -> Option<String> {
+    if let Some(ref account_id) = instance.bind_account_id {
+        return Some(account_id.clone());
+    }
+    if !instance.follow_local_account {
+        return None;
+    }
+    let account_id = resolve_local_account_id();
+    if account_id.is_none() {
+        modules::logger::log_warn(&format!(
+            "[Codex Instance] 实例已开启跟随当前账号，但当前没有选中的账号: instance_id={}",
+            instance.id
+        ));
+    }
+    account_id
+}
+
 #[tauri::command]
 pub async fn codex_get_instance_defaults() -> Result<modules::instance::InstanceDefaults, String> {
     modules::codex_instance::get_instance_defaults()
@@ -72,12 +92,18 @@ pub async fn codex_list_instances() -> Result<Vec<InstanceProfileView>, String>
         initialized: modules::instance::is_profile_initialized(&default_dir),
         is_default: true,
         follow_local_account: default_settings.follow_local_account,
+        minimize_after_launch: false,
+        low_power: false,
+        launch_args_template: None,
+        env_vars: HashMap::new(),
+        bind_fingerprint_id: None,
     });
 
     Ok(result)
 }
 
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn codex_create_instance(
     name: String,
     user_data_dir: String,
@@ -85,7 +111,16 @@ pub async fn codex_create_instance(
     bind_account_id: Option<String>,
     copy_source_instance_id: Option<String>,
     init_mode: Option<String>,
+    launch_args_template: Option<String>,
+    follow_launch_args_template: Option<bool>,
 ) -> Result<InstanceProfileView, String> {
+    let (extra_args, template) = modules::launch_args_template::resolve_template_reference(
+        modules::tray_layout::PLATFORM_CODEX,
+        None,
+        extra_args,
+        Some(launch_args_template),
+        follow_launch_args_template.unwrap_or(false),
+    )?;
     let instance =
         modules::codex_instance::create_instance(modules::codex_instance::CreateInstanceParams {
             name,
@@ -94,6 +129,9 @@ pub async fn codex_create_instance(
             bind_account_id,
             copy_source_instance_id,
             init_mode,
+            launch_args_template: template.flatten(),
+            env_vars: HashMap::new(),
+            bind_fingerprint_id: None,
         })?;
 
     let initialized = is_profile_initialized(&instance.user_data_dir);
@@ -105,13 +143,25 @@ pub async fn codex_create_instance(
 }
 
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn codex_update_instance(
     instance_id: String,
     name: Option<String>,
     extra_args: Option<String>,
     bind_account_id: Option<Option<String>>,
     follow_local_account: Option<bool>,
+    minimize_after_launch: Option<bool>,
+    launch_args_template: Option<Option<String>>,
+    follow_launch_args_template: Option<bool>,
 ) -> Result<InstanceProfileView, String> {
+    let (extra_args, launch_args_template) =
+        modules::launch_args_template::resolve_template_reference(
+            modules::tray_layout::PLATFORM_CODEX,
+            Some(&instance_id),
+            extra_args,
+            launch_args_template,
+            follow_launch_args_template.unwrap_or(false),
+        )?;
     if instance_id == DEFAULT_INSTANCE_ID {
         let default_dir = modules::codex_instance::get_default_codex_home()?;
         let default_dir_str = default_dir.to_string_lossy().to_string();
@@ -138,6 +188,11 @@ pub async fn codex_update_instance(
             initialized: modules::instance::is_profile_initialized(&default_dir),
             is_default: true,
             follow_local_account: updated.follow_local_account,
+            minimize_after_launch: false,
+            low_power: false,
+            launch_args_template: None,
+            env_vars: HashMap::new(),
+            bind_fingerprint_id: None,
         });
     }
 
@@ -163,6 +218,12 @@ pub async fn codex_update_instance(
             name,
             extra_args,
             bind_account_id,
+            minimize_after_launch,
+            low_power: None,
+            launch_args_template,
+            env_vars: None,
+            bind_fingerprint_id: None,
+            follow_local_account,
         })?;
 
     let running = instance
@@ -187,6 +248,7 @@ pub async fn codex_delete_instance(instance_id: String) -> Result<(), String> {
 
 #[tauri::command]
 pub async fn codex_start_instance(instance_id: String) -> Result<InstanceProfileView, String> {
+    crate::modules::fault_injection::check("launch")?;
     if instance_id == DEFAULT_INSTANCE_ID {
         let default_dir = modules::codex_instance::get_default_codex_home()?;
         let default_dir_str = default_dir.to_string_lossy().to_string();
@@ -215,6 +277,11 @@ pub async fn codex_start_instance(instance_id: String) -> Result<InstanceProfile
             initialized: modules::instance::is_profile_initialized(&default_dir),
             is_default: true,
             follow_local_account: default_settings.follow_local_account,
+            minimize_after_launch: false,
+            low_power: false,
+            launch_args_template: None,
+            env_vars: HashMap::new(),
+            bind_fingerprint_id: None,
         });
     }
 
@@ -232,10 +299,12 @@ pub async fn codex_start_instance(instance_id: String) -> Result<InstanceProfile
         let _ = modules::codex_instance::update_instance_pid(&instance.id, None)?;
     }
 
-    if let Some(ref account_id) = instance.bind_account_id {
+    modules::codex_instance::ensure_codex_home_writable(Path::new(&instance.user_data_dir))?;
+
+    if let Some(account_id) = resolve_instance_account_id(&instance) {
         modules::codex_instance::inject_account_to_profile(
             Path::new(&instance.user_data_dir),
-            account_id,
+            &account_id,
         )
         .await?;
     }
@@ -243,6 +312,9 @@ pub async fn codex_start_instance(instance_id: String) -> Result<InstanceProfile
     let extra_args = modules::process::parse_extra_args(&instance.extra_args);
     let pid = modules::process::start_codex_with_args(&instance.user_data_dir, &extra_args)?;
     let updated = modules::codex_instance::update_instance_after_start(&instance.id, pid)?;
+    if updated.minimize_after_launch {
+        modules::process::minimize_window_after_launch(pid);
+    }
     let running = modules::process::is_pid_running(pid);
     let initialized = is_profile_initialized(&updated.user_data_dir);
     Ok(InstanceProfileView::from_profile(
@@ -277,6 +349,11 @@ pub async fn codex_stop_instance(instance_id: String) -> Result<InstanceProfileV
             initialized: modules::instance::is_profile_initialized(&default_dir),
             is_default: true,
             follow_local_account: default_settings.follow_local_account,
+            minimize_after_launch: false,
+            low_power: false,
+            launch_args_template: None,
+            env_vars: HashMap::new(),
+            bind_fingerprint_id: None,
         });
     }
 
@@ -301,20 +378,40 @@ pub async fn codex_stop_instance(instance_id: String) -> Result<InstanceProfileV
     ))
 }
 
+/// 关闭全部实例；每个实例确认退出后发送 `codex:instance_close_progress` 事件
 #[tauri::command]
-pub async fn codex_close_all_instances() -> Result<(), String> {
+pub async fn codex_close_all_instances(app: AppHandle) -> Result<(), String> {
     let store = modules::codex_instance::load_instance_store()?;
-    let default_home = modules::codex_instance::get_default_codex_home()?;
-    let mut target_homes: Vec<String> = Vec::new();
-    target_homes.push(default_home.to_string_lossy().to_string());
+    let default_dir = modules::codex_instance::get_default_codex_home()?;
+    let mut targets: Vec<(String, String)> = vec![(
+        DEFAULT_INSTANCE_ID.to_string(),
+        default_dir.to_string_lossy().to_string(),
+    )];
     for instance in &store.instances {
-        let home = instance.user_data_dir.trim();
-        if !home.is_empty() {
-            target_homes.push(home.to_string());
+        let dir = instance.user_data_dir.trim();
+        if !dir.is_empty() {
+            targets.push((instance.id.clone(), dir.to_string()));
         }
     }
-
-    modules::process::close_codex_instances(&target_homes, 20)?;
+    let target_dirs: Vec<String> = targets.iter().map(|(_, dir)| dir.clone()).collect();
+    modules::process::close_codex_instances_with_progress(
+        &target_dirs,
+        20,
+        &mut |dir, remaining| {
+            let instance_id = targets
+                .iter()
+                .find(|(_, target_dir)| target_dir == dir)
+                .map(|(id, _)| id.clone())
+                .unwrap_or_default();
+            let _ = app.emit(
+                modules::process::CODEX_CLOSE_PROGRESS_EVENT,
+                modules::process::InstanceCloseProgress {
+                    instance_id,
+                    remaining,
+                },
+            );
+        },
+    )?;
     let _ = modules::codex_instance::clear_all_pids();
     Ok(())
 }
@@ -348,6 +445,7 @@ pub async fn codex_open_instance_window(instance_id: String) -> Result<(), Strin
             "定位 Codex 实例窗口失败，回退为启动实例: instance_id={}, err={}",
             instance.id, err
         ));
+        modules::codex_instance::ensure_codex_home_writable(Path::new(&instance.user_data_dir))?;
         let extra_args = modules::process::parse_extra_args(&instance.extra_args);
         let pid = modules::process::start_codex_with_args(&instance.user_data_dir, &extra_args)?;
         let _ = modules::codex_instance::update_instance_after_start(&instance.id, pid)?;
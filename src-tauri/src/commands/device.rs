@@ -67,6 +67,15 @@ pub async fn preview_current_profile() -> Result<models::DeviceProfile, String>
     modules::device::read_profile(&storage_path)
 }
 
+/// 预览应用指纹后会变化的设备字段（include_unchanged 为 true 时返回全部字段）
+#[tauri::command]
+pub async fn preview_profile_diff(
+    fingerprint_id: String,
+    include_unchanged: Option<bool>,
+) -> Result<Vec<modules::fingerprint::FieldDiff>, String> {
+    modules::fingerprint::preview_profile_diff(&fingerprint_id, include_unchanged.unwrap_or(false))
+}
+
 // ==================== 指纹管理命令 ====================
 
 #[tauri::command]
@@ -109,6 +118,27 @@ pub async fn apply_fingerprint(fingerprint_id: String) -> Result<String, String>
     modules::fingerprint::apply_fingerprint(&fingerprint_id)
 }
 
+/// 在临时实例中试应用指纹并校验是否生效
+#[tauri::command]
+pub async fn test_fingerprint(
+    fingerprint_id: String,
+) -> Result<modules::fingerprint::FingerprintTestResult, String> {
+    // 试应用需等待编辑器启动并关闭进程，放到阻塞线程执行以免占用异步运行时
+    tauri::async_runtime::spawn_blocking(move || {
+        modules::fingerprint::test_fingerprint(&fingerprint_id)
+    })
+    .await
+    .map_err(|e| format!("指纹试应用失败: {}", e))?
+}
+
+/// 比对当前设备指纹与已保存的指纹（只读）
+#[tauri::command]
+pub async fn compare_current_fingerprint(
+    fingerprint_id: String,
+) -> Result<modules::fingerprint::FingerprintDiff, String> {
+    modules::fingerprint::compare_current_fingerprint(&fingerprint_id)
+}
+
 #[tauri::command]
 pub async fn delete_fingerprint(fingerprint_id: String) -> Result<(), String> {
     modules::fingerprint::delete_fingerprint(&fingerprint_id)
@@ -119,6 +149,12 @@ pub async fn rename_fingerprint(fingerprint_id: String, name: String) -> Result<
     modules::fingerprint::rename_fingerprint(&fingerprint_id, name)
 }
 
+/// 获取本机稳定标识（随机种子的哈希，不含硬件信息）
+#[tauri::command]
+pub async fn get_machine_id() -> Result<String, String> {
+    modules::device::get_cockpit_machine_id()
+}
+
 #[tauri::command]
 pub async fn get_current_fingerprint_id() -> Result<Option<String>, String> {
     modules::fingerprint::get_current_fingerprint_id()
@@ -1,7 +1,10 @@
 use tauri::{AppHandle, Emitter};
 
-use crate::models::github_copilot::{GitHubCopilotAccount, GitHubCopilotOAuthStartResponse};
-use crate::modules::{github_copilot_account, github_copilot_oauth, logger};
+use crate::models::github_copilot::{
+    GitHubCopilotAccount, GitHubCopilotAccountSummary, GitHubCopilotOAuthStartResponse,
+};
+use crate::modules::github_copilot_oauth::CopilotRefreshError;
+use crate::modules::{github_copilot_account, github_copilot_oauth, logger, websocket};
 
 /// 列出所有 GitHub Copilot 账号
 #[tauri::command]
@@ -29,6 +32,17 @@ pub fn import_github_copilot_from_json(
     github_copilot_account::import_from_json(&json_content)
 }
 
+/// 从 VS Code 用户数据目录导入 Copilot 已登录的 GitHub 账号
+#[tauri::command]
+pub async fn import_github_copilot_from_vscode(
+    app: AppHandle,
+    user_data_dir: String,
+) -> Result<GitHubCopilotAccountSummary, String> {
+    let summary = github_copilot_account::import_from_vscode(&user_data_dir).await?;
+    let _ = crate::modules::tray::update_tray_menu(&app);
+    Ok(summary)
+}
+
 /// 导出 GitHub Copilot 账号为 JSON
 #[tauri::command]
 pub fn export_github_copilot_accounts(account_ids: Vec<String>) -> Result<String, String> {
@@ -36,11 +50,13 @@ pub fn export_github_copilot_accounts(account_ids: Vec<String>) -> Result<String
 }
 
 /// 刷新单个账号 Copilot token/配额信息（GitHub API）
+/// GitHub 授权失效时返回 `COPILOT_REAUTH_REQUIRED:` 前缀的错误
 #[tauri::command]
 pub async fn refresh_github_copilot_token(
     app: AppHandle,
     account_id: String,
 ) -> Result<GitHubCopilotAccount, String> {
+    crate::modules::fault_injection::check("refresh")?;
     let result = github_copilot_account::refresh_account_token(&account_id).await;
     if result.is_ok() {
         if let Err(e) = github_copilot_account::run_quota_alert_if_needed() {
@@ -48,14 +64,25 @@ pub async fn refresh_github_copilot_token(
         }
         let _ = crate::modules::tray::update_tray_menu(&app);
     }
-    result
+    result.map_err(|e| e.to_string())
 }
 
 /// 刷新所有账号 Copilot token/配额信息（GitHub API）
 #[tauri::command]
 pub async fn refresh_all_github_copilot_tokens(app: AppHandle) -> Result<i32, String> {
+    crate::modules::fault_injection::check("refresh")?;
     let results = github_copilot_account::refresh_all_tokens().await?;
     let success_count = results.iter().filter(|(_, r)| r.is_ok()).count();
+    let reauth_count = results
+        .iter()
+        .filter(|(_, r)| matches!(r, Err(CopilotRefreshError::Unauthorized)))
+        .count();
+    if reauth_count > 0 {
+        logger::log_warn(&format!(
+            "[GitHubCopilot] 全量刷新完成，{} 个账号需要重新登录",
+            reauth_count
+        ));
+    }
     if success_count > 0 {
         if let Err(e) = github_copilot_account::run_quota_alert_if_needed() {
             logger::log_warn(&format!(
@@ -132,6 +159,17 @@ pub async fn update_github_copilot_account_tags(
     github_copilot_account::update_account_tags(&account_id, tags)
 }
 
+/// 设置账号显示名称（空字符串清除）
+#[tauri::command]
+pub async fn set_github_copilot_account_label(
+    account_id: String,
+    label: String,
+) -> Result<GitHubCopilotAccount, String> {
+    let account = github_copilot_account::set_account_label(&account_id, &label)?;
+    websocket::broadcast_data_changed("github_copilot_account_label_updated");
+    Ok(account)
+}
+
 /// 返回 GitHub Copilot 账号索引文件路径（便于排障/查看）
 #[tauri::command]
 pub fn get_github_copilot_accounts_index_path() -> Result<String, String> {
@@ -169,14 +207,16 @@ pub async fn inject_github_copilot_to_vscode(
     let launch_warning =
         match crate::commands::github_copilot_instance::github_copilot_start_instance(
             "__default__".to_string(),
+            None,
         )
         .await
         {
             Ok(_) => None,
             Err(e) => {
-                if e.starts_with("APP_PATH_NOT_FOUND:") || e.contains("启动 VS Code 失败") {
+                if crate::modules::process::is_app_path_error(&e) || e.contains("启动 VS Code 失败")
+                {
                     logger::log_warn(&format!("GitHub Copilot 默认实例启动失败: {}", e));
-                    if e.starts_with("APP_PATH_NOT_FOUND:") || e.contains("APP_PATH_NOT_FOUND:") {
+                    if crate::modules::process::is_app_path_error(&e) {
                         let _ = app.emit(
                             "app:path_missing",
                             serde_json::json!({ "app": "vscode", "retry": { "kind": "default" } }),
@@ -1,4 +1,6 @@
+use std::collections::HashMap;
 use std::path::Path;
+use tauri::{AppHandle, Emitter};
 
 use crate::models::{DefaultInstanceSettings, InstanceProfileView};
 use crate::modules;
@@ -105,12 +107,18 @@ pub async fn github_copilot_list_instances() -> Result<Vec<InstanceProfileView>,
         initialized: is_profile_initialized(&default_dir.to_string_lossy()),
         is_default: true,
         follow_local_account: false,
+        minimize_after_launch: false,
+        low_power: false,
+        launch_args_template: None,
+        env_vars: HashMap::new(),
+        bind_fingerprint_id: None,
     });
 
     Ok(result)
 }
 
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn github_copilot_create_instance(
     name: String,
     user_data_dir: String,
@@ -118,7 +126,17 @@ pub async fn github_copilot_create_instance(
     bind_account_id: Option<String>,
     copy_source_instance_id: Option<String>,
     init_mode: Option<String>,
+    launch_args_template: Option<String>,
+    follow_launch_args_template: Option<bool>,
+    env_vars: Option<HashMap<String, String>>,
 ) -> Result<InstanceProfileView, String> {
+    let (extra_args, template) = modules::launch_args_template::resolve_template_reference(
+        modules::tray_layout::PLATFORM_GITHUB_COPILOT,
+        None,
+        extra_args,
+        Some(launch_args_template),
+        follow_launch_args_template.unwrap_or(false),
+    )?;
     let instance = modules::github_copilot_instance::create_instance(
         modules::github_copilot_instance::CreateInstanceParams {
             name,
@@ -127,6 +145,9 @@ pub async fn github_copilot_create_instance(
             bind_account_id,
             copy_source_instance_id,
             init_mode,
+            launch_args_template: template.flatten(),
+            env_vars: env_vars.unwrap_or_default(),
+            bind_fingerprint_id: None,
         },
     )?;
 
@@ -139,13 +160,26 @@ pub async fn github_copilot_create_instance(
 }
 
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn github_copilot_update_instance(
     instance_id: String,
     name: Option<String>,
     extra_args: Option<String>,
     bind_account_id: Option<Option<String>>,
     follow_local_account: Option<bool>,
+    minimize_after_launch: Option<bool>,
+    launch_args_template: Option<Option<String>>,
+    follow_launch_args_template: Option<bool>,
+    env_vars: Option<HashMap<String, String>>,
 ) -> Result<InstanceProfileView, String> {
+    let (extra_args, launch_args_template) =
+        modules::launch_args_template::resolve_template_reference(
+            modules::tray_layout::PLATFORM_GITHUB_COPILOT,
+            Some(&instance_id),
+            extra_args,
+            launch_args_template,
+            follow_launch_args_template.unwrap_or(false),
+        )?;
     if instance_id == DEFAULT_INSTANCE_ID {
         let default_dir = modules::github_copilot_instance::get_default_vscode_user_data_dir()?;
         let default_dir_str = default_dir.to_string_lossy().to_string();
@@ -171,6 +205,11 @@ pub async fn github_copilot_update_instance(
             initialized: is_profile_initialized(&default_dir.to_string_lossy()),
             is_default: true,
             follow_local_account: false,
+            minimize_after_launch: false,
+            low_power: false,
+            launch_args_template: None,
+            env_vars: HashMap::new(),
+            bind_fingerprint_id: None,
         });
     }
 
@@ -196,6 +235,12 @@ pub async fn github_copilot_update_instance(
             name,
             extra_args,
             bind_account_id,
+            minimize_after_launch,
+            low_power: None,
+            launch_args_template,
+            env_vars,
+            bind_fingerprint_id: None,
+            follow_local_account: None,
         },
     )?;
 
@@ -222,7 +267,9 @@ pub async fn github_copilot_delete_instance(instance_id: String) -> Result<(), S
 #[tauri::command]
 pub async fn github_copilot_start_instance(
     instance_id: String,
+    target_monitor: Option<usize>,
 ) -> Result<InstanceProfileView, String> {
+    crate::modules::fault_injection::check("launch")?;
     modules::logger::log_info(&format!("开始启动 GitHub Copilot 实例: {}", instance_id));
     if instance_id == DEFAULT_INSTANCE_ID {
         let default_dir = modules::github_copilot_instance::get_default_vscode_user_data_dir()?;
@@ -238,10 +285,16 @@ pub async fn github_copilot_start_instance(
             default_settings.bind_account_id.as_deref(),
         )?;
         let extra_args = modules::process::parse_extra_args(&default_settings.extra_args);
-        let pid =
-            modules::process::start_vscode_default_with_args_with_new_window(&extra_args, true)?;
+        let use_new_window = modules::process::resolve_use_new_window("vscode", &extra_args, true);
+        let pid = modules::process::start_vscode_default_with_args_with_new_window(
+            &extra_args,
+            use_new_window,
+        )?;
         modules::logger::log_info(&format!("GitHub Copilot 默认实例已启动: pid={}", pid));
         let _ = modules::github_copilot_instance::update_default_pid(Some(pid))?;
+        if let Some(monitor_index) = target_monitor {
+            modules::process::place_window_on_monitor_after_launch(pid, monitor_index);
+        }
         let running = modules::process::is_pid_running(pid);
         return Ok(InstanceProfileView {
             id: DEFAULT_INSTANCE_ID.to_string(),
@@ -256,6 +309,11 @@ pub async fn github_copilot_start_instance(
             initialized: is_profile_initialized(&default_dir.to_string_lossy()),
             is_default: true,
             follow_local_account: false,
+            minimize_after_launch: false,
+            low_power: false,
+            launch_args_template: None,
+            env_vars: HashMap::new(),
+            bind_fingerprint_id: None,
         });
     }
 
@@ -282,13 +340,20 @@ pub async fn github_copilot_start_instance(
     let pid = modules::process::start_vscode_with_args_with_new_window(
         &instance.user_data_dir,
         &extra_args,
-        true,
+        modules::process::resolve_use_new_window("vscode", &extra_args, true),
+        &instance.env_vars,
     )?;
     modules::logger::log_info(&format!(
         "GitHub Copilot 实例已启动: instance_id={}, pid={}",
         instance.id, pid
     ));
     let updated = modules::github_copilot_instance::update_instance_after_start(&instance.id, pid)?;
+    if let Some(monitor_index) = target_monitor {
+        modules::process::place_window_on_monitor_after_launch(pid, monitor_index);
+    }
+    if updated.minimize_after_launch {
+        modules::process::minimize_window_after_launch(pid);
+    }
     let running = modules::process::is_pid_running(pid);
     let initialized = is_profile_initialized(&updated.user_data_dir);
     Ok(InstanceProfileView::from_profile(
@@ -327,6 +392,11 @@ pub async fn github_copilot_stop_instance(
             initialized: is_profile_initialized(&default_dir.to_string_lossy()),
             is_default: true,
             follow_local_account: false,
+            minimize_after_launch: false,
+            low_power: false,
+            launch_args_template: None,
+            env_vars: HashMap::new(),
+            bind_fingerprint_id: None,
         });
     }
 
@@ -390,25 +460,43 @@ pub async fn github_copilot_open_instance_window(instance_id: String) -> Result<
             &instance.user_data_dir,
             &extra_args,
             false,
+            &instance.env_vars,
         )?;
         let _ = modules::github_copilot_instance::update_instance_after_start(&instance.id, pid)?;
     }
     Ok(())
 }
 
+/// 关闭全部实例；每个实例确认退出后发送 `github_copilot:instance_close_progress` 事件
 #[tauri::command]
-pub async fn github_copilot_close_all_instances() -> Result<(), String> {
+pub async fn github_copilot_close_all_instances(app: AppHandle) -> Result<(), String> {
     let store = modules::github_copilot_instance::load_instance_store()?;
     let default_dir = modules::github_copilot_instance::get_default_vscode_user_data_dir()?;
-    let mut target_dirs: Vec<String> = Vec::new();
-    target_dirs.push(default_dir.to_string_lossy().to_string());
+    let mut targets: Vec<(String, String)> = vec![(
+        DEFAULT_INSTANCE_ID.to_string(),
+        default_dir.to_string_lossy().to_string(),
+    )];
     for instance in &store.instances {
         let dir = instance.user_data_dir.trim();
         if !dir.is_empty() {
-            target_dirs.push(dir.to_string());
+            targets.push((instance.id.clone(), dir.to_string()));
         }
     }
-    modules::process::close_vscode(&target_dirs, 20)?;
+    let target_dirs: Vec<String> = targets.iter().map(|(_, dir)| dir.clone()).collect();
+    modules::process::close_vscode_with_progress(&target_dirs, 20, &mut |dir, remaining| {
+        let instance_id = targets
+            .iter()
+            .find(|(_, target_dir)| target_dir == dir)
+            .map(|(id, _)| id.clone())
+            .unwrap_or_default();
+        let _ = app.emit(
+            modules::process::VSCODE_CLOSE_PROGRESS_EVENT,
+            modules::process::InstanceCloseProgress {
+                instance_id,
+                remaining,
+            },
+        );
+    })?;
     let _ = modules::github_copilot_instance::clear_all_pids();
     Ok(())
 }
@@ -2,8 +2,16 @@ use crate::models;
 use crate::modules;
 
 #[tauri::command]
-pub async fn import_from_old_tools() -> Result<Vec<models::Account>, String> {
-    modules::import::import_from_old_tools_logic().await
+pub async fn import_from_old_tools(
+    skip_conflicts: Option<bool>,
+) -> Result<Vec<models::Account>, String> {
+    modules::import::import_from_old_tools_logic(skip_conflicts.unwrap_or(false)).await
+}
+
+/// 预览从旧版导入的结果（不修改任何数据）
+#[tauri::command]
+pub fn preview_import_from_old_tools() -> Result<modules::import::ImportPreview, String> {
+    modules::import::preview_import_from_old_tools_logic()
 }
 
 #[tauri::command]
@@ -16,6 +24,14 @@ pub async fn import_fingerprints_from_json(json_content: String) -> Result<usize
     modules::import::import_fingerprints_from_json_logic(json_content).await
 }
 
+/// 从 zip 压缩包导入指纹，无效条目跳过并计入报告
+#[tauri::command]
+pub async fn import_fingerprints_from_zip(
+    path: String,
+) -> Result<modules::import::FingerprintZipImportReport, String> {
+    modules::import::import_fingerprints_from_zip_logic(path).await
+}
+
 #[tauri::command]
 pub async fn import_from_local() -> Result<models::Account, String> {
     modules::import::import_from_local_logic().await
@@ -26,37 +42,30 @@ pub async fn import_from_json(json_content: String) -> Result<Vec<models::Accoun
     modules::import::import_from_json_logic(json_content).await
 }
 
+/// 导出账号，format 未指定时为 JSON（可重新导入），CSV 不含任何 token
 #[tauri::command]
-pub async fn export_accounts(account_ids: Vec<String>) -> Result<String, String> {
-    let mut accounts_to_export = Vec::new();
-
-    if account_ids.is_empty() {
-        // 导出全部
-        accounts_to_export = modules::list_accounts()?;
-    } else {
-        for id in &account_ids {
-            if let Ok(account) = modules::load_account(id) {
-                accounts_to_export.push(account);
-            }
-        }
-    }
-
-    #[derive(serde::Serialize)]
-    struct SimpleAccount {
-        email: String,
-        refresh_token: String,
-    }
-
-    let simplified: Vec<SimpleAccount> = accounts_to_export
-        .into_iter()
-        .map(|account| SimpleAccount {
-            email: account.email,
-            refresh_token: account.token.refresh_token,
-        })
-        .collect();
+pub async fn export_accounts(
+    account_ids: Vec<String>,
+    format: Option<modules::import::ExportFormat>,
+) -> Result<String, String> {
+    modules::import::export_accounts_logic(&account_ids, format.unwrap_or_default())
+}
 
-    let json =
-        serde_json::to_string_pretty(&simplified).map_err(|e| format!("序列化失败: {}", e))?;
+/// 导出全部平台账号到加密备份文件
+#[tauri::command]
+pub async fn export_all_accounts(
+    password: String,
+    path: String,
+) -> Result<modules::account_bundle::AccountBundleExportResult, String> {
+    modules::account_bundle::export_all_accounts(&password, &path)
+}
 
-    Ok(json)
+/// 从加密备份文件恢复账号，providers 为空时恢复全部平台
+#[tauri::command]
+pub async fn import_all_accounts(
+    password: String,
+    path: String,
+    providers: Option<Vec<String>>,
+) -> Result<modules::account_bundle::AccountBundleImportResult, String> {
+    modules::account_bundle::import_all_accounts(&password, &path, providers)
 }
@@ -1,16 +1,78 @@
 use base64::{engine::general_purpose, Engine as _};
-use rusqlite::Connection;
+use serde::Serialize;
+use std::collections::HashMap;
 use std::path::Path;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
 
-use crate::models::{DefaultInstanceSettings, InstanceProfileView};
+use crate::models::{DefaultInstanceSettings, InstanceProfile, InstanceProfileView};
 use crate::modules;
 
 const DEFAULT_INSTANCE_ID: &str = "__default__";
+/// 批量重启实例之间的间隔，避免同时拉起多个进程
+const RESTART_STAGGER: Duration = Duration::from_secs(2);
+/// 批量启动时相邻两次启动之间的最小间隔
+const BATCH_START_STAGGER: Duration = Duration::from_millis(800);
+/// 批量启动的并发上限
+const BATCH_START_MAX_CONCURRENT: usize = 8;
+
+pub const INSTANCE_BATCH_START_PROGRESS_EVENT: &str = "instance:batch_start_progress";
+
+/// 重新绑定实例账号的结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RebindInstanceResult {
+    pub instance: InstanceProfileView,
+    /// 是否已将新账号凭据写入实例数据目录
+    pub migrated: bool,
+    /// 请求迁移但未执行时的原因
+    pub skipped_reason: Option<String>,
+}
+
+/// 单个实例的重启结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RestartInstanceOutcome {
+    pub instance_id: String,
+    pub name: String,
+    /// 是否已将绑定账号的指纹写入实例目录
+    pub fingerprint_applied: bool,
+    pub pid: Option<u32>,
+    pub error: Option<String>,
+}
+
+/// 批量启动中单个实例的结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchStartOutcome {
+    pub instance_id: String,
+    pub pid: Option<u32>,
+    pub error: Option<String>,
+}
+
+/// 批量启动进度（每个实例完成后推送）
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchStartProgress {
+    #[serde(flatten)]
+    pub outcome: BatchStartOutcome,
+    pub completed: usize,
+    pub total: usize,
+}
 
 fn is_profile_initialized(user_data_dir: &str) -> bool {
     modules::instance::is_profile_initialized(Path::new(user_data_dir))
 }
 
+/// 解析实例启动参数（含低功耗模式附加的参数）
+fn instance_launch_args(instance: &InstanceProfile) -> Vec<String> {
+    let mut args = modules::process::parse_extra_args(&instance.extra_args);
+    if instance.low_power {
+        modules::process::apply_low_power_args(&mut args);
+    }
+    args
+}
+
 fn resolve_default_account_id(settings: &DefaultInstanceSettings) -> Option<String> {
     if settings.follow_local_account {
         resolve_local_account_id()
@@ -21,7 +83,7 @@ fn resolve_default_account_id(settings: &DefaultInstanceSettings) -> Option<Stri
 
 fn resolve_local_account_id() -> Option<String> {
     let db_path = modules::db::get_db_path().ok()?;
-    let conn = Connection::open(&db_path).ok()?;
+    let conn = modules::db::open_readonly(&db_path).ok()?;
     let state_data: String = conn
         .query_row(
             "SELECT value FROM ItemTable WHERE key = ?",
@@ -92,12 +154,18 @@ pub async fn list_instances() -> Result<Vec<InstanceProfileView>, String> {
         initialized: modules::instance::is_profile_initialized(&default_dir),
         is_default: true,
         follow_local_account: default_settings.follow_local_account,
+        minimize_after_launch: false,
+        low_power: false,
+        launch_args_template: None,
+        env_vars: HashMap::new(),
+        bind_fingerprint_id: None,
     });
 
     Ok(result)
 }
 
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn create_instance(
     name: String,
     user_data_dir: String,
@@ -105,7 +173,18 @@ pub async fn create_instance(
     bind_account_id: Option<String>,
     copy_source_instance_id: Option<String>,
     init_mode: Option<String>,
+    launch_args_template: Option<String>,
+    follow_launch_args_template: Option<bool>,
+    env_vars: Option<HashMap<String, String>>,
+    bind_fingerprint_id: Option<String>,
 ) -> Result<InstanceProfileView, String> {
+    let (extra_args, template) = modules::launch_args_template::resolve_template_reference(
+        modules::tray_layout::PLATFORM_ANTIGRAVITY,
+        None,
+        extra_args,
+        Some(launch_args_template),
+        follow_launch_args_template.unwrap_or(false),
+    )?;
     let instance = modules::instance::create_instance(modules::instance::CreateInstanceParams {
         name,
         user_data_dir,
@@ -113,6 +192,9 @@ pub async fn create_instance(
         bind_account_id,
         copy_source_instance_id,
         init_mode,
+        launch_args_template: template.flatten(),
+        env_vars: env_vars.unwrap_or_default(),
+        bind_fingerprint_id,
     })?;
 
     let initialized = is_profile_initialized(&instance.user_data_dir);
@@ -124,14 +206,39 @@ pub async fn create_instance(
 }
 
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn update_instance(
     instance_id: String,
     name: Option<String>,
     extra_args: Option<String>,
     bind_account_id: Option<Option<String>>,
     follow_local_account: Option<bool>,
+    minimize_after_launch: Option<bool>,
+    low_power: Option<bool>,
+    launch_args_template: Option<Option<String>>,
+    follow_launch_args_template: Option<bool>,
+    env_vars: Option<HashMap<String, String>>,
+    bind_fingerprint_id: Option<Option<String>>,
 ) -> Result<InstanceProfileView, String> {
+    let (extra_args, launch_args_template) =
+        modules::launch_args_template::resolve_template_reference(
+            modules::tray_layout::PLATFORM_ANTIGRAVITY,
+            Some(&instance_id),
+            extra_args,
+            launch_args_template,
+            follow_launch_args_template.unwrap_or(false),
+        )?;
     if instance_id == DEFAULT_INSTANCE_ID {
+        if bind_fingerprint_id
+            .as_ref()
+            .and_then(|next| next.as_ref())
+            .is_some()
+        {
+            return Err(
+                "FINGERPRINT_BINDING_UNSUPPORTED:默认实例使用系统指纹，请在指纹管理中直接应用"
+                    .to_string(),
+            );
+        }
         let default_dir = modules::instance::get_default_user_data_dir()?;
         let default_dir_str = default_dir.to_string_lossy().to_string();
         let updated = modules::instance::update_default_settings(
@@ -157,6 +264,11 @@ pub async fn update_instance(
             initialized: modules::instance::is_profile_initialized(&default_dir),
             is_default: true,
             follow_local_account: updated.follow_local_account,
+            minimize_after_launch: false,
+            low_power: false,
+            launch_args_template: None,
+            env_vars: HashMap::new(),
+            bind_fingerprint_id: None,
         });
     }
 
@@ -181,6 +293,12 @@ pub async fn update_instance(
         name,
         extra_args,
         bind_account_id,
+        minimize_after_launch,
+        low_power,
+        launch_args_template,
+        env_vars,
+        bind_fingerprint_id,
+        follow_local_account,
     })?;
 
     let running = instance
@@ -195,6 +313,67 @@ pub async fn update_instance(
     ))
 }
 
+/// 将实例重新绑定到另一个账号；migrate 为 true 且实例未运行时，同时把新账号凭据写入实例数据目录
+#[tauri::command]
+pub async fn rebind_instance_account(
+    instance_id: String,
+    new_account_id: String,
+    migrate: bool,
+) -> Result<RebindInstanceResult, String> {
+    modules::load_account(&new_account_id)?;
+    let view = update_instance(
+        instance_id,
+        None,
+        None,
+        Some(Some(new_account_id.clone())),
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+    )
+    .await?;
+
+    let mut result = RebindInstanceResult {
+        instance: view,
+        migrated: false,
+        skipped_reason: None,
+    };
+    if !migrate {
+        return Ok(result);
+    }
+
+    let (profile_dir, running_pid) = if result.instance.is_default {
+        (
+            modules::instance::get_default_user_data_dir()?,
+            modules::process::resolve_antigravity_pid(result.instance.last_pid, None),
+        )
+    } else {
+        (
+            std::path::PathBuf::from(&result.instance.user_data_dir),
+            modules::process::resolve_antigravity_pid(
+                result.instance.last_pid,
+                Some(&result.instance.user_data_dir),
+            ),
+        )
+    };
+    if running_pid.is_some() {
+        result.skipped_reason = Some("实例正在运行，请关闭后再迁移凭据".to_string());
+        return Ok(result);
+    }
+
+    let _ = modules::prepare_account_for_injection(&new_account_id).await?;
+    modules::instance::inject_account_to_profile(&profile_dir, &new_account_id)?;
+    result.migrated = true;
+    modules::logger::log_info(&format!(
+        "[Instance] 实例已重新绑定并迁移凭据: instance_id={}, account_id={}",
+        result.instance.id, new_account_id
+    ));
+    Ok(result)
+}
+
 #[tauri::command]
 pub async fn delete_instance(instance_id: String) -> Result<(), String> {
     if instance_id == DEFAULT_INSTANCE_ID {
@@ -204,7 +383,11 @@ pub async fn delete_instance(instance_id: String) -> Result<(), String> {
 }
 
 #[tauri::command]
-pub async fn start_instance(instance_id: String) -> Result<InstanceProfileView, String> {
+pub async fn start_instance(
+    instance_id: String,
+    target_monitor: Option<usize>,
+) -> Result<InstanceProfileView, String> {
+    crate::modules::fault_injection::check("launch")?;
     if instance_id == DEFAULT_INSTANCE_ID {
         let default_dir = modules::instance::get_default_user_data_dir()?;
         let default_dir_str = default_dir.to_string_lossy().to_string();
@@ -222,6 +405,9 @@ pub async fn start_instance(instance_id: String) -> Result<InstanceProfileView,
         }
         let pid = modules::process::start_antigravity()?;
         let _ = modules::instance::update_default_pid(Some(pid))?;
+        if let Some(monitor_index) = target_monitor {
+            modules::process::place_window_on_monitor_after_launch(pid, monitor_index);
+        }
         let running = modules::process::is_pid_running(pid);
         return Ok(InstanceProfileView {
             id: DEFAULT_INSTANCE_ID.to_string(),
@@ -236,6 +422,11 @@ pub async fn start_instance(instance_id: String) -> Result<InstanceProfileView,
             initialized: modules::instance::is_profile_initialized(&default_dir),
             is_default: true,
             follow_local_account: default_settings.follow_local_account,
+            minimize_after_launch: false,
+            low_power: false,
+            launch_args_template: None,
+            env_vars: HashMap::new(),
+            bind_fingerprint_id: None,
         });
     }
 
@@ -258,10 +449,25 @@ pub async fn start_instance(instance_id: String) -> Result<InstanceProfileView,
         let profile_dir = std::path::PathBuf::from(&instance.user_data_dir);
         modules::instance::inject_account_to_profile(&profile_dir, account_id)?;
     }
+    // 实例绑定的指纹优先于账号绑定的指纹
+    modules::instance::apply_instance_fingerprint(
+        &instance,
+        &std::path::PathBuf::from(&instance.user_data_dir),
+    )?;
 
-    let extra_args = modules::process::parse_extra_args(&instance.extra_args);
-    let pid = modules::process::start_antigravity_with_args(&instance.user_data_dir, &extra_args)?;
+    let extra_args = instance_launch_args(&instance);
+    let pid = modules::process::start_antigravity_with_args(
+        &instance.user_data_dir,
+        &extra_args,
+        &instance.env_vars,
+    )?;
     let updated = modules::instance::update_instance_after_start(&instance.id, pid)?;
+    if let Some(monitor_index) = target_monitor {
+        modules::process::place_window_on_monitor_after_launch(pid, monitor_index);
+    }
+    if updated.minimize_after_launch {
+        modules::process::minimize_window_after_launch(pid);
+    }
     let running = modules::process::is_pid_running(pid);
     let initialized = is_profile_initialized(&updated.user_data_dir);
     Ok(InstanceProfileView::from_profile(
@@ -271,6 +477,216 @@ pub async fn start_instance(instance_id: String) -> Result<InstanceProfileView,
     ))
 }
 
+async fn restart_single_instance(
+    info: &modules::instance_registry::InstanceRuntimeInfo,
+    apply_fingerprint: bool,
+) -> Result<(bool, u32), String> {
+    let bind_account_id = if info.is_default {
+        resolve_default_account_id(&modules::instance::load_default_settings()?)
+    } else {
+        modules::instance::load_instance_store()?
+            .instances
+            .into_iter()
+            .find(|item| item.id == info.id)
+            .and_then(|item| item.bind_account_id)
+    };
+
+    if let Some(pid) = info.resolved_pid {
+        modules::process::close_pid(pid, 20)?;
+    }
+
+    let mut fingerprint_applied = false;
+    if apply_fingerprint {
+        if let Some(ref account_id) = bind_account_id {
+            let profile_dir = Path::new(&info.user_data_dir);
+            fingerprint_applied =
+                modules::instance::apply_account_fingerprint_to_profile(profile_dir, account_id)?;
+        }
+    }
+
+    let view = start_instance(info.id.clone(), None).await?;
+    Ok((fingerprint_applied, view.last_pid.unwrap_or_default()))
+}
+
+/// 重启所有运行中的实例（可选先写入绑定账号的指纹），依次执行并返回每个实例的结果
+/// 每个实例完成后发送 `instance:restart_progress` 事件
+#[tauri::command]
+pub async fn restart_running_instances(
+    app: AppHandle,
+    apply_fingerprint: bool,
+) -> Result<Vec<RestartInstanceOutcome>, String> {
+    let running: Vec<_> = modules::instance_registry::list_instances_detailed(
+        modules::tray_layout::PLATFORM_ANTIGRAVITY,
+    )?
+    .into_iter()
+    .filter(|info| info.is_running)
+    .collect();
+
+    let mut outcomes = Vec::new();
+    for (index, info) in running.iter().enumerate() {
+        if index > 0 {
+            tokio::time::sleep(RESTART_STAGGER).await;
+        }
+        let outcome = match restart_single_instance(info, apply_fingerprint).await {
+            Ok((fingerprint_applied, pid)) => RestartInstanceOutcome {
+                instance_id: info.id.clone(),
+                name: info.name.clone(),
+                fingerprint_applied,
+                pid: Some(pid),
+                error: None,
+            },
+            Err(err) => {
+                modules::logger::log_warn(&format!(
+                    "[Instance] 重启实例失败: instance_id={}, err={}",
+                    info.id, err
+                ));
+                RestartInstanceOutcome {
+                    instance_id: info.id.clone(),
+                    name: info.name.clone(),
+                    fingerprint_applied: false,
+                    pid: None,
+                    error: Some(err),
+                }
+            }
+        };
+        let _ = app.emit("instance:restart_progress", &outcome);
+        outcomes.push(outcome);
+    }
+
+    modules::logger::log_info(&format!(
+        "[Instance] 批量重启运行中实例完成: total={}, failed={}",
+        outcomes.len(),
+        outcomes.iter().filter(|item| item.error.is_some()).count()
+    ));
+    Ok(outcomes)
+}
+
+/// 临时启动实例：复制数据目录到临时目录后从副本启动，进程退出后自动删除副本
+/// 绑定账号只注入到副本中，不会修改原实例目录
+#[tauri::command]
+pub async fn launch_instance_ephemeral(
+    instance_id: String,
+) -> Result<modules::instance::EphemeralLaunchResult, String> {
+    let (source_dir, bind_account_id, extra_args, env_vars, instance) =
+        if instance_id == DEFAULT_INSTANCE_ID {
+            let default_settings = modules::instance::load_default_settings()?;
+            (
+                modules::instance::get_default_user_data_dir()?,
+                resolve_default_account_id(&default_settings),
+                modules::process::parse_extra_args(&default_settings.extra_args),
+                HashMap::new(),
+                None,
+            )
+        } else {
+            let store = modules::instance::load_instance_store()?;
+            let instance = store
+                .instances
+                .into_iter()
+                .find(|item| item.id == instance_id)
+                .ok_or("实例不存在")?;
+            (
+                std::path::PathBuf::from(&instance.user_data_dir),
+                instance.bind_account_id.clone(),
+                instance_launch_args(&instance),
+                instance.env_vars.clone(),
+                Some(instance),
+            )
+        };
+
+    let temp_dir = modules::instance::prepare_ephemeral_dir(&source_dir)?;
+    let temp_dir_str = temp_dir.to_string_lossy().to_string();
+    let launch = async {
+        if let Some(ref account_id) = bind_account_id {
+            let _ = modules::prepare_account_for_injection(account_id).await?;
+            modules::instance::inject_account_to_profile(&temp_dir, account_id)?;
+        }
+        if let Some(ref instance) = instance {
+            modules::instance::apply_instance_fingerprint(instance, &temp_dir)?;
+        }
+        modules::process::start_antigravity_with_args(&temp_dir_str, &extra_args, &env_vars)
+    };
+    let pid = match launch.await {
+        Ok(pid) => pid,
+        Err(err) => {
+            let _ = std::fs::remove_dir_all(&temp_dir);
+            return Err(err);
+        }
+    };
+
+    modules::logger::log_info(&format!(
+        "[Instance] 临时启动实例: instance_id={}, pid={}, dir={}",
+        instance_id, pid, temp_dir_str
+    ));
+    modules::instance::watch_ephemeral_exit(pid, temp_dir);
+
+    Ok(modules::instance::EphemeralLaunchResult {
+        instance_id,
+        temp_dir: temp_dir_str,
+        pid,
+    })
+}
+
+/// 预览 start_instance 将执行的完整启动命令（不实际启动）
+#[tauri::command]
+pub fn preview_launch_command(
+    instance_id: String,
+) -> Result<modules::process::LaunchCommandPreview, String> {
+    if instance_id == DEFAULT_INSTANCE_ID {
+        return modules::process::preview_antigravity_launch_command("", &[], &HashMap::new());
+    }
+
+    let store = modules::instance::load_instance_store()?;
+    let instance = store
+        .instances
+        .into_iter()
+        .find(|item| item.id == instance_id)
+        .ok_or("实例不存在")?;
+    let extra_args = instance_launch_args(&instance);
+    modules::process::preview_antigravity_launch_command(
+        &instance.user_data_dir,
+        &extra_args,
+        &instance.env_vars,
+    )
+}
+
+/// 实例最终的启动参数（含所用模板与被移除的参数）
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EffectiveArgsResult {
+    pub instance_id: String,
+    pub launch_args_template: Option<String>,
+    #[serde(flatten)]
+    pub effective: modules::process::EffectiveLaunchArgs,
+}
+
+/// 解析实例启动时实际使用的参数，与 start_instance 的解析保持一致
+#[tauri::command]
+pub fn resolve_effective_args(instance_id: String) -> Result<EffectiveArgsResult, String> {
+    if instance_id == DEFAULT_INSTANCE_ID {
+        return Ok(EffectiveArgsResult {
+            instance_id,
+            launch_args_template: None,
+            effective: modules::process::resolve_antigravity_launch_args("", &[]),
+        });
+    }
+
+    let store = modules::instance::load_instance_store()?;
+    let instance = store
+        .instances
+        .into_iter()
+        .find(|item| item.id == instance_id)
+        .ok_or("实例不存在")?;
+    let extra_args = instance_launch_args(&instance);
+    Ok(EffectiveArgsResult {
+        instance_id,
+        effective: modules::process::resolve_antigravity_launch_args(
+            &instance.user_data_dir,
+            &extra_args,
+        ),
+        launch_args_template: instance.launch_args_template,
+    })
+}
+
 #[tauri::command]
 pub async fn stop_instance(instance_id: String) -> Result<InstanceProfileView, String> {
     if instance_id == DEFAULT_INSTANCE_ID {
@@ -298,6 +714,11 @@ pub async fn stop_instance(instance_id: String) -> Result<InstanceProfileView, S
             initialized: modules::instance::is_profile_initialized(&default_dir),
             is_default: true,
             follow_local_account: default_settings.follow_local_account,
+            minimize_after_launch: false,
+            low_power: false,
+            launch_args_template: None,
+            env_vars: HashMap::new(),
+            bind_fingerprint_id: None,
         });
     }
 
@@ -322,20 +743,40 @@ pub async fn stop_instance(instance_id: String) -> Result<InstanceProfileView, S
     ))
 }
 
+/// 关闭全部实例；每个实例确认退出后发送 `instance:close_progress` 事件
 #[tauri::command]
-pub async fn close_all_instances() -> Result<(), String> {
+pub async fn close_all_instances(app: AppHandle) -> Result<(), String> {
     let store = modules::instance::load_instance_store()?;
     let default_dir = modules::instance::get_default_user_data_dir()?;
-    let mut target_dirs: Vec<String> = Vec::new();
-    target_dirs.push(default_dir.to_string_lossy().to_string());
+    let mut targets: Vec<(String, String)> = vec![(
+        DEFAULT_INSTANCE_ID.to_string(),
+        default_dir.to_string_lossy().to_string(),
+    )];
     for instance in &store.instances {
         let dir = instance.user_data_dir.trim();
         if !dir.is_empty() {
-            target_dirs.push(dir.to_string());
+            targets.push((instance.id.clone(), dir.to_string()));
         }
     }
-
-    modules::process::close_antigravity_instances(&target_dirs, 20)?;
+    let target_dirs: Vec<String> = targets.iter().map(|(_, dir)| dir.clone()).collect();
+    modules::process::close_antigravity_instances_with_progress(
+        &target_dirs,
+        20,
+        &mut |dir, remaining| {
+            let instance_id = targets
+                .iter()
+                .find(|(_, target_dir)| target_dir == dir)
+                .map(|(id, _)| id.clone())
+                .unwrap_or_default();
+            let _ = app.emit(
+                modules::process::ANTIGRAVITY_CLOSE_PROGRESS_EVENT,
+                modules::process::InstanceCloseProgress {
+                    instance_id,
+                    remaining,
+                },
+            );
+        },
+    )?;
     let _ = modules::instance::clear_all_pids();
     Ok(())
 }
@@ -372,10 +813,290 @@ pub async fn open_instance_window(instance_id: String) -> Result<(), String> {
             "定位 Antigravity 实例窗口失败，回退为启动实例: instance_id={}, err={}",
             instance.id, err
         ));
-        let extra_args = modules::process::parse_extra_args(&instance.extra_args);
-        let pid =
-            modules::process::start_antigravity_with_args(&instance.user_data_dir, &extra_args)?;
+        modules::instance::apply_instance_fingerprint(
+            &instance,
+            &std::path::PathBuf::from(&instance.user_data_dir),
+        )?;
+        let extra_args = instance_launch_args(&instance);
+        let pid = modules::process::start_antigravity_with_args(
+            &instance.user_data_dir,
+            &extra_args,
+            &instance.env_vars,
+        )?;
         let _ = modules::instance::update_instance_after_start(&instance.id, pid)?;
     }
     Ok(())
 }
+
+#[tauri::command]
+pub async fn export_instance_definitions(app: String) -> Result<String, String> {
+    modules::instance_transfer::export_instance_definitions(&app)
+}
+
+#[tauri::command]
+pub async fn import_instance_definitions(
+    app: String,
+    data: String,
+) -> Result<modules::instance_transfer::InstanceDefinitionImportResult, String> {
+    modules::instance_transfer::import_instance_definitions(&app, &data)
+}
+
+/// 完整导出实例（配置 + 数据目录），默认排除缓存目录
+#[tauri::command]
+pub async fn export_instance_full(
+    app: String,
+    instance_id: String,
+    dest: String,
+    include_caches: Option<bool>,
+) -> Result<modules::instance_transfer::InstanceFullExportResult, String> {
+    modules::instance_transfer::export_instance_full(
+        &app,
+        &instance_id,
+        &dest,
+        include_caches.unwrap_or(false),
+    )
+}
+
+#[tauri::command]
+pub async fn import_instance_full(
+    app: String,
+    archive: String,
+) -> Result<modules::instance_transfer::InstanceFullImportResult, String> {
+    modules::instance_transfer::import_instance_full(&app, &archive)
+}
+
+#[tauri::command]
+pub async fn detect_provider_dir_conflicts(
+) -> Result<Vec<modules::instance_registry::InstanceDirConflict>, String> {
+    Ok(modules::instance_registry::detect_dir_conflicts())
+}
+
+#[tauri::command]
+pub async fn save_launch_args_template(
+    name: String,
+    args: String,
+) -> Result<modules::launch_args_template::SaveLaunchArgsTemplateResult, String> {
+    modules::launch_args_template::save_template(&name, &args)
+}
+
+#[tauri::command]
+pub async fn list_launch_args_templates(
+) -> Result<Vec<modules::launch_args_template::LaunchArgsTemplate>, String> {
+    modules::launch_args_template::list_templates()
+}
+
+/// 检测是否有多个运行中的主进程共用同一用户数据目录
+#[tauri::command]
+pub async fn detect_running_dir_collisions(
+    app: String,
+) -> Result<Vec<modules::instance_registry::RunningDirCollision>, String> {
+    modules::instance_registry::detect_running_dir_collisions(&app)
+}
+
+/// 检测实例的凭据数据库是否被正在运行的编辑器锁定
+#[tauri::command]
+pub async fn is_instance_credential_db_locked(
+    app: String,
+    instance_id: String,
+) -> Result<bool, String> {
+    modules::instance_registry::is_instance_credential_db_locked(&app, &instance_id)
+}
+
+/// 列出指定平台的实例及运行状态（单次进程扫描），按最近启动时间倒序
+#[tauri::command]
+pub async fn list_instances_detailed(
+    app: String,
+) -> Result<Vec<modules::instance_registry::InstanceRuntimeInfo>, String> {
+    modules::instance_registry::list_instances_detailed(&app)
+}
+
+/// 诊断指定平台运行中进程的 user-data-dir 编码（原始值 vs 规范化值）
+#[tauri::command]
+pub async fn diagnose_path_encoding(
+    app: String,
+) -> Result<Vec<modules::process::PathEncodingDiagnosis>, String> {
+    modules::instance_registry::diagnose_path_encoding(&app)
+}
+
+/// 诊断用：列出指定平台可见的全部进程（PID、可执行路径、辅助进程判定及原因、目录）
+/// product 为平台 ID，vscode / github_copilot 视为 github-copilot
+#[tauri::command]
+pub async fn debug_list_product_processes(
+    product: String,
+) -> Result<Vec<modules::process::ProcDebugEntry>, String> {
+    let platform = modules::instance_registry::resolve_platform_alias(&product);
+    modules::instance_registry::debug_list_product_processes(platform)
+}
+
+/// 在文件管理器中打开指定实例（含默认实例）的数据目录（Codex 为 CODEX_HOME）
+/// product 为平台 ID，vscode / github_copilot 视为 github-copilot
+#[tauri::command]
+pub async fn open_instance_folder(
+    app: tauri::AppHandle,
+    product: String,
+    instance_id: String,
+) -> Result<(), String> {
+    use tauri_plugin_opener::OpenerExt;
+
+    let platform = modules::instance_registry::resolve_platform_alias(&product);
+    let dir = modules::instance_registry::resolve_instance_user_data_dir(platform, &instance_id)?;
+    if !dir.is_dir() {
+        return Err(format!(
+            "实例目录不存在: {}",
+            modules::instance_store::display_path(&dir)
+        ));
+    }
+    app.opener()
+        .open_path(dir.to_string_lossy(), None::<String>)
+        .map_err(|e| format!("打开实例目录失败: {}", e))
+}
+
+/// 列出指定平台各实例（含默认实例）的实时进程状态
+/// product 为平台 ID，vscode / github_copilot 视为 github-copilot
+#[tauri::command]
+pub async fn list_running_instances(
+    product: String,
+) -> Result<Vec<modules::instance_registry::RunningInstance>, String> {
+    let platform = modules::instance_registry::resolve_platform_alias(&product);
+    modules::instance_registry::list_running_instances(platform)
+}
+
+/// 检查指定平台的实例配置（目录缺失、名称 / 目录重复、绑定账号已删除），只读
+#[tauri::command]
+pub async fn verify_instance_store(
+    product: String,
+) -> Result<Vec<modules::instance_registry::InstanceIssue>, String> {
+    let platform = modules::instance_registry::resolve_platform_alias(&product);
+    modules::instance_registry::verify_instance_store(platform)
+}
+
+/// 移除目录已不存在的实例（先备份原实例配置）
+#[tauri::command]
+pub async fn prune_instance_store(
+    product: String,
+) -> Result<modules::instance_registry::PruneInstanceStoreResult, String> {
+    let platform = modules::instance_registry::resolve_platform_alias(&product);
+    modules::instance_registry::prune_instance_store(platform)
+}
+
+/// 识别前台窗口所属的托管实例（跨平台）并打开其数据目录
+/// 前台窗口不是受管理的实例时返回 managed=false，不视为错误
+#[tauri::command]
+pub async fn open_active_instance_folder(
+    app: tauri::AppHandle,
+) -> Result<modules::instance_registry::ActiveInstanceResult, String> {
+    use tauri_plugin_opener::OpenerExt;
+
+    let result = modules::instance_registry::identify_foreground_instance()?;
+    if let Some(ref entry) = result.instance {
+        app.opener()
+            .open_path(&entry.user_data_dir, None::<String>)
+            .map_err(|e| format!("打开实例目录失败: {}", e))?;
+    }
+    Ok(result)
+}
+
+async fn start_instance_for_platform(
+    platform: &str,
+    instance_id: String,
+) -> Result<InstanceProfileView, String> {
+    use modules::tray_layout::{
+        PLATFORM_ANTIGRAVITY, PLATFORM_CODEX, PLATFORM_GITHUB_COPILOT, PLATFORM_KIRO,
+        PLATFORM_WINDSURF,
+    };
+    match platform {
+        PLATFORM_ANTIGRAVITY => start_instance(instance_id, None).await,
+        PLATFORM_CODEX => super::codex_instance::codex_start_instance(instance_id).await,
+        PLATFORM_GITHUB_COPILOT => {
+            super::github_copilot_instance::github_copilot_start_instance(instance_id, None).await
+        }
+        PLATFORM_WINDSURF => super::windsurf_instance::windsurf_start_instance(instance_id).await,
+        PLATFORM_KIRO => super::kiro_instance::kiro_start_instance(instance_id).await,
+        other => Err(format!("未知平台: {}", other)),
+    }
+}
+
+/// 按并发上限批量启动实例，相邻两次启动之间至少间隔 BATCH_START_STAGGER
+/// 单个实例启动失败不影响其余实例；每个实例完成后发送 `instance:batch_start_progress` 事件，
+/// 返回结果与 instance_ids 顺序一致（重复的 ID 只启动一次）
+#[tauri::command]
+pub async fn start_instances_batch(
+    app: AppHandle,
+    product: String,
+    instance_ids: Vec<String>,
+    max_concurrent: usize,
+) -> Result<Vec<BatchStartOutcome>, String> {
+    let platform = modules::instance_registry::resolve_platform_alias(&product).to_string();
+    modules::instance_registry::load_instance_store(&platform)?;
+
+    let mut seen = std::collections::HashSet::new();
+    let instance_ids: Vec<String> = instance_ids
+        .into_iter()
+        .filter(|id| seen.insert(id.clone()))
+        .collect();
+    let total = instance_ids.len();
+    let max_concurrent = max_concurrent.clamp(1, BATCH_START_MAX_CONCURRENT);
+    modules::logger::log_info(&format!(
+        "[Instance] 批量启动实例: platform={}, count={}, max_concurrent={}",
+        platform, total, max_concurrent
+    ));
+
+    let semaphore = std::sync::Arc::new(tokio::sync::Semaphore::new(max_concurrent));
+    let completed = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+    let mut tasks = Vec::with_capacity(total);
+    for (index, instance_id) in instance_ids.into_iter().enumerate() {
+        let permit = semaphore
+            .clone()
+            .acquire_owned()
+            .await
+            .map_err(|e| format!("获取启动队列失败: {}", e))?;
+        if index > 0 {
+            tokio::time::sleep(BATCH_START_STAGGER).await;
+        }
+
+        let app = app.clone();
+        let platform = platform.clone();
+        let completed = completed.clone();
+        let task_instance_id = instance_id.clone();
+        let handle = tauri::async_runtime::spawn(async move {
+            let result = start_instance_for_platform(&platform, instance_id.clone()).await;
+            drop(permit);
+            let outcome = match result {
+                Ok(view) => BatchStartOutcome {
+                    instance_id,
+                    pid: view.last_pid,
+                    error: None,
+                },
+                Err(err) => {
+                    modules::logger::log_warn(&format!(
+                        "[Instance] 批量启动中实例启动失败: platform={}, id={}, err={}",
+                        platform, instance_id, err
+                    ));
+                    BatchStartOutcome {
+                        instance_id,
+                        pid: None,
+                        error: Some(err),
+                    }
+                }
+            };
+            let progress = BatchStartProgress {
+                outcome: outcome.clone(),
+                completed: completed.fetch_add(1, std::sync::atomic::Ordering::SeqCst) + 1,
+                total,
+            };
+            let _ = app.emit(INSTANCE_BATCH_START_PROGRESS_EVENT, &progress);
+            outcome
+        });
+        tasks.push((task_instance_id, handle));
+    }
+
+    let mut outcomes = Vec::with_capacity(total);
+    for (instance_id, handle) in tasks {
+        outcomes.push(handle.await.unwrap_or_else(|e| BatchStartOutcome {
+            instance_id,
+            pid: None,
+            error: Some(format!("启动任务异常退出: {}", e)),
+        }));
+    }
+    Ok(outcomes)
+}
@@ -1,7 +1,10 @@
 use std::time::Instant;
 use tauri::{AppHandle, Emitter};
 
-use crate::models::kiro::{KiroAccount, KiroOAuthStartResponse};
+use crate::models::kiro::{
+    KiroAccount, KiroBatchRefreshResult, KiroLocalFilesReport, KiroOAuthStartResponse,
+    KiroUsageSummary,
+};
 use crate::modules::{kiro_account, kiro_oauth, logger};
 
 #[tauri::command]
@@ -24,8 +27,29 @@ pub fn import_kiro_from_json(json_content: String) -> Result<Vec<KiroAccount>, S
     kiro_account::import_from_json(&json_content)
 }
 
+#[tauri::command]
+pub fn validate_kiro_local_files() -> Result<KiroLocalFilesReport, String> {
+    Ok(kiro_oauth::validate_local_files())
+}
+
+/// 解析一份原始用量 JSON（用于排查解析问题，不访问磁盘与网络）
+#[tauri::command]
+pub fn parse_kiro_usage(raw: serde_json::Value) -> Result<KiroUsageSummary, String> {
+    Ok(kiro_oauth::parse_usage_summary(&raw))
+}
+
 #[tauri::command]
 pub async fn import_kiro_from_local() -> Result<Vec<KiroAccount>, String> {
+    let report = kiro_oauth::validate_local_files();
+    if !report.ready {
+        let detail = report.issues.join("; ");
+        let message = report.message.unwrap_or_default();
+        return Err(if detail.is_empty() {
+            message
+        } else {
+            format!("{}（{}）", message, detail)
+        });
+    }
     let payload = kiro_oauth::build_payload_from_local_files()?;
     let payload = kiro_oauth::enrich_payload_with_runtime_usage(payload).await;
     let account = kiro_account::upsert_account(payload)?;
@@ -39,6 +63,7 @@ pub fn export_kiro_accounts(account_ids: Vec<String>) -> Result<String, String>
 
 #[tauri::command]
 pub async fn refresh_kiro_token(app: AppHandle, account_id: String) -> Result<KiroAccount, String> {
+    crate::modules::fault_injection::check("refresh")?;
     let started_at = Instant::now();
     logger::log_info(&format!(
         "[Kiro Command] 手动刷新账号开始: account_id={}",
@@ -73,6 +98,7 @@ pub async fn refresh_kiro_token(app: AppHandle, account_id: String) -> Result<Ki
 
 #[tauri::command]
 pub async fn refresh_all_kiro_tokens(app: AppHandle) -> Result<i32, String> {
+    crate::modules::fault_injection::check("refresh")?;
     let started_at = Instant::now();
     logger::log_info("[Kiro Command] 手动批量刷新开始");
 
@@ -97,6 +123,57 @@ pub async fn refresh_all_kiro_tokens(app: AppHandle) -> Result<i32, String> {
     Ok(success_count as i32)
 }
 
+/// 批量刷新所有 Kiro 账号（有限并发），通过 `kiro:refresh_progress` 事件逐账号推送进度
+#[tauri::command]
+pub async fn refresh_all_kiro_accounts(
+    app: AppHandle,
+    max_concurrent: Option<usize>,
+) -> Result<KiroBatchRefreshResult, String> {
+    crate::modules::fault_injection::check("refresh")?;
+    let started_at = Instant::now();
+    let max_concurrent = kiro_account::resolve_batch_refresh_concurrency(max_concurrent);
+    logger::log_info(&format!(
+        "[Kiro Command] 批量刷新账号开始: max_concurrent={}",
+        max_concurrent
+    ));
+
+    let progress_app = app.clone();
+    let (results, skipped_banned) =
+        kiro_account::refresh_all_tokens_with_progress(max_concurrent, move |progress| {
+            let _ = progress_app.emit("kiro:refresh_progress", &progress);
+        })
+        .await?;
+
+    let success = results.iter().filter(|(_, item)| item.is_ok()).count();
+    let errors: Vec<String> = results
+        .iter()
+        .filter_map(|(id, item)| item.as_ref().err().map(|err| format!("{}: {}", id, err)))
+        .collect();
+
+    logger::log_info(&format!(
+        "[Kiro Command] 批量刷新账号完成: success={}, failed={}, skipped_banned={}, elapsed={}ms",
+        success,
+        errors.len(),
+        skipped_banned,
+        started_at.elapsed().as_millis()
+    ));
+
+    if success > 0 {
+        if let Err(e) = kiro_account::run_quota_alert_if_needed() {
+            logger::log_warn(&format!("[QuotaAlert][Kiro] 全量刷新后预警检查失败: {}", e));
+        }
+    }
+
+    let _ = crate::modules::tray::update_tray_menu(&app);
+    Ok(KiroBatchRefreshResult {
+        total: results.len() + skipped_banned,
+        success,
+        failed: errors.len(),
+        skipped_banned,
+        errors,
+    })
+}
+
 #[tauri::command]
 pub async fn kiro_oauth_login_start() -> Result<KiroOAuthStartResponse, String> {
     logger::log_info("Kiro OAuth start 命令触发");
@@ -181,9 +258,10 @@ pub async fn inject_kiro_to_vscode(app: AppHandle, account_id: String) -> Result
     {
         Ok(_) => None,
         Err(err) => {
-            if err.starts_with("APP_PATH_NOT_FOUND:") || err.contains("启动 Kiro 失败") {
+            if crate::modules::process::is_app_path_error(&err) || err.contains("启动 Kiro 失败")
+            {
                 logger::log_warn(&format!("Kiro 默认实例启动失败: {}", err));
-                if err.starts_with("APP_PATH_NOT_FOUND:") {
+                if crate::modules::process::is_app_path_error(&err) {
                     let _ = app.emit(
                         "app:path_missing",
                         serde_json::json!({ "app": "kiro", "retry": { "kind": "default" } }),
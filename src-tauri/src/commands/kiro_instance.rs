@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::path::Path;
 
 use crate::models::{DefaultInstanceSettings, InstanceProfileView};
@@ -92,12 +93,18 @@ pub async fn kiro_list_instances() -> Result<Vec<InstanceProfileView>, String> {
         initialized: is_profile_initialized(&default_dir.to_string_lossy()),
         is_default: true,
         follow_local_account: false,
+        minimize_after_launch: false,
+        low_power: false,
+        launch_args_template: None,
+        env_vars: HashMap::new(),
+        bind_fingerprint_id: None,
     });
 
     Ok(result)
 }
 
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn kiro_create_instance(
     name: String,
     user_data_dir: String,
@@ -105,7 +112,16 @@ pub async fn kiro_create_instance(
     bind_account_id: Option<String>,
     copy_source_instance_id: Option<String>,
     init_mode: Option<String>,
+    launch_args_template: Option<String>,
+    follow_launch_args_template: Option<bool>,
 ) -> Result<InstanceProfileView, String> {
+    let (extra_args, template) = modules::launch_args_template::resolve_template_reference(
+        modules::tray_layout::PLATFORM_KIRO,
+        None,
+        extra_args,
+        Some(launch_args_template),
+        follow_launch_args_template.unwrap_or(false),
+    )?;
     let instance =
         modules::kiro_instance::create_instance(modules::kiro_instance::CreateInstanceParams {
             name,
@@ -114,6 +130,9 @@ pub async fn kiro_create_instance(
             bind_account_id,
             copy_source_instance_id,
             init_mode,
+            launch_args_template: template.flatten(),
+            env_vars: HashMap::new(),
+            bind_fingerprint_id: None,
         })?;
 
     let initialized = is_profile_initialized(&instance.user_data_dir);
@@ -125,13 +144,25 @@ pub async fn kiro_create_instance(
 }
 
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn kiro_update_instance(
     instance_id: String,
     name: Option<String>,
     extra_args: Option<String>,
     bind_account_id: Option<Option<String>>,
     follow_local_account: Option<bool>,
+    minimize_after_launch: Option<bool>,
+    launch_args_template: Option<Option<String>>,
+    follow_launch_args_template: Option<bool>,
 ) -> Result<InstanceProfileView, String> {
+    let (extra_args, launch_args_template) =
+        modules::launch_args_template::resolve_template_reference(
+            modules::tray_layout::PLATFORM_KIRO,
+            Some(&instance_id),
+            extra_args,
+            launch_args_template,
+            follow_launch_args_template.unwrap_or(false),
+        )?;
     if instance_id == DEFAULT_INSTANCE_ID {
         let default_dir = modules::kiro_instance::get_default_kiro_user_data_dir()?;
         let default_dir_str = default_dir.to_string_lossy().to_string();
@@ -159,6 +190,11 @@ pub async fn kiro_update_instance(
             initialized: is_profile_initialized(&default_dir.to_string_lossy()),
             is_default: true,
             follow_local_account: false,
+            minimize_after_launch: false,
+            low_power: false,
+            launch_args_template: None,
+            env_vars: HashMap::new(),
+            bind_fingerprint_id: None,
         });
     }
 
@@ -184,6 +220,12 @@ pub async fn kiro_update_instance(
             name,
             extra_args,
             bind_account_id,
+            minimize_after_launch,
+            low_power: None,
+            launch_args_template,
+            env_vars: None,
+            bind_fingerprint_id: None,
+            follow_local_account: None,
         })?;
 
     let running = instance
@@ -210,6 +252,7 @@ pub async fn kiro_delete_instance(instance_id: String) -> Result<(), String> {
 
 #[tauri::command]
 pub async fn kiro_start_instance(instance_id: String) -> Result<InstanceProfileView, String> {
+    crate::modules::fault_injection::check("launch")?;
     modules::logger::log_info(&format!("开始启动 Kiro 实例: {}", instance_id));
 
     if instance_id == DEFAULT_INSTANCE_ID {
@@ -232,7 +275,7 @@ pub async fn kiro_start_instance(instance_id: String) -> Result<InstanceProfileV
         let extra_args = modules::process::parse_extra_args(&default_settings.extra_args);
         let pid = modules::kiro_instance::start_kiro_default_with_args_with_new_window(
             &extra_args,
-            true,
+            modules::process::resolve_use_new_window("kiro", &extra_args, true),
         )?;
         let _ = modules::kiro_instance::update_default_pid(Some(pid))?;
 
@@ -250,6 +293,11 @@ pub async fn kiro_start_instance(instance_id: String) -> Result<InstanceProfileV
             initialized: is_profile_initialized(&default_dir.to_string_lossy()),
             is_default: true,
             follow_local_account: false,
+            minimize_after_launch: false,
+            low_power: false,
+            launch_args_template: None,
+            env_vars: HashMap::new(),
+            bind_fingerprint_id: None,
         });
     }
 
@@ -277,9 +325,12 @@ pub async fn kiro_start_instance(instance_id: String) -> Result<InstanceProfileV
     let pid = modules::kiro_instance::start_kiro_with_args_with_new_window(
         &instance.user_data_dir,
         &extra_args,
-        true,
+        modules::process::resolve_use_new_window("kiro", &extra_args, true),
     )?;
     let updated = modules::kiro_instance::update_instance_after_start(&instance.id, pid)?;
+    if updated.minimize_after_launch {
+        modules::process::minimize_window_after_launch(pid);
+    }
 
     let running =
         modules::kiro_instance::resolve_kiro_pid(Some(pid), Some(&updated.user_data_dir)).is_some();
@@ -322,6 +373,11 @@ pub async fn kiro_stop_instance(instance_id: String) -> Result<InstanceProfileVi
             initialized: is_profile_initialized(&default_dir.to_string_lossy()),
             is_default: true,
             follow_local_account: false,
+            minimize_after_launch: false,
+            low_power: false,
+            launch_args_template: None,
+            env_vars: HashMap::new(),
+            bind_fingerprint_id: None,
         });
     }
 
@@ -3,10 +3,13 @@ use crate::modules;
 use tauri::AppHandle;
 
 #[tauri::command]
-pub async fn start_oauth_login(app_handle: AppHandle) -> Result<models::Account, String> {
+pub async fn start_oauth_login(
+    app_handle: AppHandle,
+    callback_paths: Option<Vec<String>>,
+) -> Result<models::Account, String> {
     modules::logger::log_info("开始 OAuth 授权流程...");
 
-    let token_res = modules::oauth_server::start_oauth_flow(app_handle.clone())
+    let token_res = modules::oauth_server::start_oauth_flow(app_handle.clone(), callback_paths)
         .await
         .map_err(|e| {
             modules::logger::log_error(&format!("OAuth 流程失败: {}", e));
@@ -69,10 +72,13 @@ pub async fn start_oauth_login(app_handle: AppHandle) -> Result<models::Account,
 }
 
 #[tauri::command]
-pub async fn complete_oauth_login(app_handle: AppHandle) -> Result<models::Account, String> {
+pub async fn complete_oauth_login(
+    app_handle: AppHandle,
+    callback_paths: Option<Vec<String>>,
+) -> Result<models::Account, String> {
     modules::logger::log_info("完成 OAuth 授权流程...");
 
-    let token_res = modules::oauth_server::complete_oauth_flow(app_handle.clone())
+    let token_res = modules::oauth_server::complete_oauth_flow(app_handle.clone(), callback_paths)
         .await
         .map_err(|e| {
             modules::logger::log_error(&format!("OAuth 流程失败: {}", e));
@@ -132,9 +138,13 @@ pub async fn complete_oauth_login(app_handle: AppHandle) -> Result<models::Accou
     Ok(account)
 }
 
+/// 预生成 OAuth 授权链接，返回实际使用的回调端口与路径
 #[tauri::command]
-pub async fn prepare_oauth_url(app_handle: AppHandle) -> Result<String, String> {
-    modules::oauth_server::prepare_oauth_url(app_handle).await
+pub async fn prepare_oauth_url(
+    app_handle: AppHandle,
+    callback_paths: Option<Vec<String>>,
+) -> Result<modules::oauth_server::OAuthFlowStartResponse, String> {
+    modules::oauth_server::prepare_oauth_url(app_handle, callback_paths).await
 }
 
 #[tauri::command]
@@ -142,3 +152,45 @@ pub async fn cancel_oauth_login() -> Result<(), String> {
     modules::oauth_server::cancel_oauth_flow();
     Ok(())
 }
+
+/// 取消所有平台进行中的 OAuth 登录并释放各自的本地回调端口
+#[tauri::command]
+pub async fn cancel_all_oauth_logins() -> Result<(), String> {
+    modules::oauth_server::cancel_oauth_flow();
+    let results = [
+        ("codex", modules::codex_oauth::cancel_oauth_flow_for(None)),
+        (
+            "github-copilot",
+            modules::github_copilot_oauth::cancel_login(None),
+        ),
+        ("windsurf", modules::windsurf_oauth::cancel_login(None)),
+        ("kiro", modules::kiro_oauth::cancel_login(None)),
+    ];
+    for (platform, result) in results {
+        if let Err(err) = result {
+            modules::logger::log_warn(&format!(
+                "[OAuth] 取消登录失败: platform={}, err={}",
+                platform, err
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// 预检各平台 OAuth 回调端口，报告被其他进程占用的端口及占用者
+#[tauri::command]
+pub async fn precheck_oauth_ports() -> Result<Vec<modules::oauth_server::OAuthPortStatus>, String> {
+    Ok(modules::oauth_server::precheck_oauth_ports())
+}
+
+/// 清理各平台失效的挂起登录，返回有会话被清理的平台 ID
+#[tauri::command]
+pub async fn clear_stale_oauth_state() -> Result<Vec<String>, String> {
+    Ok(modules::oauth_server::clear_stale_oauth_states())
+}
+
+/// 关闭所有平台残留的 OAuth 回调服务，返回已关闭的平台 ID
+#[tauri::command]
+pub async fn shutdown_oauth_servers() -> Result<Vec<String>, String> {
+    Ok(modules::oauth_server::shutdown_all_oauth_servers().await)
+}
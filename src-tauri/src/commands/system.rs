@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use tauri::Manager;
 
 use crate::modules;
@@ -16,6 +17,8 @@ pub struct NetworkConfig {
     pub actual_port: Option<u16>,
     /// 默认端口
     pub default_port: u16,
+    /// 出站 HTTP 代理设置
+    pub proxy: config::ProxyConfig,
 }
 
 /// 通用设置配置（前端使用）
@@ -77,6 +80,24 @@ pub struct GeneralConfig {
     pub kiro_quota_alert_enabled: bool,
     /// Kiro 配额预警阈值（百分比）
     pub kiro_quota_alert_threshold: i32,
+    /// 启动时是否合并共享同步文件中的配置
+    pub sync_on_startup_enabled: bool,
+    /// 切换账号时是否自动应用绑定指纹
+    pub auto_apply_fingerprint_on_switch: bool,
+    /// 各应用默认窗口模式（reuse / new）
+    pub default_window_modes: HashMap<String, String>,
+    /// Kiro OAuth 回调候选端口
+    pub kiro_oauth_ports: Vec<u16>,
+    /// VS Code 优雅退出等待秒数（1-30）
+    pub vscode_graceful_wait_secs: u64,
+    /// Antigravity 优雅退出等待秒数（1-30）
+    pub antigravity_graceful_wait_secs: u64,
+    /// 后台定时刷新配额的间隔（分钟），0 表示关闭
+    pub quota_auto_refresh_minutes: u32,
+    /// 辅助进程识别关键字（为空时使用内置列表）
+    pub process_helper_exclude_keywords: Vec<String>,
+    /// 唤醒模型列表缓存有效期（分钟）
+    pub wakeup_models_cache_minutes: u32,
 }
 
 #[tauri::command]
@@ -128,6 +149,89 @@ pub fn get_downloads_dir() -> Result<String, String> {
     Err("无法获取下载目录".to_string())
 }
 
+/// 导出所有账号的配额报告（json / csv）到下载目录，返回文件路径
+#[tauri::command]
+pub async fn export_quota_report(
+    format: String,
+    refresh_first: Option<bool>,
+) -> Result<String, String> {
+    if refresh_first.unwrap_or(false) {
+        modules::quota_report::refresh_all_platform_quotas().await;
+    }
+    modules::quota_report::export_quota_report(&format)
+}
+
+/// 按缓存配额为账号打受管理标签（auto:low-quota / auto:exhausted）
+#[tauri::command]
+pub async fn auto_tag_by_quota(
+    threshold_percent: i32,
+    refresh_first: Option<bool>,
+) -> Result<modules::quota_tags::AutoTagResult, String> {
+    if refresh_first.unwrap_or(false) {
+        modules::quota_report::refresh_all_platform_quotas().await;
+    }
+    Ok(modules::quota_tags::auto_tag_by_quota(threshold_percent))
+}
+
+/// 获取所有平台及其功能矩阵
+#[tauri::command]
+pub fn get_providers() -> Result<Vec<modules::providers::ProviderInfo>, String> {
+    Ok(modules::providers::get_providers())
+}
+
+/// 获取启动报告（各初始化步骤的结果）
+#[tauri::command]
+pub fn get_startup_report() -> Result<modules::startup_report::StartupReport, String> {
+    Ok(modules::startup_report::get_startup_report())
+}
+
+/// 获取最近一次崩溃记录（仅读取本地文件，供用户手动反馈）
+#[tauri::command]
+pub fn get_last_crash_log() -> Result<Option<String>, String> {
+    modules::crash_log::get_last_crash_log()
+}
+
+/// 是否以安全模式启动（供前端显示提示横幅）
+#[tauri::command]
+pub fn is_safe_mode() -> Result<bool, String> {
+    Ok(modules::safe_mode::is_safe_mode())
+}
+
+/// 添加账号前预检 Token：返回是否可用、所属账号与配额预览
+#[tauri::command]
+pub async fn probe_token(
+    provider: String,
+    token: String,
+) -> Result<modules::token_probe::TokenProbeResult, String> {
+    Ok(modules::token_probe::probe_token(&provider, &token).await)
+}
+
+/// 一次性获取所有平台的当前账号（会修复指向已删除账号的悬空指针）
+#[tauri::command]
+pub async fn get_all_current_accounts(
+) -> Result<HashMap<String, modules::current_accounts::CurrentAccountSummary>, String> {
+    Ok(modules::current_accounts::get_all_current_accounts())
+}
+
+/// 清除所有平台的当前账号指针
+#[tauri::command]
+pub async fn clear_all_current_accounts(
+) -> Result<HashMap<String, modules::current_accounts::CurrentAccountSummary>, String> {
+    modules::current_accounts::clear_all_current_accounts()
+}
+
+/// 获取 WebSocket 服务运行状态（是否已绑定端口、连接数、最近一次错误）
+#[tauri::command]
+pub fn websocket_status() -> Result<websocket::WebSocketStatus, String> {
+    Ok(websocket::get_status())
+}
+
+/// 停止 WebSocket 服务并在指定端口（未指定时使用配置端口）重新启动，返回实际绑定的端口
+#[tauri::command]
+pub async fn restart_websocket_server(port: Option<u16>) -> Result<u16, String> {
+    websocket::restart_server(port).await
+}
+
 /// 获取网络服务配置
 #[tauri::command]
 pub fn get_network_config() -> Result<NetworkConfig, String> {
@@ -139,14 +243,26 @@ pub fn get_network_config() -> Result<NetworkConfig, String> {
         ws_port: user_config.ws_port,
         actual_port,
         default_port: DEFAULT_WS_PORT,
+        proxy: modules::http::mask_proxy_password(user_config.proxy),
     })
 }
 
 /// 保存网络服务配置
 #[tauri::command]
-pub fn save_network_config(ws_enabled: bool, ws_port: u16) -> Result<bool, String> {
+pub fn save_network_config(
+    ws_enabled: bool,
+    ws_port: u16,
+    proxy: Option<config::ProxyConfig>,
+) -> Result<bool, String> {
     let current = config::get_user_config();
+    // 代理设置在下次创建 HTTP 客户端时生效，无需重启
     let needs_restart = current.ws_port != ws_port || current.ws_enabled != ws_enabled;
+    let proxy = match proxy {
+        Some(proxy) => modules::http::normalize_proxy_config(
+            modules::http::unmask_proxy_password(proxy, &current.proxy),
+        )?,
+        None => current.proxy.clone(),
+    };
 
     let new_config = UserConfig {
         ws_enabled,
@@ -180,6 +296,17 @@ pub fn save_network_config(ws_enabled: bool, ws_port: u16) -> Result<bool, Strin
         windsurf_quota_alert_threshold: current.windsurf_quota_alert_threshold,
         kiro_quota_alert_enabled: current.kiro_quota_alert_enabled,
         kiro_quota_alert_threshold: current.kiro_quota_alert_threshold,
+        sync_on_startup_enabled: current.sync_on_startup_enabled,
+        auto_apply_fingerprint_on_switch: current.auto_apply_fingerprint_on_switch,
+        default_window_modes: current.default_window_modes,
+        kiro_oauth_ports: current.kiro_oauth_ports,
+        window_geometry: current.window_geometry,
+        vscode_graceful_wait_secs: current.vscode_graceful_wait_secs,
+        antigravity_graceful_wait_secs: current.antigravity_graceful_wait_secs,
+        quota_auto_refresh_minutes: current.quota_auto_refresh_minutes,
+        process_helper_exclude_keywords: current.process_helper_exclude_keywords,
+        wakeup_models_cache_minutes: current.wakeup_models_cache_minutes,
+        proxy,
     };
 
     config::save_user_config(&new_config)?;
@@ -227,6 +354,15 @@ pub fn get_general_config() -> Result<GeneralConfig, String> {
         windsurf_quota_alert_threshold: user_config.windsurf_quota_alert_threshold,
         kiro_quota_alert_enabled: user_config.kiro_quota_alert_enabled,
         kiro_quota_alert_threshold: user_config.kiro_quota_alert_threshold,
+        sync_on_startup_enabled: user_config.sync_on_startup_enabled,
+        auto_apply_fingerprint_on_switch: user_config.auto_apply_fingerprint_on_switch,
+        default_window_modes: user_config.default_window_modes,
+        kiro_oauth_ports: user_config.kiro_oauth_ports,
+        vscode_graceful_wait_secs: user_config.vscode_graceful_wait_secs,
+        antigravity_graceful_wait_secs: user_config.antigravity_graceful_wait_secs,
+        quota_auto_refresh_minutes: user_config.quota_auto_refresh_minutes,
+        process_helper_exclude_keywords: user_config.process_helper_exclude_keywords,
+        wakeup_models_cache_minutes: user_config.wakeup_models_cache_minutes,
     })
 }
 
@@ -262,6 +398,15 @@ pub fn save_general_config(
     windsurf_quota_alert_threshold: Option<i32>,
     kiro_quota_alert_enabled: Option<bool>,
     kiro_quota_alert_threshold: Option<i32>,
+    sync_on_startup_enabled: Option<bool>,
+    auto_apply_fingerprint_on_switch: Option<bool>,
+    default_window_modes: Option<HashMap<String, String>>,
+    kiro_oauth_ports: Option<Vec<u16>>,
+    vscode_graceful_wait_secs: Option<u64>,
+    antigravity_graceful_wait_secs: Option<u64>,
+    quota_auto_refresh_minutes: Option<u32>,
+    process_helper_exclude_keywords: Option<Vec<String>>,
+    wakeup_models_cache_minutes: Option<u32>,
 ) -> Result<(), String> {
     let current = config::get_user_config();
     let normalized_opencode_path = opencode_app_path.trim().to_string();
@@ -330,6 +475,42 @@ pub fn save_general_config(
             .unwrap_or(current.kiro_quota_alert_enabled),
         kiro_quota_alert_threshold: kiro_quota_alert_threshold
             .unwrap_or(current.kiro_quota_alert_threshold),
+        sync_on_startup_enabled: sync_on_startup_enabled.unwrap_or(current.sync_on_startup_enabled),
+        auto_apply_fingerprint_on_switch: auto_apply_fingerprint_on_switch
+            .unwrap_or(current.auto_apply_fingerprint_on_switch),
+        default_window_modes: default_window_modes
+            .map(config::normalize_window_modes)
+            .unwrap_or(current.default_window_modes),
+        kiro_oauth_ports: kiro_oauth_ports
+            .map(config::normalize_kiro_oauth_ports)
+            .unwrap_or(current.kiro_oauth_ports),
+        window_geometry: current.window_geometry,
+        vscode_graceful_wait_secs: vscode_graceful_wait_secs
+            .map(|secs| {
+                secs.clamp(
+                    config::GRACEFUL_WAIT_SECS_MIN,
+                    config::GRACEFUL_WAIT_SECS_MAX,
+                )
+            })
+            .unwrap_or(current.vscode_graceful_wait_secs),
+        antigravity_graceful_wait_secs: antigravity_graceful_wait_secs
+            .map(|secs| {
+                secs.clamp(
+                    config::GRACEFUL_WAIT_SECS_MIN,
+                    config::GRACEFUL_WAIT_SECS_MAX,
+                )
+            })
+            .unwrap_or(current.antigravity_graceful_wait_secs),
+        quota_auto_refresh_minutes: quota_auto_refresh_minutes
+            .map(|minutes| minutes.min(config::QUOTA_AUTO_REFRESH_MAX_MINUTES))
+            .unwrap_or(current.quota_auto_refresh_minutes),
+        process_helper_exclude_keywords: process_helper_exclude_keywords
+            .map(config::normalize_helper_keywords)
+            .unwrap_or(current.process_helper_exclude_keywords),
+        wakeup_models_cache_minutes: wakeup_models_cache_minutes
+            .map(|minutes| minutes.min(config::WAKEUP_MODELS_CACHE_MAX_MINUTES))
+            .unwrap_or(current.wakeup_models_cache_minutes),
+        proxy: current.proxy,
     };
 
     config::save_user_config(&new_config)?;
@@ -343,6 +524,7 @@ pub fn save_general_config(
         // 但为了减少写入，可以检查是否有客户端连接
         // 这里简化处理：总是写入，插件端启动时会比较时间戳
         modules::sync_settings::write_sync_setting("language", &normalized_language);
+        modules::sync_settings::record_local_modified("language");
 
         // 仅在语言变更时刷新托盘菜单，避免无关配置触发托盘重建
         if let Err(err) = modules::tray::update_tray_menu(&app) {
@@ -382,6 +564,21 @@ pub fn set_app_path(app: String, path: String) -> Result<(), String> {
     Ok(())
 }
 
+/// 获取正在运行的 Antigravity 进程路径与架构（诊断 Rosetta 下窗口定位问题）
+#[tauri::command]
+pub fn get_antigravity_runtime_info() -> Result<modules::process::AntigravityRuntimeInfo, String> {
+    Ok(modules::process::get_antigravity_runtime_info())
+}
+
+/// 保存前校验应用启动路径
+#[tauri::command]
+pub fn validate_app_path(
+    app: String,
+    path: String,
+) -> Result<modules::process::AppPathInfo, String> {
+    modules::process::validate_app_path(&app, &path)
+}
+
 #[tauri::command]
 pub fn detect_app_path(app: String, force: Option<bool>) -> Result<Option<String>, String> {
     let force = force.unwrap_or(false);
@@ -397,6 +594,12 @@ pub fn detect_app_path(app: String, force: Option<bool>) -> Result<Option<String
     }
 }
 
+/// 检查各应用已配置的启动路径是否仍然有效
+#[tauri::command]
+pub fn audit_app_paths() -> Result<Vec<modules::process::AppPathAudit>, String> {
+    Ok(modules::process::audit_app_paths())
+}
+
 /// 通知插件关闭/开启唤醒功能（互斥）
 #[tauri::command]
 pub fn set_wakeup_override(enabled: bool) -> Result<(), String> {
@@ -453,6 +656,39 @@ pub fn handle_window_close(
     Ok(())
 }
 
+/// 保存配额定时刷新间隔（分钟，0 表示关闭），后台调度在下一轮检查时生效
+#[tauri::command]
+pub fn save_quota_refresh_settings(minutes: u32) -> Result<(), String> {
+    let current = config::get_user_config();
+    let minutes = minutes.min(config::QUOTA_AUTO_REFRESH_MAX_MINUTES);
+    let new_config = UserConfig {
+        quota_auto_refresh_minutes: minutes,
+        ..current
+    };
+    config::save_user_config(&new_config)?;
+    modules::logger::log_info(&format!(
+        "[QuotaScheduler] 已保存配额定时刷新间隔: {} 分钟",
+        minutes
+    ));
+    Ok(())
+}
+
+/// 设置本次运行期间的关闭行为（不写入配置，应用重启后失效）
+/// behavior: "ask" | "minimize" | "quit"，为空时恢复使用已保存的设置
+#[tauri::command]
+pub fn set_session_close_behavior(behavior: Option<String>) -> Result<(), String> {
+    let behavior = match behavior.as_deref() {
+        None => None,
+        Some("ask") => Some(CloseWindowBehavior::Ask),
+        Some("minimize") => Some(CloseWindowBehavior::Minimize),
+        Some("quit") => Some(CloseWindowBehavior::Quit),
+        Some(other) => return Err(format!("无效的关闭行为: {}", other)),
+    };
+    modules::logger::log_info(&format!("[Window] 本次运行的关闭行为: {:?}", behavior));
+    config::set_session_close_behavior(behavior);
+    Ok(())
+}
+
 /// 打开指定文件夹（如不存在则创建）
 #[tauri::command]
 pub async fn open_folder(path: String) -> Result<(), String> {
@@ -490,6 +726,47 @@ pub async fn open_folder(path: String) -> Result<(), String> {
     Ok(())
 }
 
+/// 检测当前平台依赖的外部工具（wmctrl、xdotool、osascript 等）是否已安装
+#[tauri::command]
+pub fn check_external_tools() -> Result<Vec<modules::process::ExternalToolStatus>, String> {
+    Ok(modules::process::check_external_tools())
+}
+
+/// 调试用：让指定操作（switch / refresh / launch）稳定失败，mode 为 always 或 once
+#[tauri::command]
+pub fn set_fault_injection(operation: String, mode: String) -> Result<(), String> {
+    modules::fault_injection::set_fault(&operation, &mode)
+}
+
+/// 调试用：清除所有故障注入
+#[tauri::command]
+pub fn clear_fault_injection() -> Result<(), String> {
+    modules::fault_injection::clear_faults();
+    Ok(())
+}
+
+/// 检查数据目录及关键文件对当前用户是否可读写
+#[tauri::command]
+pub fn check_data_dir_permissions(
+) -> Result<modules::data_permissions::DataDirPermissionReport, String> {
+    modules::data_permissions::check_data_dir_permissions()
+}
+
+/// 修复数据目录权限（目录 0700，含令牌文件 0600）
+#[tauri::command]
+pub fn fix_data_dir_permissions(
+) -> Result<modules::data_permissions::DataDirPermissionReport, String> {
+    modules::data_permissions::fix_data_dir_permissions()
+}
+
+/// 扫描数据文件完整性并自动修复可安全修复的问题
+#[tauri::command]
+pub async fn run_integrity_scan() -> Result<modules::integrity::IntegrityReport, String> {
+    tauri::async_runtime::spawn_blocking(modules::integrity::run_integrity_scan)
+        .await
+        .map_err(|e| format!("完整性扫描失败: {}", e))
+}
+
 /// 删除损坏的文件（会先备份）
 #[tauri::command]
 pub async fn delete_corrupted_file(path: String) -> Result<(), String> {
@@ -1,4 +1,7 @@
+use tauri::AppHandle;
+
 use crate::modules::update_checker::{self, UpdateInfo, UpdateSettings};
+use crate::modules::{app_relaunch, config, logger};
 
 /// Check for updates from GitHub
 #[tauri::command]
@@ -30,3 +33,23 @@ pub fn get_update_settings() -> Result<UpdateSettings, String> {
 pub fn save_update_settings(settings: UpdateSettings) -> Result<(), String> {
     update_checker::save_update_settings(&settings)
 }
+
+/// Relaunch the app after an update is installed
+/// 可选先关闭全部 Antigravity 实例；短时间内重复调用会被拒绝
+#[tauri::command]
+pub async fn relaunch_app(app: AppHandle, close_instances: Option<bool>) -> Result<(), String> {
+    app_relaunch::begin_relaunch()?;
+    if close_instances.unwrap_or(false) {
+        if let Err(err) = crate::commands::instance::close_all_instances(app.clone()).await {
+            app_relaunch::abort_relaunch();
+            return Err(err);
+        }
+    }
+    config::flush_window_geometry();
+    logger::log_info("[Update] 正在重启应用");
+
+    // 先释放单实例锁，避免新进程被当作重复实例直接退出
+    tauri_plugin_single_instance::destroy(&app);
+    app.request_restart();
+    Ok(())
+}
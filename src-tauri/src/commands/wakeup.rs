@@ -14,8 +14,10 @@ pub async fn trigger_wakeup(
 }
 
 #[tauri::command]
-pub async fn fetch_available_models() -> Result<Vec<modules::wakeup::AvailableModel>, String> {
-    modules::wakeup::fetch_available_models().await
+pub async fn fetch_available_models(
+    force: Option<bool>,
+) -> Result<modules::wakeup::AvailableModelsResult, String> {
+    modules::wakeup::fetch_available_models(force.unwrap_or(false)).await
 }
 
 #[tauri::command]
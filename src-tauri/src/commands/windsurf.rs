@@ -49,6 +49,7 @@ pub async fn refresh_windsurf_token(
     app: AppHandle,
     account_id: String,
 ) -> Result<WindsurfAccount, String> {
+    crate::modules::fault_injection::check("refresh")?;
     let started_at = Instant::now();
     logger::log_info(&format!(
         "[Windsurf Command] 手动刷新账号开始: account_id={}",
@@ -82,6 +83,7 @@ pub async fn refresh_windsurf_token(
 
 #[tauri::command]
 pub async fn refresh_all_windsurf_tokens(app: AppHandle) -> Result<i32, String> {
+    crate::modules::fault_injection::check("refresh")?;
     let started_at = Instant::now();
     logger::log_info("[Windsurf Command] 手动批量刷新开始");
     let results = windsurf_account::refresh_all_tokens().await?;
@@ -208,9 +210,10 @@ pub async fn inject_windsurf_to_vscode(
     {
         Ok(_) => None,
         Err(e) => {
-            if e.starts_with("APP_PATH_NOT_FOUND:") || e.contains("启动 Windsurf 失败") {
+            if crate::modules::process::is_app_path_error(&e) || e.contains("启动 Windsurf 失败")
+            {
                 logger::log_warn(&format!("Windsurf 默认实例启动失败: {}", e));
-                if e.starts_with("APP_PATH_NOT_FOUND:") || e.contains("APP_PATH_NOT_FOUND:") {
+                if crate::modules::process::is_app_path_error(&e) {
                     let _ = app.emit(
                         "app:path_missing",
                         serde_json::json!({ "app": "windsurf", "retry": { "kind": "default" } }),
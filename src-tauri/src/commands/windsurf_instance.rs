@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::path::Path;
 
 use crate::models::{DefaultInstanceSettings, InstanceProfileView};
@@ -90,12 +91,18 @@ pub async fn windsurf_list_instances() -> Result<Vec<InstanceProfileView>, Strin
         initialized: is_profile_initialized(&default_dir.to_string_lossy()),
         is_default: true,
         follow_local_account: false,
+        minimize_after_launch: false,
+        low_power: false,
+        launch_args_template: None,
+        env_vars: HashMap::new(),
+        bind_fingerprint_id: None,
     });
 
     Ok(result)
 }
 
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn windsurf_create_instance(
     name: String,
     user_data_dir: String,
@@ -103,7 +110,16 @@ pub async fn windsurf_create_instance(
     bind_account_id: Option<String>,
     copy_source_instance_id: Option<String>,
     init_mode: Option<String>,
+    launch_args_template: Option<String>,
+    follow_launch_args_template: Option<bool>,
 ) -> Result<InstanceProfileView, String> {
+    let (extra_args, template) = modules::launch_args_template::resolve_template_reference(
+        modules::tray_layout::PLATFORM_WINDSURF,
+        None,
+        extra_args,
+        Some(launch_args_template),
+        follow_launch_args_template.unwrap_or(false),
+    )?;
     let instance = modules::windsurf_instance::create_instance(
         modules::windsurf_instance::CreateInstanceParams {
             name,
@@ -112,6 +128,9 @@ pub async fn windsurf_create_instance(
             bind_account_id,
             copy_source_instance_id,
             init_mode,
+            launch_args_template: template.flatten(),
+            env_vars: HashMap::new(),
+            bind_fingerprint_id: None,
         },
     )?;
 
@@ -124,13 +143,25 @@ pub async fn windsurf_create_instance(
 }
 
 #[tauri::command]
+#[allow(clippy::too_many_arguments)]
 pub async fn windsurf_update_instance(
     instance_id: String,
     name: Option<String>,
     extra_args: Option<String>,
     bind_account_id: Option<Option<String>>,
     follow_local_account: Option<bool>,
+    minimize_after_launch: Option<bool>,
+    launch_args_template: Option<Option<String>>,
+    follow_launch_args_template: Option<bool>,
 ) -> Result<InstanceProfileView, String> {
+    let (extra_args, launch_args_template) =
+        modules::launch_args_template::resolve_template_reference(
+            modules::tray_layout::PLATFORM_WINDSURF,
+            Some(&instance_id),
+            extra_args,
+            launch_args_template,
+            follow_launch_args_template.unwrap_or(false),
+        )?;
     if instance_id == DEFAULT_INSTANCE_ID {
         let default_dir = modules::windsurf_instance::get_default_windsurf_user_data_dir()?;
         let default_dir_str = default_dir.to_string_lossy().to_string();
@@ -156,6 +187,11 @@ pub async fn windsurf_update_instance(
             initialized: is_profile_initialized(&default_dir.to_string_lossy()),
             is_default: true,
             follow_local_account: false,
+            minimize_after_launch: false,
+            low_power: false,
+            launch_args_template: None,
+            env_vars: HashMap::new(),
+            bind_fingerprint_id: None,
         });
     }
 
@@ -181,6 +217,12 @@ pub async fn windsurf_update_instance(
             name,
             extra_args,
             bind_account_id,
+            minimize_after_launch,
+            low_power: None,
+            launch_args_template,
+            env_vars: None,
+            bind_fingerprint_id: None,
+            follow_local_account: None,
         },
     )?;
 
@@ -211,6 +253,7 @@ pub async fn windsurf_delete_instance(instance_id: String) -> Result<(), String>
 
 #[tauri::command]
 pub async fn windsurf_start_instance(instance_id: String) -> Result<InstanceProfileView, String> {
+    crate::modules::fault_injection::check("launch")?;
     modules::logger::log_info(&format!("开始启动 Windsurf 实例: {}", instance_id));
     if instance_id == DEFAULT_INSTANCE_ID {
         let default_dir = modules::windsurf_instance::get_default_windsurf_user_data_dir()?;
@@ -230,7 +273,7 @@ pub async fn windsurf_start_instance(instance_id: String) -> Result<InstanceProf
         let extra_args = modules::process::parse_extra_args(&default_settings.extra_args);
         let pid = modules::windsurf_instance::start_windsurf_default_with_args_with_new_window(
             &extra_args,
-            true,
+            modules::process::resolve_use_new_window("windsurf", &extra_args, true),
         )?;
         let _ = modules::windsurf_instance::update_default_pid(Some(pid))?;
         let running = modules::windsurf_instance::resolve_windsurf_pid(Some(pid), None).is_some();
@@ -247,6 +290,11 @@ pub async fn windsurf_start_instance(instance_id: String) -> Result<InstanceProf
             initialized: is_profile_initialized(&default_dir.to_string_lossy()),
             is_default: true,
             follow_local_account: false,
+            minimize_after_launch: false,
+            low_power: false,
+            launch_args_template: None,
+            env_vars: HashMap::new(),
+            bind_fingerprint_id: None,
         });
     }
 
@@ -273,9 +321,12 @@ pub async fn windsurf_start_instance(instance_id: String) -> Result<InstanceProf
     let pid = modules::windsurf_instance::start_windsurf_with_args_with_new_window(
         &instance.user_data_dir,
         &extra_args,
-        true,
+        modules::process::resolve_use_new_window("windsurf", &extra_args, true),
     )?;
     let updated = modules::windsurf_instance::update_instance_after_start(&instance.id, pid)?;
+    if updated.minimize_after_launch {
+        modules::process::minimize_window_after_launch(pid);
+    }
     let running =
         modules::windsurf_instance::resolve_windsurf_pid(Some(pid), Some(&updated.user_data_dir))
             .is_some();
@@ -316,6 +367,11 @@ pub async fn windsurf_stop_instance(instance_id: String) -> Result<InstanceProfi
             initialized: is_profile_initialized(&default_dir.to_string_lossy()),
             is_default: true,
             follow_local_account: false,
+            minimize_after_launch: false,
+            low_power: false,
+            launch_args_template: None,
+            env_vars: HashMap::new(),
+            bind_fingerprint_id: None,
         });
     }
 
@@ -30,6 +30,10 @@ pub enum AppError {
 
     #[error("Unknown error: {0}")]
     Unknown(String),
+
+    /// 配额查询超时，前端依据 `QUOTA_TIMEOUT:` 前缀与授权失败区分
+    #[error("QUOTA_TIMEOUT:{0}")]
+    QuotaTimeout(String),
 }
 
 impl Serialize for AppError {
@@ -57,6 +61,88 @@ impl Serialize for AppError {
     }
 }
 
+/// 外部 HTTP 接口错误
+/// 序列化时带稳定的 `kind` 字段，前端据此区分处理，而不是匹配错误文案
+#[derive(Error, Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ApiError {
+    #[error("网络请求失败: {message}")]
+    Network { message: String },
+
+    #[error("请求超时: {message}")]
+    Timeout { message: String },
+
+    #[error("授权已失效: {body}")]
+    Unauthorized { body: String },
+
+    #[error("请求过于频繁，请稍后重试")]
+    RateLimited { retry_after: Option<u64> },
+
+    #[error("账号已被封禁: {reason}")]
+    Banned { reason: String },
+
+    #[error("接口返回异常: status={status}, body={body}")]
+    Unexpected { status: u16, body: String },
+}
+
+impl ApiError {
+    /// 转换请求发送阶段的错误（连接失败、超时等）
+    pub fn from_reqwest(context: &str, err: reqwest::Error) -> Self {
+        let message = format!("{}: {}", context, err);
+        if err.is_timeout() {
+            ApiError::Timeout { message }
+        } else {
+            ApiError::Network { message }
+        }
+    }
+
+    /// 按 HTTP 状态码转换非成功响应
+    pub fn from_status(
+        status: reqwest::StatusCode,
+        retry_after: Option<u64>,
+        body: String,
+    ) -> Self {
+        match status {
+            reqwest::StatusCode::UNAUTHORIZED => ApiError::Unauthorized { body },
+            reqwest::StatusCode::TOO_MANY_REQUESTS => ApiError::RateLimited { retry_after },
+            _ => ApiError::Unexpected {
+                status: status.as_u16(),
+                body,
+            },
+        }
+    }
+
+    /// 是否为可重试的瞬时错误（网络失败、超时、5xx），4xx 等确定性错误不应重试
+    pub fn is_transient(&self) -> bool {
+        match self {
+            ApiError::Network { .. } | ApiError::Timeout { .. } => true,
+            ApiError::Unexpected { status, .. } => *status >= 500,
+            _ => false,
+        }
+    }
+
+    /// 转换为命令返回的错误字符串（JSON，含 error_type / kind / message）
+    pub fn to_command_error(&self) -> String {
+        let mut value = serde_json::to_value(self).unwrap_or_else(|_| serde_json::json!({}));
+        if let Some(map) = value.as_object_mut() {
+            map.insert("error_type".to_string(), "api_error".into());
+            map.insert("message".to_string(), self.to_string().into());
+        }
+        value.to_string()
+    }
+}
+
+/// 解析 Retry-After 响应头（仅支持秒数形式）
+pub fn parse_retry_after(headers: &reqwest::header::HeaderMap) -> Option<u64> {
+    headers
+        .get(reqwest::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse::<u64>()
+        .ok()
+}
+
 /// 创建文件损坏错误的辅助函数
 pub fn file_corrupted_error(file_name: &str, file_path: &str, original_error: &str) -> String {
     serde_json::json!({
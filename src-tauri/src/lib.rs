@@ -7,7 +7,6 @@ mod utils;
 use modules::config::CloseWindowBehavior;
 use modules::logger;
 use std::sync::OnceLock;
-#[cfg(target_os = "macos")]
 use tauri::RunEvent;
 use tauri::WindowEvent;
 use tauri::{Emitter, Manager};
@@ -43,14 +42,52 @@ pub fn run() {
             // 存储全局 AppHandle
             let _ = APP_HANDLE.set(app.handle().clone());
 
+            // 安全模式（COCKPIT_SAFE_MODE=1）：跳过启动时配置合并与 WebSocket 自动启动
+            let safe_mode = modules::safe_mode::is_safe_mode();
+
+            // 恢复主窗口上次的位置与尺寸
+            modules::window_state::restore_main_window(app.handle());
+
+            // 由 relaunch_app 重启时主动显示并聚焦主窗口（macOS 重启后的进程可能停留在后台）
+            if modules::app_relaunch::take_pending_show_window() {
+                if let Some(window) = app.get_webview_window("main") {
+                    let _ = window.show();
+                    let _ = window.unminimize();
+                    let _ = window.set_focus();
+                }
+            }
+
+            // 检查配置文件能否正常解析（解析失败时运行时使用默认配置）
+            match modules::config::load_user_config() {
+                Ok(_) => modules::startup_report::record_step(
+                    modules::startup_report::STEP_CONFIG,
+                    true,
+                    None,
+                ),
+                Err(e) => modules::startup_report::record_step(
+                    modules::startup_report::STEP_CONFIG,
+                    false,
+                    Some(e),
+                ),
+            }
+
             // 启动时同步：读取共享配置文件，与本地配置比较时间戳后合并
             {
                 let current_config = modules::config::get_user_config();
-                if let Some(merged_language) = modules::sync_settings::merge_setting_on_startup(
-                    "language",
-                    &current_config.language,
-                    None, // 本地暂无更新时间记录，始终以共享文件为准
-                ) {
+                let mut sync_result: (bool, Option<String>) = (true, None);
+                if safe_mode {
+                    info!("[SyncSettings] 安全模式下跳过启动时配置合并");
+                    sync_result.1 = Some("safe_mode".to_string());
+                } else if !current_config.sync_on_startup_enabled {
+                    info!("[SyncSettings] 启动时配置合并已禁用，跳过");
+                    sync_result.1 = Some("disabled".to_string());
+                } else if let Some(merged_language) =
+                    modules::sync_settings::merge_setting_on_startup(
+                        "language",
+                        &current_config.language,
+                        modules::sync_settings::get_local_modified("language"),
+                    )
+                {
                     info!(
                         "[SyncSettings] 启动时合并语言设置: {} -> {}",
                         current_config.language, merged_language
@@ -61,27 +98,75 @@ pub fn run() {
                     };
                     if let Err(e) = modules::config::save_user_config(&new_config) {
                         logger::log_error(&format!("[SyncSettings] 保存合并后的配置失败: {}", e));
+                        sync_result = (false, Some(e));
+                    } else {
+                        sync_result.1 = Some(format!("language={}", new_config.language));
                     }
                 }
+                modules::startup_report::record_step(
+                    modules::startup_report::STEP_SYNC_MERGE,
+                    sync_result.0,
+                    sync_result.1,
+                );
             }
 
-            // 启动 WebSocket 服务（使用 Tauri 的 async runtime）
-            tauri::async_runtime::spawn(async {
-                modules::websocket::start_server().await;
+            // 检查已配置的应用路径是否失效（仅记录日志，启动时由前端按需提示）
+            let _ = modules::process::audit_app_paths();
+
+            // 后台扫描数据文件完整性，结果通过 integrity:report 事件通知前端
+            std::thread::spawn(|| {
+                let _ = modules::integrity::run_integrity_scan();
+            });
+
+            // 后台预检 OAuth 回调端口占用（仅记录日志，不阻塞启动）
+            std::thread::spawn(|| {
+                let _ = modules::oauth_server::precheck_oauth_ports();
             });
 
+            // 启动配额定时刷新调度（间隔为 0 时空转，修改配置后自动生效）
+            modules::quota_refresh_scheduler::ensure_started();
+
+            // 启动 WebSocket 服务（使用 Tauri 的 async runtime）
+            if safe_mode {
+                info!("[WS] 安全模式下跳过 WebSocket 自动启动");
+                modules::startup_report::record_step(
+                    modules::startup_report::STEP_WEBSOCKET,
+                    true,
+                    Some("safe_mode".to_string()),
+                );
+            } else {
+                tauri::async_runtime::spawn(async {
+                    let _ = modules::websocket::start_server().await;
+                });
+            }
+
             // 初始化系统托盘
-            if let Err(e) = modules::tray::create_tray(app.handle()) {
-                logger::log_error(&format!("[Tray] 创建系统托盘失败: {}", e));
+            match modules::tray::create_tray(app.handle()) {
+                Ok(_) => modules::startup_report::record_step(
+                    modules::startup_report::STEP_TRAY,
+                    true,
+                    None,
+                ),
+                Err(e) => {
+                    logger::log_error(&format!("[Tray] 创建系统托盘失败: {}", e));
+                    modules::startup_report::record_step(
+                        modules::startup_report::STEP_TRAY,
+                        false,
+                        Some(e.to_string()),
+                    );
+                }
             }
 
             Ok(())
         })
         .on_window_event(|window, event| {
+            if let WindowEvent::Moved(_) | WindowEvent::Resized(_) = event {
+                modules::window_state::track_main_window(window);
+            }
             if let WindowEvent::CloseRequested { api, .. } = event {
-                let config = modules::config::get_user_config();
+                modules::config::flush_window_geometry();
 
-                match config.close_behavior {
+                match modules::config::get_effective_close_behavior() {
                     CloseWindowBehavior::Minimize => {
                         // 直接最小化到托盘
                         api.prevent_close();
@@ -112,11 +197,18 @@ pub fn run() {
             commands::account::set_current_account,
             commands::account::fetch_account_quota,
             commands::account::refresh_all_quotas,
+            commands::account::refresh_all_quotas_detailed,
+            commands::account::refresh_group_quotas,
+            commands::account::cancel_group_quota_refresh,
             commands::account::refresh_current_quota,
+            commands::account::get_quota_history,
+            commands::account::repair_account_index,
             commands::account::switch_account,
             commands::account::bind_account_fingerprint,
             commands::account::get_bound_accounts,
             commands::account::update_account_tags,
+            commands::account::set_account_label,
+            commands::account::bulk_update_account_tags,
             commands::account::sync_current_from_client,
             commands::account::sync_from_extension,
             // Device Commands
@@ -130,6 +222,7 @@ pub fn run() {
             commands::device::open_device_folder,
             commands::device::preview_generate_profile,
             commands::device::preview_current_profile,
+            commands::device::preview_profile_diff,
             // Fingerprint Commands
             commands::device::list_fingerprints,
             commands::device::get_fingerprint,
@@ -137,36 +230,69 @@ pub fn run() {
             commands::device::capture_current_fingerprint,
             commands::device::create_fingerprint_with_profile,
             commands::device::apply_fingerprint,
+            commands::device::test_fingerprint,
+            commands::device::compare_current_fingerprint,
             commands::device::delete_fingerprint,
             commands::device::rename_fingerprint,
             commands::device::get_current_fingerprint_id,
+            commands::device::get_machine_id,
             // OAuth Commands
             commands::oauth::start_oauth_login,
             commands::oauth::prepare_oauth_url,
             commands::oauth::complete_oauth_login,
             commands::oauth::cancel_oauth_login,
+            commands::oauth::cancel_all_oauth_logins,
+            commands::oauth::shutdown_oauth_servers,
+            commands::oauth::clear_stale_oauth_state,
+            commands::oauth::precheck_oauth_ports,
             // Import/Export Commands
             commands::import::import_from_old_tools,
+            commands::import::preview_import_from_old_tools,
             commands::import::import_fingerprints_from_old_tools,
             commands::import::import_fingerprints_from_json,
+            commands::import::import_fingerprints_from_zip,
             commands::import::import_from_local,
             commands::import::import_from_json,
             commands::import::export_accounts,
+            commands::import::export_all_accounts,
+            commands::import::import_all_accounts,
             // System Commands
             commands::system::open_data_folder,
             commands::system::save_text_file,
             commands::system::get_downloads_dir,
+            commands::system::export_quota_report,
+            commands::system::auto_tag_by_quota,
+            commands::system::get_providers,
+            commands::system::get_startup_report,
+            commands::system::is_safe_mode,
+            commands::system::get_last_crash_log,
+            commands::system::probe_token,
+            commands::system::get_all_current_accounts,
+            commands::system::clear_all_current_accounts,
             commands::system::get_network_config,
+            commands::system::websocket_status,
+            commands::system::restart_websocket_server,
             commands::system::save_network_config,
             commands::system::get_general_config,
             commands::system::save_general_config,
             commands::system::save_tray_platform_layout,
             commands::system::set_app_path,
+            commands::system::validate_app_path,
+            commands::system::get_antigravity_runtime_info,
             commands::system::detect_app_path,
+            commands::system::audit_app_paths,
             commands::system::set_wakeup_override,
             commands::system::handle_window_close,
+            commands::system::set_session_close_behavior,
+            commands::system::save_quota_refresh_settings,
             commands::system::open_folder,
+            commands::system::check_external_tools,
+            commands::system::check_data_dir_permissions,
+            commands::system::fix_data_dir_permissions,
+            commands::system::set_fault_injection,
+            commands::system::clear_fault_injection,
             commands::system::delete_corrupted_file,
+            commands::system::run_integrity_scan,
             // Wakeup Commands
             commands::wakeup::trigger_wakeup,
             commands::wakeup::fetch_available_models,
@@ -179,6 +305,7 @@ pub fn run() {
             commands::update::update_last_check_time,
             commands::update::get_update_settings,
             commands::update::save_update_settings,
+            commands::update::relaunch_app,
             // Group Commands
             commands::group::get_group_settings,
             commands::group::save_group_settings,
@@ -190,10 +317,12 @@ pub fn run() {
             commands::group::get_display_groups,
             // Codex Commands
             commands::codex::list_codex_accounts,
+            commands::codex::list_codex_accounts_page,
             commands::codex::get_current_codex_account,
             commands::codex::switch_codex_account,
             commands::codex::delete_codex_account,
             commands::codex::delete_codex_accounts,
+            commands::codex::reorder_codex_accounts,
             commands::codex::import_codex_from_local,
             commands::codex::import_codex_from_json,
             commands::codex::export_codex_accounts,
@@ -207,11 +336,13 @@ pub fn run() {
             commands::codex::is_codex_oauth_port_in_use,
             commands::codex::close_codex_oauth_port,
             commands::codex::update_codex_account_tags,
+            commands::codex::set_codex_account_label,
             // GitHub Copilot Commands
             commands::github_copilot::list_github_copilot_accounts,
             commands::github_copilot::delete_github_copilot_account,
             commands::github_copilot::delete_github_copilot_accounts,
             commands::github_copilot::import_github_copilot_from_json,
+            commands::github_copilot::import_github_copilot_from_vscode,
             commands::github_copilot::export_github_copilot_accounts,
             commands::github_copilot::refresh_github_copilot_token,
             commands::github_copilot::refresh_all_github_copilot_tokens,
@@ -220,6 +351,7 @@ pub fn run() {
             commands::github_copilot::github_copilot_oauth_login_cancel,
             commands::github_copilot::add_github_copilot_account_with_token,
             commands::github_copilot::update_github_copilot_account_tags,
+            commands::github_copilot::set_github_copilot_account_label,
             commands::github_copilot::get_github_copilot_accounts_index_path,
             commands::github_copilot::inject_github_copilot_to_vscode,
             // GitHub Copilot Instance Commands
@@ -253,10 +385,13 @@ pub fn run() {
             commands::kiro::delete_kiro_account,
             commands::kiro::delete_kiro_accounts,
             commands::kiro::import_kiro_from_json,
+            commands::kiro::validate_kiro_local_files,
+            commands::kiro::parse_kiro_usage,
             commands::kiro::import_kiro_from_local,
             commands::kiro::export_kiro_accounts,
             commands::kiro::refresh_kiro_token,
             commands::kiro::refresh_all_kiro_tokens,
+            commands::kiro::refresh_all_kiro_accounts,
             commands::kiro::kiro_oauth_login_start,
             commands::kiro::kiro_oauth_login_complete,
             commands::kiro::kiro_oauth_login_cancel,
@@ -301,14 +436,42 @@ pub fn run() {
             commands::instance::update_instance,
             commands::instance::delete_instance,
             commands::instance::start_instance,
+            commands::instance::launch_instance_ephemeral,
+            commands::instance::rebind_instance_account,
+            commands::instance::preview_launch_command,
+            commands::instance::resolve_effective_args,
             commands::instance::stop_instance,
             commands::instance::open_instance_window,
             commands::instance::close_all_instances,
+            commands::instance::export_instance_definitions,
+            commands::instance::import_instance_definitions,
+            commands::instance::restart_running_instances,
+            commands::instance::is_instance_credential_db_locked,
+            commands::instance::export_instance_full,
+            commands::instance::import_instance_full,
+            commands::instance::detect_provider_dir_conflicts,
+            commands::instance::detect_running_dir_collisions,
+            commands::instance::save_launch_args_template,
+            commands::instance::list_launch_args_templates,
+            commands::instance::open_active_instance_folder,
+            commands::instance::open_instance_folder,
+            commands::instance::list_running_instances,
+            commands::instance::start_instances_batch,
+            commands::instance::verify_instance_store,
+            commands::instance::prune_instance_store,
+            commands::instance::diagnose_path_encoding,
+            commands::instance::debug_list_product_processes,
+            commands::instance::list_instances_detailed,
         ])
         .build(tauri::generate_context!())
         .expect("error while building tauri application");
 
     app.run(|app_handle, event| {
+        // 退出前关闭残留的 OAuth 回调服务，避免下次启动时端口被占用
+        if let RunEvent::Exit = event {
+            tauri::async_runtime::block_on(modules::oauth_server::shutdown_all_oauth_servers());
+            return;
+        }
         #[cfg(target_os = "macos")]
         {
             if let RunEvent::Reopen { .. } = event {
@@ -321,7 +484,7 @@ pub fn run() {
         }
         #[cfg(not(target_os = "macos"))]
         {
-            let _ = (app_handle, event);
+            let _ = app_handle;
         }
     });
 }
@@ -8,6 +8,9 @@ pub struct Account {
     pub id: String,
     pub email: String,
     pub name: Option<String>,
+    /// 自定义显示名称（为空时显示邮箱）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub tags: Vec<String>,
     pub token: TokenData,
@@ -38,6 +41,54 @@ fn default_fingerprint_id() -> Option<String> {
     Some("original".to_string())
 }
 
+/// 账号列表筛选条件（标签匹配不区分大小写）
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct AccountFilter {
+    /// 包含其中任一标签
+    pub tags_any: Vec<String>,
+    /// 包含全部标签
+    pub tags_all: Vec<String>,
+    /// 邮箱包含该文本
+    pub query: Option<String>,
+}
+
+impl AccountFilter {
+    fn normalize(values: &[String]) -> Vec<String> {
+        values
+            .iter()
+            .map(|value| value.trim().to_lowercase())
+            .filter(|value| !value.is_empty())
+            .collect()
+    }
+
+    /// 邮箱是否匹配 query（query 为空时总是匹配）
+    pub fn matches_email(&self, email: &str) -> bool {
+        match self.query.as_deref().map(str::trim) {
+            Some(query) if !query.is_empty() => {
+                email.to_lowercase().contains(&query.to_lowercase())
+            }
+            _ => true,
+        }
+    }
+
+    /// 账号是否满足全部筛选条件
+    pub fn matches(&self, account: &Account) -> bool {
+        if !self.matches_email(&account.email) {
+            return false;
+        }
+        let tags: HashSet<String> = account
+            .tags
+            .iter()
+            .map(|tag| tag.trim().to_lowercase())
+            .collect();
+        let tags_any = Self::normalize(&self.tags_any);
+        let tags_all = Self::normalize(&self.tags_all);
+        (tags_any.is_empty() || tags_any.iter().any(|tag| tags.contains(tag)))
+            && tags_all.iter().all(|tag| tags.contains(tag))
+    }
+}
+
 impl Account {
     pub fn new(id: String, email: String, token: TokenData) -> Self {
         let now = chrono::Utc::now().timestamp();
@@ -45,6 +96,7 @@ impl Account {
             id,
             email,
             name: None,
+            label: None,
             tags: Vec::new(),
             token,
             fingerprint_id: Some("original".to_string()),
@@ -91,6 +143,9 @@ pub struct AccountSummary {
     pub id: String,
     pub email: String,
     pub name: Option<String>,
+    /// 自定义显示名称（为空时显示邮箱）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
     pub created_at: i64,
     pub last_used: i64,
 }
@@ -132,3 +187,67 @@ pub struct DeviceProfileVersion {
     #[serde(default)]
     pub is_current: bool,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn account(email: &str, tags: &[&str]) -> Account {
+        let mut account = Account::new(
+            "a".to_string(),
+            email.to_string(),
+            TokenData::new(
+                "access".to_string(),
+                "refresh".to_string(),
+                3600,
+                None,
+                None,
+                None,
+            ),
+        );
+        account.tags = tags.iter().map(|tag| tag.to_string()).collect();
+        account
+    }
+
+    fn tags(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[test]
+    fn test_account_filter_matches() {
+        let work = account("Alice@Example.com", &["Work", "pro"]);
+
+        assert!(AccountFilter::default().matches(&work));
+        assert!(AccountFilter {
+            query: Some(" alice@ ".to_string()),
+            ..Default::default()
+        }
+        .matches(&work));
+        assert!(!AccountFilter {
+            query: Some("bob".to_string()),
+            ..Default::default()
+        }
+        .matches(&work));
+
+        assert!(AccountFilter {
+            tags_any: tags(&["personal", " WORK "]),
+            ..Default::default()
+        }
+        .matches(&work));
+        assert!(!AccountFilter {
+            tags_any: tags(&["personal"]),
+            ..Default::default()
+        }
+        .matches(&work));
+        assert!(AccountFilter {
+            tags_all: tags(&["work", "PRO"]),
+            ..Default::default()
+        }
+        .matches(&work));
+        assert!(!AccountFilter {
+            tags_all: tags(&["work", "free"]),
+            ..Default::default()
+        }
+        .matches(&work));
+    }
+}
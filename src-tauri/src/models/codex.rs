@@ -5,6 +5,9 @@ use serde::{Deserialize, Serialize};
 pub struct CodexAccount {
     pub id: String,
     pub email: String,
+    /// 自定义显示名称（为空时显示邮箱）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
     pub user_id: Option<String>,
     pub plan_type: Option<String>,
     pub account_id: Option<String>,
@@ -98,6 +101,9 @@ pub struct CodexAccountIndex {
 pub struct CodexAccountSummary {
     pub id: String,
     pub email: String,
+    /// 自定义显示名称（为空时显示邮箱）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
     pub plan_type: Option<String>,
     pub created_at: i64,
     pub last_used: i64,
@@ -119,6 +125,31 @@ impl Default for CodexAccountIndex {
     }
 }
 
+/// 分页后的账号列表
+#[derive(Debug, Clone, Serialize)]
+pub struct CodexAccountPage {
+    pub accounts: Vec<CodexAccount>,
+    /// 索引中的账号总数（不受分页窗口影响）
+    pub total: usize,
+}
+
+/// 批量刷新配额时因限流（429）被推迟的账号
+#[derive(Debug, Clone, Serialize)]
+pub struct CodexDeferredAccount {
+    pub account_id: String,
+    /// 建议的重试等待时间（秒）
+    pub retry_after_secs: u64,
+}
+
+/// 批量刷新配额结果
+#[derive(Debug, Clone, Serialize)]
+pub struct CodexQuotaRefreshSummary {
+    pub success: usize,
+    pub failed: usize,
+    /// 被限流推迟、本轮未刷新的账号（不计入 failed）
+    pub deferred: Vec<CodexDeferredAccount>,
+}
+
 /// JWT Payload 中的用户信息
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CodexJwtPayload {
@@ -148,6 +179,7 @@ impl CodexAccount {
         Self {
             id,
             email,
+            label: None,
             user_id: None,
             plan_type: None,
             account_id: None,
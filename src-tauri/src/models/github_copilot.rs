@@ -9,6 +9,9 @@ pub struct GitHubCopilotAccount {
     pub github_name: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub github_email: Option<String>,
+    /// 自定义显示名称（为空时显示 GitHub 用户名）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub tags: Option<Vec<String>>,
     pub github_access_token: String,
@@ -33,6 +36,9 @@ pub struct GitHubCopilotAccount {
     pub copilot_limited_user_quotas: Option<serde_json::Value>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub copilot_limited_user_reset_date: Option<i64>,
+    /// GitHub 授权已失效，需要重新登录
+    #[serde(default)]
+    pub reauth_required: bool,
     pub created_at: i64,
     pub last_used: i64,
 }
@@ -43,6 +49,9 @@ pub struct GitHubCopilotAccountSummary {
     pub github_login: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub github_email: Option<String>,
+    /// 自定义显示名称（为空时显示 GitHub 用户名）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub tags: Option<Vec<String>>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -109,6 +118,7 @@ impl GitHubCopilotAccount {
             id: self.id.clone(),
             github_login: self.github_login.clone(),
             github_email: self.github_email.clone(),
+            label: self.label.clone(),
             tags: self.tags.clone(),
             copilot_plan: self.copilot_plan.clone(),
             created_at: self.created_at,
@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -12,6 +13,24 @@ pub struct InstanceProfile {
     pub last_launched_at: Option<i64>,
     #[serde(default)]
     pub last_pid: Option<u32>,
+    /// 启动成功后自动最小化窗口（用于后台预热实例）
+    #[serde(default)]
+    pub minimize_after_launch: bool,
+    /// 低功耗启动：禁用 GPU 加速以降低耗电（目前仅 Antigravity 实例生效）
+    #[serde(default)]
+    pub low_power: bool,
+    /// 跟随的启动参数模板名称，模板更新时同步改写 extra_args
+    #[serde(default)]
+    pub launch_args_template: Option<String>,
+    /// 仅对该实例生效的环境变量（如 HTTP_PROXY）
+    #[serde(default)]
+    pub env_vars: HashMap<String, String>,
+    /// 绑定的设备指纹，启动前写入实例目录（目前仅 Antigravity 实例支持）
+    #[serde(default)]
+    pub bind_fingerprint_id: Option<String>,
+    /// 未绑定账号时，启动前注入当前选中的账号（需手动开启，目前仅 Codex 实例支持）
+    #[serde(default)]
+    pub follow_local_account: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -74,6 +93,11 @@ pub struct InstanceProfileView {
     pub initialized: bool,
     pub is_default: bool,
     pub follow_local_account: bool,
+    pub minimize_after_launch: bool,
+    pub low_power: bool,
+    pub launch_args_template: Option<String>,
+    pub env_vars: HashMap<String, String>,
+    pub bind_fingerprint_id: Option<String>,
 }
 
 impl InstanceProfileView {
@@ -90,7 +114,12 @@ impl InstanceProfileView {
             running,
             initialized,
             is_default: false,
-            follow_local_account: false,
+            follow_local_account: profile.follow_local_account,
+            minimize_after_launch: profile.minimize_after_launch,
+            low_power: profile.low_power,
+            launch_args_template: profile.launch_args_template,
+            env_vars: profile.env_vars,
+            bind_fingerprint_id: profile.bind_fingerprint_id,
         }
     }
 }
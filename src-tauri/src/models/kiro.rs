@@ -154,3 +154,70 @@ impl KiroAccount {
         }
     }
 }
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KiroLocalFileStatus {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    pub exists: bool,
+    pub parsed: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// 本机 Kiro 登录文件就绪检查结果（导入前预检）
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KiroLocalFilesReport {
+    pub ready: bool,
+    pub auth_token: KiroLocalFileStatus,
+    pub profile: KiroLocalFileStatus,
+    pub usage_snapshot: KiroLocalFileStatus,
+    pub has_access_token: bool,
+    pub has_refresh_token: bool,
+    pub has_profile_arn: bool,
+    /// 缺失或异常项（面向用户的说明）
+    pub issues: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+/// 用量 JSON 解析结果（用于校验解析逻辑，不对应任何账号）
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KiroUsageSummary {
+    pub plan_name: Option<String>,
+    pub plan_tier: Option<String>,
+    pub credits_total: Option<f64>,
+    pub credits_used: Option<f64>,
+    pub bonus_total: Option<f64>,
+    pub bonus_used: Option<f64>,
+    pub usage_reset_at: Option<i64>,
+    pub bonus_expire_days: Option<i64>,
+}
+
+/// Kiro 批量刷新进度（逐账号推送）
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KiroRefreshProgress {
+    pub account_id: String,
+    pub email: String,
+    /// "success" / "failed" / "skipped"（封禁账号）
+    pub status: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub completed: usize,
+    pub total: usize,
+}
+
+/// Kiro 批量刷新汇总
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KiroBatchRefreshResult {
+    pub total: usize,
+    pub success: usize,
+    pub failed: usize,
+    pub skipped_banned: usize,
+    pub errors: Vec<String>,
+}
@@ -8,7 +8,8 @@ pub mod token;
 pub mod windsurf;
 
 pub use account::{
-    Account, AccountIndex, AccountSummary, DeviceProfile, DeviceProfileVersion, QuotaErrorInfo,
+    Account, AccountFilter, AccountIndex, AccountSummary, DeviceProfile, DeviceProfileVersion,
+    QuotaErrorInfo,
 };
 pub use instance::{DefaultInstanceSettings, InstanceProfile, InstanceProfileView, InstanceStore};
 pub use quota::QuotaData;
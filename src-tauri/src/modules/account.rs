@@ -7,8 +7,8 @@ use std::sync::Mutex;
 use uuid::Uuid;
 
 use crate::models::{
-    Account, AccountIndex, AccountSummary, DeviceProfile, DeviceProfileVersion, QuotaData,
-    QuotaErrorInfo, TokenData,
+    Account, AccountFilter, AccountIndex, AccountSummary, DeviceProfile, DeviceProfileVersion,
+    QuotaData, QuotaErrorInfo, TokenData,
 };
 use crate::modules;
 
@@ -88,6 +88,186 @@ pub fn save_account_index(index: &AccountIndex) -> Result<(), String> {
     fs::rename(temp_path, index_path).map_err(|e| format!("替换索引文件失败: {}", e))
 }
 
+/// 账号索引文件路径
+pub fn get_account_index_path() -> Result<PathBuf, String> {
+    Ok(get_data_dir()?.join(ACCOUNTS_INDEX))
+}
+
+/// 扫描账号目录，返回可解析的账号摘要与无法解析的账号文件名
+fn scan_account_files() -> Result<(Vec<AccountSummary>, Vec<String>), String> {
+    let accounts_dir = get_accounts_dir()?;
+    let entries = fs::read_dir(&accounts_dir).map_err(|e| format!("读取账号目录失败: {}", e))?;
+
+    let mut summaries = Vec::new();
+    let mut skipped = Vec::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+            continue;
+        }
+        let Some(account_id) = path.file_stem().and_then(|stem| stem.to_str()) else {
+            continue;
+        };
+        match load_account(account_id) {
+            Ok(account) => summaries.push(AccountSummary {
+                id: account.id,
+                email: account.email,
+                name: account.name,
+                label: account.label,
+                created_at: account.created_at,
+                last_used: account.last_used,
+            }),
+            Err(e) => {
+                modules::logger::log_warn(&format!("重建索引时跳过账号文件: {}", e));
+                skipped.push(entry.file_name().to_string_lossy().to_string());
+            }
+        }
+    }
+    summaries.sort_by_key(|summary| summary.created_at);
+    skipped.sort();
+    Ok((summaries, skipped))
+}
+
+/// 根据账号目录中的账号文件重建索引，返回收录的账号数量
+/// 无法解析的账号文件会被跳过；原索引可读取时保留其中的账号顺序；
+/// 指定的当前账号不在新索引中时置空
+pub fn rebuild_account_index(current_account_id: Option<String>) -> Result<usize, String> {
+    let _lock = ACCOUNT_INDEX_LOCK
+        .lock()
+        .map_err(|e| format!("获取锁失败: {}", e))?;
+    let previous = load_account_index().ok();
+    let (summaries, _) = scan_account_files()?;
+
+    let index = build_rebuilt_index(summaries, previous.as_ref(), current_account_id);
+    save_account_index(&index)?;
+    Ok(index.accounts.len())
+}
+
+/// 由扫描到的账号摘要组装新索引：按原索引排序，当前账号不在新索引中时置空
+fn build_rebuilt_index(
+    summaries: Vec<AccountSummary>,
+    previous: Option<&AccountIndex>,
+    current_account_id: Option<String>,
+) -> AccountIndex {
+    let mut index = AccountIndex::new();
+    index.accounts = order_by_previous_index(summaries, previous);
+    index.current_account_id =
+        current_account_id.filter(|id| index.accounts.iter().any(|summary| summary.id == *id));
+    index
+}
+
+/// 账号索引修复结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RepairReport {
+    /// 收录进索引的账号 ID
+    pub recovered: Vec<String>,
+    /// 无法解析而跳过的账号文件名
+    pub skipped: Vec<String>,
+    /// 扫描到的账号文件总数
+    pub total: usize,
+    /// 索引内容是否发生变化（未变化时不写入也不备份）
+    pub changed: bool,
+    /// 修复前索引的备份路径
+    pub backup_path: Option<String>,
+}
+
+/// 按新的账号列表排序：原索引中已有的账号保持原顺序，其余按创建时间追加在后面
+fn order_by_previous_index(
+    mut summaries: Vec<AccountSummary>,
+    previous: Option<&AccountIndex>,
+) -> Vec<AccountSummary> {
+    let Some(previous) = previous else {
+        return summaries;
+    };
+    let positions: HashMap<&str, usize> = previous
+        .accounts
+        .iter()
+        .enumerate()
+        .map(|(position, summary)| (summary.id.as_str(), position))
+        .collect();
+    summaries.sort_by_key(|summary| {
+        (
+            positions
+                .get(summary.id.as_str())
+                .copied()
+                .unwrap_or(usize::MAX),
+            summary.created_at,
+        )
+    });
+    summaries
+}
+
+fn same_index(a: &AccountIndex, b: &AccountIndex) -> bool {
+    a.current_account_id == b.current_account_id
+        && a.accounts.len() == b.accounts.len()
+        && a.accounts.iter().zip(b.accounts.iter()).all(|(x, y)| {
+            x.id == y.id
+                && x.email == y.email
+                && x.name == y.name
+                && x.created_at == y.created_at
+                && x.last_used == y.last_used
+        })
+}
+
+/// 扫描账号文件修复账号索引（索引损坏或与账号文件不一致时使用）
+/// 写入前先备份原索引；索引已与账号文件一致时不做任何修改，可重复执行
+pub fn repair_account_index() -> Result<RepairReport, String> {
+    let _lock = ACCOUNT_INDEX_LOCK
+        .lock()
+        .map_err(|e| format!("获取锁失败: {}", e))?;
+    let index_path = get_account_index_path()?;
+    let previous = match load_account_index() {
+        Ok(index) => Some(index),
+        Err(err) => {
+            modules::logger::log_warn(&format!("[AccountIndex] 原账号索引无法读取: {}", err));
+            None
+        }
+    };
+    let (summaries, skipped) = scan_account_files()?;
+
+    let current_account_id = previous
+        .as_ref()
+        .and_then(|previous| previous.current_account_id.clone());
+    let index = build_rebuilt_index(summaries, previous.as_ref(), current_account_id);
+
+    let recovered: Vec<String> = index.accounts.iter().map(|s| s.id.clone()).collect();
+    let total = recovered.len() + skipped.len();
+    let changed = previous
+        .as_ref()
+        .map(|previous| !same_index(previous, &index))
+        .unwrap_or(true);
+
+    let mut backup_path = None;
+    if changed {
+        if index_path.exists() {
+            let backup = index_path.with_file_name(format!(
+                "{}.bak.{}",
+                ACCOUNTS_INDEX,
+                chrono::Utc::now().timestamp()
+            ));
+            fs::copy(&index_path, &backup).map_err(|e| format!("备份账号索引失败: {}", e))?;
+            backup_path = Some(backup.to_string_lossy().to_string());
+        }
+        save_account_index(&index)?;
+    }
+
+    modules::logger::log_info(&format!(
+        "[AccountIndex] 账号索引修复完成: recovered={}, skipped={}, total={}, changed={}",
+        recovered.len(),
+        skipped.len(),
+        total,
+        changed
+    ));
+    Ok(RepairReport {
+        recovered,
+        skipped,
+        total,
+        changed,
+        backup_path,
+    })
+}
+
 /// 加载账号数据
 pub fn load_account(account_id: &str) -> Result<Account, String> {
     let accounts_dir = get_accounts_dir()?;
@@ -148,15 +328,158 @@ pub fn update_account_tags(account_id: &str, tags: Vec<String>) -> Result<Accoun
     Ok(account)
 }
 
+/// 账号显示名称最大长度（字符）
+const ACCOUNT_LABEL_MAX_CHARS: usize = 64;
+
+/// 规范化账号显示名称：去除首尾空白，为空时返回 None（清除显示名称）
+pub fn normalize_account_label(label: &str) -> Result<Option<String>, String> {
+    let trimmed = label.trim();
+    if trimmed.is_empty() {
+        return Ok(None);
+    }
+    if trimmed.chars().count() > ACCOUNT_LABEL_MAX_CHARS {
+        return Err(format!(
+            "显示名称不能超过 {} 个字符",
+            ACCOUNT_LABEL_MAX_CHARS
+        ));
+    }
+    Ok(Some(trimmed.to_string()))
+}
+
+/// 设置账号显示名称（同步更新索引摘要）；传入空字符串时清除
+pub fn set_account_label(account_id: &str, label: &str) -> Result<Account, String> {
+    let label = normalize_account_label(label)?;
+    let _lock = ACCOUNT_INDEX_LOCK
+        .lock()
+        .map_err(|e| format!("获取锁失败: {}", e))?;
+
+    let mut account = load_account(account_id)?;
+    account.label = label.clone();
+    save_account(&account)?;
+
+    let mut index = load_account_index()?;
+    if let Some(summary) = index.accounts.iter_mut().find(|s| s.id == account_id) {
+        summary.label = label;
+        save_account_index(&index)?;
+    }
+    Ok(account)
+}
+
+/// 在现有标签上移除/添加标签：移除按不区分大小写匹配，结果去重并校验数量
+fn apply_tag_changes(
+    current: &[String],
+    add_tags: &[String],
+    remove_tags: &HashSet<String>,
+) -> Result<Vec<String>, String> {
+    let merged = current
+        .iter()
+        .filter(|tag| !remove_tags.contains(&tag.trim().to_lowercase()))
+        .chain(add_tags.iter())
+        .cloned()
+        .collect();
+    normalize_tags(merged)
+}
+
+/// 批量添加/移除账号标签
+/// 所有账号校验通过后才写入；任一账号无效时返回失败的 ID 列表，写入失败时回滚已写入的账号
+pub fn bulk_update_account_tags(
+    account_ids: &[String],
+    add_tags: Vec<String>,
+    remove_tags: Vec<String>,
+) -> Result<Vec<Account>, String> {
+    let _lock = ACCOUNT_INDEX_LOCK
+        .lock()
+        .map_err(|e| format!("获取锁失败: {}", e))?;
+
+    let add_tags = normalize_tags(add_tags)?;
+    let remove_tags: HashSet<String> = remove_tags
+        .iter()
+        .map(|tag| tag.trim().to_lowercase())
+        .filter(|tag| !tag.is_empty())
+        .collect();
+
+    let index = load_account_index()?;
+    let mut seen_ids: HashSet<&str> = HashSet::new();
+    let mut originals: Vec<Account> = Vec::new();
+    let mut updated: Vec<Account> = Vec::new();
+    let mut failed: Vec<String> = Vec::new();
+
+    for account_id in account_ids {
+        if !seen_ids.insert(account_id.as_str()) {
+            continue;
+        }
+        if !index
+            .accounts
+            .iter()
+            .any(|summary| &summary.id == account_id)
+        {
+            failed.push(format!("{} (账号不存在)", account_id));
+            continue;
+        }
+        let account = match load_account(account_id) {
+            Ok(account) => account,
+            Err(e) => {
+                failed.push(format!("{} ({})", account_id, e));
+                continue;
+            }
+        };
+        match apply_tag_changes(&account.tags, &add_tags, &remove_tags) {
+            Ok(tags) => {
+                let mut next = account.clone();
+                next.tags = tags;
+                originals.push(account);
+                updated.push(next);
+            }
+            Err(e) => failed.push(format!("{} ({})", account_id, e)),
+        }
+    }
+
+    if !failed.is_empty() {
+        return Err(format!(
+            "批量更新标签失败，以下账号无效: {}",
+            failed.join(", ")
+        ));
+    }
+
+    for (written, account) in updated.iter().enumerate() {
+        if let Err(e) = save_account(account) {
+            for original in &originals[..written] {
+                if let Err(rollback_err) = save_account(original) {
+                    modules::logger::log_error(&format!(
+                        "批量更新标签回滚失败: {} ({})",
+                        original.id, rollback_err
+                    ));
+                }
+            }
+            return Err(format!("批量更新标签失败，已回滚: {} ({})", account.id, e));
+        }
+    }
+
+    modules::logger::log_info(&format!("批量更新标签完成: {} 个账号", updated.len()));
+    Ok(updated)
+}
+
 /// 列出所有账号
 pub fn list_accounts() -> Result<Vec<Account>, String> {
+    list_accounts_filtered(None)
+}
+
+/// 列出满足筛选条件的账号（filter 为 None 时返回全部）
+pub fn list_accounts_filtered(filter: Option<&AccountFilter>) -> Result<Vec<Account>, String> {
     modules::logger::log_info("开始列出账号...");
     let index = load_account_index()?;
     let mut accounts = Vec::new();
 
     for summary in &index.accounts {
+        // 索引中已有邮箱，先按 query 过滤，避免读取无关的账号文件
+        if filter.is_some_and(|filter| !filter.matches_email(&summary.email)) {
+            continue;
+        }
         match load_account(&summary.id) {
             Ok(mut account) => {
+                if filter.is_some_and(|filter| !filter.matches(&account)) {
+                    continue;
+                }
                 let _ = modules::quota_cache::apply_cached_quota(&mut account, "authorized");
                 accounts.push(account);
             }
@@ -265,6 +588,7 @@ pub fn add_account(
         id: account_id.clone(),
         email: email.clone(),
         name: name.clone(),
+        label: None,
         created_at: account.created_at,
         last_used: account.last_used,
     });
@@ -278,6 +602,18 @@ pub fn add_account(
     Ok(account)
 }
 
+/// 已有账号重新登录：更新 Token 与名称并解除禁用，保留显示名称、标签等用户设置
+fn apply_relogin(account: &mut Account, name: Option<String>, token: TokenData) {
+    account.token = token;
+    account.name = name;
+    if account.disabled {
+        account.disabled = false;
+        account.disabled_reason = None;
+        account.disabled_at = None;
+    }
+    account.update_last_used();
+}
+
 /// 添加或更新账号
 pub fn upsert_account(
     email: String,
@@ -294,14 +630,7 @@ pub fn upsert_account(
     if let Some(account_id) = existing_account_id {
         match load_account(&account_id) {
             Ok(mut account) => {
-                account.token = token;
-                account.name = name.clone();
-                if account.disabled {
-                    account.disabled = false;
-                    account.disabled_reason = None;
-                    account.disabled_at = None;
-                }
-                account.update_last_used();
+                apply_relogin(&mut account, name.clone(), token);
                 save_account(&account)?;
 
                 if let Some(idx_summary) = index.accounts.iter_mut().find(|s| s.id == account_id) {
@@ -333,6 +662,59 @@ pub fn upsert_account(
     add_account(email, name, token)
 }
 
+/// 按备份中的完整账号记录恢复（保留标签、显示名称和指纹绑定）
+/// 本地已有相同账号时沿用本地账号 ID 覆盖其内容；备份中的指纹在本机不存在时沿用本地绑定
+pub fn restore_account_record(mut account: Account) -> Result<Account, String> {
+    let _lock = ACCOUNT_INDEX_LOCK
+        .lock()
+        .map_err(|e| format!("获取锁失败: {}", e))?;
+    let mut index = load_account_index()?;
+
+    let existing_account_id =
+        find_matching_account_id(&index, &account.email, &account.token, true)?;
+    let previous = existing_account_id
+        .as_deref()
+        .and_then(|id| load_account(id).ok());
+    match existing_account_id {
+        Some(id) => account.id = id,
+        None if index.accounts.iter().any(|s| s.id == account.id) => {
+            account.id = Uuid::new_v4().to_string();
+        }
+        None => {}
+    }
+
+    let fingerprint_exists = account
+        .fingerprint_id
+        .as_deref()
+        .is_some_and(|id| modules::fingerprint::get_fingerprint(id).is_ok());
+    if !fingerprint_exists {
+        account.fingerprint_id = previous
+            .and_then(|previous| previous.fingerprint_id)
+            .or_else(|| Some("original".to_string()));
+    }
+
+    save_account(&account)?;
+
+    let summary = AccountSummary {
+        id: account.id.clone(),
+        email: account.email.clone(),
+        name: account.name.clone(),
+        label: account.label.clone(),
+        created_at: account.created_at,
+        last_used: account.last_used,
+    };
+    match index.accounts.iter_mut().find(|s| s.id == account.id) {
+        Some(existing) => *existing = summary,
+        None => index.accounts.push(summary),
+    }
+    if index.current_account_id.is_none() {
+        index.current_account_id = Some(account.id.clone());
+    }
+    save_account_index(&index)?;
+
+    Ok(account)
+}
+
 /// 删除账号
 pub fn delete_account(account_id: &str) -> Result<(), String> {
     let _lock = ACCOUNT_INDEX_LOCK
@@ -359,6 +741,7 @@ pub fn delete_account(account_id: &str) -> Result<(), String> {
     if account_path.exists() {
         fs::remove_file(&account_path).map_err(|e| format!("删除账号文件失败: {}", e))?;
     }
+    let _ = modules::quota_cache::remove_quota_history(account_id);
 
     Ok(())
 }
@@ -457,6 +840,44 @@ pub fn set_current_account_id(account_id: &str) -> Result<(), String> {
     Ok(())
 }
 
+/// 清除当前账号 ID（账号本身保留）
+pub fn clear_current_account_id() -> Result<(), String> {
+    let _lock = ACCOUNT_INDEX_LOCK
+        .lock()
+        .map_err(|e| format!("获取锁失败: {}", e))?;
+    let mut index = load_account_index()?;
+    if index.current_account_id.take().is_some() {
+        save_account_index(&index)?;
+    }
+    Ok(())
+}
+
+/// 修复悬空的当前账号 ID：指向的账号已不存在时改为第一个可用账号，返回是否做了修复
+pub fn repair_current_account_id() -> Result<bool, String> {
+    let _lock = ACCOUNT_INDEX_LOCK
+        .lock()
+        .map_err(|e| format!("获取锁失败: {}", e))?;
+    let mut index = load_account_index()?;
+    let Some(current_id) = index.current_account_id.clone() else {
+        return Ok(false);
+    };
+    if index.accounts.iter().any(|s| s.id == current_id) && load_account(&current_id).is_ok() {
+        return Ok(false);
+    }
+
+    index.current_account_id = index
+        .accounts
+        .iter()
+        .find(|s| s.id != current_id && load_account(&s.id).is_ok())
+        .map(|s| s.id.clone());
+    save_account_index(&index)?;
+    modules::logger::log_warn(&format!(
+        "[Account] 当前账号指向不存在的账号，已修复: {} -> {:?}",
+        current_id, index.current_account_id
+    ));
+    Ok(true)
+}
+
 /// 保存当前账号信息到共享文件（供扩展启动时读取）
 fn save_current_account_file(email: &str) -> Result<(), String> {
     use std::fs;
@@ -480,6 +901,12 @@ fn save_current_account_file(email: &str) -> Result<(), String> {
     Ok(())
 }
 
+fn record_quota_history(account_id: &str, quota: &QuotaData) {
+    if let Err(e) = modules::quota_cache::append_quota_history(account_id, quota) {
+        modules::logger::log_warn(&format!("[QuotaHistory] 记录配额历史失败: {}", e));
+    }
+}
+
 /// 更新账号配额
 pub fn update_account_quota(account_id: &str, quota: QuotaData) -> Result<(), String> {
     let mut account = load_account(account_id)?;
@@ -497,8 +924,13 @@ pub fn update_account_quota(account_id: &str, quota: QuotaData) -> Result<(), St
                 merged_quota.subscription_tier = quota.subscription_tier.clone();
                 merged_quota.is_forbidden = quota.is_forbidden;
                 merged_quota.last_updated = quota.last_updated;
-                account.update_quota(merged_quota);
+                account.update_quota(merged_quota.clone());
                 save_account(&account)?;
+                record_quota_history(account_id, &merged_quota);
+                modules::websocket::broadcast_event(modules::websocket::WsMessage::QuotaUpdated {
+                    account_id: account_id.to_string(),
+                    quota: merged_quota,
+                });
                 return Ok(());
             }
         }
@@ -508,6 +940,11 @@ pub fn update_account_quota(account_id: &str, quota: QuotaData) -> Result<(), St
     save_account(&account)?;
     if let Some(ref quota) = account.quota {
         let _ = modules::quota_cache::write_quota_cache("authorized", &account.email, quota);
+        record_quota_history(account_id, quota);
+        modules::websocket::broadcast_event(modules::websocket::WsMessage::QuotaUpdated {
+            account_id: account_id.to_string(),
+            quota: quota.clone(),
+        });
     }
     Ok(())
 }
@@ -615,7 +1052,7 @@ pub fn delete_device_version(_account_id: &str, version_id: &str) -> Result<(),
     crate::modules::fingerprint::delete_fingerprint(version_id)
 }
 
-#[derive(Serialize)]
+#[derive(Debug, Clone, Serialize)]
 pub struct RefreshStats {
     pub total: usize,
     pub success: usize,
@@ -995,13 +1432,24 @@ pub async fn run_auto_switch_if_needed() -> Result<Option<Account>, String> {
     result
 }
 
-/// 批量刷新所有账号配额
-pub async fn refresh_all_quotas_logic() -> Result<RefreshStats, String> {
-    use futures::future::join_all;
+/// 单个账号的配额刷新结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountQuotaRefreshResult {
+    pub account_id: String,
+    pub ok: bool,
+    pub error: Option<String>,
+    pub quota: Option<QuotaData>,
+}
+
+/// 批量刷新所有账号配额，返回每个账号的结果（单个账号失败不影响其他账号）
+/// 已禁用或 forbidden 的账号不参与刷新，结果顺序与账号列表一致
+pub async fn refresh_all_quotas_detailed_logic() -> Result<Vec<AccountQuotaRefreshResult>, String> {
     use std::sync::Arc;
     use tokio::sync::Semaphore;
+    use tokio::task::JoinSet;
 
-    const MAX_CONCURRENT: usize = 5;
+    const MAX_CONCURRENT: usize = 4;
     let start = std::time::Instant::now();
 
     modules::logger::log_info(&format!(
@@ -1011,8 +1459,11 @@ pub async fn refresh_all_quotas_logic() -> Result<RefreshStats, String> {
     let accounts = list_accounts()?;
 
     let semaphore = Arc::new(Semaphore::new(MAX_CONCURRENT));
+    let mut tasks = JoinSet::new();
+    // 记录每个任务对应的账号，任务 panic 或被取消时仍能返回该账号的失败结果
+    let mut task_accounts: HashMap<tokio::task::Id, (usize, String)> = HashMap::new();
 
-    let tasks: Vec<_> = accounts
+    for (order, mut account) in accounts
         .into_iter()
         .filter(|account| {
             if account.disabled {
@@ -1027,68 +1478,313 @@ pub async fn refresh_all_quotas_logic() -> Result<RefreshStats, String> {
             }
             true
         })
-        .map(|mut account| {
-            let email = account.email.clone();
+        .enumerate()
+    {
+        let permit = semaphore.clone();
+        let task_account_id = account.id.clone();
+        let handle = tasks.spawn(async move {
+            let _guard = permit.acquire().await.unwrap();
             let account_id = account.id.clone();
+            let email = account.email.clone();
+            let result = match fetch_quota_with_retry(&mut account, false).await {
+                Ok(quota) => match update_account_quota(&account_id, quota.clone()) {
+                    Ok(()) => Ok(quota),
+                    Err(e) => Err(format!("Account {}: Save quota failed - {}", email, e)),
+                },
+                Err(e) => Err(format!("Account {}: Fetch quota failed - {}", email, e)),
+            };
+            let item = match result {
+                Ok(quota) => AccountQuotaRefreshResult {
+                    account_id,
+                    ok: true,
+                    error: None,
+                    quota: Some(quota),
+                },
+                Err(msg) => AccountQuotaRefreshResult {
+                    account_id,
+                    ok: false,
+                    error: Some(msg),
+                    quota: None,
+                },
+            };
+            (order, item)
+        });
+        task_accounts.insert(handle.id(), (order, task_account_id));
+    }
+
+    let mut results = Vec::new();
+    while let Some(joined) = tasks.join_next().await {
+        match joined {
+            Ok(item) => results.push(item),
+            Err(e) => {
+                modules::logger::log_error(&format!("配额刷新任务异常退出: {}", e));
+                if let Some((order, account_id)) = task_accounts.remove(&e.id()) {
+                    results.push((
+                        order,
+                        AccountQuotaRefreshResult {
+                            account_id,
+                            ok: false,
+                            error: Some(format!("刷新任务异常退出: {}", e)),
+                            quota: None,
+                        },
+                    ));
+                }
+            }
+        }
+    }
+    results.sort_by_key(|(order, _)| *order);
+    let results: Vec<AccountQuotaRefreshResult> =
+        results.into_iter().map(|(_, item)| item).collect();
+
+    let success = results.iter().filter(|item| item.ok).count();
+    modules::logger::log_info(&format!(
+        "批量刷新完成: {} 成功, {} 失败, 耗时: {}ms",
+        success,
+        results.len() - success,
+        start.elapsed().as_millis()
+    ));
+
+    Ok(results)
+}
+
+/// 批量刷新成功后执行自动切号；未切号时检查配额预警
+pub async fn run_post_batch_refresh_hooks() {
+    let mut switched = false;
+    match run_auto_switch_if_needed().await {
+        Ok(Some(account)) => {
+            modules::logger::log_info(&format!("[AutoSwitch] 自动切号完成: {}", account.email));
+            switched = true;
+        }
+        Ok(None) => {}
+        Err(e) => {
+            modules::logger::log_warn(&format!("[AutoSwitch] 自动切号执行失败: {}", e));
+        }
+    }
+    if !switched {
+        if let Err(e) = run_quota_alert_if_needed() {
+            modules::logger::log_warn(&format!("[QuotaAlert] 预警检查失败: {}", e));
+        }
+    }
+}
+
+/// 批量刷新所有账号配额
+pub async fn refresh_all_quotas_logic() -> Result<RefreshStats, String> {
+    let results = refresh_all_quotas_detailed_logic().await?;
+    let success = results.iter().filter(|item| item.ok).count();
+    let details: Vec<String> = results.into_iter().filter_map(|item| item.error).collect();
+
+    Ok(RefreshStats {
+        total: success + details.len(),
+        success,
+        failed: details.len(),
+        details,
+    })
+}
+
+pub const GROUP_REFRESH_PROGRESS_EVENT: &str = "quota:group_refresh_progress";
+
+/// 进行中的分组刷新取消标记：按分组名区分，每次刷新开始时新建、结束时移除
+static GROUP_REFRESH_CANCEL_TOKENS: std::sync::LazyLock<
+    Mutex<HashMap<String, Vec<std::sync::Arc<AtomicBool>>>>,
+> = std::sync::LazyLock::new(|| Mutex::new(HashMap::new()));
+
+fn begin_group_refresh(group: &str) -> std::sync::Arc<AtomicBool> {
+    let token = std::sync::Arc::new(AtomicBool::new(false));
+    if let Ok(mut tokens) = GROUP_REFRESH_CANCEL_TOKENS.lock() {
+        tokens
+            .entry(group.to_string())
+            .or_default()
+            .push(token.clone());
+    }
+    token
+}
+
+fn end_group_refresh(group: &str, token: &std::sync::Arc<AtomicBool>) {
+    if let Ok(mut tokens) = GROUP_REFRESH_CANCEL_TOKENS.lock() {
+        if let Some(items) = tokens.get_mut(group) {
+            items.retain(|item| !std::sync::Arc::ptr_eq(item, token));
+            if items.is_empty() {
+                tokens.remove(group);
+            }
+        }
+    }
+}
+
+/// 分组刷新中单个账号的结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GroupAccountRefreshResult {
+    pub account_id: String,
+    pub email: String,
+    pub success: bool,
+    /// 因取消而未执行刷新
+    pub skipped: bool,
+    pub error: Option<String>,
+}
+
+/// 分组刷新汇总
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GroupRefreshSummary {
+    pub group_id: String,
+    pub total: usize,
+    pub success: usize,
+    pub failed: usize,
+    pub cancelled: bool,
+    pub accounts: Vec<GroupAccountRefreshResult>,
+}
+
+/// 分组刷新进度事件
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GroupRefreshProgress {
+    group_id: String,
+    completed: usize,
+    total: usize,
+    result: GroupAccountRefreshResult,
+}
+
+/// 取消该分组正在进行的刷新（已开始的请求会继续完成，尚未开始的账号将被跳过）
+/// 返回是否有可取消的刷新；不影响其他分组的刷新
+pub fn cancel_group_quota_refresh(group_id: &str) -> bool {
+    let group = group_id.trim().to_lowercase();
+    let Ok(tokens) = GROUP_REFRESH_CANCEL_TOKENS.lock() else {
+        return false;
+    };
+    match tokens.get(&group) {
+        Some(items) if !items.is_empty() => {
+            for token in items {
+                token.store(true, Ordering::SeqCst);
+            }
+            true
+        }
+        _ => false,
+    }
+}
+
+/// 仅刷新某个分组（账号标签）内的账号配额
+/// 并发与跳过规则与全量刷新一致，每完成一个账号发送一次进度事件
+pub async fn refresh_group_quotas_logic(group_id: &str) -> Result<GroupRefreshSummary, String> {
+    use futures::future::join_all;
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::Arc;
+    use tauri::Emitter;
+    use tokio::sync::Semaphore;
+
+    const MAX_CONCURRENT: usize = 5;
+
+    let group = group_id.trim().to_lowercase();
+    if group.is_empty() {
+        return Err("分组不能为空".to_string());
+    }
+
+    let accounts: Vec<Account> = list_accounts()?
+        .into_iter()
+        .filter(|account| account.tags.contains(&group))
+        .filter(|account| {
+            !account.disabled
+                && !account
+                    .quota
+                    .as_ref()
+                    .map(|quota| quota.is_forbidden)
+                    .unwrap_or(false)
+        })
+        .collect();
+    let total = accounts.len();
+    modules::logger::log_info(&format!(
+        "开始刷新分组配额: group={}, 账号数={} (最大并发: {})",
+        group, total, MAX_CONCURRENT
+    ));
+
+    let cancel_token = begin_group_refresh(&group);
+    let semaphore = Arc::new(Semaphore::new(MAX_CONCURRENT));
+    let completed = Arc::new(AtomicUsize::new(0));
+
+    let tasks: Vec<_> = accounts
+        .into_iter()
+        .map(|mut account| {
             let permit = semaphore.clone();
+            let completed = completed.clone();
+            let group = group.clone();
+            let cancel_token = cancel_token.clone();
             async move {
                 let _guard = permit.acquire().await.unwrap();
-                match fetch_quota_with_retry(&mut account, false).await {
-                    Ok(quota) => {
-                        if let Err(e) = update_account_quota(&account_id, quota) {
-                            let msg = format!("Account {}: Save quota failed - {}", email, e);
-                            Err(msg)
-                        } else {
-                            Ok(())
-                        }
-                    }
-                    Err(e) => {
-                        let msg = format!("Account {}: Fetch quota failed - {}", email, e);
-                        Err(msg)
+                let mut result = GroupAccountRefreshResult {
+                    account_id: account.id.clone(),
+                    email: account.email.clone(),
+                    success: false,
+                    skipped: false,
+                    error: None,
+                };
+                if cancel_token.load(Ordering::SeqCst) {
+                    result.skipped = true;
+                } else {
+                    match fetch_quota_with_retry(&mut account, false).await {
+                        Ok(quota) => match update_account_quota(&account.id, quota) {
+                            Ok(()) => result.success = true,
+                            Err(e) => result.error = Some(format!("保存配额失败: {}", e)),
+                        },
+                        Err(e) => result.error = Some(format!("查询配额失败: {}", e)),
                     }
                 }
+
+                let done = completed.fetch_add(1, Ordering::SeqCst) + 1;
+                if let Some(app_handle) = crate::get_app_handle() {
+                    let _ = app_handle.emit(
+                        GROUP_REFRESH_PROGRESS_EVENT,
+                        GroupRefreshProgress {
+                            group_id: group,
+                            completed: done,
+                            total,
+                            result: result.clone(),
+                        },
+                    );
+                }
+                result
             }
         })
         .collect();
 
-    let total = tasks.len();
     let results = join_all(tasks).await;
+    let cancelled = cancel_token.load(Ordering::SeqCst);
+    end_group_refresh(&group, &cancel_token);
+    let success = results.iter().filter(|item| item.success).count();
+    let failed = results.iter().filter(|item| item.error.is_some()).count();
 
-    let mut success = 0;
-    let mut failed = 0;
-    let mut details = Vec::new();
-
-    for result in results {
-        match result {
-            Ok(()) => success += 1,
-            Err(msg) => {
-                failed += 1;
-                details.push(msg);
-            }
-        }
-    }
-
-    let elapsed = start.elapsed();
     modules::logger::log_info(&format!(
-        "批量刷新完成: {} 成功, {} 失败, 耗时: {}ms",
-        success,
-        failed,
-        elapsed.as_millis()
+        "分组配额刷新完成: group={}, {} 成功, {} 失败, 已取消={}",
+        group, success, failed, cancelled
     ));
 
-    Ok(RefreshStats {
+    Ok(GroupRefreshSummary {
+        group_id: group,
         total,
         success,
         failed,
-        details,
+        cancelled,
+        accounts: results,
     })
 }
 
-/// 带重试的配额查询
+/// 带重试的配额查询（使用默认超时，批量刷新时每个账号同样受此超时约束）
 /// skip_cache: 是否跳过缓存，单个账号刷新应传 true
 pub async fn fetch_quota_with_retry(
     account: &mut Account,
     skip_cache: bool,
+) -> crate::error::AppResult<QuotaData> {
+    fetch_quota_with_timeout(
+        account,
+        skip_cache,
+        modules::quota::DEFAULT_QUOTA_TIMEOUT_MS,
+    )
+    .await
+}
+
+/// 带重试的配额查询，超时后返回 `QUOTA_TIMEOUT:` 错误
+pub async fn fetch_quota_with_timeout(
+    account: &mut Account,
+    skip_cache: bool,
+    timeout_ms: u64,
 ) -> crate::error::AppResult<QuotaData> {
     use crate::error::AppError;
     use crate::modules::oauth;
@@ -1117,8 +1813,13 @@ pub async fn fetch_quota_with_retry(
         let _ = upsert_account(account.email.clone(), account.name.clone(), token.clone());
     }
 
-    let result =
-        modules::quota::fetch_quota(&account.token.access_token, &account.email, skip_cache).await;
+    let result = modules::quota::fetch_quota_with_timeout(
+        &account.token.access_token,
+        &account.email,
+        skip_cache,
+        timeout_ms,
+    )
+    .await;
     match result {
         Ok(payload) => {
             account.quota_error = payload.error.map(|err| QuotaErrorInfo {
@@ -1216,3 +1917,114 @@ pub async fn prepare_account_for_injection(account_id: &str) -> Result<Account,
     }
     Ok(account)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary(id: &str, created_at: i64) -> AccountSummary {
+        AccountSummary {
+            id: id.to_string(),
+            email: format!("{}@example.com", id),
+            name: None,
+            label: None,
+            created_at,
+            last_used: created_at,
+        }
+    }
+
+    #[test]
+    fn test_rebuilt_index_keeps_reordered_order() {
+        let mut previous = AccountIndex::new();
+        previous.accounts = vec![summary("c", 3), summary("a", 1), summary("b", 2)];
+        previous.current_account_id = Some("a".to_string());
+
+        let scanned = vec![
+            summary("a", 1),
+            summary("b", 2),
+            summary("c", 3),
+            summary("d", 4),
+        ];
+        let index = build_rebuilt_index(scanned, Some(&previous), Some("a".to_string()));
+        let order: Vec<&str> = index.accounts.iter().map(|s| s.id.as_str()).collect();
+        assert_eq!(order, vec!["c", "a", "b", "d"]);
+        assert_eq!(index.current_account_id.as_deref(), Some("a"));
+
+        let without_previous = build_rebuilt_index(
+            vec![summary("b", 2), summary("a", 1)],
+            None,
+            Some("x".to_string()),
+        );
+        assert_eq!(without_previous.accounts[0].id, "b");
+        assert!(without_previous.current_account_id.is_none());
+    }
+
+    #[test]
+    fn test_group_refresh_cancel_is_scoped_per_run() {
+        let work = begin_group_refresh("cancel-test-work");
+        let home = begin_group_refresh("cancel-test-home");
+
+        assert!(cancel_group_quota_refresh(" Cancel-Test-Work "));
+        assert!(work.load(Ordering::SeqCst));
+        assert!(!home.load(Ordering::SeqCst));
+
+        // 下一次刷新使用新的标记，不受上一次取消影响
+        end_group_refresh("cancel-test-work", &work);
+        let next = begin_group_refresh("cancel-test-work");
+        assert!(!next.load(Ordering::SeqCst));
+
+        end_group_refresh("cancel-test-work", &next);
+        end_group_refresh("cancel-test-home", &home);
+        assert!(!cancel_group_quota_refresh("cancel-test-work"));
+    }
+
+    #[test]
+    fn test_apply_tag_changes() {
+        let tags = |values: &[&str]| values.iter().map(|v| v.to_string()).collect::<Vec<_>>();
+        let remove: HashSet<String> = ["old".to_string()].into_iter().collect();
+
+        let result = apply_tag_changes(&tags(&["Old", "work"]), &tags(&["pro", "WORK"]), &remove);
+        assert_eq!(result.unwrap(), tags(&["work", "pro"]));
+
+        let full = tags(&["a", "b", "c", "d", "e", "f", "g", "h", "i", "j"]);
+        assert!(apply_tag_changes(&full, &tags(&["k"]), &HashSet::new()).is_err());
+    }
+
+    #[test]
+    fn test_normalize_account_label() {
+        assert_eq!(
+            normalize_account_label("  Work  ").unwrap().as_deref(),
+            Some("Work")
+        );
+        assert_eq!(normalize_account_label("").unwrap(), None);
+        assert_eq!(normalize_account_label(" \t ").unwrap(), None);
+
+        let max = "名".repeat(ACCOUNT_LABEL_MAX_CHARS);
+        assert_eq!(normalize_account_label(&max).unwrap(), Some(max.clone()));
+        assert!(normalize_account_label(&format!("{}x", max)).is_err());
+    }
+
+    #[test]
+    fn test_relogin_keeps_label() {
+        let token = |access: &str| {
+            TokenData::new(
+                access.to_string(),
+                "refresh".to_string(),
+                3600,
+                None,
+                None,
+                None,
+            )
+        };
+        let mut account = Account::new("a".to_string(), "a@example.com".to_string(), token("old"));
+        account.label = Some("Work".to_string());
+        account.disabled = true;
+
+        apply_relogin(&mut account, Some("Alice".to_string()), token("new"));
+
+        assert_eq!(account.label.as_deref(), Some("Work"));
+        assert_eq!(account.token.access_token, "new");
+        assert_eq!(account.name.as_deref(), Some("Alice"));
+        assert!(!account.disabled);
+    }
+}
@@ -0,0 +1,449 @@
+//! 全平台账号加密备份
+//! 将 Antigravity / Codex / GitHub Copilot / Kiro / Windsurf 账号序列化为一个 JSON 信封，
+//! 使用密码派生密钥（PBKDF2-HMAC-SHA256）加 AES-256-GCM 加密后写入单个文件；
+//! 明文只在内存中存在，不落盘
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+use aes_gcm::aead::generic_array::GenericArray;
+use aes_gcm::aead::{Aead, AeadCore, OsRng};
+use aes_gcm::{Aes256Gcm, KeyInit, Nonce};
+use base64::{engine::general_purpose, Engine as _};
+use chrono::Utc;
+use pbkdf2::pbkdf2_hmac;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+
+use crate::models::codex::CodexAccount;
+use crate::models::github_copilot::GitHubCopilotAccount;
+use crate::models::kiro::KiroAccount;
+use crate::models::windsurf::WindsurfAccount;
+use crate::models::Account;
+use crate::modules;
+use crate::modules::tray_layout::{
+    PLATFORM_ANTIGRAVITY, PLATFORM_CODEX, PLATFORM_GITHUB_COPILOT, PLATFORM_KIRO, PLATFORM_WINDSURF,
+};
+
+const BUNDLE_FORMAT: &str = "cockpit-accounts-bundle";
+const BUNDLE_VERSION: u32 = 1;
+const KDF_ITERATIONS: u32 = 210_000;
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+
+/// 加密文件外层（明文部分只包含解密参数与平台列表）
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EncryptedBundleFile {
+    format: String,
+    version: u32,
+    providers: Vec<String>,
+    iterations: u32,
+    salt: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+/// 加密前的账号信封，未导出的平台字段为空
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AccountBundle {
+    version: u32,
+    exported_at: i64,
+    providers: Vec<String>,
+    #[serde(default)]
+    antigravity: Option<Vec<Account>>,
+    #[serde(default)]
+    codex: Option<Vec<CodexAccount>>,
+    #[serde(default)]
+    github_copilot: Option<Vec<GitHubCopilotAccount>>,
+    #[serde(default)]
+    kiro: Option<Vec<KiroAccount>>,
+    #[serde(default)]
+    windsurf: Option<Vec<WindsurfAccount>>,
+}
+
+/// 导出结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountBundleExportResult {
+    pub path: String,
+    pub providers: Vec<ProviderAccountCount>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderAccountCount {
+    pub provider: String,
+    pub count: usize,
+}
+
+/// 单个平台的恢复结果
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderRestoreSummary {
+    pub provider: String,
+    /// 本地不存在、新建的账号数量
+    pub created: usize,
+    /// 本地已存在、被备份覆盖的账号数量
+    pub updated: usize,
+    pub failed: usize,
+    pub errors: Vec<String>,
+}
+
+/// 导入结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountBundleImportResult {
+    pub exported_at: i64,
+    pub providers: Vec<ProviderRestoreSummary>,
+    /// 备份中存在但本次未选择恢复的平台
+    pub skipped_providers: Vec<String>,
+}
+
+fn derive_key(password: &str, salt: &[u8], iterations: u32) -> [u8; 32] {
+    let mut key = [0u8; 32];
+    pbkdf2_hmac::<Sha256>(password.as_bytes(), salt, iterations, &mut key);
+    key
+}
+
+fn validate_password(password: &str) -> Result<(), String> {
+    if password.is_empty() {
+        return Err("备份密码不能为空".to_string());
+    }
+    Ok(())
+}
+
+fn encrypt_bundle(bundle: &AccountBundle, password: &str) -> Result<EncryptedBundleFile, String> {
+    let plaintext = serde_json::to_vec(bundle).map_err(|e| format!("序列化账号备份失败: {}", e))?;
+    let mut salt = [0u8; SALT_LEN];
+    rand::rngs::OsRng.fill_bytes(&mut salt);
+    let key = derive_key(password, &salt, KDF_ITERATIONS);
+
+    let cipher = Aes256Gcm::new(GenericArray::from_slice(&key));
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(&nonce, plaintext.as_slice())
+        .map_err(|e| format!("AES-GCM 加密失败: {}", e))?;
+
+    Ok(EncryptedBundleFile {
+        format: BUNDLE_FORMAT.to_string(),
+        version: BUNDLE_VERSION,
+        providers: bundle.providers.clone(),
+        iterations: KDF_ITERATIONS,
+        salt: general_purpose::STANDARD.encode(salt),
+        nonce: general_purpose::STANDARD.encode(nonce),
+        ciphertext: general_purpose::STANDARD.encode(ciphertext),
+    })
+}
+
+fn decrypt_bundle(file: &EncryptedBundleFile, password: &str) -> Result<AccountBundle, String> {
+    if file.format != BUNDLE_FORMAT {
+        return Err("不是有效的账号备份文件".to_string());
+    }
+    if file.version > BUNDLE_VERSION {
+        return Err(format!(
+            "账号备份版本过新（{}），请升级应用后再导入",
+            file.version
+        ));
+    }
+    let decode = |value: &str, label: &str| {
+        general_purpose::STANDARD
+            .decode(value)
+            .map_err(|e| format!("账号备份 {} 解码失败: {}", label, e))
+    };
+    let salt = decode(&file.salt, "salt")?;
+    let nonce = decode(&file.nonce, "nonce")?;
+    let ciphertext = decode(&file.ciphertext, "ciphertext")?;
+    if salt.len() != SALT_LEN || nonce.len() != NONCE_LEN {
+        return Err("账号备份文件已损坏".to_string());
+    }
+    // 迭代次数来自文件本身，只接受本应用写入的值，避免构造的文件长时间占用 CPU
+    if file.iterations != KDF_ITERATIONS {
+        return Err("账号备份文件的密钥派生参数无效".to_string());
+    }
+
+    let key = derive_key(password, &salt, file.iterations);
+    let cipher = Aes256Gcm::new(GenericArray::from_slice(&key));
+    let plaintext = cipher
+        .decrypt(Nonce::from_slice(&nonce), ciphertext.as_slice())
+        .map_err(|_| "密码错误或账号备份文件已损坏".to_string())?;
+    serde_json::from_slice(&plaintext).map_err(|e| format!("解析账号备份失败: {}", e))
+}
+
+fn collect_bundle() -> Result<AccountBundle, String> {
+    let mut bundle = AccountBundle {
+        version: BUNDLE_VERSION,
+        exported_at: Utc::now().timestamp(),
+        ..Default::default()
+    };
+
+    let accounts = modules::list_accounts()?;
+    if !accounts.is_empty() {
+        bundle.antigravity = Some(accounts);
+    }
+    let accounts = modules::codex_account::list_accounts();
+    if !accounts.is_empty() {
+        bundle.codex = Some(accounts);
+    }
+    let accounts = modules::github_copilot_account::list_accounts();
+    if !accounts.is_empty() {
+        bundle.github_copilot = Some(accounts);
+    }
+    let accounts = modules::kiro_account::list_accounts();
+    if !accounts.is_empty() {
+        bundle.kiro = Some(accounts);
+    }
+    let accounts = modules::windsurf_account::list_accounts();
+    if !accounts.is_empty() {
+        bundle.windsurf = Some(accounts);
+    }
+
+    bundle.providers = provider_counts(&bundle)
+        .into_iter()
+        .map(|item| item.provider)
+        .collect();
+    Ok(bundle)
+}
+
+fn provider_counts(bundle: &AccountBundle) -> Vec<ProviderAccountCount> {
+    [
+        (
+            PLATFORM_ANTIGRAVITY,
+            bundle.antigravity.as_ref().map(Vec::len),
+        ),
+        (PLATFORM_CODEX, bundle.codex.as_ref().map(Vec::len)),
+        (
+            PLATFORM_GITHUB_COPILOT,
+            bundle.github_copilot.as_ref().map(Vec::len),
+        ),
+        (PLATFORM_KIRO, bundle.kiro.as_ref().map(Vec::len)),
+        (PLATFORM_WINDSURF, bundle.windsurf.as_ref().map(Vec::len)),
+    ]
+    .into_iter()
+    .filter_map(|(provider, count)| {
+        count.map(|count| ProviderAccountCount {
+            provider: provider.to_string(),
+            count,
+        })
+    })
+    .collect()
+}
+
+/// 导出全部平台账号到加密备份文件
+pub fn export_all_accounts(
+    password: &str,
+    path: &str,
+) -> Result<AccountBundleExportResult, String> {
+    validate_password(password)?;
+    let path = path.trim();
+    if path.is_empty() {
+        return Err("导出路径不能为空".to_string());
+    }
+
+    let bundle = collect_bundle()?;
+    let encrypted = encrypt_bundle(&bundle, password)?;
+    let content = serde_json::to_string_pretty(&encrypted)
+        .map_err(|e| format!("序列化账号备份失败: {}", e))?;
+
+    let target = Path::new(path);
+    if let Some(parent) = target.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        fs::create_dir_all(parent).map_err(|e| format!("创建导出目录失败: {}", e))?;
+    }
+    // 临时文件中只有密文
+    let temp_path = target.with_extension("tmp");
+    fs::write(&temp_path, content).map_err(|e| format!("写入账号备份失败: {}", e))?;
+    fs::rename(&temp_path, target).map_err(|e| format!("保存账号备份失败: {}", e))?;
+
+    let providers = provider_counts(&bundle);
+    modules::logger::log_info(&format!(
+        "[AccountBundle] 已导出账号备份: providers={:?}, path={}",
+        bundle.providers, path
+    ));
+    Ok(AccountBundleExportResult {
+        path: path.to_string(),
+        providers,
+    })
+}
+
+fn restore_records<T, Exists, Restore>(
+    provider: &str,
+    accounts: Vec<T>,
+    exists: Exists,
+    restore: Restore,
+) -> ProviderRestoreSummary
+where
+    Exists: Fn(&T) -> bool,
+    Restore: Fn(T) -> Result<(), String>,
+{
+    let mut summary = ProviderRestoreSummary {
+        provider: provider.to_string(),
+        ..Default::default()
+    };
+    for account in accounts {
+        let existed = exists(&account);
+        match restore(account) {
+            Ok(()) if existed => summary.updated += 1,
+            Ok(()) => summary.created += 1,
+            Err(err) => {
+                summary.failed += 1;
+                summary.errors.push(err);
+            }
+        }
+    }
+    summary
+}
+
+/// 从加密备份文件恢复账号
+/// providers 为空时恢复备份中的全部平台；本地已存在的账号以备份内容覆盖，不会重复创建
+pub fn import_all_accounts(
+    password: &str,
+    path: &str,
+    providers: Option<Vec<String>>,
+) -> Result<AccountBundleImportResult, String> {
+    validate_password(password)?;
+    let content =
+        fs::read_to_string(path.trim()).map_err(|e| format!("读取账号备份失败: {}", e))?;
+    let file: EncryptedBundleFile =
+        serde_json::from_str(&content).map_err(|_| "不是有效的账号备份文件".to_string())?;
+    let bundle = decrypt_bundle(&file, password)?;
+
+    let selected: Option<HashSet<String>> =
+        providers.map(|items| items.into_iter().collect::<HashSet<_>>());
+    let wanted = |provider: &str| {
+        selected
+            .as_ref()
+            .map(|items| items.contains(provider))
+            .unwrap_or(true)
+    };
+
+    let mut restored = Vec::new();
+    let mut skipped_providers = Vec::new();
+    let mut skip = |provider: &str| skipped_providers.push(provider.to_string());
+
+    if let Some(accounts) = bundle.antigravity {
+        if wanted(PLATFORM_ANTIGRAVITY) {
+            let existing_emails: HashSet<String> = modules::list_accounts()
+                .unwrap_or_default()
+                .into_iter()
+                .map(|account| account.email)
+                .collect();
+            restored.push(restore_records(
+                PLATFORM_ANTIGRAVITY,
+                accounts,
+                |account| existing_emails.contains(&account.email),
+                |account| modules::account::restore_account_record(account).map(|_| ()),
+            ));
+        } else {
+            skip(PLATFORM_ANTIGRAVITY);
+        }
+    }
+    if let Some(accounts) = bundle.codex {
+        if wanted(PLATFORM_CODEX) {
+            restored.push(restore_records(
+                PLATFORM_CODEX,
+                accounts,
+                |account| modules::codex_account::find_matching_account_id(account).is_some(),
+                |account| modules::codex_account::upsert_account_record(account).map(|_| ()),
+            ));
+        } else {
+            skip(PLATFORM_CODEX);
+        }
+    }
+    if let Some(accounts) = bundle.github_copilot {
+        if wanted(PLATFORM_GITHUB_COPILOT) {
+            restored.push(restore_records(
+                PLATFORM_GITHUB_COPILOT,
+                accounts,
+                |account| modules::github_copilot_account::load_account(&account.id).is_some(),
+                |account| {
+                    modules::github_copilot_account::upsert_account_record(account).map(|_| ())
+                },
+            ));
+        } else {
+            skip(PLATFORM_GITHUB_COPILOT);
+        }
+    }
+    if let Some(accounts) = bundle.kiro {
+        if wanted(PLATFORM_KIRO) {
+            restored.push(restore_records(
+                PLATFORM_KIRO,
+                accounts,
+                |account| modules::kiro_account::load_account(&account.id).is_some(),
+                |account| modules::kiro_account::upsert_account_record(account).map(|_| ()),
+            ));
+        } else {
+            skip(PLATFORM_KIRO);
+        }
+    }
+    if let Some(accounts) = bundle.windsurf {
+        if wanted(PLATFORM_WINDSURF) {
+            restored.push(restore_records(
+                PLATFORM_WINDSURF,
+                accounts,
+                |account| modules::windsurf_account::load_account(&account.id).is_some(),
+                |account| modules::windsurf_account::upsert_account_record(account).map(|_| ()),
+            ));
+        } else {
+            skip(PLATFORM_WINDSURF);
+        }
+    }
+
+    modules::logger::log_info(&format!(
+        "[AccountBundle] 已导入账号备份: restored={:?}, skipped={:?}",
+        restored
+            .iter()
+            .map(|item| format!(
+                "{}(+{} ~{} !{})",
+                item.provider, item.created, item.updated, item.failed
+            ))
+            .collect::<Vec<_>>(),
+        skipped_providers
+    ));
+    Ok(AccountBundleImportResult {
+        exported_at: bundle.exported_at,
+        providers: restored,
+        skipped_providers,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bundle_round_trip_and_wrong_password() {
+        let bundle = AccountBundle {
+            version: BUNDLE_VERSION,
+            exported_at: 1,
+            providers: vec![PLATFORM_CODEX.to_string()],
+            codex: Some(Vec::new()),
+            ..Default::default()
+        };
+        let encrypted = encrypt_bundle(&bundle, "secret").unwrap();
+        assert_eq!(encrypted.providers, vec![PLATFORM_CODEX.to_string()]);
+        assert!(!encrypted.ciphertext.contains("codex"));
+
+        let decrypted = decrypt_bundle(&encrypted, "secret").unwrap();
+        assert_eq!(decrypted.exported_at, 1);
+        assert!(decrypted.codex.is_some());
+        assert!(decrypted.kiro.is_none());
+
+        assert!(decrypt_bundle(&encrypted, "wrong").is_err());
+    }
+
+    #[test]
+    fn test_decrypt_rejects_untrusted_iterations() {
+        let bundle = AccountBundle {
+            version: BUNDLE_VERSION,
+            ..Default::default()
+        };
+        let mut encrypted = encrypt_bundle(&bundle, "secret").unwrap();
+        encrypted.iterations = u32::MAX;
+        assert!(decrypt_bundle(&encrypted, "secret").is_err());
+        encrypted.iterations = 1;
+        assert!(decrypt_bundle(&encrypted, "secret").is_err());
+    }
+}
@@ -0,0 +1,90 @@
+//! 应用重启（安装更新后）
+//! 重启前写入标记文件：新进程启动时据此显示并聚焦主窗口；
+//! 同时用于防止重启后立即再次重启形成循环
+
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use serde::{Deserialize, Serialize};
+
+use crate::modules;
+
+const RELAUNCH_MARKER_FILE: &str = "relaunch.json";
+/// 两次重启之间的最小间隔（秒）
+const RELAUNCH_MIN_INTERVAL_SECS: i64 = 60;
+/// 标记超过该时长后不再视为刚重启（秒）
+const RELAUNCH_MARKER_TTL_SECS: i64 = 120;
+
+static RELAUNCH_IN_PROGRESS: AtomicBool = AtomicBool::new(false);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RelaunchMarker {
+    relaunched_at: i64,
+    /// 新进程启动后是否需要显示主窗口
+    #[serde(default)]
+    show_window: bool,
+}
+
+fn marker_path() -> Result<PathBuf, String> {
+    Ok(modules::account::get_data_dir()?.join(RELAUNCH_MARKER_FILE))
+}
+
+fn read_marker() -> Option<RelaunchMarker> {
+    let content = fs::read_to_string(marker_path().ok()?).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+fn write_marker(marker: &RelaunchMarker) -> Result<(), String> {
+    let content =
+        serde_json::to_string(marker).map_err(|e| format!("序列化重启标记失败: {}", e))?;
+    fs::write(marker_path()?, content).map_err(|e| format!("写入重启标记失败: {}", e))
+}
+
+/// 标记开始重启；正在重启或距上次重启不足 RELAUNCH_MIN_INTERVAL_SECS 时拒绝
+pub fn begin_relaunch() -> Result<(), String> {
+    if RELAUNCH_IN_PROGRESS.swap(true, Ordering::SeqCst) {
+        return Err("应用正在重启".to_string());
+    }
+    let now = chrono::Utc::now().timestamp();
+    if let Some(marker) = read_marker() {
+        let elapsed = now - marker.relaunched_at;
+        if (0..RELAUNCH_MIN_INTERVAL_SECS).contains(&elapsed) {
+            RELAUNCH_IN_PROGRESS.store(false, Ordering::SeqCst);
+            return Err(format!("距上次重启仅 {} 秒，请稍后再试", elapsed));
+        }
+    }
+    if let Err(err) = write_marker(&RelaunchMarker {
+        relaunched_at: now,
+        show_window: true,
+    }) {
+        RELAUNCH_IN_PROGRESS.store(false, Ordering::SeqCst);
+        return Err(err);
+    }
+    Ok(())
+}
+
+/// 重启前的准备步骤失败时撤销重启标记
+pub fn abort_relaunch() {
+    if let Ok(path) = marker_path() {
+        let _ = fs::remove_file(path);
+    }
+    RELAUNCH_IN_PROGRESS.store(false, Ordering::SeqCst);
+}
+
+/// 启动时调用：若本进程由重启产生，返回 true 并清除显示窗口标记（只生效一次）
+pub fn take_pending_show_window() -> bool {
+    let Some(marker) = read_marker() else {
+        return false;
+    };
+    let elapsed = chrono::Utc::now().timestamp() - marker.relaunched_at;
+    if !marker.show_window || !(0..RELAUNCH_MARKER_TTL_SECS).contains(&elapsed) {
+        return false;
+    }
+    let _ = write_marker(&RelaunchMarker {
+        show_window: false,
+        ..marker
+    });
+    true
+}
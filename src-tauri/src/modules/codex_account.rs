@@ -1,10 +1,10 @@
 use crate::models::codex::{
-    CodexAccount, CodexAccountIndex, CodexAccountSummary, CodexAuthFile, CodexAuthTokens,
-    CodexJwtPayload, CodexTokens,
+    CodexAccount, CodexAccountIndex, CodexAccountPage, CodexAccountSummary, CodexAuthFile,
+    CodexAuthTokens, CodexJwtPayload, CodexTokens,
 };
 use crate::modules::{codex_oauth, logger};
 use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::sync::Mutex;
@@ -23,21 +23,23 @@ pub fn get_auth_json_path() -> PathBuf {
     get_codex_home().join("auth.json")
 }
 
+/// 获取 Codex 多账号存储根目录
+pub fn get_storage_dir() -> PathBuf {
+    dirs::data_local_dir()
+        .unwrap_or_else(|| dirs::home_dir().expect("无法获取用户目录"))
+        .join("com.antigravity.cockpit-tools")
+}
+
 /// 获取我们的多账号存储路径
 fn get_accounts_storage_path() -> PathBuf {
-    let data_dir = dirs::data_local_dir()
-        .unwrap_or_else(|| dirs::home_dir().expect("无法获取用户目录"))
-        .join("com.antigravity.cockpit-tools");
+    let data_dir = get_storage_dir();
     fs::create_dir_all(&data_dir).ok();
     data_dir.join("codex_accounts.json")
 }
 
 /// 获取账号详情存储目录
 fn get_accounts_dir() -> PathBuf {
-    let data_dir = dirs::data_local_dir()
-        .unwrap_or_else(|| dirs::home_dir().expect("无法获取用户目录"))
-        .join("com.antigravity.cockpit-tools")
-        .join("codex_accounts");
+    let data_dir = get_storage_dir().join("codex_accounts");
     fs::create_dir_all(&data_dir).ok();
     data_dir
 }
@@ -253,6 +255,33 @@ pub fn save_account_index(index: &CodexAccountIndex) -> Result<(), String> {
     Ok(())
 }
 
+/// 清除索引中记录的当前账号 ID（不修改 auth.json）
+pub fn clear_current_account_id() -> Result<(), String> {
+    let mut index = load_account_index();
+    if index.current_account_id.take().is_some() {
+        save_account_index(&index)?;
+    }
+    Ok(())
+}
+
+/// 修复悬空的当前账号 ID：指向的账号已不存在时清除，返回是否做了修复
+pub fn repair_current_account_id() -> Result<bool, String> {
+    let mut index = load_account_index();
+    let Some(current_id) = index.current_account_id.clone() else {
+        return Ok(false);
+    };
+    if index.accounts.iter().any(|a| a.id == current_id) && load_account(&current_id).is_some() {
+        return Ok(false);
+    }
+    index.current_account_id = None;
+    save_account_index(&index)?;
+    logger::log_warn(&format!(
+        "[Codex] 当前账号指向不存在的账号，已清除: {}",
+        current_id
+    ));
+    Ok(true)
+}
+
 /// 读取单个账号详情
 pub fn load_account(account_id: &str) -> Option<CodexAccount> {
     let path = get_accounts_dir().join(format!("{}.json", account_id));
@@ -294,11 +323,50 @@ pub fn list_accounts() -> Vec<CodexAccount> {
         .collect()
 }
 
+/// 按索引顺序分页列出账号，只读取窗口内的账号文件（不刷新配额）
+/// 未指定 offset / limit 时返回全部账号
+pub fn list_accounts_page(offset: Option<usize>, limit: Option<usize>) -> CodexAccountPage {
+    let index = load_account_index();
+    let total = index.accounts.len();
+    let accounts = page_window(&index.accounts, offset, limit)
+        .iter()
+        .filter_map(|summary| load_account(&summary.id))
+        .collect();
+    CodexAccountPage { accounts, total }
+}
+
+/// 计算分页窗口；offset 越界时返回空切片
+fn page_window<T>(items: &[T], offset: Option<usize>, limit: Option<usize>) -> &[T] {
+    let start = offset.unwrap_or(0).min(items.len());
+    let end = match limit {
+        Some(limit) => start.saturating_add(limit).min(items.len()),
+        None => items.len(),
+    };
+    &items[start..end]
+}
+
 /// 添加或更新账号
 pub fn upsert_account(tokens: CodexTokens) -> Result<CodexAccount, String> {
     upsert_account_with_hints(tokens, None, None)
 }
 
+/// 已有账号重新登录：更新 Token 与账号信息，保留显示名称、标签等用户设置
+fn apply_relogin(
+    acc: &mut CodexAccount,
+    tokens: CodexTokens,
+    user_id: Option<String>,
+    plan_type: Option<String>,
+    account_id: Option<String>,
+    organization_id: Option<String>,
+) {
+    acc.tokens = tokens;
+    acc.user_id = user_id;
+    acc.plan_type = plan_type;
+    acc.account_id = account_id;
+    acc.organization_id = organization_id;
+    acc.update_last_used();
+}
+
 fn upsert_account_with_hints(
     tokens: CodexTokens,
     account_id_hint: Option<String>,
@@ -336,12 +404,14 @@ fn upsert_account_with_hints(
         let existing_id = index.accounts[pos].id.clone();
         let mut acc = load_account(&existing_id)
             .unwrap_or_else(|| CodexAccount::new(existing_id, email.clone(), tokens.clone()));
-        acc.tokens = tokens;
-        acc.user_id = user_id;
-        acc.plan_type = plan_type.clone();
-        acc.account_id = account_id.clone();
-        acc.organization_id = organization_id.clone();
-        acc.update_last_used();
+        apply_relogin(
+            &mut acc,
+            tokens,
+            user_id,
+            plan_type.clone(),
+            account_id.clone(),
+            organization_id.clone(),
+        );
         acc
     } else {
         // 创建新账号
@@ -355,6 +425,7 @@ fn upsert_account_with_hints(
         index.accounts.push(CodexAccountSummary {
             id: existing_id.clone(),
             email: email.clone(),
+            label: None,
             plan_type: plan_type.clone(),
             created_at: acc.created_at,
             last_used: acc.last_used,
@@ -374,6 +445,7 @@ fn upsert_account_with_hints(
         index.accounts.push(CodexAccountSummary {
             id: account.id.clone(),
             email: account.email.clone(),
+            label: account.label.clone(),
             plan_type: account.plan_type.clone(),
             created_at: account.created_at,
             last_used: account.last_used,
@@ -390,6 +462,40 @@ fn upsert_account_with_hints(
     Ok(account)
 }
 
+/// 查找与账号记录对应的本地账号 ID（与 upsert_account 相同，按 email + account_id + organization_id 匹配）
+pub fn find_matching_account_id(account: &CodexAccount) -> Option<String> {
+    find_existing_account_id(
+        &load_account_index(),
+        &account.email,
+        account.account_id.as_deref(),
+        account.organization_id.as_deref(),
+    )
+}
+
+/// 按完整账号记录写入（保留显示名称、标签等元数据），本地已有相同账号时沿用本地账号 ID
+pub fn upsert_account_record(mut account: CodexAccount) -> Result<CodexAccount, String> {
+    if let Some(existing_id) = find_matching_account_id(&account) {
+        account.id = existing_id;
+    }
+    save_account(&account)?;
+
+    let mut index = load_account_index();
+    let summary = CodexAccountSummary {
+        id: account.id.clone(),
+        email: account.email.clone(),
+        label: account.label.clone(),
+        plan_type: account.plan_type.clone(),
+        created_at: account.created_at,
+        last_used: account.last_used,
+    };
+    match index.accounts.iter_mut().find(|a| a.id == account.id) {
+        Some(existing) => *existing = summary,
+        None => index.accounts.push(summary),
+    }
+    save_account_index(&index)?;
+    Ok(account)
+}
+
 /// 删除账号
 pub fn remove_account(account_id: &str) -> Result<(), String> {
     let mut index = load_account_index();
@@ -416,6 +522,43 @@ pub fn remove_accounts(account_ids: &[String]) -> Result<(), String> {
     Ok(())
 }
 
+/// 按给定 ID 顺序重排账号摘要；ID 集合必须与现有账号完全一致
+fn reorder_summaries(
+    accounts: &[CodexAccountSummary],
+    ordered_ids: &[String],
+) -> Result<Vec<CodexAccountSummary>, String> {
+    let mut seen = HashSet::new();
+    if let Some(duplicated) = ordered_ids.iter().find(|id| !seen.insert(id.as_str())) {
+        return Err(format!("排序列表中存在重复的账号 ID: {}", duplicated));
+    }
+    if let Some(unknown) = ordered_ids
+        .iter()
+        .find(|id| !accounts.iter().any(|summary| &summary.id == *id))
+    {
+        return Err(format!("排序列表中存在未知的账号 ID: {}", unknown));
+    }
+    let missing: Vec<&str> = accounts
+        .iter()
+        .filter(|summary| !seen.contains(summary.id.as_str()))
+        .map(|summary| summary.id.as_str())
+        .collect();
+    if !missing.is_empty() {
+        return Err(format!("排序列表缺少账号: {}", missing.join(", ")));
+    }
+
+    Ok(ordered_ids
+        .iter()
+        .filter_map(|id| accounts.iter().find(|summary| &summary.id == id).cloned())
+        .collect())
+}
+
+/// 调整账号顺序（写入账号索引，list_accounts 按此顺序返回）
+pub fn reorder_accounts(ordered_ids: &[String]) -> Result<(), String> {
+    let mut index = load_account_index();
+    index.accounts = reorder_summaries(&index.accounts, ordered_ids)?;
+    save_account_index(&index)
+}
+
 /// 获取当前激活的账号（基于 auth.json）
 pub fn get_current_account() -> Option<CodexAccount> {
     let auth_path = get_auth_json_path();
@@ -619,6 +762,24 @@ pub fn update_account_tags(account_id: &str, tags: Vec<String>) -> Result<CodexA
     Ok(account)
 }
 
+/// 设置账号显示名称（同步更新索引摘要）；传入空字符串时清除
+pub fn set_account_label(account_id: &str, label: &str) -> Result<CodexAccount, String> {
+    let label = crate::modules::account::normalize_account_label(label)?;
+    let mut account =
+        load_account(account_id).ok_or_else(|| format!("账号不存在: {}", account_id))?;
+
+    account.label = label.clone();
+    save_account(&account)?;
+
+    let mut index = load_account_index();
+    if let Some(summary) = index.accounts.iter_mut().find(|a| a.id == account_id) {
+        summary.label = label;
+        save_account_index(&index)?;
+    }
+
+    Ok(account)
+}
+
 fn normalize_quota_alert_threshold(raw: i32) -> i32 {
     raw.clamp(0, 100)
 }
@@ -820,3 +981,69 @@ pub fn run_quota_alert_if_needed(
     crate::modules::account::dispatch_quota_alert(&payload);
     Ok(Some(payload))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary(id: &str) -> CodexAccountSummary {
+        CodexAccountSummary {
+            id: id.to_string(),
+            email: format!("{}@example.com", id),
+            label: None,
+            plan_type: None,
+            created_at: 0,
+            last_used: 0,
+        }
+    }
+
+    #[test]
+    fn test_reorder_summaries_requires_exact_id_set() {
+        let accounts = vec![summary("a"), summary("b"), summary("c")];
+        let ids = |values: &[&str]| values.iter().map(|v| v.to_string()).collect::<Vec<_>>();
+
+        let reordered = reorder_summaries(&accounts, &ids(&["c", "a", "b"])).unwrap();
+        let order: Vec<&str> = reordered.iter().map(|s| s.id.as_str()).collect();
+        assert_eq!(order, vec!["c", "a", "b"]);
+
+        assert!(reorder_summaries(&accounts, &ids(&["c", "a"])).is_err());
+        assert!(reorder_summaries(&accounts, &ids(&["c", "a", "b", "d"])).is_err());
+        assert!(reorder_summaries(&accounts, &ids(&["c", "a", "a"])).is_err());
+    }
+
+    #[test]
+    fn test_page_window_bounds() {
+        let items = [1, 2, 3, 4, 5];
+        assert_eq!(page_window(&items, None, None), &items[..]);
+        assert_eq!(page_window(&items, Some(1), Some(2)), &[2, 3]);
+        assert_eq!(page_window(&items, Some(3), None), &[4, 5]);
+        assert_eq!(page_window(&items, None, Some(10)), &items[..]);
+        assert!(page_window(&items, Some(5), Some(2)).is_empty());
+        assert!(page_window(&items, Some(usize::MAX), Some(usize::MAX)).is_empty());
+        assert!(page_window(&items, Some(0), Some(0)).is_empty());
+    }
+    #[test]
+    fn test_relogin_keeps_label() {
+        let tokens = |access: &str| CodexTokens {
+            id_token: "id".to_string(),
+            access_token: access.to_string(),
+            refresh_token: None,
+        };
+        let mut acc =
+            CodexAccount::new("a".to_string(), "a@example.com".to_string(), tokens("old"));
+        acc.label = Some("Work".to_string());
+
+        apply_relogin(
+            &mut acc,
+            tokens("new"),
+            Some("user".to_string()),
+            Some("plus".to_string()),
+            None,
+            None,
+        );
+
+        assert_eq!(acc.label.as_deref(), Some("Work"));
+        assert_eq!(acc.tokens.access_token, "new");
+        assert_eq!(acc.plan_type.as_deref(), Some("plus"));
+    }
+}
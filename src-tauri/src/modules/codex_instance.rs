@@ -16,6 +16,8 @@ static CODEX_INSTANCE_STORE_LOCK: std::sync::LazyLock<Mutex<()>> =
     std::sync::LazyLock::new(|| Mutex::new(()));
 
 const CODEX_INSTANCES_FILE: &str = "codex_instances.json";
+/// CODEX_HOME 可写性检查使用的临时文件前缀
+const WRITE_PROBE_PREFIX: &str = ".cockpit_write_probe_";
 
 fn instances_path() -> Result<PathBuf, String> {
     let data_dir = modules::account::get_data_dir()?;
@@ -105,6 +107,8 @@ pub fn create_instance(params: CreateInstanceParams) -> Result<InstanceProfile,
     let _lock = CODEX_INSTANCE_STORE_LOCK
         .lock()
         .map_err(|_| "无法获取实例锁")?;
+    let env_vars = instance_store::normalize_env_vars(params.env_vars.clone())?;
+    instance_store::reject_fingerprint_binding("Codex", params.bind_fingerprint_id.as_deref())?;
     let mut store = load_instance_store()?;
 
     let name = instance_store::normalize_name(&params.name)?;
@@ -116,6 +120,7 @@ pub fn create_instance(params: CreateInstanceParams) -> Result<InstanceProfile,
     instance_store::ensure_unique(&store, &name, &user_data_dir, None)?;
 
     let user_dir_path = PathBuf::from(&user_data_dir);
+    instance_store::check_instance_dir_length(&user_dir_path)?;
     let init_mode = params
         .init_mode
         .as_deref()
@@ -187,6 +192,12 @@ pub fn create_instance(params: CreateInstanceParams) -> Result<InstanceProfile,
         created_at: Utc::now().timestamp_millis(),
         last_launched_at: None,
         last_pid: None,
+        minimize_after_launch: false,
+        low_power: false,
+        launch_args_template: params.launch_args_template,
+        env_vars,
+        bind_fingerprint_id: None,
+        follow_local_account: false,
     };
 
     store.instances.push(instance.clone());
@@ -198,6 +209,10 @@ pub fn update_instance(params: UpdateInstanceParams) -> Result<InstanceProfile,
     let _lock = CODEX_INSTANCE_STORE_LOCK
         .lock()
         .map_err(|_| "无法获取实例锁")?;
+    instance_store::reject_fingerprint_binding(
+        "Codex",
+        params.bind_fingerprint_id.clone().flatten().as_deref(),
+    )?;
     let mut store = load_instance_store()?;
     let index = store
         .instances
@@ -224,9 +239,21 @@ pub fn update_instance(params: UpdateInstanceParams) -> Result<InstanceProfile,
     if let Some(ref extra_args) = params.extra_args {
         instance.extra_args = extra_args.trim().to_string();
     }
+    if let Some(template) = params.launch_args_template.clone() {
+        instance.launch_args_template = template;
+    }
+    if let Some(env_vars) = params.env_vars.clone() {
+        instance.env_vars = instance_store::normalize_env_vars(env_vars)?;
+    }
     if let Some(bind) = params.bind_account_id.clone() {
         instance.bind_account_id = bind;
     }
+    if let Some(minimize) = params.minimize_after_launch {
+        instance.minimize_after_launch = minimize;
+    }
+    if let Some(follow) = params.follow_local_account {
+        instance.follow_local_account = follow;
+    }
 
     let updated = instance.clone();
     save_instance_store(&store)?;
@@ -339,3 +366,73 @@ pub async fn inject_account_to_profile(profile_dir: &Path, account_id: &str) ->
     let account = modules::codex_account::prepare_account_for_injection(account_id).await?;
     modules::codex_account::write_auth_file_to_dir(profile_dir, &account)
 }
+
+fn describe_io_error(err: &std::io::Error) -> String {
+    match err.kind() {
+        std::io::ErrorKind::PermissionDenied => "没有写入权限".to_string(),
+        std::io::ErrorKind::NotFound => "路径不存在".to_string(),
+        _ => err.to_string(),
+    }
+}
+
+/// 启动前检查实例的 CODEX_HOME：不存在时创建，并写入临时文件确认可写（检查后立即删除）
+pub fn ensure_codex_home_writable(codex_home: &Path) -> Result<(), String> {
+    let display = instance_store::display_path(codex_home);
+    if codex_home.exists() && !codex_home.is_dir() {
+        return Err(format!("CODEX_HOME 不是目录: {}", display));
+    }
+    fs::create_dir_all(codex_home).map_err(|e| {
+        format!(
+            "无法创建 CODEX_HOME 目录（{}）: {}",
+            describe_io_error(&e),
+            display
+        )
+    })?;
+
+    let probe_path = codex_home.join(format!("{}{}", WRITE_PROBE_PREFIX, Uuid::new_v4()));
+    let write_result = fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&probe_path);
+    match write_result {
+        Ok(file) => {
+            drop(file);
+            if let Err(e) = fs::remove_file(&probe_path) {
+                modules::logger::log_warn(&format!(
+                    "[Codex Instance] 删除可写性检查文件失败: {} ({})",
+                    probe_path.display(),
+                    e
+                ));
+            }
+            Ok(())
+        }
+        Err(e) => Err(format!(
+            "CODEX_HOME 目录不可写（{}）: {}",
+            describe_io_error(&e),
+            display
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ensure_codex_home_writable_creates_dir_without_leftovers() {
+        let root = std::env::temp_dir().join(format!("cockpit-codex-home-test-{}", Uuid::new_v4()));
+        let codex_home = root.join("nested").join("home");
+
+        ensure_codex_home_writable(&codex_home).unwrap();
+        assert!(codex_home.is_dir());
+        assert_eq!(fs::read_dir(&codex_home).unwrap().count(), 0);
+
+        let file_path = root.join("not_a_dir");
+        fs::write(&file_path, "x").unwrap();
+        assert!(ensure_codex_home_writable(&file_path)
+            .unwrap_err()
+            .contains("不是目录"));
+
+        let _ = fs::remove_dir_all(&root);
+    }
+}
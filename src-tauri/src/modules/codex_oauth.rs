@@ -58,6 +58,7 @@ struct OAuthState {
 lazy_static::lazy_static! {
     static ref OAUTH_STATE: Arc<Mutex<Option<OAuthState>>> = Arc::new(Mutex::new(None));
     static ref COMPLETE_ATTEMPT_SEQ: AtomicU64 = AtomicU64::new(0);
+    static ref CALLBACK_SERVER_TASK: Mutex<Option<tokio::task::JoinHandle<()>>> = Mutex::new(None);
 }
 
 fn generate_base64url_token() -> String {
@@ -187,7 +188,7 @@ pub async fn start_oauth_login(
     let expected_state = state_token.clone();
     let expected_login_id = login_id.clone();
     let callback_url = redirect_uri.clone();
-    tokio::spawn(async move {
+    let task = tokio::spawn(async move {
         if let Err(e) = start_callback_server(
             port,
             expected_state,
@@ -200,6 +201,9 @@ pub async fn start_oauth_login(
             logger::log_error(&format!("OAuth 回调服务器错误: {}", e));
         }
     });
+    if let Ok(mut guard) = CALLBACK_SERVER_TASK.lock() {
+        *guard = Some(task);
+    }
 
     logger::log_info(&format!(
         "Codex OAuth 登录会话已创建: login_id={}, port={}, redirect_uri={}",
@@ -424,7 +428,7 @@ async fn exchange_code_for_token_internal(
     port: u16,
 ) -> Result<CodexTokens, String> {
     let redirect_uri = format!("http://localhost:{}/auth/callback", port);
-    let client = reqwest::Client::new();
+    let client = crate::modules::http::default_client();
 
     let params = [
         ("grant_type", "authorization_code"),
@@ -581,6 +585,46 @@ pub fn cancel_oauth_flow_for(login_id: Option<&str>) -> Result<(), String> {
     Ok(())
 }
 
+fn callback_server_running() -> bool {
+    CALLBACK_SERVER_TASK
+        .lock()
+        .ok()
+        .and_then(|guard| guard.as_ref().map(|task| !task.is_finished()))
+        .unwrap_or(false)
+}
+
+/// 清理失效的登录会话（仍在等待回调但回调服务已退出），返回是否有会话被清理
+/// Codex 会话没有过期时间，超时由回调服务自身处理
+pub fn clear_stale_login() -> bool {
+    let server_running = callback_server_running();
+    let stale_login_id = OAUTH_STATE.lock().ok().and_then(|guard| {
+        guard
+            .as_ref()
+            .filter(|state| state.code.is_none() && !server_running)
+            .map(|state| state.login_id.clone())
+    });
+    let Some(login_id) = stale_login_id else {
+        return false;
+    };
+    if cancel_oauth_flow_for(Some(&login_id)).is_err() {
+        return false;
+    }
+    logger::log_info(&format!(
+        "Codex OAuth 已清理失效的登录会话: login_id={}",
+        login_id
+    ));
+    true
+}
+
+/// 取消挂起的登录并取出回调服务任务（应用退出时调用，由调用方等待任务结束）
+pub fn shutdown_callback_server() -> Option<tokio::task::JoinHandle<()>> {
+    let _ = cancel_oauth_flow_for(None);
+    CALLBACK_SERVER_TASK
+        .lock()
+        .ok()
+        .and_then(|mut guard| guard.take())
+}
+
 pub fn is_token_expired(access_token: &str) -> bool {
     let parts: Vec<&str> = access_token.split('.').collect();
     if parts.len() != 3 {
@@ -613,7 +657,7 @@ pub fn is_token_expired(access_token: &str) -> bool {
 }
 
 pub async fn refresh_access_token(refresh_token: &str) -> Result<CodexTokens, String> {
-    let client = reqwest::Client::new();
+    let client = crate::modules::http::default_client();
 
     let params = [
         ("grant_type", "refresh_token"),
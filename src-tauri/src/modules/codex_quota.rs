@@ -1,11 +1,21 @@
 use crate::models::codex::{CodexAccount, CodexQuota, CodexQuotaErrorInfo};
 use crate::modules::{codex_account, logger};
 use reqwest::header::{HeaderMap, HeaderValue, ACCEPT, AUTHORIZATION};
+use reqwest::StatusCode;
 use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tokio::time::Instant;
 
 // 使用 wham/usage 端点（Quotio 使用的）
 const USAGE_URL: &str = "https://chatgpt.com/backend-api/wham/usage";
 
+/// 429 响应未携带 Retry-After 时的默认等待时间（秒）
+const DEFAULT_RETRY_AFTER_SECS: u64 = 30;
+/// 批量刷新时相邻两次配额请求的最小间隔
+const BATCH_REQUEST_INTERVAL: Duration = Duration::from_millis(300);
+/// 批量刷新中最多为限流等待的时长，超过后剩余账号直接推迟到下一轮
+const BATCH_MAX_RATE_LIMIT_WAIT: Duration = Duration::from_secs(60);
+
 fn get_header_value(headers: &HeaderMap, name: &str) -> String {
     headers
         .get(name)
@@ -40,6 +50,73 @@ fn extract_error_code_from_message(message: &str) -> Option<String> {
     Some(message[code_start..code_start + end].to_string())
 }
 
+/// 配额查询失败原因：限流单独区分，批量刷新据此推迟而不是记为失败
+#[derive(Debug)]
+pub enum QuotaFetchError {
+    /// 429 限流，retry_after 取自 Retry-After 头（秒）
+    RateLimited {
+        retry_after: Option<u64>,
+        message: String,
+    },
+    Failed(String),
+}
+
+impl std::fmt::Display for QuotaFetchError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            QuotaFetchError::RateLimited { message, .. } | QuotaFetchError::Failed(message) => {
+                f.write_str(message)
+            }
+        }
+    }
+}
+
+impl From<String> for QuotaFetchError {
+    fn from(message: String) -> Self {
+        QuotaFetchError::Failed(message)
+    }
+}
+
+/// 批量刷新共享的请求节流：相邻请求至少间隔 interval，
+/// 收到 429 后在 Retry-After 到期前暂停所有后续请求
+struct RateLimitGate {
+    interval: Duration,
+    next_at: tokio::sync::Mutex<Instant>,
+}
+
+impl RateLimitGate {
+    fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            next_at: tokio::sync::Mutex::new(Instant::now()),
+        }
+    }
+
+    /// 等待下一个请求时机；需要等待的时间超过 max_wait 时立即返回剩余等待时间
+    async fn acquire(&self, max_wait: Duration) -> Result<(), Duration> {
+        let mut next_at = self.next_at.lock().await;
+        let now = Instant::now();
+        let wait = next_at.saturating_duration_since(now);
+        if wait > max_wait {
+            return Err(wait);
+        }
+        let start = (*next_at).max(now);
+        *next_at = start + self.interval;
+        drop(next_at);
+        tokio::time::sleep_until(start).await;
+        Ok(())
+    }
+
+    /// 收到限流响应后推迟所有后续请求
+    async fn defer(&self, retry_after: Duration) {
+        let mut next_at = self.next_at.lock().await;
+        let until = Instant::now() + retry_after;
+        if until > *next_at {
+            *next_at = until;
+        }
+    }
+}
+
 fn write_quota_error(account: &mut CodexAccount, message: String) {
     account.quota_error = Some(CodexQuotaErrorInfo {
         code: extract_error_code_from_message(&message),
@@ -112,8 +189,16 @@ fn normalize_reset_time(window: &WindowInfo) -> Option<i64> {
 
 /// 查询单个账号的配额
 pub async fn fetch_quota(account: &CodexAccount) -> Result<CodexQuota, String> {
-    let client = reqwest::Client::new();
+    request_usage(&crate::modules::http::default_client(), USAGE_URL, account)
+        .await
+        .map_err(|e| e.to_string())
+}
 
+async fn request_usage(
+    client: &reqwest::Client,
+    url: &str,
+    account: &CodexAccount,
+) -> Result<CodexQuota, QuotaFetchError> {
     let mut headers = HeaderMap::new();
     headers.insert(
         AUTHORIZATION,
@@ -139,11 +224,11 @@ pub async fn fetch_quota(account: &CodexAccount) -> Result<CodexQuota, String> {
 
     logger::log_info(&format!(
         "Codex 配额请求: {} (account_id: {:?})",
-        USAGE_URL, account_id
+        url, account_id
     ));
 
     let response = client
-        .get(USAGE_URL)
+        .get(url)
         .headers(headers)
         .send()
         .await
@@ -163,7 +248,7 @@ pub async fn fetch_quota(account: &CodexAccount) -> Result<CodexQuota, String> {
 
     logger::log_info(&format!(
         "Codex 配额响应元信息: url={}, status={}, request-id={}, x-request-id={}, cf-ray={}, body_len={}",
-        USAGE_URL, status, request_id, x_request_id, cf_ray, body_len
+        url, status, request_id, x_request_id, cf_ray, body_len
     ));
 
     if !status.is_success() {
@@ -171,7 +256,7 @@ pub async fn fetch_quota(account: &CodexAccount) -> Result<CodexQuota, String> {
 
         logger::log_error(&format!(
             "Codex 配额接口返回非成功状态: url={}, status={}, request-id={}, x-request-id={}, cf-ray={}, detail_code={:?}, body={}",
-            USAGE_URL, status, request_id, x_request_id, cf_ray, detail_code, body
+            url, status, request_id, x_request_id, cf_ray, detail_code, body
         ));
 
         let body_preview = if body.len() > 200 {
@@ -184,14 +269,20 @@ pub async fn fetch_quota(account: &CodexAccount) -> Result<CodexQuota, String> {
             error_message.push_str(&format!(" [error_code:{}]", code));
         }
         error_message.push_str(&format!(" - {}", body_preview));
-        return Err(error_message);
+        if status == StatusCode::TOO_MANY_REQUESTS {
+            return Err(QuotaFetchError::RateLimited {
+                retry_after: crate::error::parse_retry_after(&headers),
+                message: error_message,
+            });
+        }
+        return Err(error_message.into());
     }
 
     // 解析响应
     let usage: UsageResponse =
         serde_json::from_str(&body).map_err(|e| format!("解析 JSON 失败: {}", e))?;
 
-    parse_quota_from_usage(&usage, &body)
+    parse_quota_from_usage(&usage, &body).map_err(QuotaFetchError::from)
 }
 
 /// 从使用率响应中解析配额信息
@@ -242,6 +333,12 @@ fn parse_quota_from_usage(usage: &UsageResponse, raw_body: &str) -> Result<Codex
 
 /// 刷新账号配额并保存（包含 token 自动刷新）
 pub async fn refresh_account_quota(account_id: &str) -> Result<CodexQuota, String> {
+    refresh_account_quota_inner(account_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+async fn refresh_account_quota_inner(account_id: &str) -> Result<CodexQuota, QuotaFetchError> {
     let mut account = codex_account::load_account(account_id)
         .ok_or_else(|| format!("账号不存在: {}", account_id))?;
 
@@ -263,7 +360,7 @@ pub async fn refresh_account_quota(account_id: &str) -> Result<CodexQuota, Strin
                     if let Err(save_err) = codex_account::save_account(&account) {
                         logger::log_warn(&format!("写入 Codex 配额错误失败: {}", save_err));
                     }
-                    return Err(message);
+                    return Err(message.into());
                 }
             }
         } else {
@@ -272,14 +369,17 @@ pub async fn refresh_account_quota(account_id: &str) -> Result<CodexQuota, Strin
             if let Err(save_err) = codex_account::save_account(&account) {
                 logger::log_warn(&format!("写入 Codex 配额错误失败: {}", save_err));
             }
-            return Err(message);
+            return Err(message.into());
         }
     }
 
-    let quota = match fetch_quota(&account).await {
+    let client = crate::modules::http::default_client();
+    let quota = match request_usage(&client, USAGE_URL, &account).await {
         Ok(quota) => quota,
+        // 限流只是暂时不可查询，不覆盖账号上已有的配额状态
+        Err(e @ QuotaFetchError::RateLimited { .. }) => return Err(e),
         Err(e) => {
-            write_quota_error(&mut account, e.clone());
+            write_quota_error(&mut account, e.to_string());
             if let Err(save_err) = codex_account::save_account(&account) {
                 logger::log_warn(&format!("写入 Codex 配额错误失败: {}", save_err));
             }
@@ -294,8 +394,17 @@ pub async fn refresh_account_quota(account_id: &str) -> Result<CodexQuota, Strin
     Ok(quota)
 }
 
+/// 批量刷新中单个账号的结果
+pub enum BatchRefreshOutcome {
+    Refreshed(Result<CodexQuota, String>),
+    /// 因限流推迟，附带建议的重试等待时间（秒）
+    Deferred(u64),
+}
+
 /// 刷新所有账号配额
-pub async fn refresh_all_quotas() -> Result<Vec<(String, Result<CodexQuota, String>)>, String> {
+/// 请求之间按 BATCH_REQUEST_INTERVAL 节流；收到 429 时该账号记为推迟，
+/// 其余请求等到 Retry-After 到期后再继续，等待过长则一并推迟
+pub async fn refresh_all_quotas() -> Result<Vec<(String, BatchRefreshOutcome)>, String> {
     use futures::future::join_all;
     use std::sync::Arc;
     use tokio::sync::Semaphore;
@@ -304,18 +413,35 @@ pub async fn refresh_all_quotas() -> Result<Vec<(String, Result<CodexQuota, Stri
     let accounts = codex_account::list_accounts();
 
     let semaphore = Arc::new(Semaphore::new(MAX_CONCURRENT));
+    let gate = Arc::new(RateLimitGate::new(BATCH_REQUEST_INTERVAL));
     let tasks: Vec<_> = accounts
         .into_iter()
         .map(|account| {
             let account_id = account.id;
             let semaphore = semaphore.clone();
+            let gate = gate.clone();
             async move {
                 let _permit = semaphore
                     .acquire_owned()
                     .await
                     .map_err(|e| format!("获取 Codex 刷新并发许可失败: {}", e))?;
-                let result = refresh_account_quota(&account_id).await;
-                Ok::<(String, Result<CodexQuota, String>), String>((account_id, result))
+                if let Err(wait) = gate.acquire(BATCH_MAX_RATE_LIMIT_WAIT).await {
+                    let outcome = BatchRefreshOutcome::Deferred(wait.as_secs().max(1));
+                    return Ok::<(String, BatchRefreshOutcome), String>((account_id, outcome));
+                }
+                let outcome = match refresh_account_quota_inner(&account_id).await {
+                    Err(QuotaFetchError::RateLimited { retry_after, .. }) => {
+                        let retry_after = retry_after.unwrap_or(DEFAULT_RETRY_AFTER_SECS);
+                        logger::log_warn(&format!(
+                            "[Codex Quota] 配额接口限流，暂停后续请求: account_id={}, retry_after={}s",
+                            account_id, retry_after
+                        ));
+                        gate.defer(Duration::from_secs(retry_after)).await;
+                        BatchRefreshOutcome::Deferred(retry_after)
+                    }
+                    result => BatchRefreshOutcome::Refreshed(result.map_err(|e| e.to_string())),
+                };
+                Ok((account_id, outcome))
             }
         })
         .collect();
@@ -330,3 +456,65 @@ pub async fn refresh_all_quotas() -> Result<Vec<(String, Result<CodexQuota, Stri
 
     Ok(results)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::codex::CodexTokens;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+
+    /// 只响应一次 429 的本地配额接口
+    fn spawn_rate_limited_endpoint(retry_after: &'static str) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            if let Ok((mut stream, _)) = listener.accept() {
+                let mut buf = [0u8; 2048];
+                let _ = stream.read(&mut buf);
+                let body = r#"{"detail":{"code":"rate_limit_exceeded"}}"#;
+                let response = format!(
+                    "HTTP/1.1 429 Too Many Requests\r\nRetry-After: {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    retry_after,
+                    body.len(),
+                    body
+                );
+                let _ = stream.write_all(response.as_bytes());
+            }
+        });
+        format!("http://{}/backend-api/wham/usage", addr)
+    }
+
+    #[tokio::test]
+    async fn test_rate_limited_response_delays_next_request() {
+        let endpoint = spawn_rate_limited_endpoint("1");
+        let account = CodexAccount::new(
+            "codex_test".to_string(),
+            "test@example.com".to_string(),
+            CodexTokens {
+                id_token: String::new(),
+                access_token: "token".to_string(),
+                refresh_token: None,
+            },
+        );
+        let err = request_usage(&reqwest::Client::new(), &endpoint, &account)
+            .await
+            .err()
+            .expect("429 应当返回错误");
+        let QuotaFetchError::RateLimited { retry_after, .. } = err else {
+            panic!("429 应当返回限流错误");
+        };
+        let retry_after = retry_after.expect("应当解析出 Retry-After");
+        assert_eq!(retry_after, 1);
+
+        let gate = RateLimitGate::new(Duration::ZERO);
+        gate.defer(Duration::from_secs(retry_after)).await;
+        let started = Instant::now();
+        assert!(gate.acquire(BATCH_MAX_RATE_LIMIT_WAIT).await.is_ok());
+        assert!(started.elapsed() >= Duration::from_millis(950));
+
+        // 等待时间超过上限时直接推迟，不阻塞
+        gate.defer(Duration::from_secs(120)).await;
+        assert!(gate.acquire(BATCH_MAX_RATE_LIMIT_WAIT).await.is_err());
+    }
+}
@@ -3,9 +3,11 @@
 
 use serde::{Deserialize, Serialize};
 use serde_json::json;
+use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
-use std::sync::{OnceLock, RwLock};
+use std::sync::{mpsc, Mutex, OnceLock, RwLock};
+use std::time::Duration;
 
 /// 默认 WebSocket 端口
 pub const DEFAULT_WS_PORT: u16 = 19528;
@@ -22,6 +24,12 @@ const USER_CONFIG_FILE: &str = "config.json";
 /// 数据目录名
 const DATA_DIR: &str = ".antigravity_cockpit";
 
+/// 窗口模式：复用已有窗口
+pub const WINDOW_MODE_REUSE: &str = "reuse";
+
+/// 窗口模式：打开新窗口
+pub const WINDOW_MODE_NEW: &str = "new";
+
 /// 服务状态（写入共享文件供其他客户端读取）
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ServerStatus {
@@ -128,6 +136,107 @@ pub struct UserConfig {
     /// Kiro 配额预警阈值（百分比）
     #[serde(default = "default_kiro_quota_alert_threshold")]
     pub kiro_quota_alert_threshold: i32,
+    /// 启动时是否合并共享同步文件中的配置（如语言）
+    #[serde(default = "default_sync_on_startup_enabled")]
+    pub sync_on_startup_enabled: bool,
+    /// 切换账号时是否自动应用账号绑定的设备指纹
+    #[serde(default = "default_auto_apply_fingerprint_on_switch")]
+    pub auto_apply_fingerprint_on_switch: bool,
+    /// 各应用默认窗口模式（键为应用名，值为 reuse / new）；未配置的应用沿用内置默认行为
+    #[serde(default = "default_default_window_modes")]
+    pub default_window_modes: HashMap<String, String>,
+    /// Kiro OAuth 回调候选端口（为空时使用内置端口列表）
+    #[serde(default = "default_kiro_oauth_ports")]
+    pub kiro_oauth_ports: Vec<u16>,
+    /// 主窗口上次关闭时的位置与尺寸（物理像素）
+    #[serde(default = "default_window_geometry")]
+    pub window_geometry: Option<WindowGeometry>,
+    /// VS Code 优雅退出（Cmd+Q）后等待进程退出的秒数，范围 1-30
+    #[serde(default = "default_vscode_graceful_wait_secs")]
+    pub vscode_graceful_wait_secs: u64,
+    /// Antigravity 优雅退出后等待进程退出的秒数，范围 1-30
+    #[serde(default = "default_antigravity_graceful_wait_secs")]
+    pub antigravity_graceful_wait_secs: u64,
+    /// 后台定时刷新 Antigravity 配额的间隔（分钟），0 表示关闭
+    #[serde(default = "default_quota_auto_refresh_minutes")]
+    pub quota_auto_refresh_minutes: u32,
+    /// 识别辅助进程（非主窗口进程）的关键字，非空时替换内置列表
+    #[serde(default = "default_process_helper_exclude_keywords")]
+    pub process_helper_exclude_keywords: Vec<String>,
+    /// 唤醒模型列表的内存缓存有效期（分钟），0 表示不缓存
+    #[serde(default = "default_wakeup_models_cache_minutes")]
+    pub wakeup_models_cache_minutes: u32,
+    /// 出站 HTTP 请求的代理设置
+    #[serde(default)]
+    pub proxy: ProxyConfig,
+}
+
+/// 窗口尺寸下限 / 上限（物理像素），超出范围视为损坏数据
+const WINDOW_GEOMETRY_MIN_SIZE: u32 = 200;
+const WINDOW_GEOMETRY_MAX_SIZE: u32 = 16384;
+
+/// 窗口位置变化后延迟写入配置的时间，拖动过程中的连续事件合并为一次写入
+const WINDOW_GEOMETRY_SAVE_DEBOUNCE_MS: u64 = 500;
+
+/// 主窗口位置与尺寸
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WindowGeometry {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+    #[serde(default)]
+    pub maximized: bool,
+}
+
+impl WindowGeometry {
+    /// 尺寸是否在合理范围内
+    pub fn is_valid(&self) -> bool {
+        (WINDOW_GEOMETRY_MIN_SIZE..=WINDOW_GEOMETRY_MAX_SIZE).contains(&self.width)
+            && (WINDOW_GEOMETRY_MIN_SIZE..=WINDOW_GEOMETRY_MAX_SIZE).contains(&self.height)
+    }
+}
+
+/// 代理模式：跟随系统环境变量（HTTP_PROXY / HTTPS_PROXY / NO_PROXY）
+pub const PROXY_MODE_SYSTEM: &str = "system";
+/// 代理模式：直连，忽略系统代理
+pub const PROXY_MODE_DIRECT: &str = "direct";
+/// 代理模式：使用自定义代理
+pub const PROXY_MODE_CUSTOM: &str = "custom";
+
+/// 出站 HTTP 代理设置
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProxyConfig {
+    /// 代理模式：system / direct / custom
+    #[serde(default = "default_proxy_mode")]
+    pub mode: String,
+    /// 自定义代理地址（如 http://127.0.0.1:7890），仅 custom 模式生效
+    #[serde(default)]
+    pub url: String,
+    /// 代理认证用户名（为空时不认证）
+    #[serde(default)]
+    pub username: String,
+    #[serde(default)]
+    pub password: String,
+    /// 不走代理的主机（逗号分隔，格式同 NO_PROXY），仅 custom 模式生效
+    #[serde(default)]
+    pub no_proxy: String,
+}
+
+fn default_proxy_mode() -> String {
+    PROXY_MODE_SYSTEM.to_string()
+}
+
+impl Default for ProxyConfig {
+    fn default() -> Self {
+        Self {
+            mode: default_proxy_mode(),
+            url: String::new(),
+            username: String::new(),
+            password: String::new(),
+            no_proxy: String::new(),
+        }
+    }
 }
 
 /// 窗口关闭行为
@@ -238,6 +347,45 @@ fn default_kiro_quota_alert_enabled() -> bool {
 fn default_kiro_quota_alert_threshold() -> i32 {
     20
 }
+fn default_sync_on_startup_enabled() -> bool {
+    true
+}
+
+fn default_auto_apply_fingerprint_on_switch() -> bool {
+    true
+}
+
+fn default_default_window_modes() -> HashMap<String, String> {
+    HashMap::new()
+}
+
+fn default_kiro_oauth_ports() -> Vec<u16> {
+    Vec::new()
+}
+
+fn default_window_geometry() -> Option<WindowGeometry> {
+    None
+}
+
+fn default_vscode_graceful_wait_secs() -> u64 {
+    2
+}
+
+fn default_antigravity_graceful_wait_secs() -> u64 {
+    3
+}
+
+fn default_quota_auto_refresh_minutes() -> u32 {
+    0
+}
+
+fn default_process_helper_exclude_keywords() -> Vec<String> {
+    Vec::new()
+}
+
+fn default_wakeup_models_cache_minutes() -> u32 {
+    10
+}
 
 impl Default for UserConfig {
     fn default() -> Self {
@@ -272,6 +420,17 @@ impl Default for UserConfig {
             windsurf_quota_alert_threshold: default_windsurf_quota_alert_threshold(),
             kiro_quota_alert_enabled: default_kiro_quota_alert_enabled(),
             kiro_quota_alert_threshold: default_kiro_quota_alert_threshold(),
+            sync_on_startup_enabled: default_sync_on_startup_enabled(),
+            auto_apply_fingerprint_on_switch: default_auto_apply_fingerprint_on_switch(),
+            default_window_modes: default_default_window_modes(),
+            kiro_oauth_ports: default_kiro_oauth_ports(),
+            window_geometry: default_window_geometry(),
+            vscode_graceful_wait_secs: default_vscode_graceful_wait_secs(),
+            antigravity_graceful_wait_secs: default_antigravity_graceful_wait_secs(),
+            quota_auto_refresh_minutes: default_quota_auto_refresh_minutes(),
+            process_helper_exclude_keywords: default_process_helper_exclude_keywords(),
+            wakeup_models_cache_minutes: default_wakeup_models_cache_minutes(),
+            proxy: ProxyConfig::default(),
         }
     }
 }
@@ -287,6 +446,16 @@ struct RuntimeState {
 /// 全局运行时状态
 static RUNTIME_STATE: OnceLock<RwLock<RuntimeState>> = OnceLock::new();
 
+/// 配置文件写入锁，保证“读取-修改-写入”不会与其他写入交错
+static USER_CONFIG_WRITE_LOCK: Mutex<()> = Mutex::new(());
+
+/// 待写入的窗口位置，以及负责延迟写入的后台线程
+static PENDING_WINDOW_GEOMETRY: Mutex<Option<WindowGeometry>> = Mutex::new(None);
+static WINDOW_GEOMETRY_WORKER: OnceLock<mpsc::Sender<()>> = OnceLock::new();
+
+/// 本次运行期间的关闭行为（仅保存在内存中，优先于配置文件，应用重启后失效）
+static SESSION_CLOSE_BEHAVIOR: OnceLock<Mutex<Option<CloseWindowBehavior>>> = OnceLock::new();
+
 fn get_runtime_state() -> &'static RwLock<RuntimeState> {
     RUNTIME_STATE.get_or_init(|| {
         RwLock::new(RuntimeState {
@@ -296,6 +465,26 @@ fn get_runtime_state() -> &'static RwLock<RuntimeState> {
     })
 }
 
+fn session_close_behavior() -> &'static Mutex<Option<CloseWindowBehavior>> {
+    SESSION_CLOSE_BEHAVIOR.get_or_init(|| Mutex::new(None))
+}
+
+/// 设置本次运行期间的关闭行为，传入 None 时恢复使用配置文件中的设置
+pub fn set_session_close_behavior(behavior: Option<CloseWindowBehavior>) {
+    if let Ok(mut session) = session_close_behavior().lock() {
+        *session = behavior;
+    }
+}
+
+/// 获取实际生效的关闭行为：本次运行的临时设置优先，其次为配置文件
+pub fn get_effective_close_behavior() -> CloseWindowBehavior {
+    session_close_behavior()
+        .lock()
+        .ok()
+        .and_then(|session| session.clone())
+        .unwrap_or_else(|| get_user_config().close_behavior)
+}
+
 /// 获取数据目录路径
 pub fn get_data_dir() -> Result<PathBuf, String> {
     let home = dirs::home_dir().ok_or("无法获取 Home 目录")?;
@@ -415,6 +604,28 @@ pub fn load_user_config() -> Result<UserConfig, String> {
 
 /// 保存用户配置
 pub fn save_user_config(config: &UserConfig) -> Result<(), String> {
+    let _lock = USER_CONFIG_WRITE_LOCK
+        .lock()
+        .map_err(|_| "无法获取配置写入锁".to_string())?;
+    write_user_config(config)
+}
+
+/// 在写入锁内基于最新配置修改并保存；update 返回 false 表示无需写入
+pub fn update_user_config<F>(update: F) -> Result<(), String>
+where
+    F: FnOnce(&mut UserConfig) -> bool,
+{
+    let _lock = USER_CONFIG_WRITE_LOCK
+        .lock()
+        .map_err(|_| "无法获取配置写入锁".to_string())?;
+    let mut config = get_user_config();
+    if !update(&mut config) {
+        return Ok(());
+    }
+    write_user_config(&config)
+}
+
+fn write_user_config(config: &UserConfig) -> Result<(), String> {
     let config_path = get_user_config_path()?;
     let data_dir = get_data_dir()?;
 
@@ -449,6 +660,156 @@ pub fn get_user_config() -> UserConfig {
         .unwrap_or_default()
 }
 
+/// 获取出站 HTTP 代理设置
+pub fn get_proxy_config() -> ProxyConfig {
+    get_user_config().proxy
+}
+
+/// 获取已保存的主窗口位置与尺寸（尺寸无效时返回 None）
+pub fn get_window_geometry() -> Option<WindowGeometry> {
+    get_user_config()
+        .window_geometry
+        .filter(WindowGeometry::is_valid)
+}
+
+/// 窗口位置写入线程：收到通知后等到 500ms 内不再有新通知时才写入
+fn window_geometry_worker() -> &'static mpsc::Sender<()> {
+    WINDOW_GEOMETRY_WORKER.get_or_init(|| {
+        let (tx, rx) = mpsc::channel::<()>();
+        std::thread::spawn(move || {
+            let debounce = Duration::from_millis(WINDOW_GEOMETRY_SAVE_DEBOUNCE_MS);
+            while rx.recv().is_ok() {
+                while rx.recv_timeout(debounce).is_ok() {}
+                flush_window_geometry();
+            }
+        });
+        tx
+    })
+}
+
+/// 保存主窗口位置与尺寸（延迟写入，500ms 内的连续调用只写入最后一次）
+pub fn save_window_geometry(geometry: WindowGeometry) {
+    if !geometry.is_valid() {
+        return;
+    }
+    if let Ok(mut pending) = PENDING_WINDOW_GEOMETRY.lock() {
+        *pending = Some(geometry);
+    }
+    let _ = window_geometry_worker().send(());
+}
+
+/// 立即写入待保存的窗口位置（窗口关闭时调用）
+pub fn flush_window_geometry() {
+    let Some(geometry) = PENDING_WINDOW_GEOMETRY
+        .lock()
+        .ok()
+        .and_then(|mut pending| pending.take())
+    else {
+        return;
+    };
+    let result = update_user_config(|config| {
+        if config.window_geometry == Some(geometry) {
+            return false;
+        }
+        config.window_geometry = Some(geometry);
+        true
+    });
+    if let Err(e) = result {
+        crate::modules::logger::log_warn(&format!("[Window] 保存窗口位置失败: {}", e));
+    }
+}
+
+/// 规范化各应用默认窗口模式：键值去空白并转小写，丢弃无效的模式
+pub fn normalize_window_modes(modes: HashMap<String, String>) -> HashMap<String, String> {
+    modes
+        .into_iter()
+        .filter_map(|(app, mode)| {
+            let app = app.trim().to_lowercase();
+            let mode = mode.trim().to_lowercase();
+            let valid = mode == WINDOW_MODE_REUSE || mode == WINDOW_MODE_NEW;
+            (valid && !app.is_empty()).then_some((app, mode))
+        })
+        .collect()
+}
+
+/// 规范化辅助进程关键字：去空白、转小写、去重，丢弃空项
+pub fn normalize_helper_keywords(keywords: Vec<String>) -> Vec<String> {
+    let mut normalized: Vec<String> = Vec::new();
+    for keyword in keywords {
+        let keyword = keyword.trim().to_lowercase();
+        if !keyword.is_empty() && !normalized.contains(&keyword) {
+            normalized.push(keyword);
+        }
+    }
+    normalized
+}
+
+/// 获取用户配置的辅助进程关键字（为空表示使用内置列表）
+/// 每轮进程扫描开始时调用一次，只克隆该字段而不是整份配置
+pub fn get_process_helper_exclude_keywords() -> Vec<String> {
+    get_runtime_state()
+        .read()
+        .map(|state| state.user_config.process_helper_exclude_keywords.clone())
+        .unwrap_or_default()
+}
+
+/// 优雅退出等待秒数范围
+pub const GRACEFUL_WAIT_SECS_MIN: u64 = 1;
+pub const GRACEFUL_WAIT_SECS_MAX: u64 = 30;
+
+/// 获取 VS Code 优雅退出等待秒数（限制在 1-30 之间）
+pub fn get_vscode_graceful_wait_secs() -> u64 {
+    get_user_config()
+        .vscode_graceful_wait_secs
+        .clamp(GRACEFUL_WAIT_SECS_MIN, GRACEFUL_WAIT_SECS_MAX)
+}
+
+/// 获取 Antigravity 优雅退出等待秒数（限制在 1-30 之间）
+pub fn get_antigravity_graceful_wait_secs() -> u64 {
+    get_user_config()
+        .antigravity_graceful_wait_secs
+        .clamp(GRACEFUL_WAIT_SECS_MIN, GRACEFUL_WAIT_SECS_MAX)
+}
+
+/// 配额定时刷新间隔上限（分钟）
+pub const QUOTA_AUTO_REFRESH_MAX_MINUTES: u32 = 24 * 60;
+
+/// 获取配额定时刷新间隔（分钟），0 表示关闭
+pub fn get_quota_auto_refresh_minutes() -> u32 {
+    get_user_config()
+        .quota_auto_refresh_minutes
+        .min(QUOTA_AUTO_REFRESH_MAX_MINUTES)
+}
+
+/// 唤醒模型列表缓存有效期上限（分钟）
+pub const WAKEUP_MODELS_CACHE_MAX_MINUTES: u32 = 24 * 60;
+
+/// 获取唤醒模型列表缓存有效期（分钟），0 表示不缓存
+pub fn get_wakeup_models_cache_minutes() -> u32 {
+    get_user_config()
+        .wakeup_models_cache_minutes
+        .min(WAKEUP_MODELS_CACHE_MAX_MINUTES)
+}
+
+/// 规范化 Kiro OAuth 回调端口：过滤 1024-65535 以外的端口并去重（保持原有顺序）
+pub fn normalize_kiro_oauth_ports(ports: Vec<u16>) -> Vec<u16> {
+    let mut normalized = Vec::with_capacity(ports.len());
+    for port in ports {
+        if port >= 1024 && !normalized.contains(&port) {
+            normalized.push(port);
+        }
+    }
+    normalized
+}
+
+/// 获取应用的默认窗口模式：Some(true) 为新窗口，Some(false) 为复用窗口，未配置返回 None
+pub fn get_default_new_window(app: &str) -> Option<bool> {
+    get_user_config()
+        .default_window_modes
+        .get(app)
+        .map(|mode| mode == WINDOW_MODE_NEW)
+}
+
 /// 获取用户配置的首选端口
 pub fn get_preferred_port() -> u16 {
     get_user_config().ws_port
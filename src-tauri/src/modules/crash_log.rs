@@ -0,0 +1,185 @@
+//! 本地崩溃日志
+//! 通过 panic hook 将 panic 信息（消息、位置、已启用时的 backtrace）写入日志目录下的 crash.log，
+//! 仅保存在本地、不上传；hook 执行完后交还给默认 hook，panic 行为保持不变
+
+use std::fs;
+use std::io::Write;
+use std::panic::PanicHookInfo;
+use std::path::{Path, PathBuf};
+use std::sync::Once;
+
+use crate::modules::logger;
+
+const CRASH_LOG_FILE: &str = "crash.log";
+/// 单个崩溃日志文件的大小上限，超过后轮转
+const CRASH_LOG_MAX_BYTES: u64 = 256 * 1024;
+/// 保留的历史崩溃日志数量（crash.log.1 ~ crash.log.N）
+const CRASH_LOG_KEEP: usize = 3;
+/// 每条崩溃记录的起始标记
+const ENTRY_MARKER: &str = "===== panic at ";
+
+static INSTALL_ONCE: Once = Once::new();
+
+fn crash_log_path(log_dir: &Path) -> PathBuf {
+    log_dir.join(CRASH_LOG_FILE)
+}
+
+fn rotated_path(log_dir: &Path, index: usize) -> PathBuf {
+    log_dir.join(format!("{}.{}", CRASH_LOG_FILE, index))
+}
+
+/// 当前文件超过大小上限时依次后移：crash.log -> crash.log.1 -> ... 最旧的被覆盖
+fn rotate_if_needed(log_dir: &Path) {
+    let current = crash_log_path(log_dir);
+    let size = fs::metadata(&current).map(|meta| meta.len()).unwrap_or(0);
+    if size < CRASH_LOG_MAX_BYTES {
+        return;
+    }
+    for index in (1..CRASH_LOG_KEEP).rev() {
+        let from = rotated_path(log_dir, index);
+        if from.exists() {
+            let _ = fs::rename(&from, rotated_path(log_dir, index + 1));
+        }
+    }
+    let _ = fs::rename(&current, rotated_path(log_dir, 1));
+}
+
+fn append_entry(log_dir: &Path, entry: &str) -> std::io::Result<()> {
+    rotate_if_needed(log_dir);
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(crash_log_path(log_dir))?;
+    file.write_all(entry.as_bytes())
+}
+
+fn panic_message(info: &PanicHookInfo<'_>) -> String {
+    let payload = info.payload();
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "<非字符串 panic 信息>".to_string()
+    }
+}
+
+fn format_entry(
+    timestamp: &str,
+    thread: &str,
+    message: &str,
+    location: Option<String>,
+    backtrace: Option<String>,
+) -> String {
+    let mut entry = format!(
+        "{}{} =====\nversion: {}\nthread: {}\nmessage: {}\nlocation: {}\n",
+        ENTRY_MARKER,
+        timestamp,
+        env!("CARGO_PKG_VERSION"),
+        thread,
+        logger::sanitize_message(message),
+        location.unwrap_or_else(|| "<unknown>".to_string()),
+    );
+    if let Some(backtrace) = backtrace {
+        entry.push_str("backtrace:\n");
+        entry.push_str(&backtrace);
+        if !backtrace.ends_with('\n') {
+            entry.push('\n');
+        }
+    }
+    entry.push('\n');
+    entry
+}
+
+fn record_panic(info: &PanicHookInfo<'_>) {
+    let Ok(log_dir) = logger::get_log_dir() else {
+        return;
+    };
+    let thread = std::thread::current();
+    // 仅在 RUST_BACKTRACE 等环境变量启用时才会真正捕获
+    let backtrace = std::backtrace::Backtrace::capture();
+    let backtrace = (backtrace.status() == std::backtrace::BacktraceStatus::Captured)
+        .then(|| backtrace.to_string());
+    let entry = format_entry(
+        &chrono::Local::now().to_rfc3339(),
+        thread.name().unwrap_or("<unnamed>"),
+        &panic_message(info),
+        info.location().map(|location| {
+            format!(
+                "{}:{}:{}",
+                location.file(),
+                location.line(),
+                location.column()
+            )
+        }),
+        backtrace,
+    );
+    if let Err(err) = append_entry(&log_dir, &entry) {
+        eprintln!("写入崩溃日志失败: {}", err);
+    }
+}
+
+/// 安装 panic hook（重复调用无副作用），记录后继续调用原有 hook
+pub fn install_panic_hook() {
+    INSTALL_ONCE.call_once(|| {
+        let default_hook = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            record_panic(info);
+            default_hook(info);
+        }));
+    });
+}
+
+/// 取内容中的最后一条崩溃记录
+fn last_entry(content: &str) -> Option<String> {
+    let start = content.rfind(ENTRY_MARKER)?;
+    Some(content[start..].trim_end().to_string())
+}
+
+fn read_last_crash_log(log_dir: &Path) -> Option<String> {
+    std::iter::once(crash_log_path(log_dir))
+        .chain((1..=CRASH_LOG_KEEP).map(|index| rotated_path(log_dir, index)))
+        .filter_map(|path| fs::read_to_string(path).ok())
+        .find_map(|content| last_entry(&content))
+}
+
+/// 获取最近一次崩溃记录，没有崩溃记录时返回 None
+pub fn get_last_crash_log() -> Result<Option<String>, String> {
+    let log_dir = logger::get_log_dir()?;
+    Ok(read_last_crash_log(&log_dir))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    #[test]
+    fn test_rotation_keeps_latest_entry_readable() {
+        let dir = std::env::temp_dir().join(format!("cockpit-crash-log-test-{}", Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        assert!(read_last_crash_log(&dir).is_none());
+
+        let padding = "x".repeat(CRASH_LOG_MAX_BYTES as usize);
+        let first = format_entry("t1", "main", &padding, None, None);
+        append_entry(&dir, &first).unwrap();
+        let second = format_entry(
+            "t2",
+            "ws",
+            "boom user@example.com",
+            Some("src/a.rs:1:2".to_string()),
+            Some("0: frame".to_string()),
+        );
+        append_entry(&dir, &second).unwrap();
+
+        // 第一条超过大小上限，写入第二条前已轮转
+        assert!(rotated_path(&dir, 1).exists());
+        let last = read_last_crash_log(&dir).unwrap();
+        assert!(last.starts_with("===== panic at t2"));
+        assert!(last.contains("location: src/a.rs:1:2"));
+        assert!(last.contains("backtrace:\n0: frame"));
+        assert!(!last.contains("user@example.com"));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,146 @@
+//! 各平台当前账号汇总
+//! 一次性读取 / 清除所有平台的“当前账号”，并统一修复指向已删除账号的悬空指针
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use crate::modules;
+use crate::modules::instance_registry;
+use crate::modules::tray_layout::{
+    PLATFORM_ANTIGRAVITY, PLATFORM_CODEX, PLATFORM_GITHUB_COPILOT, PLATFORM_KIRO,
+    PLATFORM_WINDSURF, SUPPORTED_PLATFORM_IDS,
+};
+
+/// 单个平台的当前账号摘要
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CurrentAccountSummary {
+    pub account_id: Option<String>,
+    /// 邮箱或登录名
+    pub identity: Option<String>,
+    /// 本次读取时是否修复了悬空的当前账号指针
+    pub repaired: bool,
+    /// 读取失败时的错误信息
+    pub error: Option<String>,
+}
+
+/// 清除默认实例绑定的账号（GitHub Copilot / Windsurf / Kiro 以此作为当前账号）
+fn clear_default_binding(platform: &str) -> Result<(), String> {
+    match platform {
+        PLATFORM_GITHUB_COPILOT => {
+            modules::github_copilot_instance::update_default_settings(Some(None), None, None)
+        }
+        PLATFORM_WINDSURF => {
+            modules::windsurf_instance::update_default_settings(Some(None), None, None)
+        }
+        PLATFORM_KIRO => modules::kiro_instance::update_default_settings(Some(None), None, None),
+        _ => return Ok(()),
+    }
+    .map(|_| ())
+}
+
+/// 默认实例绑定的账号已不存在时清除绑定，返回是否做了修复
+fn repair_default_binding(platform: &str) -> Result<bool, String> {
+    let store = instance_registry::load_instance_store(platform)?;
+    let Some(bind_id) = store
+        .default_settings
+        .bind_account_id
+        .filter(|id| !id.trim().is_empty())
+    else {
+        return Ok(false);
+    };
+    if instance_registry::account_identity(platform, bind_id.trim()).is_some() {
+        return Ok(false);
+    }
+    clear_default_binding(platform)?;
+    modules::logger::log_warn(&format!(
+        "[CurrentAccount] 默认实例绑定的账号不存在，已清除: platform={}, account_id={}",
+        platform, bind_id
+    ));
+    Ok(true)
+}
+
+fn repair_current_pointer(platform: &str) -> Result<bool, String> {
+    match platform {
+        PLATFORM_ANTIGRAVITY => modules::account::repair_current_account_id(),
+        PLATFORM_CODEX => modules::codex_account::repair_current_account_id(),
+        _ => repair_default_binding(platform),
+    }
+}
+
+fn resolve_current_account_id(platform: &str) -> Result<Option<String>, String> {
+    let account_id = match platform {
+        PLATFORM_ANTIGRAVITY => modules::account::get_current_account_id()?,
+        PLATFORM_CODEX => modules::codex_account::get_current_account().map(|account| account.id),
+        PLATFORM_GITHUB_COPILOT => modules::github_copilot_account::resolve_current_account_id(
+            &modules::github_copilot_account::list_accounts(),
+        ),
+        PLATFORM_WINDSURF => modules::windsurf_account::resolve_current_account_id(
+            &modules::windsurf_account::list_accounts(),
+        ),
+        PLATFORM_KIRO => modules::kiro_account::resolve_current_account_id(
+            &modules::kiro_account::list_accounts(),
+        ),
+        _ => return Err(format!("未知平台: {}", platform)),
+    };
+    Ok(account_id)
+}
+
+fn summarize_platform(platform: &str) -> CurrentAccountSummary {
+    let mut summary = CurrentAccountSummary {
+        account_id: None,
+        identity: None,
+        repaired: false,
+        error: None,
+    };
+    match repair_current_pointer(platform) {
+        Ok(repaired) => summary.repaired = repaired,
+        Err(err) => {
+            modules::logger::log_warn(&format!(
+                "[CurrentAccount] 修复当前账号失败: platform={}, err={}",
+                platform, err
+            ));
+        }
+    }
+    match resolve_current_account_id(platform) {
+        Ok(account_id) => {
+            summary.identity = account_id
+                .as_deref()
+                .and_then(|id| instance_registry::account_identity(platform, id));
+            summary.account_id = account_id;
+        }
+        Err(err) => summary.error = Some(err),
+    }
+    summary
+}
+
+/// 获取所有平台的当前账号（键为平台 ID），读取前先修复悬空指针
+pub fn get_all_current_accounts() -> HashMap<String, CurrentAccountSummary> {
+    SUPPORTED_PLATFORM_IDS
+        .iter()
+        .map(|platform| (platform.to_string(), summarize_platform(platform)))
+        .collect()
+}
+
+/// 清除所有平台的当前账号指针，返回清除后的汇总
+/// 不会删除账号，也不会改动客户端自身的登录文件（如 Codex 的 auth.json）
+/// GitHub Copilot / Windsurf / Kiro 清除默认实例绑定后，当前账号会回退为最近使用的账号
+pub fn clear_all_current_accounts() -> Result<HashMap<String, CurrentAccountSummary>, String> {
+    let mut errors = Vec::new();
+    for platform in SUPPORTED_PLATFORM_IDS {
+        let result = match platform {
+            PLATFORM_ANTIGRAVITY => modules::account::clear_current_account_id(),
+            PLATFORM_CODEX => modules::codex_account::clear_current_account_id(),
+            _ => clear_default_binding(platform),
+        };
+        if let Err(err) = result {
+            errors.push(format!("{}: {}", platform, err));
+        }
+    }
+    if !errors.is_empty() {
+        return Err(format!("清除当前账号失败: {}", errors.join("; ")));
+    }
+    modules::logger::log_info("[CurrentAccount] 已清除所有平台的当前账号");
+    Ok(get_all_current_accounts())
+}
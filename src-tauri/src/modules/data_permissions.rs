@@ -0,0 +1,222 @@
+//! 数据目录权限检查与修复
+//! 恢复备份或以其他用户身份运行后，数据文件权限可能异常导致保存失败；
+//! 含令牌的文件应仅当前用户可读写（目录 0700，文件 0600）
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use crate::modules;
+
+/// 含账号令牌的索引文件（相对数据目录）
+const TOKEN_FILES: [&str; 4] = [
+    "accounts.json",
+    "github_copilot_accounts.json",
+    "windsurf_accounts.json",
+    "kiro_accounts.json",
+];
+
+/// 含账号令牌的账号详情目录（相对数据目录）
+const TOKEN_DIRS: [&str; 4] = [
+    "accounts",
+    "github_copilot_accounts",
+    "windsurf_accounts",
+    "kiro_accounts",
+];
+
+/// 其他需要可读写的配置文件（相对数据目录）
+const CONFIG_FILES: [&str; 3] = ["config.json", "instances.json", "fingerprints.json"];
+
+#[cfg(unix)]
+const DIR_MODE: u32 = 0o700;
+#[cfg(unix)]
+const TOKEN_FILE_MODE: u32 = 0o600;
+
+/// 单个路径的权限状态
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PathPermissionStatus {
+    pub path: String,
+    pub is_dir: bool,
+    pub readable: bool,
+    pub writable: bool,
+    /// 当前权限位（八进制，仅 Unix）
+    pub mode: Option<String>,
+    /// 是否包含账号令牌
+    pub token_bearing: bool,
+    /// 权限是否符合预期（可读写，且令牌文件未向其他用户开放）
+    pub ok: bool,
+}
+
+/// 数据目录权限检查报告
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DataDirPermissionReport {
+    pub data_dir: String,
+    pub ok: bool,
+    pub entries: Vec<PathPermissionStatus>,
+    /// 修复时失败的路径及原因（仅 fix 时填充）
+    pub errors: Vec<String>,
+}
+
+/// 收集需要检查的路径：(路径, 是否含令牌)
+fn collect_target_paths(data_dir: &Path) -> Vec<(PathBuf, bool)> {
+    let mut targets = vec![(data_dir.to_path_buf(), true)];
+    for name in CONFIG_FILES {
+        targets.push((data_dir.join(name), false));
+    }
+    for name in TOKEN_FILES {
+        targets.push((data_dir.join(name), true));
+    }
+
+    let codex_dir = modules::codex_account::get_storage_dir();
+    targets.push((codex_dir.join("codex_accounts.json"), true));
+
+    let account_dirs = TOKEN_DIRS
+        .iter()
+        .map(|name| data_dir.join(name))
+        .chain(std::iter::once(codex_dir.join("codex_accounts")));
+    for dir in account_dirs {
+        if !dir.is_dir() {
+            continue;
+        }
+        targets.push((dir.clone(), true));
+        if let Ok(entries) = fs::read_dir(&dir) {
+            for entry in entries.flatten() {
+                let path = entry.path();
+                if path.is_file() {
+                    targets.push((path, true));
+                }
+            }
+        }
+    }
+
+    targets.retain(|(path, _)| path.exists());
+    targets
+}
+
+#[cfg(unix)]
+fn check_access(path: &Path) -> (bool, bool) {
+    use std::os::unix::ffi::OsStrExt;
+
+    let Ok(c_path) = std::ffi::CString::new(path.as_os_str().as_bytes()) else {
+        return (false, false);
+    };
+    // SAFETY: c_path 为合法的以 NUL 结尾的字符串，access 不会保留指针
+    let readable = unsafe { libc::access(c_path.as_ptr(), libc::R_OK) } == 0;
+    let writable = unsafe { libc::access(c_path.as_ptr(), libc::W_OK) } == 0;
+    (readable, writable)
+}
+
+#[cfg(not(unix))]
+fn check_access(path: &Path) -> (bool, bool) {
+    match fs::metadata(path) {
+        Ok(meta) => (true, !meta.permissions().readonly()),
+        Err(_) => (false, false),
+    }
+}
+
+fn inspect_path(path: &Path, token_bearing: bool) -> PathPermissionStatus {
+    let is_dir = path.is_dir();
+    let (readable, writable) = check_access(path);
+
+    #[cfg(unix)]
+    let (mode, private) = {
+        use std::os::unix::fs::PermissionsExt;
+        match fs::metadata(path) {
+            Ok(meta) => {
+                let mode = meta.permissions().mode() & 0o777;
+                (Some(format!("{:o}", mode)), mode & 0o077 == 0)
+            }
+            Err(_) => (None, false),
+        }
+    };
+    #[cfg(not(unix))]
+    let (mode, private) = (None, true);
+
+    PathPermissionStatus {
+        path: path.to_string_lossy().to_string(),
+        is_dir,
+        readable,
+        writable,
+        mode,
+        token_bearing,
+        ok: readable && writable && (!token_bearing || private),
+    }
+}
+
+fn build_report(data_dir: &Path, errors: Vec<String>) -> DataDirPermissionReport {
+    let entries: Vec<PathPermissionStatus> = collect_target_paths(data_dir)
+        .iter()
+        .map(|(path, token_bearing)| inspect_path(path, *token_bearing))
+        .collect();
+    DataDirPermissionReport {
+        data_dir: data_dir.to_string_lossy().to_string(),
+        ok: entries.iter().all(|entry| entry.ok),
+        entries,
+        errors,
+    }
+}
+
+/// 检查数据目录及关键文件的权限
+pub fn check_data_dir_permissions() -> Result<DataDirPermissionReport, String> {
+    let data_dir = modules::account::get_data_dir()?;
+    let report = build_report(&data_dir, Vec::new());
+    if !report.ok {
+        let bad = report.entries.iter().filter(|entry| !entry.ok).count();
+        modules::logger::log_warn(&format!("[Permissions] 发现 {} 个路径权限异常", bad));
+    }
+    Ok(report)
+}
+
+#[cfg(unix)]
+fn fix_path(path: &Path, token_bearing: bool) -> Result<(), String> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let meta = fs::metadata(path).map_err(|e| format!("读取权限失败: {}", e))?;
+    let current = meta.permissions().mode() & 0o777;
+    let target = if meta.is_dir() {
+        DIR_MODE
+    } else if token_bearing {
+        TOKEN_FILE_MODE
+    } else {
+        current | 0o600
+    };
+    if current == target {
+        return Ok(());
+    }
+    fs::set_permissions(path, fs::Permissions::from_mode(target))
+        .map_err(|e| format!("修改权限失败: {}", e))
+}
+
+#[cfg(not(unix))]
+fn fix_path(path: &Path, _token_bearing: bool) -> Result<(), String> {
+    let meta = fs::metadata(path).map_err(|e| format!("读取权限失败: {}", e))?;
+    let mut permissions = meta.permissions();
+    if !permissions.readonly() {
+        return Ok(());
+    }
+    #[allow(clippy::permissions_set_readonly_false)]
+    permissions.set_readonly(false);
+    fs::set_permissions(path, permissions).map_err(|e| format!("修改权限失败: {}", e))
+}
+
+/// 将数据目录及关键文件的权限恢复为合理值（目录 0700，令牌文件 0600），返回修复后的报告
+pub fn fix_data_dir_permissions() -> Result<DataDirPermissionReport, String> {
+    let data_dir = modules::account::get_data_dir()?;
+    let mut errors = Vec::new();
+    for (path, token_bearing) in collect_target_paths(&data_dir) {
+        if let Err(err) = fix_path(&path, token_bearing) {
+            errors.push(format!("{}: {}", path.to_string_lossy(), err));
+        }
+    }
+
+    let report = build_report(&data_dir, errors);
+    modules::logger::log_info(&format!(
+        "[Permissions] 数据目录权限修复完成: ok={}, errors={}",
+        report.ok,
+        report.errors.len()
+    ));
+    Ok(report)
+}
@@ -1,6 +1,8 @@
 use crate::utils::protobuf;
 use base64::{engine::general_purpose, Engine as _};
-use rusqlite::{Connection, Error as SqliteError};
+use rusqlite::{Connection, Error as SqliteError, OpenFlags};
+use std::fs;
+use std::ops::Deref;
 use std::path::{Path, PathBuf};
 
 /// 获取 Antigravity 数据库路径
@@ -38,6 +40,119 @@ pub fn get_db_path() -> Result<PathBuf, String> {
     }
 }
 
+/// 只读的编辑器数据库连接
+/// 读取快照时持有临时目录，连接关闭后自动删除
+pub struct ReadonlyDb {
+    conn: Option<Connection>,
+    snapshot_dir: Option<PathBuf>,
+}
+
+impl Deref for ReadonlyDb {
+    type Target = Connection;
+
+    fn deref(&self) -> &Connection {
+        self.conn.as_ref().expect("ReadonlyDb 连接已关闭")
+    }
+}
+
+impl Drop for ReadonlyDb {
+    fn drop(&mut self) {
+        // 先关闭连接再删除快照，Windows 下打开中的文件无法删除
+        drop(self.conn.take());
+        if let Some(dir) = self.snapshot_dir.take() {
+            let _ = fs::remove_dir_all(dir);
+        }
+    }
+}
+
+fn sidecar_path(db_path: &Path, suffix: &str) -> PathBuf {
+    let mut raw = db_path.as_os_str().to_os_string();
+    raw.push(suffix);
+    PathBuf::from(raw)
+}
+
+/// 将数据库及其 -wal / -shm 文件复制到临时目录，返回副本路径
+fn snapshot_db_with_wal(db_path: &Path, snapshot_dir: &Path) -> Result<PathBuf, String> {
+    fs::create_dir_all(snapshot_dir).map_err(|e| format!("创建数据库快照目录失败: {}", e))?;
+    let file_name = db_path.file_name().ok_or("无效的数据库路径")?;
+    let target = snapshot_dir.join(file_name);
+    fs::copy(db_path, &target).map_err(|e| format!("复制数据库快照失败: {}", e))?;
+    for suffix in ["-wal", "-shm"] {
+        let source = sidecar_path(db_path, suffix);
+        if source.exists() {
+            fs::copy(&source, sidecar_path(&target, suffix))
+                .map_err(|e| format!("复制数据库快照失败: {}", e))?;
+        }
+    }
+    Ok(target)
+}
+
+/// 打开编辑器的 SQLite 数据库用于只读查询
+/// 存在 WAL 文件（编辑器通常正在运行）时复制 db + wal 到临时目录后读取副本，
+/// 既能读到 WAL 中尚未回写的最新数据，也不会与编辑器争锁；否则直接以只读模式打开
+pub fn open_readonly(db_path: &Path) -> Result<ReadonlyDb, String> {
+    if sidecar_path(db_path, "-wal").exists() {
+        let snapshot_dir =
+            std::env::temp_dir().join(format!("cockpit-db-{}", uuid::Uuid::new_v4()));
+        let opened = snapshot_db_with_wal(db_path, &snapshot_dir).and_then(|copy| {
+            Connection::open(copy).map_err(|e| format!("打开数据库快照失败: {}", e))
+        });
+        return match opened {
+            Ok(conn) => Ok(ReadonlyDb {
+                conn: Some(conn),
+                snapshot_dir: Some(snapshot_dir),
+            }),
+            Err(err) => {
+                let _ = fs::remove_dir_all(&snapshot_dir);
+                Err(err)
+            }
+        };
+    }
+
+    let conn = Connection::open_with_flags(
+        db_path,
+        OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+    )
+    .map_err(|e| format!("打开数据库失败: {}", e))?;
+    Ok(ReadonlyDb {
+        conn: Some(conn),
+        snapshot_dir: None,
+    })
+}
+
+/// 检测 state.vscdb 是否被其他进程（通常是正在运行的编辑器）锁定
+/// 短暂尝试 BEGIN IMMEDIATE 获取写锁，成功后立即回滚，不修改数据
+pub fn is_db_locked(db_path: &Path) -> Result<bool, String> {
+    let conn = Connection::open(db_path).map_err(|e| format!("打开数据库失败: {}", e))?;
+    conn.busy_timeout(std::time::Duration::from_millis(300))
+        .map_err(|e| format!("设置数据库超时失败: {}", e))?;
+    match conn.execute_batch("BEGIN IMMEDIATE; ROLLBACK;") {
+        Ok(()) => Ok(false),
+        Err(SqliteError::SqliteFailure(err, _))
+            if matches!(
+                err.code,
+                rusqlite::ErrorCode::DatabaseBusy | rusqlite::ErrorCode::DatabaseLocked
+            ) =>
+        {
+            Ok(true)
+        }
+        Err(e) => Err(format!("检测数据库锁失败: {}", e)),
+    }
+}
+
+/// 写入凭据前的预检：数据库被占用时返回可操作的错误提示
+pub fn ensure_db_writable(db_path: &Path, app_label: &str) -> Result<(), String> {
+    if db_path.exists() && is_db_locked(db_path)? {
+        return Err(format!(
+            "{} 正在使用数据库 {}，请先关闭 {} 后重试",
+            app_label,
+            db_path.display(),
+            app_label
+        ));
+    }
+    Ok(())
+}
+
 /// 注入 Token 到指定数据库路径
 pub fn inject_token_to_path(
     db_path: &Path,
@@ -46,6 +161,7 @@ pub fn inject_token_to_path(
     expiry: i64,
 ) -> Result<String, String> {
     crate::modules::logger::log_info(&format!("注入 Token 到数据库: {:?}", db_path));
+    ensure_db_writable(db_path, "Antigravity")?;
 
     // 新格式：antigravityUnifiedStateSync.oauthToken
     inject_unified_oauth_token_to_path(db_path, access_token, refresh_token, expiry)?;
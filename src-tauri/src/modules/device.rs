@@ -3,12 +3,18 @@ use crate::modules::logger;
 use rand::{distributions::Alphanumeric, Rng};
 use rusqlite::Connection;
 use serde_json::Value;
+use sha2::{Digest, Sha256};
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
 use uuid::Uuid;
 
 const DATA_DIR: &str = ".antigravity_cockpit";
 const GLOBAL_BASELINE: &str = "device_original.json";
+const COCKPIT_MACHINE_SEED_FILE: &str = "machine_seed";
+const COCKPIT_MACHINE_SEED_LEN: usize = 64;
+
+static COCKPIT_MACHINE_ID: OnceLock<String> = OnceLock::new();
 
 fn get_data_dir() -> Result<PathBuf, String> {
     let home = dirs::home_dir().ok_or("无法获取用户主目录")?;
@@ -134,7 +140,7 @@ fn read_state_service_machine_id_value() -> Option<String> {
     if !db_path.exists() {
         return None;
     }
-    let conn = Connection::open(&db_path).ok()?;
+    let conn = crate::modules::db::open_readonly(&db_path).ok()?;
     let value: Result<String, _> = conn.query_row(
         "SELECT value FROM ItemTable WHERE key = 'storage.serviceMachineId'",
         [],
@@ -396,6 +402,34 @@ fn new_standard_machine_id() -> String {
     id
 }
 
+fn load_or_create_machine_seed() -> Result<String, String> {
+    let path = get_data_dir()?.join(COCKPIT_MACHINE_SEED_FILE);
+    if let Ok(content) = fs::read_to_string(&path) {
+        let seed = content.trim().to_string();
+        if seed.len() == COCKPIT_MACHINE_SEED_LEN {
+            return Ok(seed);
+        }
+        logger::log_warn("[Device] 本机标识种子文件无效，重新生成");
+    }
+    let seed = random_hex(COCKPIT_MACHINE_SEED_LEN);
+    fs::write(&path, &seed).map_err(|e| format!("写入本机标识种子失败: {}", e))?;
+    Ok(seed)
+}
+
+/// 获取 Cockpit 本机稳定标识（用于跨设备日志关联与同步来源区分）
+/// 基于首次生成并持久化在数据目录的随机种子，只返回其哈希，不涉及任何硬件信息
+pub fn get_cockpit_machine_id() -> Result<String, String> {
+    if let Some(id) = COCKPIT_MACHINE_ID.get() {
+        return Ok(id.clone());
+    }
+    let seed = load_or_create_machine_seed()?;
+    let mut hasher = Sha256::new();
+    hasher.update(b"cockpit-machine-id:");
+    hasher.update(seed.as_bytes());
+    let id = format!("{:x}", hasher.finalize())[..32].to_string();
+    Ok(COCKPIT_MACHINE_ID.get_or_init(|| id).clone())
+}
+
 /// 打开设备存储目录
 pub fn open_device_folder() -> Result<(), String> {
     let storage_dir = get_storage_dir()?;
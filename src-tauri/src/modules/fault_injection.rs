@@ -0,0 +1,91 @@
+//! 故障注入（仅调试构建）
+//! 让切换、刷新、启动等操作稳定失败，便于验证前端的错误处理路径；发布构建中不生效
+
+#[cfg(debug_assertions)]
+use std::collections::HashMap;
+#[cfg(debug_assertions)]
+use std::sync::Mutex;
+
+#[cfg(debug_assertions)]
+use crate::modules::logger;
+
+/// 支持注入故障的操作
+pub const FAULT_OPERATIONS: [&str; 3] = ["switch", "refresh", "launch"];
+
+/// 故障模式：always 每次失败；once 仅下一次失败后自动清除
+const FAULT_MODE_ALWAYS: &str = "always";
+const FAULT_MODE_ONCE: &str = "once";
+
+#[cfg(debug_assertions)]
+static FAULTS: std::sync::LazyLock<Mutex<HashMap<String, String>>> =
+    std::sync::LazyLock::new(|| Mutex::new(HashMap::new()));
+
+fn validate(operation: &str, mode: &str) -> Result<(), String> {
+    if !FAULT_OPERATIONS.contains(&operation) {
+        return Err(format!(
+            "不支持的操作: {}（可选: {}）",
+            operation,
+            FAULT_OPERATIONS.join(", ")
+        ));
+    }
+    if mode != FAULT_MODE_ALWAYS && mode != FAULT_MODE_ONCE {
+        return Err(format!("不支持的故障模式: {}（可选: always, once）", mode));
+    }
+    Ok(())
+}
+
+/// 为指定操作设置故障注入
+#[cfg(debug_assertions)]
+pub fn set_fault(operation: &str, mode: &str) -> Result<(), String> {
+    let operation = operation.trim().to_lowercase();
+    let mode = mode.trim().to_lowercase();
+    validate(&operation, &mode)?;
+    let mut faults = FAULTS.lock().map_err(|_| "故障注入状态锁不可用")?;
+    logger::log_warn(&format!(
+        "[FaultInjection] 已启用: operation={}, mode={}",
+        operation, mode
+    ));
+    faults.insert(operation, mode);
+    Ok(())
+}
+
+#[cfg(not(debug_assertions))]
+pub fn set_fault(operation: &str, mode: &str) -> Result<(), String> {
+    validate(operation.trim(), mode.trim())?;
+    Err("故障注入仅在调试构建中可用".to_string())
+}
+
+/// 清除所有故障注入
+pub fn clear_faults() {
+    #[cfg(debug_assertions)]
+    if let Ok(mut faults) = FAULTS.lock() {
+        if !faults.is_empty() {
+            logger::log_info("[FaultInjection] 已清除所有故障注入");
+        }
+        faults.clear();
+    }
+}
+
+/// 检查操作是否被注入故障，命中时返回模拟错误
+#[cfg(debug_assertions)]
+pub fn check(operation: &str) -> Result<(), String> {
+    let Ok(mut faults) = FAULTS.lock() else {
+        return Ok(());
+    };
+    let Some(mode) = faults.get(operation).cloned() else {
+        return Ok(());
+    };
+    if mode == FAULT_MODE_ONCE {
+        faults.remove(operation);
+    }
+    logger::log_warn(&format!(
+        "[FaultInjection] 模拟操作失败: operation={}, mode={}",
+        operation, mode
+    ));
+    Err(format!("[FaultInjection] 模拟失败: {}", operation))
+}
+
+#[cfg(not(debug_assertions))]
+pub fn check(_operation: &str) -> Result<(), String> {
+    Ok(())
+}
@@ -1,10 +1,11 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use uuid::Uuid;
 
 use crate::models::DeviceProfile;
-use crate::modules::{device, logger};
+use crate::modules::{device, logger, process};
 
 const FINGERPRINTS_FILE: &str = "fingerprints.json";
 
@@ -27,6 +28,15 @@ impl FingerprintStore {
             fingerprints: Vec::new(),
         }
     }
+
+    /// 是否已存在相同 ID 或名称（忽略首尾空白）的指纹，包含原始指纹
+    pub fn contains_id_or_name(&self, id: Option<&str>, name: &str) -> bool {
+        let name = name.trim();
+        self.original_baseline
+            .iter()
+            .chain(self.fingerprints.iter())
+            .any(|fp| id.is_some_and(|id| fp.id == id) || fp.name.trim() == name)
+    }
 }
 
 /// 单个指纹
@@ -50,7 +60,7 @@ impl Fingerprint {
 }
 
 /// 获取指纹存储文件路径
-fn get_fingerprints_path() -> Result<PathBuf, String> {
+pub fn get_fingerprints_path() -> Result<PathBuf, String> {
     let data_dir = crate::modules::account::get_data_dir()?;
     Ok(data_dir.join(FINGERPRINTS_FILE))
 }
@@ -137,10 +147,15 @@ pub fn generate_fingerprint(name: String) -> Result<Fingerprint, String> {
     create_fingerprint_with_profile(name, profile)
 }
 
+/// 读取当前系统设备指纹
+fn read_current_profile() -> Result<DeviceProfile, String> {
+    let storage_path = device::get_storage_path()?;
+    device::read_profile(&storage_path)
+}
+
 /// 捕获当前系统指纹
 pub fn capture_fingerprint(name: String) -> Result<Fingerprint, String> {
-    let storage_path = device::get_storage_path()?;
-    let profile = device::read_profile(&storage_path)?;
+    let profile = read_current_profile()?;
     create_fingerprint_with_profile(name, profile)
 }
 
@@ -180,6 +195,22 @@ pub fn apply_fingerprint(fingerprint_id: &str) -> Result<String, String> {
     Ok(format!("已应用指纹: {}", fingerprint.name))
 }
 
+/// 校验设备指纹：machine_id / mac_machine_id / dev_device_id / sqm_id 均不能为空
+pub fn validate_profile(profile: &DeviceProfile) -> Result<(), String> {
+    let required = [
+        ("machine_id", &profile.machine_id),
+        ("mac_machine_id", &profile.mac_machine_id),
+        ("dev_device_id", &profile.dev_device_id),
+        ("sqm_id", &profile.sqm_id),
+    ];
+    for (field, value) in required {
+        if value.trim().is_empty() {
+            return Err(format!("指纹字段 {} 为空", field));
+        }
+    }
+    Ok(())
+}
+
 /// 重命名指纹
 pub fn rename_fingerprint(fingerprint_id: &str, name: String) -> Result<(), String> {
     if fingerprint_id == "original" {
@@ -343,3 +374,347 @@ pub fn list_fingerprints_with_stats() -> Result<Vec<FingerprintWithStats>, Strin
 
     Ok(result)
 }
+
+/// 设备指纹字段差异
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FieldDiff {
+    /// telemetry 键名
+    pub field: String,
+    pub current: Option<String>,
+    pub proposed: Option<String>,
+}
+
+fn profile_fields(profile: &DeviceProfile) -> [(&'static str, &str); 5] {
+    [
+        ("machineId", profile.machine_id.as_str()),
+        ("macMachineId", profile.mac_machine_id.as_str()),
+        ("devDeviceId", profile.dev_device_id.as_str()),
+        ("sqmId", profile.sqm_id.as_str()),
+        ("serviceMachineId", profile.service_machine_id.as_str()),
+    ]
+}
+
+fn diff_profiles(
+    current: Option<&DeviceProfile>,
+    proposed: &DeviceProfile,
+    include_unchanged: bool,
+) -> Vec<FieldDiff> {
+    let non_empty = |value: &str| (!value.is_empty()).then(|| value.to_string());
+    let current_fields = current.map(profile_fields);
+    profile_fields(proposed)
+        .into_iter()
+        .enumerate()
+        .map(|(index, (field, proposed_value))| FieldDiff {
+            field: field.to_string(),
+            current: current_fields
+                .as_ref()
+                .and_then(|fields| non_empty(fields[index].1)),
+            proposed: non_empty(proposed_value),
+        })
+        .filter(|diff| include_unchanged || diff.current != diff.proposed)
+        .collect()
+}
+
+/// 预览应用指纹后 storage.json 中会变化的字段
+/// 当前设备指纹无法读取时，current 为空
+pub fn preview_profile_diff(
+    fingerprint_id: &str,
+    include_unchanged: bool,
+) -> Result<Vec<FieldDiff>, String> {
+    let fingerprint = get_fingerprint(fingerprint_id)?;
+    let storage_path = device::get_storage_path()?;
+    let current = match device::read_profile(&storage_path) {
+        Ok(profile) => Some(profile),
+        Err(e) => {
+            logger::log_warn(&format!("[Fingerprint] 读取当前设备指纹失败: {}", e));
+            None
+        }
+    };
+    Ok(diff_profiles(
+        current.as_ref(),
+        &fingerprint.profile,
+        include_unchanged,
+    ))
+}
+
+/// 字段稳定性
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum FieldStability {
+    /// 机器标识，通常只在重装系统、更换硬件或被工具改写时变化，变化后需要关注
+    Stable,
+    /// 编辑器在重置遥测或重新初始化存储时会重新生成，变化一般无需处理
+    Volatile,
+}
+
+fn field_stability(field: &str) -> FieldStability {
+    match field {
+        "sqmId" | "serviceMachineId" => FieldStability::Volatile,
+        _ => FieldStability::Stable,
+    }
+}
+
+/// 已保存指纹与当前设备不一致的字段
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FingerprintFieldChange {
+    /// telemetry 键名
+    pub field: String,
+    pub stored: Option<String>,
+    pub current: Option<String>,
+    pub stability: FieldStability,
+}
+
+/// 当前设备指纹与已保存指纹的比对结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FingerprintDiff {
+    pub fingerprint_id: String,
+    pub changed_fields: Vec<FingerprintFieldChange>,
+    /// 是否有稳定字段发生变化
+    pub stable_changed: bool,
+    pub stable_fields: Vec<String>,
+    pub volatile_fields: Vec<String>,
+}
+
+fn diff_against_current(
+    stored: &DeviceProfile,
+    current: &DeviceProfile,
+) -> Vec<FingerprintFieldChange> {
+    diff_profiles(Some(current), stored, false)
+        .into_iter()
+        .map(|diff| FingerprintFieldChange {
+            stability: field_stability(&diff.field),
+            field: diff.field,
+            stored: diff.proposed,
+            current: diff.current,
+        })
+        .collect()
+}
+
+/// 读取当前设备指纹并与已保存的指纹逐字段比对（只读，不写入任何数据）
+pub fn compare_current_fingerprint(fingerprint_id: &str) -> Result<FingerprintDiff, String> {
+    let fingerprint = get_fingerprint(fingerprint_id)?;
+    let current = read_current_profile()?;
+    let changed_fields = diff_against_current(&fingerprint.profile, &current);
+    let stable_changed = changed_fields
+        .iter()
+        .any(|change| change.stability == FieldStability::Stable);
+    let (stable_fields, volatile_fields): (Vec<_>, Vec<_>) = profile_fields(&current)
+        .into_iter()
+        .map(|(field, _)| field)
+        .partition(|field| field_stability(field) == FieldStability::Stable);
+
+    Ok(FingerprintDiff {
+        fingerprint_id: fingerprint.id,
+        changed_fields,
+        stable_changed,
+        stable_fields: stable_fields.into_iter().map(str::to_string).collect(),
+        volatile_fields: volatile_fields.into_iter().map(str::to_string).collect(),
+    })
+}
+
+/// 试应用时等待编辑器完成启动的时间
+const FINGERPRINT_TEST_WAIT_SECS: u64 = 8;
+
+/// 指纹试应用结果
+#[derive(Debug, Clone, Serialize)]
+pub struct FingerprintTestResult {
+    pub fingerprint_id: String,
+    /// 编辑器启动后读回的设备标识是否与指纹一致
+    pub matched: bool,
+    pub expected: DeviceProfile,
+    pub actual: Option<DeviceProfile>,
+    /// 不一致的字段（telemetry 键名）
+    pub mismatched_fields: Vec<String>,
+    pub error: Option<String>,
+}
+
+fn diff_telemetry_fields(expected: &DeviceProfile, actual: &DeviceProfile) -> Vec<String> {
+    [
+        ("machineId", &expected.machine_id, &actual.machine_id),
+        (
+            "macMachineId",
+            &expected.mac_machine_id,
+            &actual.mac_machine_id,
+        ),
+        (
+            "devDeviceId",
+            &expected.dev_device_id,
+            &actual.dev_device_id,
+        ),
+        ("sqmId", &expected.sqm_id, &actual.sqm_id),
+    ]
+    .into_iter()
+    .filter(|(_, left, right)| left != right)
+    .map(|(key, _, _)| key.to_string())
+    .collect()
+}
+
+fn profile_storage_path(profile_dir: &Path) -> PathBuf {
+    profile_dir
+        .join("User")
+        .join("globalStorage")
+        .join("storage.json")
+}
+
+/// 创建只包含指定设备标识的实例目录
+fn prepare_test_profile(profile_dir: &Path, profile: &DeviceProfile) -> Result<(), String> {
+    let storage_path = profile_storage_path(profile_dir);
+    if let Some(storage_dir) = storage_path.parent() {
+        fs::create_dir_all(storage_dir).map_err(|e| format!("创建临时实例目录失败: {}", e))?;
+    }
+    fs::write(&storage_path, "{\"telemetry\":{}}")
+        .map_err(|e| format!("创建临时 storage.json 失败: {}", e))?;
+    device::write_profile(&storage_path, profile)
+}
+
+/// 读取实例目录中实际生效的设备标识
+pub fn detect_applied_fingerprints(profile_dir: &Path) -> Result<DeviceProfile, String> {
+    device::read_profile(&profile_storage_path(profile_dir))
+}
+
+/// 关闭临时实例并等待使用该目录的进程全部退出，返回是否已确认退出
+fn close_test_instance(temp_dir_str: &str) -> bool {
+    let dirs = [temp_dir_str.to_string()];
+    match process::close_antigravity_instances_with_progress(&dirs, 20, &mut |_, _| {}) {
+        Ok(()) => true,
+        Err(e) => {
+            logger::log_warn(&format!("[FingerprintTest] 关闭临时实例失败: {}", e));
+            false
+        }
+    }
+}
+
+/// 启动临时实例并读回指纹；第二个返回值表示实例进程是否已退出（未退出时不能删除目录）
+fn run_fingerprint_test(
+    temp_dir: &Path,
+    profile: &DeviceProfile,
+) -> (Result<DeviceProfile, String>, bool) {
+    if let Err(e) = prepare_test_profile(temp_dir, profile) {
+        return (Err(e), true);
+    }
+
+    let temp_dir_str = temp_dir.to_string_lossy().to_string();
+    if let Err(e) = process::start_antigravity_with_args(&temp_dir_str, &[], &HashMap::new()) {
+        return (Err(e), true);
+    }
+    std::thread::sleep(std::time::Duration::from_secs(FINGERPRINT_TEST_WAIT_SECS));
+    let actual = detect_applied_fingerprints(temp_dir);
+    let exited = close_test_instance(&temp_dir_str);
+    (actual, exited)
+}
+
+/// 在临时实例中试应用指纹：写入设备标识 → 启动编辑器 → 读回比对 → 关闭并清理
+/// 不影响默认实例与已有实例
+pub fn test_fingerprint(fingerprint_id: &str) -> Result<FingerprintTestResult, String> {
+    let fingerprint = get_fingerprint(fingerprint_id)?;
+    let temp_dir =
+        std::env::temp_dir().join(format!("cockpit-fingerprint-test-{}", Uuid::new_v4()));
+    logger::log_info(&format!(
+        "[FingerprintTest] 开始试应用指纹: {}, dir={}",
+        fingerprint.name,
+        temp_dir.display()
+    ));
+
+    let (outcome, exited) = run_fingerprint_test(&temp_dir, &fingerprint.profile);
+    if !exited {
+        logger::log_warn(&format!(
+            "[FingerprintTest] 临时实例仍在运行，保留目录: {}",
+            temp_dir.display()
+        ));
+    } else if let Err(e) = fs::remove_dir_all(&temp_dir) {
+        logger::log_warn(&format!(
+            "[FingerprintTest] 清理临时实例目录失败: {}, err={}",
+            temp_dir.display(),
+            e
+        ));
+    }
+
+    let (actual, error) = match outcome {
+        Ok(profile) => (Some(profile), None),
+        Err(e) => (None, Some(e)),
+    };
+    let mismatched_fields = actual
+        .as_ref()
+        .map(|profile| diff_telemetry_fields(&fingerprint.profile, profile))
+        .unwrap_or_default();
+    let matched = actual.is_some() && mismatched_fields.is_empty();
+
+    logger::log_info(&format!(
+        "[FingerprintTest] 试应用完成: {}, matched={}, mismatched={:?}, error={:?}",
+        fingerprint.name, matched, mismatched_fields, error
+    ));
+
+    Ok(FingerprintTestResult {
+        fingerprint_id: fingerprint.id,
+        matched,
+        expected: fingerprint.profile,
+        actual,
+        mismatched_fields,
+        error,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn profile(machine_id: &str) -> DeviceProfile {
+        DeviceProfile {
+            machine_id: machine_id.to_string(),
+            mac_machine_id: "mac".to_string(),
+            dev_device_id: "dev".to_string(),
+            sqm_id: "{SQM}".to_string(),
+            service_machine_id: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_diff_profiles_reports_changed_fields() {
+        let diffs = diff_profiles(Some(&profile("a")), &profile("b"), false);
+        assert_eq!(diffs.len(), 1);
+        assert_eq!(diffs[0].field, "machineId");
+        assert_eq!(diffs[0].current.as_deref(), Some("a"));
+        assert_eq!(diffs[0].proposed.as_deref(), Some("b"));
+
+        assert_eq!(
+            diff_profiles(Some(&profile("a")), &profile("b"), true).len(),
+            5
+        );
+        assert_eq!(diff_profiles(None, &profile("b"), false).len(), 4);
+    }
+
+    #[test]
+    fn test_diff_against_current_marks_stability() {
+        let mut current = profile("a");
+        current.service_machine_id = "svc".to_string();
+        let changes = diff_against_current(&profile("b"), &current);
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[0].field, "machineId");
+        assert_eq!(changes[0].stored.as_deref(), Some("b"));
+        assert_eq!(changes[0].current.as_deref(), Some("a"));
+        assert_eq!(changes[0].stability, FieldStability::Stable);
+        assert_eq!(changes[1].field, "serviceMachineId");
+        assert_eq!(changes[1].stored, None);
+        assert_eq!(changes[1].stability, FieldStability::Volatile);
+    }
+
+    #[test]
+    fn test_detect_applied_fingerprints_reads_prepared_profile() {
+        let dir = std::env::temp_dir().join(format!("cockpit-fingerprint-test-{}", Uuid::new_v4()));
+        let expected = profile("machine");
+
+        prepare_test_profile(&dir, &expected).unwrap();
+        let actual = detect_applied_fingerprints(&dir);
+        let _ = fs::remove_dir_all(&dir);
+
+        let actual = actual.unwrap();
+        assert!(diff_telemetry_fields(&expected, &actual).is_empty());
+        assert_eq!(
+            diff_telemetry_fields(&profile("other"), &actual),
+            vec!["machineId".to_string()]
+        );
+    }
+}
@@ -1,7 +1,9 @@
 use crate::models::github_copilot::{
-    GitHubCopilotAccount, GitHubCopilotAccountIndex, GitHubCopilotOAuthCompletePayload,
+    GitHubCopilotAccount, GitHubCopilotAccountIndex, GitHubCopilotAccountSummary,
+    GitHubCopilotOAuthCompletePayload,
 };
-use crate::modules::{account, github_copilot_oauth, logger};
+use crate::modules::github_copilot_oauth::CopilotRefreshError;
+use crate::modules::{account, github_copilot_oauth, logger, vscode_inject};
 use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
@@ -104,7 +106,9 @@ fn refresh_summary(index: &mut GitHubCopilotAccountIndex, account: &GitHubCopilo
     index.accounts.push(account.summary());
 }
 
-fn upsert_account_record(account: GitHubCopilotAccount) -> Result<GitHubCopilotAccount, String> {
+pub fn upsert_account_record(
+    account: GitHubCopilotAccount,
+) -> Result<GitHubCopilotAccount, String> {
     let _lock = GHCP_ACCOUNT_INDEX_LOCK
         .lock()
         .map_err(|_| "获取 GitHub Copilot 账号锁失败".to_string())?;
@@ -124,35 +128,23 @@ pub fn list_accounts() -> Vec<GitHubCopilotAccount> {
         .collect()
 }
 
-pub fn upsert_account(
+/// 合并 OAuth 登录结果：已有账号保留显示名称、标签和创建时间，其余字段以本次登录为准
+fn merge_oauth_payload(
+    existing: Option<GitHubCopilotAccount>,
+    account_id: String,
     payload: GitHubCopilotOAuthCompletePayload,
-) -> Result<GitHubCopilotAccount, String> {
-    let _lock = GHCP_ACCOUNT_INDEX_LOCK
-        .lock()
-        .map_err(|_| "获取 GitHub Copilot 账号锁失败".to_string())?;
-    let now = now_ts();
-    let mut index = load_account_index();
-    let generated_id = format!(
-        "ghcp_{:x}",
-        md5::compute(format!("{}:{}", payload.github_login, payload.github_id))
-    );
-    let account_id = index
-        .accounts
-        .iter()
-        .find(|item| item.github_login == payload.github_login)
-        .map(|item| item.id.clone())
-        .unwrap_or(generated_id);
-
-    let existing = load_account_file(&account_id);
+    now: i64,
+) -> GitHubCopilotAccount {
     let tags = existing.as_ref().and_then(|acc| acc.tags.clone());
     let created_at = existing.as_ref().map(|acc| acc.created_at).unwrap_or(now);
 
     let mut account = existing.unwrap_or(GitHubCopilotAccount {
-        id: account_id.clone(),
+        id: account_id,
         github_login: payload.github_login.clone(),
         github_id: payload.github_id,
         github_name: payload.github_name.clone(),
         github_email: payload.github_email.clone(),
+        label: None,
         tags,
         github_access_token: payload.github_access_token.clone(),
         github_token_type: payload.github_token_type.clone(),
@@ -166,6 +158,7 @@ pub fn upsert_account(
         copilot_quota_reset_date: payload.copilot_quota_reset_date.clone(),
         copilot_limited_user_quotas: payload.copilot_limited_user_quotas.clone(),
         copilot_limited_user_reset_date: payload.copilot_limited_user_reset_date,
+        reauth_required: false,
         created_at,
         last_used: now,
     });
@@ -186,8 +179,33 @@ pub fn upsert_account(
     account.copilot_quota_reset_date = payload.copilot_quota_reset_date;
     account.copilot_limited_user_quotas = payload.copilot_limited_user_quotas;
     account.copilot_limited_user_reset_date = payload.copilot_limited_user_reset_date;
+    account.reauth_required = false;
     account.created_at = created_at;
     account.last_used = now;
+    account
+}
+
+pub fn upsert_account(
+    payload: GitHubCopilotOAuthCompletePayload,
+) -> Result<GitHubCopilotAccount, String> {
+    let _lock = GHCP_ACCOUNT_INDEX_LOCK
+        .lock()
+        .map_err(|_| "获取 GitHub Copilot 账号锁失败".to_string())?;
+    let now = now_ts();
+    let mut index = load_account_index();
+    let generated_id = format!(
+        "ghcp_{:x}",
+        md5::compute(format!("{}:{}", payload.github_login, payload.github_id))
+    );
+    let account_id = index
+        .accounts
+        .iter()
+        .find(|item| item.github_login == payload.github_login)
+        .map(|item| item.id.clone())
+        .unwrap_or(generated_id);
+
+    let existing = load_account_file(&account_id);
+    let account = merge_oauth_payload(existing, account_id, payload, now);
 
     save_account_file(&account)?;
     refresh_summary(&mut index, &account);
@@ -200,9 +218,30 @@ pub fn upsert_account(
     Ok(account)
 }
 
-pub async fn refresh_account_token(account_id: &str) -> Result<GitHubCopilotAccount, String> {
-    let mut account = load_account_file(account_id).ok_or_else(|| "账号不存在".to_string())?;
-    let bundle = github_copilot_oauth::refresh_copilot_token(&account.github_access_token).await?;
+/// 刷新账号的 Copilot token；GitHub 授权失效时标记账号需要重新登录
+pub async fn refresh_account_token(
+    account_id: &str,
+) -> Result<GitHubCopilotAccount, CopilotRefreshError> {
+    let mut account = load_account_file(account_id)
+        .ok_or_else(|| CopilotRefreshError::Transient("账号不存在".to_string()))?;
+    let bundle =
+        match github_copilot_oauth::refresh_copilot_token(&account.github_access_token).await {
+            Ok(bundle) => bundle,
+            Err(CopilotRefreshError::Unauthorized) => {
+                logger::log_warn(&format!(
+                    "GitHub Copilot 授权已失效，需要重新登录: id={}, login={}",
+                    account.id, account.github_login
+                ));
+                if !account.reauth_required {
+                    account.reauth_required = true;
+                    if let Err(e) = upsert_account_record(account) {
+                        logger::log_warn(&format!("标记 GitHub Copilot 账号失效失败: {}", e));
+                    }
+                }
+                return Err(CopilotRefreshError::Unauthorized);
+            }
+            Err(err) => return Err(err),
+        };
 
     account.copilot_token = bundle.token;
     account.copilot_plan = bundle.plan;
@@ -213,15 +252,17 @@ pub async fn refresh_account_token(account_id: &str) -> Result<GitHubCopilotAcco
     account.copilot_quota_reset_date = bundle.quota_reset_date;
     account.copilot_limited_user_quotas = bundle.limited_user_quotas;
     account.copilot_limited_user_reset_date = bundle.limited_user_reset_date;
+    account.reauth_required = false;
     account.last_used = now_ts();
 
     let updated = account.clone();
-    upsert_account_record(account)?;
+    upsert_account_record(account).map_err(CopilotRefreshError::Transient)?;
     Ok(updated)
 }
 
+/// 刷新全部账号，单个账号失败（包括授权失效）不影响其他账号
 pub async fn refresh_all_tokens(
-) -> Result<Vec<(String, Result<GitHubCopilotAccount, String>)>, String> {
+) -> Result<Vec<(String, Result<GitHubCopilotAccount, CopilotRefreshError>)>, String> {
     use futures::future::join_all;
     use std::sync::Arc;
     use tokio::sync::Semaphore;
@@ -240,7 +281,7 @@ pub async fn refresh_all_tokens(
                     .await
                     .map_err(|e| format!("获取 GitHub Copilot 刷新并发许可失败: {}", e))?;
                 let res = refresh_account_token(&id).await;
-                Ok::<(String, Result<GitHubCopilotAccount, String>), String>((id, res))
+                Ok::<(String, Result<GitHubCopilotAccount, CopilotRefreshError>), String>((id, res))
             }
         })
         .collect();
@@ -286,6 +327,34 @@ pub fn update_account_tags(
     Ok(updated)
 }
 
+/// 设置账号显示名称；传入空字符串时清除
+pub fn set_account_label(account_id: &str, label: &str) -> Result<GitHubCopilotAccount, String> {
+    let label = crate::modules::account::normalize_account_label(label)?;
+    let mut account = load_account_file(account_id).ok_or_else(|| "账号不存在".to_string())?;
+    account.label = label;
+    upsert_account_record(account)
+}
+
+/// 从 VS Code 用户数据目录导入 Copilot 登录的 GitHub 账号（按 login 去重，已存在时更新）
+pub async fn import_from_vscode(
+    user_data_dir: &str,
+) -> Result<GitHubCopilotAccountSummary, String> {
+    let session = vscode_inject::read_copilot_session_for_user_data_dir(user_data_dir)?;
+    let existed = load_account_index()
+        .accounts
+        .iter()
+        .any(|item| item.github_login.eq_ignore_ascii_case(&session.login));
+
+    let payload =
+        github_copilot_oauth::build_payload_from_github_access_token(&session.access_token).await?;
+    let account = upsert_account(payload)?;
+    logger::log_info(&format!(
+        "从 VS Code 导入 GitHub Copilot 账号: login={}, existed={}",
+        account.github_login, existed
+    ));
+    Ok(account.summary())
+}
+
 pub fn import_from_json(json_content: &str) -> Result<Vec<GitHubCopilotAccount>, String> {
     if let Ok(account) = serde_json::from_str::<GitHubCopilotAccount>(json_content) {
         let saved = upsert_account_record(account)?;
@@ -410,7 +479,7 @@ fn extract_premium_metric(account: &GitHubCopilotAccount) -> Option<(String, i32
     Some(("Premium Interactions".to_string(), percent_remaining))
 }
 
-fn extract_quota_metrics(account: &GitHubCopilotAccount) -> Vec<(String, i32)> {
+pub fn extract_quota_metrics(account: &GitHubCopilotAccount) -> Vec<(String, i32)> {
     let mut metrics = extract_limited_metrics(account);
     if let Some(premium) = extract_premium_metric(account) {
         metrics.push(premium);
@@ -426,7 +495,8 @@ fn average_quota_percentage(metrics: &[(String, i32)]) -> f64 {
     sum as f64 / metrics.len() as f64
 }
 
-fn resolve_current_account_id(accounts: &[GitHubCopilotAccount]) -> Option<String> {
+/// 解析当前账号：优先默认实例绑定的账号，否则取最近使用的账号
+pub fn resolve_current_account_id(accounts: &[GitHubCopilotAccount]) -> Option<String> {
     if let Ok(settings) = crate::modules::github_copilot_instance::load_default_settings() {
         if let Some(bind_id) = settings.bind_account_id {
             let trimmed = bind_id.trim();
@@ -555,3 +625,46 @@ pub fn run_quota_alert_if_needed(
     crate::modules::account::dispatch_quota_alert(&payload);
     Ok(Some(payload))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn payload(access_token: &str) -> GitHubCopilotOAuthCompletePayload {
+        GitHubCopilotOAuthCompletePayload {
+            github_login: "octocat".to_string(),
+            github_id: 1,
+            github_name: None,
+            github_email: None,
+            github_access_token: access_token.to_string(),
+            github_token_type: None,
+            github_scope: None,
+            copilot_token: "copilot".to_string(),
+            copilot_plan: None,
+            copilot_chat_enabled: None,
+            copilot_expires_at: None,
+            copilot_refresh_in: None,
+            copilot_quota_snapshots: None,
+            copilot_quota_reset_date: None,
+            copilot_limited_user_quotas: None,
+            copilot_limited_user_reset_date: None,
+        }
+    }
+
+    #[test]
+    fn test_relogin_keeps_label() {
+        let mut existing = merge_oauth_payload(None, "ghcp_1".to_string(), payload("old"), 100);
+        assert!(existing.label.is_none());
+        existing.label = Some("Work".to_string());
+        existing.reauth_required = true;
+
+        let account =
+            merge_oauth_payload(Some(existing), "ghcp_1".to_string(), payload("new"), 200);
+
+        assert_eq!(account.label.as_deref(), Some("Work"));
+        assert_eq!(account.github_access_token, "new");
+        assert_eq!(account.created_at, 100);
+        assert_eq!(account.last_used, 200);
+        assert!(!account.reauth_required);
+    }
+}
@@ -128,6 +128,11 @@ pub fn create_instance(params: CreateInstanceParams) -> Result<InstanceProfile,
     let _lock = GHCP_INSTANCE_STORE_LOCK
         .lock()
         .map_err(|_| "无法获取实例锁")?;
+    let env_vars = instance_store::normalize_env_vars(params.env_vars.clone())?;
+    instance_store::reject_fingerprint_binding(
+        "GitHub Copilot",
+        params.bind_fingerprint_id.as_deref(),
+    )?;
     let mut store = load_instance_store()?;
 
     let name = instance_store::normalize_name(&params.name)?;
@@ -139,6 +144,7 @@ pub fn create_instance(params: CreateInstanceParams) -> Result<InstanceProfile,
     instance_store::ensure_unique(&store, &name, &user_data_dir, None)?;
 
     let user_dir_path = PathBuf::from(&user_data_dir);
+    instance_store::check_instance_dir_length(&user_dir_path)?;
     let init_mode = params
         .init_mode
         .as_deref()
@@ -194,7 +200,11 @@ pub fn create_instance(params: CreateInstanceParams) -> Result<InstanceProfile,
             return Err("未找到复制来源目录，请先确保来源实例已初始化".to_string());
         }
 
-        instance_store::copy_dir_recursive(&source_dir, &user_dir_path)?;
+        if init_mode == instance_store::INIT_MODE_COPY_SETTINGS {
+            instance_store::copy_user_settings(&source_dir, &user_dir_path)?;
+        } else {
+            instance_store::copy_dir_recursive(&source_dir, &user_dir_path)?;
+        }
     }
 
     let instance = InstanceProfile {
@@ -210,6 +220,12 @@ pub fn create_instance(params: CreateInstanceParams) -> Result<InstanceProfile,
         created_at: Utc::now().timestamp_millis(),
         last_launched_at: None,
         last_pid: None,
+        minimize_after_launch: false,
+        low_power: false,
+        launch_args_template: params.launch_args_template,
+        env_vars,
+        bind_fingerprint_id: None,
+        follow_local_account: false,
     };
 
     store.instances.push(instance.clone());
@@ -221,6 +237,10 @@ pub fn update_instance(params: UpdateInstanceParams) -> Result<InstanceProfile,
     let _lock = GHCP_INSTANCE_STORE_LOCK
         .lock()
         .map_err(|_| "无法获取实例锁")?;
+    instance_store::reject_fingerprint_binding(
+        "GitHub Copilot",
+        params.bind_fingerprint_id.clone().flatten().as_deref(),
+    )?;
     let mut store = load_instance_store()?;
     let index = store
         .instances
@@ -247,9 +267,18 @@ pub fn update_instance(params: UpdateInstanceParams) -> Result<InstanceProfile,
     if let Some(ref extra_args) = params.extra_args {
         instance.extra_args = extra_args.trim().to_string();
     }
+    if let Some(template) = params.launch_args_template.clone() {
+        instance.launch_args_template = template;
+    }
+    if let Some(env_vars) = params.env_vars.clone() {
+        instance.env_vars = instance_store::normalize_env_vars(env_vars)?;
+    }
     if let Some(bind) = params.bind_account_id.clone() {
         instance.bind_account_id = bind;
     }
+    if let Some(minimize) = params.minimize_after_launch {
+        instance.minimize_after_launch = minimize;
+    }
 
     let updated = instance.clone();
     save_instance_store(&store)?;
@@ -86,6 +86,18 @@ struct CopilotUserInfoResponse {
     quota_reset_date: Option<String>,
 }
 
+/// Copilot token 刷新错误
+/// 区分需要重新登录的授权失效与可稍后重试的失败
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum CopilotRefreshError {
+    /// GitHub access token 已被撤销或过期（401），前端依据 `COPILOT_REAUTH_REQUIRED:` 前缀提示重新登录
+    #[error("COPILOT_REAUTH_REQUIRED:GitHub 授权已失效，请重新登录")]
+    Unauthorized,
+    /// 网络失败、服务端错误等
+    #[error("{0}")]
+    Transient(String),
+}
+
 #[derive(Debug, Clone)]
 pub struct CopilotTokenBundle {
     pub token: String,
@@ -160,7 +172,7 @@ fn to_start_response(state: &PendingDeviceLogin) -> GitHubCopilotOAuthStartRespo
 }
 
 async fn request_device_code() -> Result<DeviceCodeResponse, String> {
-    let client = reqwest::Client::new();
+    let client = crate::modules::http::default_client();
     let response = client
         .post(GITHUB_DEVICE_CODE_ENDPOINT)
         .header(USER_AGENT, APP_USER_AGENT)
@@ -334,41 +346,55 @@ async fn fetch_github_email(
         .map(|item| item.email.clone()))
 }
 
-async fn fetch_copilot_token(
+async fn request_copilot_token(
     client: &reqwest::Client,
+    endpoint: &str,
     github_access_token: &str,
-) -> Result<CopilotTokenBundle, String> {
+) -> Result<CopilotTokenResponse, CopilotRefreshError> {
     let response = client
-        .get(GITHUB_COPILOT_TOKEN_ENDPOINT)
+        .get(endpoint)
         .header(USER_AGENT, APP_USER_AGENT)
         .header(ACCEPT, "application/json")
         .header("X-GitHub-Api-Version", "2025-04-01")
         .header(AUTHORIZATION, format!("token {}", github_access_token))
         .send()
         .await
-        .map_err(|e| format!("请求 Copilot token 失败: {}", e))?;
+        .map_err(|e| CopilotRefreshError::Transient(format!("请求 Copilot token 失败: {}", e)))?;
 
-    if !response.status().is_success() {
-        let status = response.status();
+    let status = response.status();
+    if status == reqwest::StatusCode::UNAUTHORIZED {
+        return Err(CopilotRefreshError::Unauthorized);
+    }
+    if !status.is_success() {
         let body = response
             .text()
             .await
             .unwrap_or_else(|_| "<no-body>".to_string());
-        return Err(format!(
+        return Err(CopilotRefreshError::Transient(format!(
             "请求 Copilot token 失败: status={}, body={}",
             status, body
-        ));
+        )));
     }
 
-    let payload = response
+    response
         .json::<CopilotTokenResponse>()
         .await
-        .map_err(|e| format!("解析 Copilot token 响应失败: {}", e))?;
+        .map_err(|e| CopilotRefreshError::Transient(format!("解析 Copilot token 响应失败: {}", e)))
+}
+
+async fn fetch_copilot_token(
+    client: &reqwest::Client,
+    github_access_token: &str,
+) -> Result<CopilotTokenBundle, CopilotRefreshError> {
+    let payload =
+        request_copilot_token(client, GITHUB_COPILOT_TOKEN_ENDPOINT, github_access_token).await?;
 
     let token = payload.token.ok_or_else(|| {
-        payload
-            .message
-            .unwrap_or_else(|| "Copilot token 缺失".to_string())
+        CopilotRefreshError::Transient(
+            payload
+                .message
+                .unwrap_or_else(|| "Copilot token 缺失".to_string()),
+        )
     })?;
 
     let user_info = fetch_copilot_user_info(client, github_access_token)
@@ -429,8 +455,8 @@ async fn fetch_copilot_user_info(
 
 pub async fn refresh_copilot_token(
     github_access_token: &str,
-) -> Result<CopilotTokenBundle, String> {
-    let client = reqwest::Client::new();
+) -> Result<CopilotTokenBundle, CopilotRefreshError> {
+    let client = crate::modules::http::default_client();
     fetch_copilot_token(&client, github_access_token).await
 }
 
@@ -446,7 +472,7 @@ pub async fn complete_login(login_id: &str) -> Result<GitHubCopilotOAuthComplete
         pending.login_id
     ));
 
-    let client = reqwest::Client::new();
+    let client = crate::modules::http::default_client();
     let mut interval_seconds = pending.interval_seconds.max(1);
 
     let token_result = loop {
@@ -504,7 +530,9 @@ pub async fn complete_login(login_id: &str) -> Result<GitHubCopilotOAuthComplete
         fetch_github_email(&client, &github_access_token).await?
     };
     let _ = get_pending_login_for(login_id)?;
-    let copilot = fetch_copilot_token(&client, &github_access_token).await?;
+    let copilot = fetch_copilot_token(&client, &github_access_token)
+        .await
+        .map_err(|e| e.to_string())?;
 
     clear_pending_login_if_matches(login_id);
 
@@ -547,17 +575,35 @@ pub fn cancel_login(login_id: Option<&str>) -> Result<(), String> {
     }
 }
 
+/// 清理已过期的设备码登录会话，返回是否有会话被清理
+pub fn clear_stale_login() -> bool {
+    let Some(state) = get_pending_login() else {
+        return false;
+    };
+    if state.expires_at > now_timestamp() {
+        return false;
+    }
+    clear_pending_login_if_matches(&state.login_id);
+    logger::log_info(&format!(
+        "[GitHub Copilot OAuth] 已清理过期的登录会话: login_id={}",
+        state.login_id
+    ));
+    true
+}
+
 pub async fn build_payload_from_github_access_token(
     github_access_token: &str,
 ) -> Result<GitHubCopilotOAuthCompletePayload, String> {
-    let client = reqwest::Client::new();
+    let client = crate::modules::http::default_client();
     let github_user = fetch_github_user(&client, github_access_token).await?;
     let github_email = if github_user.email.is_some() {
         github_user.email.clone()
     } else {
         fetch_github_email(&client, github_access_token).await?
     };
-    let copilot = fetch_copilot_token(&client, github_access_token).await?;
+    let copilot = fetch_copilot_token(&client, github_access_token)
+        .await
+        .map_err(|e| e.to_string())?;
 
     Ok(GitHubCopilotOAuthCompletePayload {
         github_login: github_user.login,
@@ -578,3 +624,52 @@ pub async fn build_payload_from_github_access_token(
         copilot_limited_user_reset_date: copilot.limited_user_reset_date,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+
+    /// 只响应一次固定状态码的本地 token 接口
+    fn spawn_token_endpoint(status_line: &'static str, body: &'static str) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            if let Ok((mut stream, _)) = listener.accept() {
+                let mut buf = [0u8; 2048];
+                let _ = stream.read(&mut buf);
+                let response = format!(
+                    "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status_line,
+                    body.len(),
+                    body
+                );
+                let _ = stream.write_all(response.as_bytes());
+            }
+        });
+        format!("http://{}/copilot_internal/v2/token", addr)
+    }
+
+    #[tokio::test]
+    async fn test_unauthorized_requires_reauth() {
+        let endpoint = spawn_token_endpoint("401 Unauthorized", r#"{"message":"Bad credentials"}"#);
+        let err = request_copilot_token(&reqwest::Client::new(), &endpoint, "revoked")
+            .await
+            .err()
+            .expect("401 应当返回错误");
+        assert_eq!(err, CopilotRefreshError::Unauthorized);
+        assert!(err.to_string().starts_with("COPILOT_REAUTH_REQUIRED:"));
+    }
+
+    #[tokio::test]
+    async fn test_server_error_is_transient() {
+        let endpoint = spawn_token_endpoint("500 Internal Server Error", "{}");
+        let err = request_copilot_token(&reqwest::Client::new(), &endpoint, "token")
+            .await
+            .err()
+            .expect("500 应当返回错误");
+        assert!(matches!(err, CopilotRefreshError::Transient(_)));
+        assert!(!err.to_string().starts_with("COPILOT_REAUTH_REQUIRED:"));
+    }
+}
@@ -228,7 +228,7 @@ fn migrate_legacy_group_names(settings: &mut GroupSettings) {
 }
 
 /// 获取分组配置文件路径
-fn get_group_settings_path() -> PathBuf {
+pub fn get_group_settings_path() -> PathBuf {
     get_shared_dir().join(GROUP_SETTINGS_FILE)
 }
 
@@ -0,0 +1,264 @@
+//! 出站 HTTP 客户端
+//! 按网络设置中的代理配置统一创建 reqwest 客户端：跟随系统代理（环境变量）、直连或自定义代理；
+//! 每次创建时读取配置，修改代理后无需重启
+
+use std::time::Duration;
+
+use reqwest::{Client, ClientBuilder, NoProxy, Proxy};
+
+use crate::modules::config::{
+    self, ProxyConfig, PROXY_MODE_CUSTOM, PROXY_MODE_DIRECT, PROXY_MODE_SYSTEM,
+};
+use crate::modules::logger;
+
+/// 返回给前端的代理密码占位符；保存时收到该值表示沿用已保存的密码
+pub const MASKED_PROXY_PASSWORD: &str = "********";
+
+/// 校验并规范化代理设置（去除首尾空白，模式转小写）
+pub fn normalize_proxy_config(proxy: ProxyConfig) -> Result<ProxyConfig, String> {
+    let mode = proxy.mode.trim().to_lowercase();
+    let mode = if mode.is_empty() {
+        PROXY_MODE_SYSTEM.to_string()
+    } else {
+        mode
+    };
+    if ![PROXY_MODE_SYSTEM, PROXY_MODE_DIRECT, PROXY_MODE_CUSTOM].contains(&mode.as_str()) {
+        return Err(format!(
+            "不支持的代理模式: {}（可选: {}, {}, {}）",
+            mode, PROXY_MODE_SYSTEM, PROXY_MODE_DIRECT, PROXY_MODE_CUSTOM
+        ));
+    }
+
+    let normalized = ProxyConfig {
+        mode,
+        url: proxy.url.trim().to_string(),
+        username: proxy.username.trim().to_string(),
+        password: proxy.password,
+        no_proxy: proxy.no_proxy.trim().to_string(),
+    };
+    if normalized.mode == PROXY_MODE_CUSTOM {
+        if normalized.url.is_empty() {
+            return Err("自定义代理地址不能为空".to_string());
+        }
+        let parsed = reqwest::Url::parse(&normalized.url)
+            .map_err(|e| format!("代理地址无效: {} ({})", normalized.url, e))?;
+        if !matches!(parsed.scheme(), "http" | "https") {
+            return Err(format!("代理地址仅支持 http / https: {}", normalized.url));
+        }
+    }
+    Ok(normalized)
+}
+
+/// 隐藏代理密码（已设置密码时替换为占位符），用于返回给前端
+pub fn mask_proxy_password(proxy: ProxyConfig) -> ProxyConfig {
+    if proxy.password.is_empty() {
+        return proxy;
+    }
+    ProxyConfig {
+        password: MASKED_PROXY_PASSWORD.to_string(),
+        ..proxy
+    }
+}
+
+/// 保存时还原前端回传的密码占位符
+pub fn unmask_proxy_password(proxy: ProxyConfig, current: &ProxyConfig) -> ProxyConfig {
+    if proxy.password != MASKED_PROXY_PASSWORD {
+        return proxy;
+    }
+    ProxyConfig {
+        password: current.password.clone(),
+        ..proxy
+    }
+}
+
+fn build_custom_proxy(proxy: &ProxyConfig) -> Result<Proxy, String> {
+    let mut custom = Proxy::all(&proxy.url).map_err(|e| format!("代理地址无效: {}", e))?;
+    if !proxy.username.is_empty() {
+        custom = custom.basic_auth(&proxy.username, &proxy.password);
+    }
+    Ok(custom.no_proxy(NoProxy::from_string(&proxy.no_proxy)))
+}
+
+/// 按指定代理设置创建 ClientBuilder；代理设置无效时回退为跟随系统代理
+pub fn client_builder_with_proxy(proxy: &ProxyConfig) -> ClientBuilder {
+    let builder = Client::builder();
+    match proxy.mode.as_str() {
+        PROXY_MODE_DIRECT => builder.no_proxy(),
+        PROXY_MODE_CUSTOM => match build_custom_proxy(proxy) {
+            Ok(custom) => builder.proxy(custom),
+            Err(err) => {
+                logger::log_warn(&format!("[HTTP] 自定义代理不可用，回退为系统代理: {}", err));
+                builder
+            }
+        },
+        // reqwest 默认读取 HTTP_PROXY / HTTPS_PROXY / NO_PROXY 环境变量
+        _ => builder,
+    }
+}
+
+/// 按当前网络设置创建 ClientBuilder（供需要额外定制的调用方使用）
+pub fn client_builder() -> ClientBuilder {
+    client_builder_with_proxy(&config::get_proxy_config())
+}
+
+fn build_or_default(builder: ClientBuilder) -> Client {
+    builder.build().unwrap_or_else(|e| {
+        logger::log_warn(&format!(
+            "[HTTP] 创建 HTTP 客户端失败，使用默认客户端: {}",
+            e
+        ));
+        Client::new()
+    })
+}
+
+/// 创建不限制超时的 HTTP 客户端
+pub fn default_client() -> Client {
+    build_or_default(client_builder())
+}
+
+/// 创建统一配置的 HTTP 客户端
+pub fn create_client(timeout_secs: u64) -> Client {
+    create_client_with_timeout(Duration::from_secs(timeout_secs))
+}
+
+/// 创建指定请求超时的 HTTP 客户端
+pub fn create_client_with_timeout(timeout: Duration) -> Client {
+    build_or_default(client_builder().timeout(timeout))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    fn custom_proxy(url: &str) -> ProxyConfig {
+        ProxyConfig {
+            mode: PROXY_MODE_CUSTOM.to_string(),
+            url: url.to_string(),
+            ..ProxyConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_normalize_proxy_config() {
+        let normalized = normalize_proxy_config(ProxyConfig {
+            mode: " Custom ".to_string(),
+            url: " http://127.0.0.1:7890 ".to_string(),
+            ..ProxyConfig::default()
+        })
+        .unwrap();
+        assert_eq!(normalized.mode, PROXY_MODE_CUSTOM);
+        assert_eq!(normalized.url, "http://127.0.0.1:7890");
+
+        assert!(normalize_proxy_config(custom_proxy("")).is_err());
+        assert!(normalize_proxy_config(custom_proxy("socks5://127.0.0.1:1080")).is_err());
+        assert!(normalize_proxy_config(ProxyConfig {
+            mode: "auto".to_string(),
+            ..ProxyConfig::default()
+        })
+        .is_err());
+        // 非 custom 模式不校验地址
+        assert!(normalize_proxy_config(ProxyConfig::default()).is_ok());
+    }
+
+    #[test]
+    fn test_proxy_password_is_masked_and_restored() {
+        let saved = ProxyConfig {
+            username: "user".to_string(),
+            password: "secret".to_string(),
+            ..custom_proxy("http://127.0.0.1:7890")
+        };
+        let masked = mask_proxy_password(saved.clone());
+        assert_eq!(masked.password, MASKED_PROXY_PASSWORD);
+        assert_eq!(unmask_proxy_password(masked, &saved).password, "secret");
+
+        let changed = ProxyConfig {
+            password: "new-secret".to_string(),
+            ..saved.clone()
+        };
+        assert_eq!(
+            unmask_proxy_password(changed, &saved).password,
+            "new-secret"
+        );
+        assert!(mask_proxy_password(ProxyConfig::default())
+            .password
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_no_proxy_hosts_bypass_custom_proxy() {
+        // 代理指向已关闭的端口：若请求走了代理就会失败
+        let closed = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let proxy_addr = closed.local_addr().unwrap();
+        drop(closed);
+
+        let upstream = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let upstream_addr = upstream.local_addr().unwrap();
+        let server = tokio::spawn(async move {
+            let (mut socket, _) = upstream.accept().await.unwrap();
+            let mut buf = vec![0u8; 4096];
+            let n = socket.read(&mut buf).await.unwrap();
+            let _ = socket
+                .write_all(b"HTTP/1.1 204 No Content\r\nContent-Length: 0\r\n\r\n")
+                .await;
+            String::from_utf8_lossy(&buf[..n]).to_string()
+        });
+
+        let proxy = ProxyConfig {
+            no_proxy: "localhost, 127.0.0.1".to_string(),
+            ..custom_proxy(&format!("http://{}", proxy_addr))
+        };
+        let client = client_builder_with_proxy(&proxy)
+            .timeout(Duration::from_secs(5))
+            .build()
+            .unwrap();
+        let response = client
+            .get(format!("http://{}/ping", upstream_addr))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::NO_CONTENT);
+
+        // 直连时请求行是相对路径，而不是代理使用的绝对 URL
+        let request = server.await.unwrap();
+        assert!(request.starts_with("GET /ping HTTP/1.1"));
+    }
+
+    #[tokio::test]
+    async fn test_custom_proxy_receives_request_with_auth() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = vec![0u8; 4096];
+            let n = socket.read(&mut buf).await.unwrap();
+            let _ = socket
+                .write_all(b"HTTP/1.1 204 No Content\r\nContent-Length: 0\r\n\r\n")
+                .await;
+            String::from_utf8_lossy(&buf[..n]).to_string()
+        });
+
+        let proxy = ProxyConfig {
+            username: "user".to_string(),
+            password: "pass".to_string(),
+            ..custom_proxy(&format!("http://{}", addr))
+        };
+        let client = client_builder_with_proxy(&proxy)
+            .timeout(Duration::from_secs(5))
+            .build()
+            .unwrap();
+        let response = client
+            .get("http://upstream.invalid/ping")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::NO_CONTENT);
+
+        let request = server.await.unwrap();
+        assert!(request.starts_with("GET http://upstream.invalid/ping HTTP/1.1"));
+        // user:pass 的 Base64
+        assert!(request
+            .to_lowercase()
+            .contains("proxy-authorization: basic dxnlcjpwyxnz"));
+    }
+}
@@ -1,9 +1,10 @@
 use crate::models;
 use crate::modules;
 use crate::utils;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
+use std::path::{Path, PathBuf};
 use uuid::Uuid;
 
 // ==================== 辅助结构体和函数 ====================
@@ -21,6 +22,7 @@ pub struct OldToolAccount {
 
 #[derive(Debug, Deserialize)]
 pub struct FingerprintJsonInput {
+    pub id: Option<String>,
     pub name: Option<String>,
     pub label: Option<String>,
     pub created_at: Option<i64>,
@@ -225,21 +227,165 @@ pub fn resolve_json_import_name(
 
 // ==================== 导入命令逻辑 ====================
 
-/// 从旧版 ~/.antigravity_tools/ 导入账号
-pub async fn import_from_old_tools_logic() -> Result<Vec<models::Account>, String> {
-    use std::fs;
+/// 旧版导入与现有账号的冲突
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportCollision {
+    /// 旧版账号 ID
+    pub old_id: String,
+    pub email: String,
+    /// 冲突的现有账号 ID
+    pub existing_account_id: String,
+    /// 冲突原因：email / id
+    pub reason: String,
+}
 
+/// 旧版导入预览（不修改任何数据）
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportPreview {
+    /// 可导入的账号总数
+    pub accounts: usize,
+    /// 与现有账号冲突的条目（按邮箱或 ID 匹配）
+    pub collisions: Vec<ImportCollision>,
+    /// 将新增的指纹数量（与现有指纹重复的不计入）
+    pub fingerprints: usize,
+    /// 旧版账号携带的设备配置数量（当前配置 + 历史版本）
+    pub device_profiles: usize,
+}
+
+fn old_tools_dir() -> Result<PathBuf, String> {
     let home = dirs::home_dir().ok_or("无法获取用户主目录")?;
     let old_dir = home.join(".antigravity_tools");
 
     if !old_dir.exists() {
         return Err("未找到旧版数据目录 ~/.antigravity_tools/".to_string());
     }
+    if !old_dir.join("accounts").exists() {
+        return Err("未找到旧版账号目录 ~/.antigravity_tools/accounts/".to_string());
+    }
+    Ok(old_dir)
+}
+
+/// 读取旧版索引中的账号，返回 (旧版账号 ID, 账号)；单个账号文件读取失败时记录日志并跳过
+fn load_old_tool_accounts(old_dir: &Path) -> Result<Vec<(String, OldToolAccount)>, String> {
+    let old_index_path = old_dir.join("accounts.json");
+    if !old_index_path.exists() {
+        return Ok(Vec::new());
+    }
+    let content =
+        fs::read_to_string(&old_index_path).map_err(|e| format!("读取旧版索引失败: {}", e))?;
+    let old_index: models::AccountIndex =
+        serde_json::from_str(&content).map_err(|e| format!("解析旧版索引失败: {}", e))?;
 
     let old_accounts_dir = old_dir.join("accounts");
-    if !old_accounts_dir.exists() {
-        return Err("未找到旧版账号目录 ~/.antigravity_tools/accounts/".to_string());
+    let mut accounts = Vec::new();
+    for summary in old_index.accounts {
+        let old_account_path = old_accounts_dir.join(format!("{}.json", summary.id));
+        if !old_account_path.exists() {
+            continue;
+        }
+        let account_content = match fs::read_to_string(&old_account_path) {
+            Ok(content) => content,
+            Err(e) => {
+                modules::logger::log_error(&format!(
+                    "读取账号文件失败 {:?}: {}",
+                    old_account_path, e
+                ));
+                continue;
+            }
+        };
+        match serde_json::from_str::<OldToolAccount>(&account_content) {
+            Ok(old_account) => accounts.push((summary.id, old_account)),
+            Err(e) => modules::logger::log_error(&format!(
+                "解析账号文件失败 {:?}: {}",
+                old_account_path, e
+            )),
+        }
+    }
+    Ok(accounts)
+}
+
+/// 查找旧版账号与现有账号的冲突（先按邮箱，再按 ID）
+fn find_import_collision(
+    old_id: &str,
+    old_account: &OldToolAccount,
+    existing: &[models::Account],
+) -> Option<ImportCollision> {
+    let collision = |account: &models::Account, reason: &str| ImportCollision {
+        old_id: old_id.to_string(),
+        email: old_account.email.clone(),
+        existing_account_id: account.id.clone(),
+        reason: reason.to_string(),
+    };
+    existing
+        .iter()
+        .find(|account| account.email == old_account.email)
+        .map(|account| collision(account, "email"))
+        .or_else(|| {
+            existing
+                .iter()
+                .find(|account| account.id == old_id)
+                .map(|account| collision(account, "id"))
+        })
+}
+
+fn build_import_preview(
+    old_accounts: &[(String, OldToolAccount)],
+    existing: &[models::Account],
+    fingerprint_store: &modules::fingerprint::FingerprintStore,
+) -> ImportPreview {
+    // 在副本上模拟指纹去重，不写回磁盘
+    let mut store = fingerprint_store.clone();
+    let mut fingerprint_map = build_fingerprint_profile_map(&store);
+    let mut preview = ImportPreview {
+        accounts: old_accounts.len(),
+        ..ImportPreview::default()
+    };
+
+    for (old_id, old_account) in old_accounts {
+        preview.device_profiles +=
+            old_account.device_history.len() + usize::from(old_account.device_profile.is_some());
+        if let Some(collision) = find_import_collision(old_id, old_account, existing) {
+            preview.collisions.push(collision);
+        }
+        if let Some((profile, label, created_at)) = select_account_profile(old_account) {
+            let base = old_account.name.as_deref().unwrap_or(&old_account.email);
+            let name = format_import_name(base, label.as_deref(), created_at);
+            let (_, inserted) = upsert_fingerprint_in_store(
+                &mut store,
+                profile,
+                name,
+                created_at,
+                &mut fingerprint_map,
+            );
+            if inserted {
+                preview.fingerprints += 1;
+            }
+        }
     }
+    preview
+}
+
+/// 预览从旧版 ~/.antigravity_tools/ 导入的结果（不修改任何数据）
+pub fn preview_import_from_old_tools_logic() -> Result<ImportPreview, String> {
+    let old_dir = old_tools_dir()?;
+    let old_accounts = load_old_tool_accounts(&old_dir)?;
+    let existing = modules::list_accounts()?;
+    let fingerprint_store = modules::fingerprint::load_fingerprint_store()?;
+    Ok(build_import_preview(
+        &old_accounts,
+        &existing,
+        &fingerprint_store,
+    ))
+}
+
+/// 从旧版 ~/.antigravity_tools/ 导入账号
+/// skip_conflicts 为 true 时跳过与现有账号冲突的条目（与预览结果一致）
+pub async fn import_from_old_tools_logic(
+    skip_conflicts: bool,
+) -> Result<Vec<models::Account>, String> {
+    let old_dir = old_tools_dir()?;
 
     modules::logger::log_info("开始从旧版目录导入账号...");
 
@@ -247,91 +393,57 @@ pub async fn import_from_old_tools_logic() -> Result<Vec<models::Account>, Strin
     let mut fingerprint_store = modules::fingerprint::load_fingerprint_store()?;
     let mut fingerprint_map = build_fingerprint_profile_map(&fingerprint_store);
     let mut fingerprint_dirty = false;
+    let existing = if skip_conflicts {
+        modules::list_accounts()?
+    } else {
+        Vec::new()
+    };
 
-    // 读取旧版索引
-    let old_index_path = old_dir.join("accounts.json");
-    if old_index_path.exists() {
-        let content =
-            fs::read_to_string(&old_index_path).map_err(|e| format!("读取旧版索引失败: {}", e))?;
+    for (old_id, old_account) in load_old_tool_accounts(&old_dir)? {
+        if let Some(collision) = find_import_collision(&old_id, &old_account, &existing) {
+            modules::logger::log_info(&format!(
+                "跳过冲突账号: {} (reason={}, existing={})",
+                old_account.email, collision.reason, collision.existing_account_id
+            ));
+            continue;
+        }
 
-        let old_index: models::AccountIndex =
-            serde_json::from_str(&content).map_err(|e| format!("解析旧版索引失败: {}", e))?;
+        // 使用 upsert 导入（避免重复）
+        let mut new_account = match modules::upsert_account(
+            old_account.email.clone(),
+            old_account.name.clone(),
+            old_account.token.clone(),
+        ) {
+            Ok(account) => account,
+            Err(e) => {
+                modules::logger::log_error(&format!("导入账号失败 {}: {}", old_account.email, e));
+                continue;
+            }
+        };
 
-        for summary in old_index.accounts {
-            let old_account_path = old_accounts_dir.join(format!("{}.json", summary.id));
-            if old_account_path.exists() {
-                match fs::read_to_string(&old_account_path) {
-                    Ok(account_content) => {
-                        match serde_json::from_str::<OldToolAccount>(&account_content) {
-                            Ok(old_account) => {
-                                // 使用 upsert 导入（避免重复）
-                                match modules::upsert_account(
-                                    old_account.email.clone(),
-                                    old_account.name.clone(),
-                                    old_account.token.clone(),
-                                ) {
-                                    Ok(mut new_account) => {
-                                        if let Some((profile, label, created_at)) =
-                                            select_account_profile(&old_account)
-                                        {
-                                            let base = old_account
-                                                .name
-                                                .as_deref()
-                                                .unwrap_or(&old_account.email);
-                                            let name = format_import_name(
-                                                base,
-                                                label.as_deref(),
-                                                created_at,
-                                            );
-                                            let (fp_id, inserted) = upsert_fingerprint_in_store(
-                                                &mut fingerprint_store,
-                                                profile,
-                                                name,
-                                                created_at,
-                                                &mut fingerprint_map,
-                                            );
-                                            if inserted {
-                                                fingerprint_dirty = true;
-                                            }
-                                            new_account.fingerprint_id = Some(fp_id);
-                                            if let Err(e) = modules::save_account(&new_account) {
-                                                modules::logger::log_error(&format!(
-                                                    "更新账号指纹失败 {}: {}",
-                                                    new_account.email, e
-                                                ));
-                                            }
-                                        }
-                                        modules::logger::log_info(&format!(
-                                            "导入账号: {}",
-                                            new_account.email
-                                        ));
-                                        imported.push(new_account);
-                                    }
-                                    Err(e) => {
-                                        modules::logger::log_error(&format!(
-                                            "导入账号失败 {}: {}",
-                                            old_account.email, e
-                                        ));
-                                    }
-                                }
-                            }
-                            Err(e) => {
-                                modules::logger::log_error(&format!(
-                                    "解析账号文件失败 {:?}: {}",
-                                    old_account_path, e
-                                ));
-                            }
-                        }
-                    }
-                    Err(e) => {
-                        modules::logger::log_error(&format!(
-                            "读取账号文件失败 {:?}: {}",
-                            old_account_path, e
-                        ));
-                    }
-                }
+        if let Some((profile, label, created_at)) = select_account_profile(&old_account) {
+            let base = old_account.name.as_deref().unwrap_or(&old_account.email);
+            let name = format_import_name(base, label.as_deref(), created_at);
+            let (fp_id, inserted) = upsert_fingerprint_in_store(
+                &mut fingerprint_store,
+                profile,
+                name,
+                created_at,
+                &mut fingerprint_map,
+            );
+            if inserted {
+                fingerprint_dirty = true;
+            }
+            new_account.fingerprint_id = Some(fp_id);
+            if let Err(e) = modules::save_account(&new_account) {
+                modules::logger::log_error(&format!(
+                    "更新账号指纹失败 {}: {}",
+                    new_account.email, e
+                ));
             }
         }
+        modules::logger::log_info(&format!("导入账号: {}", new_account.email));
+        imported.push(new_account);
     }
     if fingerprint_dirty {
         modules::fingerprint::save_fingerprint_store(&fingerprint_store)?;
@@ -451,22 +563,43 @@ pub async fn import_fingerprints_from_old_tools_logic() -> Result<usize, String>
     Ok(imported_count)
 }
 
-/// 从 JSON 导入指纹
-pub async fn import_fingerprints_from_json_logic(json_content: String) -> Result<usize, String> {
-    let trimmed = json_content.trim();
-    if trimmed.is_empty() {
-        return Err("JSON 内容为空".to_string());
+/// 待导入的指纹条目
+struct FingerprintCandidate {
+    id: Option<String>,
+    name: Option<String>,
+    label: Option<String>,
+    profile: models::DeviceProfile,
+    created_at: Option<i64>,
+}
+
+impl FingerprintCandidate {
+    fn from_input(input: FingerprintJsonInput) -> Option<Self> {
+        let profile = extract_profile_from_input(&input)?;
+        Some(Self {
+            id: input.id,
+            name: input.name,
+            label: input.label,
+            profile,
+            created_at: input.created_at,
+        })
     }
 
-    let value: serde_json::Value =
-        serde_json::from_str(trimmed).map_err(|e| format!("JSON 格式错误: {}", e))?;
+    fn from_fingerprint(fp: modules::fingerprint::Fingerprint) -> Self {
+        Self {
+            id: Some(fp.id),
+            name: Some(fp.name),
+            label: None,
+            profile: fp.profile,
+            created_at: Some(fp.created_at),
+        }
+    }
+}
 
-    let mut candidates: Vec<(
-        Option<String>,
-        Option<String>,
-        models::DeviceProfile,
-        Option<i64>,
-    )> = Vec::new();
+/// 从 JSON 中提取指纹：支持指纹存储文件、单个指纹对象和指纹数组
+fn collect_fingerprint_candidates(
+    value: serde_json::Value,
+) -> Result<Vec<FingerprintCandidate>, String> {
+    let mut candidates = Vec::new();
 
     if value.is_object() {
         let obj = value.as_object().ok_or("JSON 格式错误")?;
@@ -474,35 +607,40 @@ pub async fn import_fingerprints_from_json_logic(json_content: String) -> Result
             let store: modules::fingerprint::FingerprintStore =
                 serde_json::from_value(value).map_err(|e| format!("解析指纹存储失败: {}", e))?;
             if let Some(baseline) = store.original_baseline {
-                candidates.push((
-                    Some(baseline.name),
-                    None,
-                    baseline.profile,
-                    Some(baseline.created_at),
-                ));
+                candidates.push(FingerprintCandidate::from_fingerprint(baseline));
             }
             for fp in store.fingerprints {
-                candidates.push((Some(fp.name), None, fp.profile, Some(fp.created_at)));
+                candidates.push(FingerprintCandidate::from_fingerprint(fp));
             }
         } else {
             let input: FingerprintJsonInput =
                 serde_json::from_value(value).map_err(|e| format!("解析指纹数据失败: {}", e))?;
-            if let Some(profile) = extract_profile_from_input(&input) {
-                candidates.push((input.name, input.label, profile, input.created_at));
-            }
+            candidates.extend(FingerprintCandidate::from_input(input));
         }
     } else if let Some(list) = value.as_array() {
         for item in list {
             let input: FingerprintJsonInput = serde_json::from_value(item.clone())
                 .map_err(|e| format!("解析指纹数据失败: {}", e))?;
-            if let Some(profile) = extract_profile_from_input(&input) {
-                candidates.push((input.name, input.label, profile, input.created_at));
-            }
+            candidates.extend(FingerprintCandidate::from_input(input));
         }
     } else {
         return Err("JSON 格式错误".to_string());
     }
 
+    Ok(candidates)
+}
+
+/// 从 JSON 导入指纹
+pub async fn import_fingerprints_from_json_logic(json_content: String) -> Result<usize, String> {
+    let trimmed = json_content.trim();
+    if trimmed.is_empty() {
+        return Err("JSON 内容为空".to_string());
+    }
+
+    let value: serde_json::Value =
+        serde_json::from_str(trimmed).map_err(|e| format!("JSON 格式错误: {}", e))?;
+
+    let candidates = collect_fingerprint_candidates(value)?;
     if candidates.is_empty() {
         return Err("未找到可导入的指纹数据".to_string());
     }
@@ -511,14 +649,18 @@ pub async fn import_fingerprints_from_json_logic(json_content: String) -> Result
     let mut fingerprint_map = build_fingerprint_profile_map(&fingerprint_store);
     let mut imported_count = 0;
 
-    for (idx, (name, label, profile, created_at)) in candidates.into_iter().enumerate() {
-        let display_name =
-            resolve_json_import_name(name.as_deref(), label.as_deref(), created_at, idx);
+    for (idx, candidate) in candidates.into_iter().enumerate() {
+        let display_name = resolve_json_import_name(
+            candidate.name.as_deref(),
+            candidate.label.as_deref(),
+            candidate.created_at,
+            idx,
+        );
         let (_, inserted) = upsert_fingerprint_in_store(
             &mut fingerprint_store,
-            profile,
+            candidate.profile,
             display_name,
-            created_at,
+            candidate.created_at,
             &mut fingerprint_map,
         );
         if inserted {
@@ -533,6 +675,166 @@ pub async fn import_fingerprints_from_json_logic(json_content: String) -> Result
     Ok(imported_count)
 }
 
+/// 压缩包解压后的总大小上限
+const FINGERPRINT_ZIP_MAX_TOTAL_BYTES: u64 = 32 * 1024 * 1024;
+
+/// 从压缩包导入指纹的结果
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FingerprintZipImportReport {
+    /// 新增的指纹数量
+    pub imported: usize,
+    /// 与现有指纹 ID、名称或设备信息重复而未导入的数量
+    pub duplicates: usize,
+    /// 路径不安全、无法解析或字段校验失败而跳过的条目数量
+    pub skipped: usize,
+}
+
+/// 读取压缩包内的 JSON 条目，返回 (指纹条目, 跳过的条目数)
+/// 单个条目无效时记录日志并跳过；解压总大小超过上限时整体失败
+fn read_fingerprint_zip(path: &Path) -> Result<(Vec<FingerprintCandidate>, usize), String> {
+    use std::io::Read;
+
+    let file = fs::File::open(path).map_err(|e| format!("打开压缩包失败: {}", e))?;
+    let mut archive = zip::ZipArchive::new(file).map_err(|e| format!("读取压缩包失败: {}", e))?;
+
+    let mut candidates = Vec::new();
+    let mut skipped = 0;
+    let mut total_bytes: u64 = 0;
+
+    for i in 0..archive.len() {
+        let mut entry = match archive.by_index(i) {
+            Ok(entry) => entry,
+            Err(e) => {
+                modules::logger::log_warn(&format!("跳过无法读取的压缩包条目 #{}: {}", i, e));
+                skipped += 1;
+                continue;
+            }
+        };
+        if entry.is_dir() {
+            continue;
+        }
+        let entry_name = entry.name().to_string();
+        // 只读取也拒绝包含 .. 或绝对路径的条目（zip-slip）
+        let Some(enclosed) = entry.enclosed_name() else {
+            modules::logger::log_warn(&format!("跳过路径不安全的压缩包条目: {}", entry_name));
+            skipped += 1;
+            continue;
+        };
+        let is_json = enclosed
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("json"));
+        if !is_json {
+            continue;
+        }
+
+        // 不信任条目头中声明的大小，按实际读取的字节数累计
+        let remaining = FINGERPRINT_ZIP_MAX_TOTAL_BYTES - total_bytes;
+        let mut buf = Vec::new();
+        if let Err(e) = (&mut entry).take(remaining + 1).read_to_end(&mut buf) {
+            modules::logger::log_warn(&format!("读取压缩包条目失败 {}: {}", entry_name, e));
+            skipped += 1;
+            continue;
+        }
+        total_bytes += buf.len() as u64;
+        if total_bytes > FINGERPRINT_ZIP_MAX_TOTAL_BYTES {
+            return Err(format!(
+                "压缩包解压后超过 {} MB 上限",
+                FINGERPRINT_ZIP_MAX_TOTAL_BYTES / 1024 / 1024
+            ));
+        }
+
+        let parsed = serde_json::from_slice::<serde_json::Value>(&buf)
+            .map_err(|e| format!("JSON 格式错误: {}", e))
+            .and_then(collect_fingerprint_candidates);
+        let entry_candidates = match parsed {
+            Ok(list) if !list.is_empty() => list,
+            Ok(_) => {
+                modules::logger::log_warn(&format!("压缩包条目中没有指纹数据: {}", entry_name));
+                skipped += 1;
+                continue;
+            }
+            Err(e) => {
+                modules::logger::log_warn(&format!(
+                    "跳过无法解析的压缩包条目 {}: {}",
+                    entry_name, e
+                ));
+                skipped += 1;
+                continue;
+            }
+        };
+        for candidate in entry_candidates {
+            match modules::fingerprint::validate_profile(&candidate.profile) {
+                Ok(()) => candidates.push(candidate),
+                Err(e) => {
+                    modules::logger::log_warn(&format!("跳过无效指纹 {}: {}", entry_name, e));
+                    skipped += 1;
+                }
+            }
+        }
+    }
+
+    Ok((candidates, skipped))
+}
+
+/// 将指纹合并到存储中：ID 或名称已存在、或设备信息重复的不导入
+fn merge_fingerprint_candidates(
+    store: &mut modules::fingerprint::FingerprintStore,
+    candidates: Vec<FingerprintCandidate>,
+) -> FingerprintZipImportReport {
+    let mut fingerprint_map = build_fingerprint_profile_map(store);
+    let mut report = FingerprintZipImportReport::default();
+
+    for (idx, candidate) in candidates.into_iter().enumerate() {
+        let display_name = resolve_json_import_name(
+            candidate.name.as_deref(),
+            candidate.label.as_deref(),
+            candidate.created_at,
+            idx,
+        );
+        if store.contains_id_or_name(candidate.id.as_deref(), &display_name) {
+            report.duplicates += 1;
+            continue;
+        }
+        let (_, inserted) = upsert_fingerprint_in_store(
+            store,
+            candidate.profile,
+            display_name,
+            candidate.created_at,
+            &mut fingerprint_map,
+        );
+        if inserted {
+            report.imported += 1;
+        } else {
+            report.duplicates += 1;
+        }
+    }
+
+    report
+}
+
+/// 从 zip 压缩包导入指纹（每个 JSON 条目为单个指纹、指纹数组或指纹存储文件）
+pub async fn import_fingerprints_from_zip_logic(
+    path: String,
+) -> Result<FingerprintZipImportReport, String> {
+    let (candidates, skipped) = read_fingerprint_zip(Path::new(&path))?;
+
+    let mut fingerprint_store = modules::fingerprint::load_fingerprint_store()?;
+    let mut report = merge_fingerprint_candidates(&mut fingerprint_store, candidates);
+    report.skipped += skipped;
+
+    if report.imported > 0 {
+        modules::fingerprint::save_fingerprint_store(&fingerprint_store)?;
+    }
+
+    modules::logger::log_info(&format!(
+        "从压缩包导入指纹完成: imported={}, duplicates={}, skipped={}",
+        report.imported, report.duplicates, report.skipped
+    ));
+    Ok(report)
+}
+
 /// 从本地 Antigravity 客户端导入当前账号
 pub async fn import_from_local_logic() -> Result<models::Account, String> {
     use base64::{engine::general_purpose, Engine as _};
@@ -541,8 +843,7 @@ pub async fn import_from_local_logic() -> Result<models::Account, String> {
 
     // 读取 state.vscdb
     let db_path = modules::db::get_db_path()?;
-    let conn =
-        rusqlite::Connection::open(&db_path).map_err(|e| format!("打开数据库失败: {}", e))?;
+    let conn = modules::db::open_readonly(&db_path)?;
 
     // 读取 protobuf 数据
     let state_data: String = conn
@@ -773,3 +1074,279 @@ pub async fn import_from_extension_credentials() -> Result<usize, String> {
 
     Ok(imported_count)
 }
+
+// ==================== 账号导出 ====================
+
+/// 账号导出格式，未指定时为 JSON
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    #[default]
+    Json,
+    Csv,
+}
+
+/// CSV 只包含非敏感字段，不导出任何 token
+const ACCOUNT_CSV_HEADER: &str =
+    "email,name,tags,subscription_tier,disabled,lowest_remaining_percent,model_quotas,quota_updated_at";
+
+fn render_accounts_csv(accounts: &[models::Account]) -> String {
+    let mut lines = vec![ACCOUNT_CSV_HEADER.to_string()];
+    for account in accounts {
+        let quota = account.quota.as_ref();
+        let lowest = quota
+            .and_then(|quota| quota.models.iter().map(|model| model.percentage).min())
+            .map(|percentage| percentage.to_string())
+            .unwrap_or_default();
+        let model_quotas = quota
+            .map(|quota| {
+                quota
+                    .models
+                    .iter()
+                    .map(|model| format!("{}={}%", model.name, model.percentage))
+                    .collect::<Vec<String>>()
+                    .join(";")
+            })
+            .unwrap_or_default();
+        let fields = [
+            account.email.clone(),
+            account.name.clone().unwrap_or_default(),
+            account.tags.join(";"),
+            quota
+                .and_then(|quota| quota.subscription_tier.clone())
+                .unwrap_or_default(),
+            account.disabled.to_string(),
+            lowest,
+            model_quotas,
+            modules::quota_report::format_ts(quota.map(|quota| quota.last_updated))
+                .unwrap_or_default(),
+        ];
+        lines.push(
+            fields
+                .iter()
+                .map(|field| modules::quota_report::escape_csv_field(field))
+                .collect::<Vec<String>>()
+                .join(","),
+        );
+    }
+    lines.join("\n")
+}
+
+/// 导出账号；account_ids 为空时导出全部
+/// JSON 为可重新导入的 email + refresh_token 列表，CSV 仅含邮箱、标签与配额摘要
+pub fn export_accounts_logic(
+    account_ids: &[String],
+    format: ExportFormat,
+) -> Result<String, String> {
+    let accounts = if account_ids.is_empty() {
+        modules::list_accounts()?
+    } else {
+        account_ids
+            .iter()
+            .filter_map(|id| modules::load_account(id).ok())
+            .collect()
+    };
+
+    match format {
+        ExportFormat::Json => {
+            #[derive(Serialize)]
+            struct SimpleAccount {
+                email: String,
+                refresh_token: String,
+            }
+
+            let simplified: Vec<SimpleAccount> = accounts
+                .into_iter()
+                .map(|account| SimpleAccount {
+                    email: account.email,
+                    refresh_token: account.token.refresh_token,
+                })
+                .collect();
+            serde_json::to_string_pretty(&simplified).map_err(|e| format!("序列化失败: {}", e))
+        }
+        ExportFormat::Csv => Ok(render_accounts_csv(&accounts)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_render_accounts_csv_escapes_fields_and_omits_tokens() {
+        let mut account = models::Account::new(
+            "acc-1".to_string(),
+            "a@example.com".to_string(),
+            models::TokenData::new(
+                "secret-access".to_string(),
+                "secret-refresh".to_string(),
+                3600,
+                None,
+                None,
+                None,
+            ),
+        );
+        account.name = Some("Doe, \"J\"\nJr".to_string());
+        account.tags = vec!["work".to_string(), "team,a".to_string()];
+        let mut quota = models::QuotaData::new();
+        quota.add_model("gemini".to_string(), None, 80, String::new());
+        quota.add_model("claude".to_string(), None, 35, String::new());
+        account.quota = Some(quota);
+
+        let csv = render_accounts_csv(&[account]);
+        assert!(csv.starts_with(ACCOUNT_CSV_HEADER));
+        assert!(csv.contains("\"Doe, \"\"J\"\"\nJr\""));
+        assert!(csv.contains("\"work;team,a\""));
+        assert!(csv.contains(",35,gemini=80%;claude=35%,"));
+        assert!(!csv.contains("secret"));
+    }
+
+    fn write_json(path: &Path, value: &serde_json::Value) {
+        fs::write(path, serde_json::to_string(value).unwrap()).unwrap();
+    }
+
+    fn old_account_json(email: &str, machine_id: &str) -> serde_json::Value {
+        json!({
+            "email": email,
+            "name": null,
+            "token": {
+                "access_token": "access",
+                "refresh_token": format!("refresh-{}", email),
+                "expires_in": 3600,
+                "expiry_timestamp": 0,
+                "token_type": "Bearer",
+                "email": email
+            },
+            "device_profile": {
+                "machine_id": machine_id,
+                "mac_machine_id": "mac",
+                "dev_device_id": "dev",
+                "sqm_id": "sqm"
+            }
+        })
+    }
+
+    #[test]
+    fn test_preview_reports_collisions_without_writing() {
+        let old_dir = std::env::temp_dir().join(format!("cockpit-old-tools-{}", Uuid::new_v4()));
+        fs::create_dir_all(old_dir.join("accounts")).unwrap();
+        write_json(
+            &old_dir.join("accounts.json"),
+            &json!({
+                "version": "1.0",
+                "accounts": [
+                    {
+                        "id": "old-1",
+                        "email": "taken@example.com",
+                        "name": null,
+                        "created_at": 0,
+                        "last_used": 0
+                    },
+                    {
+                        "id": "old-2",
+                        "email": "fresh@example.com",
+                        "name": null,
+                        "created_at": 0,
+                        "last_used": 0
+                    }
+                ],
+                "current_account_id": null
+            }),
+        );
+        write_json(
+            &old_dir.join("accounts").join("old-1.json"),
+            &old_account_json("taken@example.com", "m1"),
+        );
+        write_json(
+            &old_dir.join("accounts").join("old-2.json"),
+            &old_account_json("fresh@example.com", "m2"),
+        );
+
+        let existing = vec![models::Account::new(
+            "existing-1".to_string(),
+            "taken@example.com".to_string(),
+            models::TokenData::new("a".to_string(), "r".to_string(), 3600, None, None, None),
+        )];
+        let store = modules::fingerprint::FingerprintStore {
+            original_baseline: None,
+            current_fingerprint_id: None,
+            fingerprints: Vec::new(),
+        };
+
+        let old_accounts = load_old_tool_accounts(&old_dir).unwrap();
+        let preview = build_import_preview(&old_accounts, &existing, &store);
+
+        assert_eq!(preview.accounts, 2);
+        assert_eq!(preview.device_profiles, 2);
+        assert_eq!(preview.fingerprints, 2);
+        assert_eq!(preview.collisions.len(), 1);
+        assert_eq!(preview.collisions[0].old_id, "old-1");
+        assert_eq!(preview.collisions[0].existing_account_id, "existing-1");
+        assert_eq!(preview.collisions[0].reason, "email");
+        assert!(store.fingerprints.is_empty());
+
+        let _ = fs::remove_dir_all(&old_dir);
+    }
+
+    #[test]
+    fn test_zip_import_skips_unsafe_and_malformed_entries() {
+        use std::io::Write;
+
+        let zip_path =
+            std::env::temp_dir().join(format!("cockpit-fingerprints-{}.zip", Uuid::new_v4()));
+        let fingerprint = |name: &str, machine_id: &str| {
+            json!({
+                "name": name,
+                "machine_id": machine_id,
+                "mac_machine_id": "mac",
+                "dev_device_id": "dev",
+                "sqm_id": "sqm"
+            })
+            .to_string()
+        };
+        {
+            let mut writer = zip::ZipWriter::new(fs::File::create(&zip_path).unwrap());
+            let options = zip::write::SimpleFileOptions::default()
+                .compression_method(zip::CompressionMethod::Stored);
+            let entries = [
+                ("fresh.json", fingerprint("fresh", "m1")),
+                ("nested/existing.json", fingerprint("existing", "m2")),
+                ("broken.json", "{ not json".to_string()),
+                ("empty-field.json", fingerprint("blank", " ")),
+                ("../evil.json", fingerprint("evil", "m3")),
+                ("readme.txt", "ignored".to_string()),
+            ];
+            for (name, content) in entries {
+                writer.start_file(name, options).unwrap();
+                writer.write_all(content.as_bytes()).unwrap();
+            }
+            writer.finish().unwrap();
+        }
+
+        let (candidates, skipped) = read_fingerprint_zip(&zip_path).unwrap();
+        assert_eq!(candidates.len(), 2);
+        assert_eq!(skipped, 3);
+
+        let mut store = modules::fingerprint::FingerprintStore::new();
+        store
+            .fingerprints
+            .push(modules::fingerprint::Fingerprint::new(
+                "existing".to_string(),
+                models::DeviceProfile {
+                    machine_id: "other".to_string(),
+                    mac_machine_id: "mac".to_string(),
+                    dev_device_id: "dev".to_string(),
+                    sqm_id: "sqm".to_string(),
+                    service_machine_id: String::new(),
+                },
+            ));
+        let report = merge_fingerprint_candidates(&mut store, candidates);
+        assert_eq!(report.imported, 1);
+        assert_eq!(report.duplicates, 1);
+        assert_eq!(store.fingerprints.len(), 2);
+        assert_eq!(store.fingerprints[1].name, "fresh");
+
+        let _ = fs::remove_file(&zip_path);
+    }
+}
@@ -1,6 +1,7 @@
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::sync::Mutex;
+use std::time::Duration;
 
 use chrono::Utc;
 use serde::{Deserialize, Serialize};
@@ -16,6 +17,10 @@ static INSTANCE_STORE_LOCK: std::sync::LazyLock<Mutex<()>> =
     std::sync::LazyLock::new(|| Mutex::new(()));
 
 const INSTANCES_FILE: &str = "instances.json";
+const EPHEMERAL_DIR_PREFIX: &str = "cockpit-ephemeral-";
+const EPHEMERAL_STARTUP_GRACE: Duration = Duration::from_secs(10);
+const EPHEMERAL_POLL_INTERVAL: Duration = Duration::from_secs(2);
+const EPHEMERAL_CLEANUP_RETRIES: u32 = 5;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -24,6 +29,16 @@ pub struct InstanceDefaults {
     pub default_user_data_dir: String,
 }
 
+/// 临时（只读）启动结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EphemeralLaunchResult {
+    pub instance_id: String,
+    /// 本次启动使用的临时数据目录，进程退出后自动删除
+    pub temp_dir: String,
+    pub pid: u32,
+}
+
 fn instances_path() -> Result<PathBuf, String> {
     let data_dir = modules::account::get_data_dir()?;
     Ok(data_dir.join(INSTANCES_FILE))
@@ -167,6 +182,56 @@ fn ensure_profile_global_storage(profile_dir: &Path) -> Result<PathBuf, String>
     Ok(global_storage)
 }
 
+/// 将账号绑定的指纹写入实例目录的 storage.json，返回是否写入（账号未绑定指纹时为 false）
+pub fn apply_account_fingerprint_to_profile(
+    profile_dir: &Path,
+    account_id: &str,
+) -> Result<bool, String> {
+    let account = modules::load_account(account_id)?;
+    let Some(fingerprint_id) = account.fingerprint_id.as_deref() else {
+        return Ok(false);
+    };
+    apply_fingerprint_to_profile(profile_dir, fingerprint_id)?;
+    Ok(true)
+}
+
+/// 将指定指纹写入实例目录的 storage.json（只影响该实例，不改动系统全局指纹）
+pub fn apply_fingerprint_to_profile(
+    profile_dir: &Path,
+    fingerprint_id: &str,
+) -> Result<(), String> {
+    let fingerprint = modules::fingerprint::get_fingerprint(fingerprint_id)?;
+    write_profile_to_profile_dir(profile_dir, &fingerprint.profile)?;
+    modules::logger::log_info(&format!(
+        "[Instance] 已将指纹写入实例目录: fingerprint={}, dir={}",
+        fingerprint.name,
+        profile_dir.to_string_lossy()
+    ));
+    Ok(())
+}
+
+/// 将实例绑定的指纹写入启动目录（实例未绑定指纹时跳过），所有启动路径共用
+pub fn apply_instance_fingerprint(
+    instance: &InstanceProfile,
+    profile_dir: &Path,
+) -> Result<(), String> {
+    match instance.bind_fingerprint_id.as_deref() {
+        Some(fingerprint_id) => apply_fingerprint_to_profile(profile_dir, fingerprint_id),
+        None => Ok(()),
+    }
+}
+
+fn write_profile_to_profile_dir(
+    profile_dir: &Path,
+    profile: &crate::models::DeviceProfile,
+) -> Result<(), String> {
+    let storage_path = ensure_profile_global_storage(profile_dir)?.join("storage.json");
+    if !storage_path.exists() {
+        fs::write(&storage_path, "{}").map_err(|e| format!("创建 storage.json 失败: {}", e))?;
+    }
+    modules::device::write_profile(&storage_path, profile)
+}
+
 fn ensure_state_db_for_injection(profile_dir: &Path) -> Result<PathBuf, String> {
     let db_path = profile_dir
         .join("User")
@@ -219,6 +284,7 @@ pub fn inject_account_to_profile(profile_dir: &Path, account_id: &str) -> Result
 
 pub fn create_instance(params: CreateInstanceParams) -> Result<InstanceProfile, String> {
     let _lock = INSTANCE_STORE_LOCK.lock().map_err(|_| "无法获取实例锁")?;
+    let env_vars = instance_store::normalize_env_vars(params.env_vars.clone())?;
     let mut store = load_instance_store()?;
 
     let name = instance_store::normalize_name(&params.name)?;
@@ -230,6 +296,7 @@ pub fn create_instance(params: CreateInstanceParams) -> Result<InstanceProfile,
     instance_store::ensure_unique(&store, &name, &user_data_dir, None)?;
 
     let user_dir_path = PathBuf::from(&user_data_dir);
+    instance_store::check_instance_dir_length(&user_dir_path)?;
 
     let init_mode = params
         .init_mode
@@ -298,6 +365,12 @@ pub fn create_instance(params: CreateInstanceParams) -> Result<InstanceProfile,
         created_at: Utc::now().timestamp_millis(),
         last_launched_at: None,
         last_pid: None,
+        minimize_after_launch: false,
+        low_power: false,
+        launch_args_template: params.launch_args_template,
+        env_vars,
+        bind_fingerprint_id: normalize_fingerprint_binding(params.bind_fingerprint_id)?,
+        follow_local_account: false,
     };
 
     store.instances.push(instance.clone());
@@ -305,8 +378,18 @@ pub fn create_instance(params: CreateInstanceParams) -> Result<InstanceProfile,
     Ok(instance)
 }
 
+/// 校验要绑定的指纹是否存在，空字符串视为不绑定
+fn normalize_fingerprint_binding(fingerprint_id: Option<String>) -> Result<Option<String>, String> {
+    let Some(fingerprint_id) = fingerprint_id.filter(|id| !id.trim().is_empty()) else {
+        return Ok(None);
+    };
+    modules::fingerprint::get_fingerprint(&fingerprint_id)?;
+    Ok(Some(fingerprint_id))
+}
+
 pub fn update_instance(params: UpdateInstanceParams) -> Result<InstanceProfile, String> {
     let _lock = INSTANCE_STORE_LOCK.lock().map_err(|_| "无法获取实例锁")?;
+    instance_store::reject_follow_local_account("Antigravity", params.follow_local_account)?;
     let mut store = load_instance_store()?;
     let index = store
         .instances
@@ -333,9 +416,24 @@ pub fn update_instance(params: UpdateInstanceParams) -> Result<InstanceProfile,
     if let Some(ref extra_args) = params.extra_args {
         instance.extra_args = extra_args.trim().to_string();
     }
+    if let Some(template) = params.launch_args_template.clone() {
+        instance.launch_args_template = template;
+    }
+    if let Some(env_vars) = params.env_vars.clone() {
+        instance.env_vars = instance_store::normalize_env_vars(env_vars)?;
+    }
     if let Some(bind) = params.bind_account_id.clone() {
         instance.bind_account_id = bind;
     }
+    if let Some(bind) = params.bind_fingerprint_id.clone() {
+        instance.bind_fingerprint_id = normalize_fingerprint_binding(bind)?;
+    }
+    if let Some(minimize) = params.minimize_after_launch {
+        instance.minimize_after_launch = minimize;
+    }
+    if let Some(low_power) = params.low_power {
+        instance.low_power = low_power;
+    }
 
     let updated = instance.clone();
     save_instance_store(&store)?;
@@ -399,6 +497,49 @@ pub fn delete_instance_directory(dir_path: &Path) -> Result<(), String> {
     }
 }
 
+/// 将实例数据目录复制到系统临时目录，用于不污染原实例的临时启动
+pub fn prepare_ephemeral_dir(source_dir: &Path) -> Result<PathBuf, String> {
+    let temp_dir = std::env::temp_dir().join(format!("{}{}", EPHEMERAL_DIR_PREFIX, Uuid::new_v4()));
+    if let Err(err) = instance_store::copy_dir_recursive(source_dir, &temp_dir) {
+        let _ = fs::remove_dir_all(&temp_dir);
+        return Err(format!("复制实例目录到临时目录失败: {}", err));
+    }
+    Ok(temp_dir)
+}
+
+/// 后台等待临时实例进程退出，随后删除临时目录
+pub fn watch_ephemeral_exit(pid: u32, temp_dir: PathBuf) {
+    std::thread::spawn(move || {
+        let dir_str = temp_dir.to_string_lossy().to_string();
+        // 启动初期进程可能尚未出现在进程列表中，先等待一段时间
+        std::thread::sleep(EPHEMERAL_STARTUP_GRACE);
+        while modules::process::resolve_antigravity_pid(Some(pid), Some(&dir_str)).is_some() {
+            std::thread::sleep(EPHEMERAL_POLL_INTERVAL);
+        }
+
+        // 进程刚退出时文件句柄可能尚未释放，失败后重试
+        for attempt in 1..=EPHEMERAL_CLEANUP_RETRIES {
+            match fs::remove_dir_all(&temp_dir) {
+                Ok(()) => {
+                    modules::logger::log_info(&format!(
+                        "[Instance] 临时实例已退出，临时目录已删除: pid={}, dir={}",
+                        pid, dir_str
+                    ));
+                    return;
+                }
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => return,
+                Err(err) if attempt == EPHEMERAL_CLEANUP_RETRIES => {
+                    modules::logger::log_warn(&format!(
+                        "[Instance] 删除临时目录失败: dir={}, err={}",
+                        dir_str, err
+                    ));
+                }
+                Err(_) => std::thread::sleep(EPHEMERAL_POLL_INTERVAL),
+            }
+        }
+    });
+}
+
 #[allow(dead_code)]
 pub fn update_instance_last_launched(instance_id: &str) -> Result<InstanceProfile, String> {
     let _lock = INSTANCE_STORE_LOCK.lock().map_err(|_| "无法获取实例锁")?;
@@ -468,3 +609,30 @@ pub fn clear_all_pids() -> Result<(), String> {
     save_instance_store(&store)?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::DeviceProfile;
+
+    #[test]
+    fn test_write_profile_to_fresh_profile_dir() {
+        let dir = std::env::temp_dir().join(format!("cockpit-instance-test-{}", Uuid::new_v4()));
+        let profile = DeviceProfile {
+            machine_id: "machine".to_string(),
+            mac_machine_id: "mac".to_string(),
+            dev_device_id: "device".to_string(),
+            sqm_id: "{SQM}".to_string(),
+            service_machine_id: "service".to_string(),
+        };
+
+        write_profile_to_profile_dir(&dir, &profile).unwrap();
+        let storage_path = dir.join("User").join("globalStorage").join("storage.json");
+        let written = modules::device::read_profile(&storage_path).unwrap();
+        let _ = fs::remove_dir_all(&dir);
+
+        assert_eq!(written.machine_id, "machine");
+        assert_eq!(written.dev_device_id, "device");
+        assert_eq!(written.sqm_id, "{SQM}");
+    }
+}
@@ -0,0 +1,945 @@
+//! 多平台实例注册表
+//! 按平台 ID 分发实例存储读写、默认目录与账号标识解析，供跨平台实例命令使用
+
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use crate::models::{DefaultInstanceSettings, InstanceStore};
+use crate::modules;
+use crate::modules::tray_layout::{
+    PLATFORM_ANTIGRAVITY, PLATFORM_CODEX, PLATFORM_GITHUB_COPILOT, PLATFORM_KIRO,
+    PLATFORM_WINDSURF, SUPPORTED_PLATFORM_IDS,
+};
+
+/// 支持多开实例的平台 ID（与托盘平台 ID 保持一致）
+pub const INSTANCE_PLATFORM_IDS: [&str; 5] = SUPPORTED_PLATFORM_IDS;
+
+/// 默认实例 ID（与各平台实例命令保持一致）
+pub const DEFAULT_INSTANCE_ID: &str = "__default__";
+
+/// 实例目录条目（跨平台汇总）
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstanceDirEntry {
+    pub platform: String,
+    pub instance_id: String,
+    pub instance_name: String,
+    pub user_data_dir: String,
+}
+
+/// 实例目录冲突
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstanceDirConflict {
+    /// "same"：目录相同；"nested"：first 的目录包含 second 的目录
+    pub kind: String,
+    pub first: InstanceDirEntry,
+    pub second: InstanceDirEntry,
+}
+
+/// 实例运行状态详情
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstanceRuntimeInfo {
+    pub id: String,
+    pub name: String,
+    pub user_data_dir: String,
+    pub is_default: bool,
+    pub last_launched_at: Option<i64>,
+    pub is_running: bool,
+    pub resolved_pid: Option<u32>,
+}
+
+/// 实例的实时进程状态
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunningInstance {
+    pub instance_id: String,
+    /// 存活时为匹配到的主进程 PID，否则为上次记录的 PID
+    pub pid: Option<u32>,
+    pub alive: bool,
+    /// 实例配置中的数据目录（Codex 为 CODEX_HOME）
+    pub user_data_dir: String,
+    /// 与进程命令行比对时使用的规范化目录，用于排查匹配失败
+    pub resolved_user_data_dir: String,
+}
+
+/// 多个主进程共用同一用户数据目录
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RunningDirCollision {
+    pub platform: String,
+    pub user_data_dir: String,
+    pub pids: Vec<u32>,
+    /// 该目录对应的托管实例（未托管时为空）
+    pub instance: Option<InstanceDirEntry>,
+}
+
+/// 实例配置问题类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum InstanceIssueKind {
+    /// 实例目录已不存在
+    MissingDir,
+    /// 与其他实例名称重复（忽略大小写）
+    DuplicateName,
+    /// 与其他实例共用同一目录
+    DuplicateUserDataDir,
+    /// 绑定的账号已被删除
+    DanglingBindAccount,
+}
+
+/// 实例配置检查发现的问题
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstanceIssue {
+    pub kind: InstanceIssueKind,
+    pub instance_id: String,
+    pub instance_name: String,
+    pub user_data_dir: String,
+    pub message: String,
+}
+
+/// 清理实例配置的结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PruneInstanceStoreResult {
+    /// 被移除的实例（目录已不存在）
+    pub removed: Vec<InstanceDirEntry>,
+    /// 清理前实例配置的备份路径（没有需要移除的实例时不备份）
+    pub backup_path: Option<String>,
+}
+
+/// 前台窗口对应的托管实例识别结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActiveInstanceResult {
+    /// 前台窗口是否属于受管理的实例
+    pub managed: bool,
+    pub foreground_pid: u32,
+    pub instance: Option<InstanceDirEntry>,
+    pub message: String,
+}
+
+fn unknown_platform(platform: &str) -> String {
+    format!("未知平台: {}", platform)
+}
+
+/// 将前端传入的产品名转换为平台 ID（vscode / github_copilot 视为 github-copilot）
+pub fn resolve_platform_alias(product: &str) -> &str {
+    match product.trim() {
+        "vscode" | "github_copilot" => PLATFORM_GITHUB_COPILOT,
+        other => other,
+    }
+}
+
+pub fn load_instance_store(platform: &str) -> Result<InstanceStore, String> {
+    match platform {
+        PLATFORM_ANTIGRAVITY => modules::instance::load_instance_store(),
+        PLATFORM_CODEX => modules::codex_instance::load_instance_store(),
+        PLATFORM_GITHUB_COPILOT => modules::github_copilot_instance::load_instance_store(),
+        PLATFORM_WINDSURF => modules::windsurf_instance::load_instance_store(),
+        PLATFORM_KIRO => modules::kiro_instance::load_instance_store(),
+        _ => Err(unknown_platform(platform)),
+    }
+}
+
+pub fn save_instance_store(platform: &str, store: &InstanceStore) -> Result<(), String> {
+    match platform {
+        PLATFORM_ANTIGRAVITY => modules::instance::save_instance_store(store),
+        PLATFORM_CODEX => modules::codex_instance::save_instance_store(store),
+        PLATFORM_GITHUB_COPILOT => modules::github_copilot_instance::save_instance_store(store),
+        PLATFORM_WINDSURF => modules::windsurf_instance::save_instance_store(store),
+        PLATFORM_KIRO => modules::kiro_instance::save_instance_store(store),
+        _ => Err(unknown_platform(platform)),
+    }
+}
+
+pub fn load_default_settings(platform: &str) -> Result<DefaultInstanceSettings, String> {
+    match platform {
+        PLATFORM_ANTIGRAVITY => modules::instance::load_default_settings(),
+        PLATFORM_CODEX => modules::codex_instance::load_default_settings(),
+        PLATFORM_GITHUB_COPILOT => modules::github_copilot_instance::load_default_settings(),
+        PLATFORM_WINDSURF => modules::windsurf_instance::load_default_settings(),
+        PLATFORM_KIRO => modules::kiro_instance::load_default_settings(),
+        _ => Err(unknown_platform(platform)),
+    }
+}
+
+pub fn get_default_instances_root_dir(platform: &str) -> Result<PathBuf, String> {
+    match platform {
+        PLATFORM_ANTIGRAVITY => modules::instance::get_default_instances_root_dir(),
+        PLATFORM_CODEX => modules::codex_instance::get_default_instances_root_dir(),
+        PLATFORM_GITHUB_COPILOT => {
+            modules::github_copilot_instance::get_default_instances_root_dir()
+        }
+        PLATFORM_WINDSURF => modules::windsurf_instance::get_default_instances_root_dir(),
+        PLATFORM_KIRO => modules::kiro_instance::get_default_instances_root_dir(),
+        _ => Err(unknown_platform(platform)),
+    }
+}
+
+pub fn get_default_user_data_dir(platform: &str) -> Result<PathBuf, String> {
+    match platform {
+        PLATFORM_ANTIGRAVITY => modules::instance::get_default_user_data_dir(),
+        PLATFORM_CODEX => modules::codex_instance::get_default_codex_home(),
+        PLATFORM_GITHUB_COPILOT => {
+            modules::github_copilot_instance::get_default_vscode_user_data_dir()
+        }
+        PLATFORM_WINDSURF => modules::windsurf_instance::get_default_windsurf_user_data_dir(),
+        PLATFORM_KIRO => modules::kiro_instance::get_default_kiro_user_data_dir(),
+        _ => Err(unknown_platform(platform)),
+    }
+}
+
+fn collect_process_entries(platform: &str) -> Vec<(u32, Option<String>)> {
+    match platform {
+        PLATFORM_ANTIGRAVITY => modules::process::collect_antigravity_process_entries(),
+        PLATFORM_CODEX => modules::process::collect_codex_process_entries(),
+        PLATFORM_GITHUB_COPILOT => modules::process::collect_vscode_process_entries(),
+        PLATFORM_WINDSURF => modules::windsurf_instance::collect_windsurf_process_entries(),
+        PLATFORM_KIRO => modules::kiro_instance::collect_kiro_process_entries(),
+        _ => Vec::new(),
+    }
+}
+
+fn resolve_pid_from_entries(
+    platform: &str,
+    last_pid: Option<u32>,
+    user_data_dir: &str,
+    entries: &[(u32, Option<String>)],
+) -> Option<u32> {
+    let dir = Some(user_data_dir);
+    match platform {
+        PLATFORM_ANTIGRAVITY => {
+            modules::process::resolve_antigravity_pid_from_entries(last_pid, dir, entries)
+        }
+        PLATFORM_CODEX => modules::process::resolve_codex_pid_from_entries(last_pid, dir, entries),
+        PLATFORM_GITHUB_COPILOT => {
+            modules::process::resolve_vscode_pid_from_entries(last_pid, dir, entries)
+        }
+        PLATFORM_WINDSURF => {
+            modules::windsurf_instance::resolve_windsurf_pid_from_entries(last_pid, dir, entries)
+        }
+        PLATFORM_KIRO => {
+            modules::kiro_instance::resolve_kiro_pid_from_entries(last_pid, dir, entries)
+        }
+        _ => None,
+    }
+}
+
+/// 列出指定平台的实例（含默认实例）及运行状态，按最近启动时间倒序
+/// 只扫描一次进程列表，供所有实例复用
+pub fn list_instances_detailed(platform: &str) -> Result<Vec<InstanceRuntimeInfo>, String> {
+    let store = load_instance_store(platform)?;
+    let entries = collect_process_entries(platform);
+    let mut result = Vec::new();
+
+    if let Ok(default_dir) = get_default_user_data_dir(platform) {
+        let user_data_dir = default_dir.to_string_lossy().to_string();
+        let resolved_pid = resolve_pid_from_entries(platform, None, &user_data_dir, &entries);
+        result.push(InstanceRuntimeInfo {
+            id: DEFAULT_INSTANCE_ID.to_string(),
+            name: String::new(),
+            user_data_dir,
+            is_default: true,
+            last_launched_at: None,
+            is_running: resolved_pid.is_some(),
+            resolved_pid,
+        });
+    }
+
+    for instance in store.instances {
+        let resolved_pid = resolve_pid_from_entries(
+            platform,
+            instance.last_pid,
+            &instance.user_data_dir,
+            &entries,
+        );
+        result.push(InstanceRuntimeInfo {
+            id: instance.id,
+            name: instance.name,
+            user_data_dir: instance.user_data_dir,
+            is_default: false,
+            last_launched_at: instance.last_launched_at,
+            is_running: resolved_pid.is_some(),
+            resolved_pid,
+        });
+    }
+
+    result.sort_by_key(|i| std::cmp::Reverse(i.last_launched_at));
+    Ok(result)
+}
+
+/// 列出指定平台各实例（含默认实例）的实时进程状态
+/// 只读且只枚举一次进程列表
+pub fn list_running_instances(platform: &str) -> Result<Vec<RunningInstance>, String> {
+    let store = load_instance_store(platform)?;
+    let entries = collect_process_entries(platform);
+    let build = |instance_id: String, last_pid: Option<u32>, user_data_dir: String| {
+        let resolved_pid = resolve_pid_from_entries(platform, last_pid, &user_data_dir, &entries);
+        RunningInstance {
+            instance_id,
+            pid: resolved_pid.or(last_pid),
+            alive: resolved_pid.is_some(),
+            resolved_user_data_dir: modules::process::normalize_path_for_compare(&user_data_dir),
+            user_data_dir,
+        }
+    };
+
+    let mut result = Vec::with_capacity(store.instances.len() + 1);
+    if let Ok(default_dir) = get_default_user_data_dir(platform) {
+        result.push(build(
+            DEFAULT_INSTANCE_ID.to_string(),
+            None,
+            default_dir.to_string_lossy().to_string(),
+        ));
+    }
+    for instance in store.instances {
+        result.push(build(
+            instance.id,
+            instance.last_pid,
+            instance.user_data_dir,
+        ));
+    }
+    Ok(result)
+}
+
+/// 诊断指定平台运行中进程命令行里的目录编码（非 ASCII 路径匹配失败排查）
+pub fn diagnose_path_encoding(
+    platform: &str,
+) -> Result<Vec<modules::process::PathEncodingDiagnosis>, String> {
+    if !INSTANCE_PLATFORM_IDS.contains(&platform) {
+        return Err(unknown_platform(platform));
+    }
+    let known_dirs: Vec<String> = collect_instance_dir_entries()
+        .into_iter()
+        .filter(|entry| entry.platform == platform)
+        .map(|entry| entry.user_data_dir)
+        .collect();
+    let entries = collect_process_entries(platform);
+    let result = modules::process::diagnose_path_encoding_entries(&entries, &known_dirs);
+    let failed = result.iter().filter(|item| item.issue.is_some()).count();
+    if failed > 0 {
+        modules::logger::log_warn(&format!(
+            "[Instance] 检测到 {} 个进程目录编码异常: platform={}",
+            failed, platform
+        ));
+    }
+    Ok(result)
+}
+
+/// 列出指定平台可见的全部相关进程（含辅助进程），并标记哪些被实例管理识别为主进程
+/// 仅用于排查实例无法关闭 / 聚焦的问题
+pub fn debug_list_product_processes(
+    platform: &str,
+) -> Result<Vec<modules::process::ProcDebugEntry>, String> {
+    let matches: fn(&str, &str) -> bool = match platform {
+        PLATFORM_ANTIGRAVITY => {
+            |name, exe| name.contains("antigravity") || exe.contains("antigravity")
+        }
+        PLATFORM_CODEX => |name, exe| name.contains("codex") || exe.contains("codex"),
+        PLATFORM_GITHUB_COPILOT => |name, exe| {
+            name == "code"
+                || name == "code.exe"
+                || name.starts_with("code helper")
+                || exe.contains("visual studio code")
+        },
+        PLATFORM_WINDSURF => |name, exe| name.contains("windsurf") || exe.contains("windsurf"),
+        PLATFORM_KIRO => |name, exe| name.contains("kiro") || exe.contains("kiro"),
+        _ => return Err(unknown_platform(platform)),
+    };
+    let tracked = collect_process_entries(platform);
+    let mut entries = modules::process::collect_debug_process_entries(matches);
+    for entry in &mut entries {
+        let Some((_, tracked_value)) = tracked.iter().find(|(pid, _)| *pid == entry.pid) else {
+            continue;
+        };
+        entry.tracked = true;
+        // Codex 的进程条目记录的是 CODEX_HOME，其余平台为 user-data-dir
+        if platform == PLATFORM_CODEX {
+            if tracked_value.is_some() {
+                entry.codex_home = tracked_value.clone();
+            }
+        } else if entry.user_data_dir.is_none() {
+            entry.user_data_dir = tracked_value.clone();
+        }
+    }
+    modules::logger::log_info(&format!(
+        "[Instance] 诊断进程列表: platform={}, total={}, tracked={}",
+        platform,
+        entries.len(),
+        entries.iter().filter(|entry| entry.tracked).count()
+    ));
+    Ok(entries)
+}
+
+/// 根据 PID 识别其所属的托管实例（PID 可为实例主进程或其子进程）
+pub fn identify_instance_by_pid(pid: u32) -> Option<InstanceDirEntry> {
+    let chain = modules::process::collect_pid_with_ancestors(pid);
+    let all_entries = collect_instance_dir_entries();
+    for platform in INSTANCE_PLATFORM_IDS {
+        let entries = collect_process_entries(platform);
+        if !entries
+            .iter()
+            .any(|(entry_pid, _)| chain.contains(entry_pid))
+        {
+            continue;
+        }
+        let matched = all_entries
+            .iter()
+            .filter(|entry| entry.platform == platform)
+            .find(|entry| {
+                resolve_pid_from_entries(platform, None, &entry.user_data_dir, &entries)
+                    .map(|resolved| chain.contains(&resolved))
+                    .unwrap_or(false)
+            });
+        if let Some(entry) = matched {
+            return Some(entry.clone());
+        }
+    }
+    None
+}
+
+/// 识别前台窗口所属的托管实例；不属于任何实例时返回 managed=false 而非报错
+pub fn identify_foreground_instance() -> Result<ActiveInstanceResult, String> {
+    let foreground_pid = modules::process::get_foreground_window_pid()?;
+    let result = match identify_instance_by_pid(foreground_pid) {
+        Some(entry) => ActiveInstanceResult {
+            managed: true,
+            foreground_pid,
+            message: format!("{} 实例: {}", entry.platform, entry.user_data_dir),
+            instance: Some(entry),
+        },
+        None => ActiveInstanceResult {
+            managed: false,
+            foreground_pid,
+            instance: None,
+            message: "前台窗口不是受管理的实例".to_string(),
+        },
+    };
+    Ok(result)
+}
+
+/// 解析实例（含默认实例）的数据目录
+pub fn resolve_instance_user_data_dir(
+    platform: &str,
+    instance_id: &str,
+) -> Result<PathBuf, String> {
+    if instance_id == DEFAULT_INSTANCE_ID {
+        return get_default_user_data_dir(platform);
+    }
+    load_instance_store(platform)?
+        .instances
+        .into_iter()
+        .find(|instance| instance.id == instance_id)
+        .map(|instance| PathBuf::from(instance.user_data_dir))
+        .ok_or_else(|| "实例不存在".to_string())
+}
+
+/// 检测实例的凭据数据库（state.vscdb）是否被占用；数据库尚不存在时视为未锁定
+pub fn is_instance_credential_db_locked(platform: &str, instance_id: &str) -> Result<bool, String> {
+    if platform == PLATFORM_CODEX {
+        return Err("Codex 实例不使用 state.vscdb".to_string());
+    }
+    let db_path = resolve_instance_user_data_dir(platform, instance_id)?
+        .join("User")
+        .join("globalStorage")
+        .join("state.vscdb");
+    if !db_path.exists() {
+        return Ok(false);
+    }
+    modules::db::is_db_locked(&db_path)
+}
+
+/// 获取账号的跨设备稳定标识（邮箱或登录名），用于在其他机器上重新匹配账号
+pub fn account_identity(platform: &str, account_id: &str) -> Option<String> {
+    match platform {
+        PLATFORM_ANTIGRAVITY => modules::load_account(account_id)
+            .ok()
+            .map(|account| account.email),
+        PLATFORM_CODEX => {
+            modules::codex_account::load_account(account_id).map(|account| account.email)
+        }
+        PLATFORM_GITHUB_COPILOT => modules::github_copilot_account::load_account(account_id)
+            .map(|account| account.github_login),
+        PLATFORM_WINDSURF => {
+            modules::windsurf_account::load_account(account_id).map(|account| account.github_login)
+        }
+        PLATFORM_KIRO => {
+            modules::kiro_account::load_account(account_id).map(|account| account.email)
+        }
+        _ => None,
+    }
+}
+
+/// 按账号标识（邮箱或登录名，忽略大小写）查找本机账号 ID
+pub fn find_account_id_by_identity(platform: &str, identity: &str) -> Option<String> {
+    let target = identity.trim().to_lowercase();
+    if target.is_empty() {
+        return None;
+    }
+    let candidates: Vec<(String, String)> = match platform {
+        PLATFORM_ANTIGRAVITY => modules::list_accounts()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|account| (account.id, account.email))
+            .collect(),
+        PLATFORM_CODEX => modules::codex_account::list_accounts()
+            .into_iter()
+            .map(|account| (account.id, account.email))
+            .collect(),
+        PLATFORM_GITHUB_COPILOT => modules::github_copilot_account::list_accounts()
+            .into_iter()
+            .map(|account| (account.id, account.github_login))
+            .collect(),
+        PLATFORM_WINDSURF => modules::windsurf_account::list_accounts()
+            .into_iter()
+            .map(|account| (account.id, account.github_login))
+            .collect(),
+        PLATFORM_KIRO => modules::kiro_account::list_accounts()
+            .into_iter()
+            .map(|account| (account.id, account.email))
+            .collect(),
+        _ => Vec::new(),
+    };
+    candidates
+        .into_iter()
+        .find(|(_, label)| label.trim().to_lowercase() == target)
+        .map(|(id, _)| id)
+}
+
+/// 检查实例配置中的问题（纯计算，目录与账号是否存在由调用方判断）
+pub fn find_instance_issues<D, A>(
+    store: &InstanceStore,
+    dir_exists: D,
+    account_exists: A,
+) -> Vec<InstanceIssue>
+where
+    D: Fn(&str) -> bool,
+    A: Fn(&str) -> bool,
+{
+    let mut issues = Vec::new();
+    for instance in &store.instances {
+        let mut push = |kind: InstanceIssueKind, message: String| {
+            issues.push(InstanceIssue {
+                kind,
+                instance_id: instance.id.clone(),
+                instance_name: instance.name.clone(),
+                user_data_dir: instance.user_data_dir.clone(),
+                message,
+            });
+        };
+
+        let dir = instance.user_data_dir.trim();
+        if dir.is_empty() || !dir_exists(dir) {
+            push(
+                InstanceIssueKind::MissingDir,
+                format!("实例目录不存在: {}", instance.user_data_dir),
+            );
+        }
+
+        let (name_taken, dir_taken) = modules::instance_store::find_unique_conflicts(
+            store,
+            &instance.name,
+            &instance.user_data_dir,
+            Some(&instance.id),
+        );
+        if name_taken {
+            push(
+                InstanceIssueKind::DuplicateName,
+                format!("实例名称重复: {}", instance.name),
+            );
+        }
+        if dir_taken {
+            push(
+                InstanceIssueKind::DuplicateUserDataDir,
+                format!("多个实例共用目录: {}", instance.user_data_dir),
+            );
+        }
+
+        if let Some(account_id) = instance
+            .bind_account_id
+            .as_deref()
+            .filter(|id| !id.trim().is_empty())
+        {
+            if !account_exists(account_id) {
+                push(
+                    InstanceIssueKind::DanglingBindAccount,
+                    format!("绑定的账号已不存在: {}", account_id),
+                );
+            }
+        }
+    }
+    issues
+}
+
+/// 检查指定平台的实例配置（只读）
+pub fn verify_instance_store(platform: &str) -> Result<Vec<InstanceIssue>, String> {
+    let store = load_instance_store(platform)?;
+    Ok(find_instance_issues(
+        &store,
+        |dir| Path::new(dir).exists(),
+        |account_id| account_identity(platform, account_id).is_some(),
+    ))
+}
+
+/// 移除目录已不存在的实例；写入前先备份原实例配置
+pub fn prune_instance_store(platform: &str) -> Result<PruneInstanceStoreResult, String> {
+    let mut store = load_instance_store(platform)?;
+    let (kept, removed): (Vec<_>, Vec<_>) =
+        store.instances.clone().into_iter().partition(|instance| {
+            let dir = instance.user_data_dir.trim();
+            !dir.is_empty() && Path::new(dir).exists()
+        });
+    if removed.is_empty() {
+        return Ok(PruneInstanceStoreResult {
+            removed: Vec::new(),
+            backup_path: None,
+        });
+    }
+
+    let backup_path = modules::account::get_data_dir()?.join(format!(
+        "{}_instances.bak.{}.json",
+        platform,
+        chrono::Utc::now().timestamp()
+    ));
+    let content =
+        serde_json::to_string_pretty(&store).map_err(|e| format!("序列化实例配置失败: {}", e))?;
+    std::fs::write(&backup_path, content).map_err(|e| format!("备份实例配置失败: {}", e))?;
+
+    store.instances = kept;
+    save_instance_store(platform, &store)?;
+
+    let removed: Vec<InstanceDirEntry> = removed
+        .into_iter()
+        .map(|instance| InstanceDirEntry {
+            platform: platform.to_string(),
+            instance_id: instance.id,
+            instance_name: instance.name,
+            user_data_dir: instance.user_data_dir,
+        })
+        .collect();
+    modules::logger::log_info(&format!(
+        "[Instance] 已清理目录不存在的实例: platform={}, removed={}",
+        platform,
+        removed.len()
+    ));
+    Ok(PruneInstanceStoreResult {
+        removed,
+        backup_path: Some(backup_path.to_string_lossy().to_string()),
+    })
+}
+
+/// 汇总所有平台的实例目录（含各平台默认实例目录）
+/// 某个平台在当前系统不可用时（如 Codex 非 macOS）直接跳过
+pub fn collect_instance_dir_entries() -> Vec<InstanceDirEntry> {
+    let mut entries = Vec::new();
+    for platform in INSTANCE_PLATFORM_IDS {
+        if let Ok(default_dir) = get_default_user_data_dir(platform) {
+            entries.push(InstanceDirEntry {
+                platform: platform.to_string(),
+                instance_id: DEFAULT_INSTANCE_ID.to_string(),
+                instance_name: String::new(),
+                user_data_dir: default_dir.to_string_lossy().to_string(),
+            });
+        }
+        let store = match load_instance_store(platform) {
+            Ok(store) => store,
+            Err(err) => {
+                modules::logger::log_warn(&format!(
+                    "[Instance] 读取实例配置失败，跳过: platform={}, err={}",
+                    platform, err
+                ));
+                continue;
+            }
+        };
+        for instance in store.instances {
+            if instance.user_data_dir.trim().is_empty() {
+                continue;
+            }
+            entries.push(InstanceDirEntry {
+                platform: platform.to_string(),
+                instance_id: instance.id,
+                instance_name: instance.name,
+                user_data_dir: instance.user_data_dir,
+            });
+        }
+    }
+    entries
+}
+
+fn normalize_dir_for_compare(raw: &str) -> PathBuf {
+    let trimmed = raw.trim().trim_end_matches(['/', '\\']);
+    let resolved = std::fs::canonicalize(trimmed).unwrap_or_else(|_| PathBuf::from(trimmed));
+    #[cfg(target_os = "windows")]
+    {
+        PathBuf::from(resolved.to_string_lossy().to_lowercase())
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        resolved
+    }
+}
+
+fn classify_dir_overlap(first: &Path, second: &Path) -> Option<&'static str> {
+    if first == second {
+        Some("same")
+    } else if second.starts_with(first) {
+        Some("nested")
+    } else {
+        None
+    }
+}
+
+/// 查找目录相同或互相嵌套的实例对
+/// 同平台内的同目录冲突已由 ensure_unique 阻止，这里主要发现跨平台及嵌套冲突
+pub fn find_dir_conflicts(entries: &[InstanceDirEntry]) -> Vec<InstanceDirConflict> {
+    let normalized: Vec<PathBuf> = entries
+        .iter()
+        .map(|entry| normalize_dir_for_compare(&entry.user_data_dir))
+        .collect();
+    let mut conflicts = Vec::new();
+    for i in 0..entries.len() {
+        for j in (i + 1)..entries.len() {
+            let (outer, inner, kind) =
+                if let Some(kind) = classify_dir_overlap(&normalized[i], &normalized[j]) {
+                    (i, j, kind)
+                } else if let Some(kind) = classify_dir_overlap(&normalized[j], &normalized[i]) {
+                    (j, i, kind)
+                } else {
+                    continue;
+                };
+            conflicts.push(InstanceDirConflict {
+                kind: kind.to_string(),
+                first: entries[outer].clone(),
+                second: entries[inner].clone(),
+            });
+        }
+    }
+    conflicts
+}
+
+/// 检测所有平台实例之间的目录冲突
+pub fn detect_dir_conflicts() -> Vec<InstanceDirConflict> {
+    let entries = collect_instance_dir_entries();
+    let conflicts = find_dir_conflicts(&entries);
+    if !conflicts.is_empty() {
+        modules::logger::log_warn(&format!(
+            "[Instance] 检测到 {} 组实例目录冲突",
+            conflicts.len()
+        ));
+    }
+    conflicts
+}
+
+/// 按目录分组进程条目，返回被多个主进程共用的目录及其 PID（命令行未指定目录时视为默认目录）
+fn find_running_dir_collisions(
+    entries: &[(u32, Option<String>)],
+    default_dir: Option<&str>,
+) -> Vec<(PathBuf, Vec<u32>)> {
+    let mut groups: Vec<(PathBuf, Vec<u32>)> = Vec::new();
+    for (pid, dir) in entries {
+        let Some(raw) = dir
+            .as_deref()
+            .filter(|value| !value.trim().is_empty())
+            .or(default_dir)
+        else {
+            continue;
+        };
+        let normalized = normalize_dir_for_compare(raw);
+        match groups.iter_mut().find(|(path, _)| *path == normalized) {
+            Some((_, pids)) => pids.push(*pid),
+            None => groups.push((normalized, vec![*pid])),
+        }
+    }
+    groups
+        .into_iter()
+        .filter_map(|(path, mut pids)| {
+            pids.sort_unstable();
+            pids.dedup();
+            (pids.len() > 1).then_some((path, pids))
+        })
+        .collect()
+}
+
+/// 检测运行中的进程是否有多个主进程共用同一用户数据目录（如在外部重复启动同一实例）
+/// 与 ensure_unique 不同，这里直接基于进程列表判断，可发现未经本应用启动的冲突
+pub fn detect_running_dir_collisions(platform: &str) -> Result<Vec<RunningDirCollision>, String> {
+    if !INSTANCE_PLATFORM_IDS.contains(&platform) {
+        return Err(unknown_platform(platform));
+    }
+    let default_dir = get_default_user_data_dir(platform)
+        .ok()
+        .map(|dir| dir.to_string_lossy().to_string());
+    let entries = collect_process_entries(platform);
+    let known: Vec<InstanceDirEntry> = collect_instance_dir_entries()
+        .into_iter()
+        .filter(|entry| entry.platform == platform)
+        .collect();
+
+    let collisions: Vec<RunningDirCollision> =
+        find_running_dir_collisions(&entries, default_dir.as_deref())
+            .into_iter()
+            .map(|(path, pids)| RunningDirCollision {
+                platform: platform.to_string(),
+                user_data_dir: path.to_string_lossy().to_string(),
+                pids,
+                instance: known
+                    .iter()
+                    .find(|entry| normalize_dir_for_compare(&entry.user_data_dir) == path)
+                    .cloned(),
+            })
+            .collect();
+    for collision in &collisions {
+        modules::logger::log_warn(&format!(
+            "[Instance] 检测到多个进程共用用户数据目录: platform={}, dir={}, pids={:?}",
+            platform, collision.user_data_dir, collision.pids
+        ));
+    }
+    Ok(collisions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::InstanceProfile;
+
+    fn entry(platform: &str, id: &str, dir: &str) -> InstanceDirEntry {
+        InstanceDirEntry {
+            platform: platform.to_string(),
+            instance_id: id.to_string(),
+            instance_name: id.to_string(),
+            user_data_dir: dir.to_string(),
+        }
+    }
+
+    fn profile(id: &str, name: &str, dir: &str, bind: Option<&str>) -> InstanceProfile {
+        InstanceProfile {
+            id: id.to_string(),
+            name: name.to_string(),
+            user_data_dir: dir.to_string(),
+            extra_args: String::new(),
+            bind_account_id: bind.map(|value| value.to_string()),
+            created_at: 0,
+            last_launched_at: None,
+            last_pid: None,
+            minimize_after_launch: false,
+            low_power: false,
+            launch_args_template: None,
+            env_vars: Default::default(),
+            bind_fingerprint_id: None,
+            follow_local_account: false,
+        }
+    }
+
+    fn issue_kinds(issues: &[InstanceIssue], id: &str) -> Vec<InstanceIssueKind> {
+        issues
+            .iter()
+            .filter(|issue| issue.instance_id == id)
+            .map(|issue| issue.kind)
+            .collect()
+    }
+
+    #[test]
+    fn test_find_instance_issues_reports_each_kind() {
+        let mut store = InstanceStore::new();
+        store.instances = vec![
+            profile("ok", "Work", "/tmp/cockpit-test/work", Some("acc-1")),
+            profile("missing", "Gone", "/tmp/cockpit-test/gone", None),
+            profile("dup-name", "work", "/tmp/cockpit-test/work-2", None),
+            profile("dup-dir-a", "A", "/tmp/cockpit-test/shared", None),
+            profile("dup-dir-b", "B", "/tmp/cockpit-test/SHARED", None),
+            profile("dangling", "C", "/tmp/cockpit-test/c", Some("deleted")),
+        ];
+        let issues = find_instance_issues(
+            &store,
+            |dir| !dir.ends_with("gone"),
+            |account_id| account_id == "acc-1",
+        );
+
+        assert_eq!(
+            issue_kinds(&issues, "ok"),
+            vec![InstanceIssueKind::DuplicateName]
+        );
+        assert_eq!(
+            issue_kinds(&issues, "missing"),
+            vec![InstanceIssueKind::MissingDir]
+        );
+        assert_eq!(
+            issue_kinds(&issues, "dup-name"),
+            vec![InstanceIssueKind::DuplicateName]
+        );
+        assert_eq!(
+            issue_kinds(&issues, "dup-dir-a"),
+            vec![InstanceIssueKind::DuplicateUserDataDir]
+        );
+        assert_eq!(
+            issue_kinds(&issues, "dup-dir-b"),
+            vec![InstanceIssueKind::DuplicateUserDataDir]
+        );
+        assert_eq!(
+            issue_kinds(&issues, "dangling"),
+            vec![InstanceIssueKind::DanglingBindAccount]
+        );
+    }
+
+    #[test]
+    fn test_find_instance_issues_empty_dir_is_missing() {
+        let mut store = InstanceStore::new();
+        store.instances = vec![profile("empty", "Empty", "  ", Some(""))];
+        let issues = find_instance_issues(&store, |_| true, |_| false);
+        assert_eq!(
+            issue_kinds(&issues, "empty"),
+            vec![InstanceIssueKind::MissingDir]
+        );
+    }
+
+    #[test]
+    fn test_find_dir_conflicts_same_and_nested() {
+        let entries = vec![
+            entry(
+                PLATFORM_GITHUB_COPILOT,
+                "a",
+                "/tmp/cockpit-test/profiles/shared",
+            ),
+            entry(PLATFORM_WINDSURF, "b", "/tmp/cockpit-test/profiles/shared/"),
+            entry(PLATFORM_KIRO, "c", "/tmp/cockpit-test/profiles"),
+            entry(PLATFORM_CODEX, "d", "/tmp/cockpit-test/other"),
+        ];
+
+        let conflicts = find_dir_conflicts(&entries);
+        assert_eq!(conflicts.len(), 3);
+        assert_eq!(conflicts[0].kind, "same");
+        assert_eq!(conflicts[1].kind, "nested");
+        assert_eq!(conflicts[1].first.instance_id, "c");
+        assert_eq!(conflicts[1].second.instance_id, "a");
+        assert!(conflicts.iter().all(
+            |conflict| conflict.first.instance_id != "d" && conflict.second.instance_id != "d"
+        ));
+    }
+
+    #[test]
+    fn test_find_running_dir_collisions_groups_by_dir() {
+        let entries = vec![
+            (30, Some("/tmp/cockpit-test/profiles/a/".to_string())),
+            (10, Some("/tmp/cockpit-test/profiles/a".to_string())),
+            (20, Some("/tmp/cockpit-test/profiles/b".to_string())),
+            (40, None),
+            (50, Some("/tmp/cockpit-test/default".to_string())),
+        ];
+        let collisions = find_running_dir_collisions(&entries, Some("/tmp/cockpit-test/default"));
+        assert_eq!(collisions.len(), 2);
+        assert_eq!(collisions[0].1, vec![10, 30]);
+        assert_eq!(collisions[1].1, vec![40, 50]);
+    }
+
+    #[test]
+    fn test_find_dir_conflicts_ignores_sibling_prefix() {
+        let entries = vec![
+            entry(PLATFORM_GITHUB_COPILOT, "a", "/tmp/cockpit-test/code"),
+            entry(PLATFORM_WINDSURF, "b", "/tmp/cockpit-test/code-insiders"),
+        ];
+        assert!(find_dir_conflicts(&entries).is_empty());
+    }
+}
@@ -1,10 +1,25 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use crate::error::file_corrupted_error;
 use crate::models::InstanceStore;
 
+/// Windows 未启用长路径支持时的路径长度上限
+pub const WINDOWS_MAX_PATH: usize = 260;
+/// 为实例目录内部嵌套文件（扩展、缓存等）预留的路径长度
+const INSTANCE_DIR_PATH_HEADROOM: usize = 100;
+
+/// 初始化方式：仅复制来源实例的用户设置
+pub const INIT_MODE_COPY_SETTINGS: &str = "copy_settings";
+
+/// 仅复制设置时复制的项（相对 user-data-dir）
+const USER_SETTINGS_ENTRIES: [&str; 3] = [
+    "User/settings.json",
+    "User/keybindings.json",
+    "User/snippets",
+];
+
 #[derive(Debug, Clone)]
 pub struct CreateInstanceParams {
     pub name: String,
@@ -12,7 +27,12 @@ pub struct CreateInstanceParams {
     pub extra_args: String,
     pub bind_account_id: Option<String>,
     pub copy_source_instance_id: Option<String>,
+    /// copy（默认，完整复制）/ empty（空白实例）/ copy_settings（仅复制用户设置）
     pub init_mode: Option<String>,
+    /// 跟随的启动参数模板名称
+    pub launch_args_template: Option<String>,
+    pub env_vars: HashMap<String, String>,
+    pub bind_fingerprint_id: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -21,6 +41,69 @@ pub struct UpdateInstanceParams {
     pub name: Option<String>,
     pub extra_args: Option<String>,
     pub bind_account_id: Option<Option<String>>,
+    pub minimize_after_launch: Option<bool>,
+    pub low_power: Option<bool>,
+    /// Some(None) 表示不再跟随模板
+    pub launch_args_template: Option<Option<String>>,
+    pub env_vars: Option<HashMap<String, String>>,
+    /// Some(None) 表示解除指纹绑定
+    pub bind_fingerprint_id: Option<Option<String>>,
+    pub follow_local_account: Option<bool>,
+}
+
+/// 不支持指纹绑定的产品收到绑定请求时返回明确的错误，而不是静默忽略
+pub fn reject_fingerprint_binding(
+    product_name: &str,
+    fingerprint_id: Option<&str>,
+) -> Result<(), String> {
+    match fingerprint_id {
+        Some(id) if !id.trim().is_empty() => Err(format!(
+            "FINGERPRINT_BINDING_UNSUPPORTED:{} 实例不支持绑定设备指纹",
+            product_name
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// 非默认实例的“跟随当前账号”目前仅 Codex 支持，其余产品收到开启请求时返回错误
+pub fn reject_follow_local_account(
+    product_name: &str,
+    follow_local_account: Option<bool>,
+) -> Result<(), String> {
+    if follow_local_account == Some(true) {
+        return Err(format!(
+            "FOLLOW_LOCAL_ACCOUNT_UNSUPPORTED:{} 实例不支持跟随当前账号",
+            product_name
+        ));
+    }
+    Ok(())
+}
+
+/// 由启动流程自行设置的环境变量，不允许被实例配置覆盖（按名称忽略大小写比较）
+const RESERVED_ENV_VARS: [&str; 2] = ["CODEX_HOME", "ELECTRON_RUN_AS_NODE"];
+
+/// 校验实例环境变量：去除键两端空白，拒绝空键、非法键与保留变量
+pub fn normalize_env_vars(
+    env_vars: HashMap<String, String>,
+) -> Result<HashMap<String, String>, String> {
+    let mut normalized = HashMap::with_capacity(env_vars.len());
+    for (key, value) in env_vars {
+        let key = key.trim();
+        if key.is_empty() {
+            return Err("环境变量名不能为空".to_string());
+        }
+        if key.starts_with('-') || key.contains(['=', '\0']) || key.contains(char::is_whitespace) {
+            return Err(format!("环境变量名无效: {}", key));
+        }
+        if RESERVED_ENV_VARS
+            .iter()
+            .any(|reserved| reserved.eq_ignore_ascii_case(key))
+        {
+            return Err(format!("环境变量 {} 由启动流程设置，不能在实例中覆盖", key));
+        }
+        normalized.insert(key.to_string(), value);
+    }
+    Ok(normalized)
 }
 
 pub fn load_instance_store(path: &Path, file_name: &str) -> Result<InstanceStore, String> {
@@ -46,8 +129,10 @@ pub fn save_instance_store(
     let temp_path = data_dir.join(format!("{}.tmp", file_name));
     let content =
         serde_json::to_string_pretty(store).map_err(|e| format!("序列化实例配置失败: {}", e))?;
-    fs::write(&temp_path, content).map_err(|e| format!("写入实例配置失败: {}", e))?;
-    fs::rename(temp_path, path).map_err(|e| format!("保存实例配置失败: {}", e))?;
+    fs::write(to_long_path(&temp_path), content)
+        .map_err(|e| format!("写入实例配置失败: {}{}", e, long_path_hint(&temp_path)))?;
+    fs::rename(to_long_path(&temp_path), to_long_path(path))
+        .map_err(|e| format!("保存实例配置失败: {}{}", e, long_path_hint(path)))?;
     Ok(())
 }
 
@@ -69,12 +154,74 @@ pub fn display_path(path: &Path) -> String {
     }
 }
 
-pub fn ensure_unique(
+fn windows_path_len(path: &Path) -> usize {
+    display_path(path).encode_utf16().count()
+}
+
+/// 转换为 Windows 长路径形式（`\\?\` 前缀），绕过 260 字符限制；其他平台原样返回
+/// 含 `.`/`..` 的路径不做转换（长路径前缀会关闭路径规范化）
+pub fn to_long_path(path: &Path) -> PathBuf {
+    #[cfg(target_os = "windows")]
+    {
+        let raw = path.to_string_lossy();
+        let has_relative_part = path.components().any(|part| {
+            matches!(
+                part,
+                std::path::Component::CurDir | std::path::Component::ParentDir
+            )
+        });
+        if !path.is_absolute() || has_relative_part || raw.starts_with(r"\\?\") {
+            return path.to_path_buf();
+        }
+        let raw = raw.replace('/', "\\");
+        if let Some(unc) = raw.strip_prefix(r"\\") {
+            return PathBuf::from(format!(r"\\?\UNC\{}", unc));
+        }
+        return PathBuf::from(format!(r"\\?\{}", raw));
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        path.to_path_buf()
+    }
+}
+
+/// 路径超过 Windows 长度限制时返回附加在错误信息后的提示
+pub fn long_path_hint(path: &Path) -> String {
+    if cfg!(target_os = "windows") && windows_path_len(path) >= WINDOWS_MAX_PATH {
+        format!(
+            "（路径长度超过 Windows {} 字符限制，请选择更短的目录）",
+            WINDOWS_MAX_PATH
+        )
+    } else {
+        String::new()
+    }
+}
+
+/// 检查实例目录路径长度（仅 Windows），为目录内的嵌套文件预留空间
+pub fn check_instance_dir_length(path: &Path) -> Result<(), String> {
+    if !cfg!(target_os = "windows") {
+        return Ok(());
+    }
+    let limit = WINDOWS_MAX_PATH - INSTANCE_DIR_PATH_HEADROOM;
+    let len = windows_path_len(path);
+    if len > limit {
+        return Err(format!(
+            "实例目录路径过长（{} 个字符，建议不超过 {}）: {}。请选择更短的实例根目录，例如 C:\\CockpitInstances",
+            len,
+            limit,
+            display_path(path)
+        ));
+    }
+    Ok(())
+}
+
+/// 检查名称 / 目录是否与其他实例重复（忽略大小写），返回 (名称重复, 目录重复)
+pub fn find_unique_conflicts(
     store: &InstanceStore,
     name: &str,
     user_data_dir: &str,
     current_id: Option<&str>,
-) -> Result<(), String> {
+) -> (bool, bool) {
     let mut names = HashSet::new();
     let mut dirs = HashSet::new();
     for instance in &store.instances {
@@ -86,19 +233,54 @@ pub fn ensure_unique(
         names.insert(instance.name.to_lowercase());
         dirs.insert(instance.user_data_dir.to_lowercase());
     }
-    if names.contains(&name.to_lowercase()) {
+    (
+        names.contains(&name.to_lowercase()),
+        dirs.contains(&user_data_dir.to_lowercase()),
+    )
+}
+
+pub fn ensure_unique(
+    store: &InstanceStore,
+    name: &str,
+    user_data_dir: &str,
+    current_id: Option<&str>,
+) -> Result<(), String> {
+    let (name_taken, dir_taken) = find_unique_conflicts(store, name, user_data_dir, current_id);
+    if name_taken {
         return Err("实例名称已存在".to_string());
     }
-    if dirs.contains(&user_data_dir.to_lowercase()) {
+    if dir_taken {
         return Err("实例目录已存在".to_string());
     }
     Ok(())
 }
 
+/// 复制/归档实例数据目录时默认排除的缓存目录名（可再生成，体积大）
+pub const CACHE_EXCLUDE_DIR_NAMES: [&str; 9] = [
+    "Cache",
+    "CachedData",
+    "Code Cache",
+    "GPUCache",
+    "DawnCache",
+    "GrShaderCache",
+    "ShaderCache",
+    "Crashpad",
+    "logs",
+];
+
+/// 判断目录名是否属于默认排除的缓存目录
+pub fn is_cache_dir_name(name: &str) -> bool {
+    CACHE_EXCLUDE_DIR_NAMES
+        .iter()
+        .any(|item| item.eq_ignore_ascii_case(name))
+}
+
 pub fn copy_dir_recursive(src: &Path, dst: &Path) -> Result<(), String> {
     if !src.exists() {
         return Err(format!("源目录不存在: {}", src.to_string_lossy()));
     }
+    let src = &to_long_path(src);
+    let dst = &to_long_path(dst);
 
     if dst.exists() {
         let mut has_entries = false;
@@ -126,9 +308,98 @@ pub fn copy_dir_recursive(src: &Path, dst: &Path) -> Result<(), String> {
         if file_type.is_dir() {
             copy_dir_recursive(&path, &target)?;
         } else if file_type.is_file() {
-            fs::copy(&path, &target).map_err(|e| format!("复制文件失败: {}", e))?;
+            fs::copy(&path, &target)
+                .map_err(|e| format!("复制文件失败: {}{}", e, long_path_hint(&target)))?;
         }
     }
 
     Ok(())
 }
+
+/// 只复制来源实例的 settings.json、keybindings.json 与 snippets，生成其余为空的新实例目录
+/// 来源中不存在的项直接跳过
+pub fn copy_user_settings(src: &Path, dst: &Path) -> Result<(), String> {
+    let src = &to_long_path(src);
+    let dst = &to_long_path(dst);
+
+    let has_entries = fs::read_dir(dst)
+        .map(|mut iter| iter.next().is_some())
+        .unwrap_or(false);
+    if has_entries {
+        return Err("目标目录已存在且不为空".to_string());
+    }
+    fs::create_dir_all(dst).map_err(|e| format!("创建目标目录失败: {}", e))?;
+
+    for entry in USER_SETTINGS_ENTRIES {
+        let source = src.join(entry);
+        let target = dst.join(entry);
+        if source.is_dir() {
+            copy_dir_recursive(&source, &target)?;
+        } else if source.is_file() {
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent).map_err(|e| format!("创建目标目录失败: {}", e))?;
+            }
+            fs::copy(&source, &target)
+                .map_err(|e| format!("复制文件失败: {}{}", e, long_path_hint(&target)))?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn env(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_normalize_env_vars() {
+        let normalized =
+            normalize_env_vars(env(&[(" HTTP_PROXY ", "http://127.0.0.1:7890")])).unwrap();
+        assert_eq!(
+            normalized.get("HTTP_PROXY").map(String::as_str),
+            Some("http://127.0.0.1:7890")
+        );
+
+        assert!(normalize_env_vars(env(&[("  ", "x")])).is_err());
+        assert!(normalize_env_vars(env(&[("A=B", "x")])).is_err());
+        assert!(normalize_env_vars(env(&[("MY VAR", "x")])).is_err());
+        assert!(normalize_env_vars(env(&[("-flag", "x")])).is_err());
+        assert!(normalize_env_vars(env(&[("codex_home", "/tmp")])).is_err());
+        assert!(normalize_env_vars(env(&[("ELECTRON_RUN_AS_NODE", "1")])).is_err());
+    }
+    #[test]
+    fn test_copy_user_settings_copies_only_settings() {
+        let root = std::env::temp_dir().join(format!(
+            "cockpit-instance-store-test-{}",
+            uuid::Uuid::new_v4()
+        ));
+        let src = root.join("src");
+        let dst = root.join("dst");
+        fs::create_dir_all(src.join("User/snippets")).unwrap();
+        fs::create_dir_all(src.join("User/globalStorage")).unwrap();
+        fs::write(src.join("User/settings.json"), "{}").unwrap();
+        fs::write(src.join("User/snippets/rust.json"), "{}").unwrap();
+        fs::write(src.join("User/globalStorage/state.vscdb"), "db").unwrap();
+        fs::write(src.join("Cookies"), "cookie").unwrap();
+
+        copy_user_settings(&src, &dst).unwrap();
+
+        assert!(dst.join("User/settings.json").is_file());
+        assert!(dst.join("User/snippets/rust.json").is_file());
+        assert!(!dst.join("User/keybindings.json").exists());
+        assert!(!dst.join("User/globalStorage").exists());
+        assert!(!dst.join("Cookies").exists());
+
+        // 目标目录非空时拒绝覆盖
+        assert!(copy_user_settings(&src, &dst).is_err());
+
+        let _ = fs::remove_dir_all(&root);
+    }
+}
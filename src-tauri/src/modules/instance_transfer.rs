@@ -0,0 +1,875 @@
+//! 实例定义导入导出
+//! 定义导入导出仅迁移实例配置（名称、启动参数、启动参数模板、环境变量、账号绑定）；
+//! 完整导入导出将实例配置与数据目录打包为单个归档文件
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::{Component, Path, PathBuf};
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::models::InstanceProfile;
+use crate::modules;
+use crate::modules::instance_registry;
+use crate::modules::instance_store;
+
+const DEFINITIONS_VERSION: u32 = 1;
+
+const ARCHIVE_MAGIC: &[u8; 6] = b"CKIA1\n";
+const ARCHIVE_VERSION: u32 = 1;
+const ARCHIVE_EXTENSION: &str = "cockpit-instance";
+/// 归档超过该大小时提示用户
+const ARCHIVE_SIZE_WARN_BYTES: u64 = 1024 * 1024 * 1024;
+const ARCHIVE_MAX_MANIFEST_BYTES: u32 = 16 * 1024 * 1024;
+const ARCHIVE_MAX_PATH_BYTES: u32 = 4096;
+
+/// 单个实例定义
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstanceDefinition {
+    pub name: String,
+    /// 相对于平台实例根目录的路径；不在根目录下的实例只保留目录名
+    #[serde(default)]
+    pub relative_dir: Option<String>,
+    #[serde(default)]
+    pub extra_args: String,
+    /// 绑定账号的邮箱/登录名（而非本机账号 ID）
+    #[serde(default)]
+    pub bind_account_identity: Option<String>,
+    /// 跟随的启动参数模板名称
+    #[serde(default)]
+    pub launch_args_template: Option<String>,
+    /// 仅对该实例生效的环境变量
+    #[serde(default)]
+    pub env_vars: HashMap<String, String>,
+}
+
+/// 实例定义导出文件
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstanceDefinitionBundle {
+    pub version: u32,
+    pub platform: String,
+    pub exported_at: i64,
+    pub instances: Vec<InstanceDefinition>,
+}
+
+/// 导入结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstanceDefinitionImportResult {
+    pub created: Vec<InstanceProfile>,
+    /// 跳过的实例及原因
+    pub skipped: Vec<String>,
+    /// 未能在本机匹配到账号的绑定标识
+    pub unresolved_bindings: Vec<String>,
+}
+
+/// 完整归档头部清单
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstanceArchiveManifest {
+    pub version: u32,
+    pub platform: String,
+    pub exported_at: i64,
+    pub definition: InstanceDefinition,
+    pub file_count: u64,
+    pub total_bytes: u64,
+    pub include_caches: bool,
+}
+
+/// 完整导出结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstanceFullExportResult {
+    pub path: String,
+    pub file_count: u64,
+    pub total_bytes: u64,
+    /// 被排除的缓存目录（相对数据目录）
+    pub excluded_dirs: Vec<String>,
+    /// 体积过大等提示
+    pub warning: Option<String>,
+}
+
+/// 完整导入结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstanceFullImportResult {
+    pub instance: InstanceProfile,
+    pub file_count: u64,
+    pub total_bytes: u64,
+    /// 未能在本机匹配到账号的绑定标识
+    pub unresolved_binding: Option<String>,
+}
+
+fn relativize_dir(user_data_dir: &str, root_dir: &Path) -> Option<String> {
+    let dir = PathBuf::from(user_data_dir);
+    let relative = match dir.strip_prefix(root_dir) {
+        Ok(relative) if !relative.as_os_str().is_empty() => relative.to_path_buf(),
+        _ => PathBuf::from(dir.file_name()?),
+    };
+    let parts: Vec<String> = relative
+        .components()
+        .map(|part| part.as_os_str().to_string_lossy().to_string())
+        .collect();
+    Some(parts.join("/"))
+}
+
+fn sanitize_dir_name(name: &str) -> String {
+    let sanitized: String = name
+        .trim()
+        .chars()
+        .map(|ch| {
+            if ch.is_alphanumeric() || ch == '-' || ch == '_' {
+                ch
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if sanitized.is_empty() {
+        Uuid::new_v4().to_string()
+    } else {
+        sanitized
+    }
+}
+
+/// 校验实例定义中的相对目录：同时按 `\` 与 `/` 分段，只允许普通路径段
+/// （拒绝 `..`、`.`、绝对路径和盘符）
+fn validate_relative_dir(value: &str) -> Result<PathBuf, String> {
+    let normalized = value.trim().replace('\\', "/");
+    if normalized.starts_with('/') {
+        return Err(format!("实例目录不能是绝对路径: {}", value));
+    }
+    let mut relative = PathBuf::new();
+    for part in normalized.split('/').filter(|part| !part.is_empty()) {
+        let mut components = Path::new(part).components();
+        let valid = !part.contains(':')
+            && matches!(components.next(), Some(Component::Normal(_)))
+            && components.next().is_none();
+        if !valid {
+            return Err(format!("实例目录包含非法路径: {}", value));
+        }
+        relative.push(part);
+    }
+    if relative.as_os_str().is_empty() {
+        return Err("实例目录为空".to_string());
+    }
+    Ok(relative)
+}
+
+fn resolve_target_dir(root_dir: &Path, definition: &InstanceDefinition) -> Result<PathBuf, String> {
+    let relative = match definition
+        .relative_dir
+        .as_deref()
+        .filter(|value| !value.trim().is_empty())
+    {
+        Some(value) => validate_relative_dir(value)?,
+        None => PathBuf::from(sanitize_dir_name(&definition.name)),
+    };
+    let target = root_dir.join(relative);
+    if !target.starts_with(root_dir) {
+        return Err(format!(
+            "实例目录超出实例根目录: {}",
+            instance_store::display_path(&target)
+        ));
+    }
+    Ok(target)
+}
+
+fn definition_from_instance(
+    platform: &str,
+    instance: &InstanceProfile,
+    root_dir: &Path,
+) -> InstanceDefinition {
+    InstanceDefinition {
+        name: instance.name.clone(),
+        relative_dir: relativize_dir(&instance.user_data_dir, root_dir),
+        extra_args: instance.extra_args.clone(),
+        bind_account_identity: instance
+            .bind_account_id
+            .as_deref()
+            .and_then(|id| instance_registry::account_identity(platform, id)),
+        launch_args_template: instance.launch_args_template.clone(),
+        env_vars: instance.env_vars.clone(),
+    }
+}
+
+/// 由实例定义生成新实例（导入的环境变量按本地创建实例的规则校验）
+fn instance_from_definition(
+    definition: &InstanceDefinition,
+    name: String,
+    user_data_dir: String,
+    bind_account_id: Option<String>,
+) -> Result<InstanceProfile, String> {
+    Ok(InstanceProfile {
+        id: Uuid::new_v4().to_string(),
+        name,
+        user_data_dir,
+        extra_args: definition.extra_args.trim().to_string(),
+        bind_account_id,
+        created_at: Utc::now().timestamp_millis(),
+        last_launched_at: None,
+        last_pid: None,
+        minimize_after_launch: false,
+        low_power: false,
+        launch_args_template: definition
+            .launch_args_template
+            .as_deref()
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(str::to_string),
+        env_vars: instance_store::normalize_env_vars(definition.env_vars.clone())?,
+        bind_fingerprint_id: None,
+        follow_local_account: false,
+    })
+}
+
+fn is_dir_non_empty(path: &Path) -> bool {
+    fs::read_dir(path)
+        .map(|mut iter| iter.next().is_some())
+        .unwrap_or(false)
+}
+
+/// 导出指定平台的实例定义（JSON 字符串）
+pub fn export_instance_definitions(platform: &str) -> Result<String, String> {
+    let store = instance_registry::load_instance_store(platform)?;
+    let root_dir = instance_registry::get_default_instances_root_dir(platform)?;
+
+    let instances = store
+        .instances
+        .iter()
+        .map(|instance| definition_from_instance(platform, instance, &root_dir))
+        .collect();
+
+    let bundle = InstanceDefinitionBundle {
+        version: DEFINITIONS_VERSION,
+        platform: platform.to_string(),
+        exported_at: Utc::now().timestamp_millis(),
+        instances,
+    };
+
+    serde_json::to_string_pretty(&bundle).map_err(|e| format!("序列化实例定义失败: {}", e))
+}
+
+/// 导入实例定义：在平台实例根目录下创建空白数据目录，并按账号标识重新绑定
+pub fn import_instance_definitions(
+    platform: &str,
+    data: &str,
+) -> Result<InstanceDefinitionImportResult, String> {
+    let bundle: InstanceDefinitionBundle =
+        serde_json::from_str(data).map_err(|e| format!("解析实例定义失败: {}", e))?;
+    if bundle.platform != platform {
+        return Err(format!(
+            "实例定义平台不匹配: 文件为 {}, 目标为 {}",
+            bundle.platform, platform
+        ));
+    }
+
+    let root_dir = instance_registry::get_default_instances_root_dir(platform)?;
+    let mut store = instance_registry::load_instance_store(platform)?;
+    let mut result = InstanceDefinitionImportResult {
+        created: Vec::new(),
+        skipped: Vec::new(),
+        unresolved_bindings: Vec::new(),
+    };
+
+    for definition in &bundle.instances {
+        let name = match instance_store::normalize_name(&definition.name) {
+            Ok(name) => name,
+            Err(err) => {
+                result.skipped.push(format!("{}: {}", definition.name, err));
+                continue;
+            }
+        };
+        let target_dir = match resolve_target_dir(&root_dir, definition) {
+            Ok(dir) => dir,
+            Err(err) => {
+                result.skipped.push(format!("{}: {}", name, err));
+                continue;
+            }
+        };
+        let user_data_dir = target_dir.to_string_lossy().to_string();
+        if let Err(err) = instance_store::normalize_env_vars(definition.env_vars.clone()) {
+            result.skipped.push(format!("{}: {}", name, err));
+            continue;
+        }
+
+        if let Err(err) = instance_store::ensure_unique(&store, &name, &user_data_dir, None) {
+            result.skipped.push(format!("{}: {}", name, err));
+            continue;
+        }
+        if is_dir_non_empty(&target_dir) {
+            result.skipped.push(format!(
+                "{}: 目标目录已存在且不为空: {}",
+                name,
+                instance_store::display_path(&target_dir)
+            ));
+            continue;
+        }
+        if let Err(err) = fs::create_dir_all(&target_dir) {
+            result
+                .skipped
+                .push(format!("{}: 创建实例目录失败: {}", name, err));
+            continue;
+        }
+
+        let bind_account_id = definition
+            .bind_account_identity
+            .as_deref()
+            .and_then(|identity| {
+                let resolved = instance_registry::find_account_id_by_identity(platform, identity);
+                if resolved.is_none() {
+                    result.unresolved_bindings.push(identity.to_string());
+                }
+                resolved
+            });
+
+        let instance = match instance_from_definition(
+            definition,
+            name.clone(),
+            user_data_dir,
+            bind_account_id,
+        ) {
+            Ok(instance) => instance,
+            Err(err) => {
+                result.skipped.push(format!("{}: {}", name, err));
+                continue;
+            }
+        };
+        store.instances.push(instance.clone());
+        result.created.push(instance);
+    }
+
+    if !result.created.is_empty() {
+        instance_registry::save_instance_store(platform, &store)?;
+    }
+
+    modules::logger::log_info(&format!(
+        "[Instance] 导入实例定义完成: platform={}, created={}, skipped={}, unresolved_bindings={}",
+        platform,
+        result.created.len(),
+        result.skipped.len(),
+        result.unresolved_bindings.len()
+    ));
+
+    Ok(result)
+}
+
+fn format_size(bytes: u64) -> String {
+    const MB: f64 = 1024.0 * 1024.0;
+    if bytes as f64 >= MB * 1024.0 {
+        format!("{:.2} GB", bytes as f64 / (MB * 1024.0))
+    } else {
+        format!("{:.1} MB", bytes as f64 / MB)
+    }
+}
+
+fn collect_archive_files(
+    dir: &Path,
+    relative: &str,
+    include_caches: bool,
+    files: &mut Vec<(String, PathBuf, u64)>,
+    excluded: &mut Vec<String>,
+) -> Result<(), String> {
+    for entry in fs::read_dir(dir).map_err(|e| format!("读取实例目录失败: {}", e))? {
+        let entry = entry.map_err(|e| format!("读取目录项失败: {}", e))?;
+        let name = entry.file_name().to_string_lossy().to_string();
+        let child_relative = if relative.is_empty() {
+            name.clone()
+        } else {
+            format!("{}/{}", relative, name)
+        };
+        let file_type = entry
+            .file_type()
+            .map_err(|e| format!("获取文件类型失败: {}", e))?;
+
+        if file_type.is_dir() {
+            if !include_caches && instance_store::is_cache_dir_name(&name) {
+                excluded.push(child_relative);
+                continue;
+            }
+            collect_archive_files(
+                &entry.path(),
+                &child_relative,
+                include_caches,
+                files,
+                excluded,
+            )?;
+        } else if file_type.is_file() {
+            let size = entry
+                .metadata()
+                .map_err(|e| format!("读取文件信息失败: {}", e))?
+                .len();
+            files.push((child_relative, entry.path(), size));
+        }
+    }
+    Ok(())
+}
+
+/// 校验归档内的相对路径，拒绝绝对路径与 ".." 等越界路径
+fn resolve_archive_entry_path(root: &Path, relative: &str) -> Result<PathBuf, String> {
+    if relative.is_empty() {
+        return Err("归档中存在空路径".to_string());
+    }
+    let mut path = root.to_path_buf();
+    for part in relative.split('/') {
+        if part.is_empty() || part == "." || part == ".." || part.contains(['\\', ':']) {
+            return Err(format!("归档中存在非法路径: {}", relative));
+        }
+        path.push(part);
+    }
+    Ok(path)
+}
+
+fn resolve_archive_dest(dest: &str, name: &str) -> PathBuf {
+    let path = PathBuf::from(dest.trim());
+    if path.is_dir() {
+        path.join(format!(
+            "{}_{}.{}",
+            sanitize_dir_name(name),
+            chrono::Local::now().format("%Y%m%d_%H%M%S"),
+            ARCHIVE_EXTENSION
+        ))
+    } else {
+        path
+    }
+}
+
+fn write_archive(
+    path: &Path,
+    manifest: &InstanceArchiveManifest,
+    files: &[(String, PathBuf, u64)],
+) -> Result<(), String> {
+    let file = fs::File::create(path).map_err(|e| format!("创建归档文件失败: {}", e))?;
+    let mut writer = BufWriter::new(file);
+    let manifest_bytes =
+        serde_json::to_vec(manifest).map_err(|e| format!("序列化归档清单失败: {}", e))?;
+    let write_err = |e: io::Error| format!("写入归档文件失败: {}", e);
+
+    writer.write_all(ARCHIVE_MAGIC).map_err(write_err)?;
+    writer
+        .write_all(&(manifest_bytes.len() as u32).to_le_bytes())
+        .map_err(write_err)?;
+    writer.write_all(&manifest_bytes).map_err(write_err)?;
+
+    for (relative, source, size) in files {
+        let source_file =
+            fs::File::open(source).map_err(|e| format!("读取文件失败 {}: {}", relative, e))?;
+        let path_bytes = relative.as_bytes();
+        writer
+            .write_all(&(path_bytes.len() as u32).to_le_bytes())
+            .map_err(write_err)?;
+        writer.write_all(path_bytes).map_err(write_err)?;
+        writer.write_all(&size.to_le_bytes()).map_err(write_err)?;
+        let copied = io::copy(&mut source_file.take(*size), &mut writer).map_err(write_err)?;
+        if copied != *size {
+            return Err(format!("文件在导出过程中被修改: {}", relative));
+        }
+    }
+
+    writer.flush().map_err(write_err)
+}
+
+fn read_u32(reader: &mut impl Read) -> Result<u32, String> {
+    let mut buf = [0u8; 4];
+    reader
+        .read_exact(&mut buf)
+        .map_err(|e| format!("读取归档失败: {}", e))?;
+    Ok(u32::from_le_bytes(buf))
+}
+
+fn read_u64(reader: &mut impl Read) -> Result<u64, String> {
+    let mut buf = [0u8; 8];
+    reader
+        .read_exact(&mut buf)
+        .map_err(|e| format!("读取归档失败: {}", e))?;
+    Ok(u64::from_le_bytes(buf))
+}
+
+fn read_manifest(reader: &mut impl Read) -> Result<InstanceArchiveManifest, String> {
+    let mut magic = [0u8; 6];
+    reader
+        .read_exact(&mut magic)
+        .map_err(|e| format!("读取归档失败: {}", e))?;
+    if &magic != ARCHIVE_MAGIC {
+        return Err("不是有效的实例归档文件".to_string());
+    }
+    let manifest_len = read_u32(reader)?;
+    if manifest_len > ARCHIVE_MAX_MANIFEST_BYTES {
+        return Err("实例归档清单异常".to_string());
+    }
+    let mut manifest_bytes = vec![0u8; manifest_len as usize];
+    reader
+        .read_exact(&mut manifest_bytes)
+        .map_err(|e| format!("读取归档失败: {}", e))?;
+    let manifest: InstanceArchiveManifest =
+        serde_json::from_slice(&manifest_bytes).map_err(|e| format!("解析归档清单失败: {}", e))?;
+    if manifest.version > ARCHIVE_VERSION {
+        return Err(format!("不支持的归档版本: {}", manifest.version));
+    }
+    // 清单来自外部文件，解包前先校验目录，避免写到实例根目录之外
+    if let Some(relative_dir) = manifest
+        .definition
+        .relative_dir
+        .as_deref()
+        .filter(|value| !value.trim().is_empty())
+    {
+        validate_relative_dir(relative_dir).map_err(|e| format!("归档清单无效: {}", e))?;
+    }
+    Ok(manifest)
+}
+
+fn extract_archive_files(
+    reader: &mut impl Read,
+    manifest: &InstanceArchiveManifest,
+    target_dir: &Path,
+) -> Result<(), String> {
+    for _ in 0..manifest.file_count {
+        let path_len = read_u32(reader)?;
+        if path_len > ARCHIVE_MAX_PATH_BYTES {
+            return Err("归档中的路径过长".to_string());
+        }
+        let mut path_bytes = vec![0u8; path_len as usize];
+        reader
+            .read_exact(&mut path_bytes)
+            .map_err(|e| format!("读取归档失败: {}", e))?;
+        let relative =
+            String::from_utf8(path_bytes).map_err(|_| "归档中的路径编码无效".to_string())?;
+        let size = read_u64(reader)?;
+        let target = resolve_archive_entry_path(target_dir, &relative)?;
+
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("创建目录失败: {}", e))?;
+        }
+        let mut file =
+            fs::File::create(&target).map_err(|e| format!("写入文件失败 {}: {}", relative, e))?;
+        let written = io::copy(&mut reader.by_ref().take(size), &mut file)
+            .map_err(|e| format!("写入文件失败 {}: {}", relative, e))?;
+        if written != size {
+            return Err("实例归档文件不完整".to_string());
+        }
+    }
+    Ok(())
+}
+
+/// 完整导出实例：实例配置与数据目录打包为单个归档文件
+/// `dest` 为目录时自动生成文件名；默认排除缓存目录，实例运行中时拒绝导出
+pub fn export_instance_full(
+    platform: &str,
+    instance_id: &str,
+    dest: &str,
+    include_caches: bool,
+) -> Result<InstanceFullExportResult, String> {
+    let store = instance_registry::load_instance_store(platform)?;
+    let instance = store
+        .instances
+        .iter()
+        .find(|item| item.id == instance_id)
+        .ok_or("实例不存在")?;
+    let running = instance_registry::list_instances_detailed(platform)?
+        .into_iter()
+        .any(|item| item.id == instance_id && item.is_running);
+    if running {
+        return Err("实例正在运行，请先关闭后再导出".to_string());
+    }
+
+    let source_dir = PathBuf::from(&instance.user_data_dir);
+    if !source_dir.is_dir() {
+        return Err(format!(
+            "实例目录不存在: {}",
+            instance_store::display_path(&source_dir)
+        ));
+    }
+    if dest.trim().is_empty() {
+        return Err("导出路径不能为空".to_string());
+    }
+
+    let mut files = Vec::new();
+    let mut excluded_dirs = Vec::new();
+    collect_archive_files(
+        &source_dir,
+        "",
+        include_caches,
+        &mut files,
+        &mut excluded_dirs,
+    )?;
+    let total_bytes: u64 = files.iter().map(|(_, _, size)| size).sum();
+
+    let root_dir = instance_registry::get_default_instances_root_dir(platform)?;
+    let manifest = InstanceArchiveManifest {
+        version: ARCHIVE_VERSION,
+        platform: platform.to_string(),
+        exported_at: Utc::now().timestamp_millis(),
+        definition: definition_from_instance(platform, instance, &root_dir),
+        file_count: files.len() as u64,
+        total_bytes,
+        include_caches,
+    };
+
+    let path = resolve_archive_dest(dest, &instance.name);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("创建导出目录失败: {}", e))?;
+    }
+    if let Err(err) = write_archive(&path, &manifest, &files) {
+        let _ = fs::remove_file(&path);
+        return Err(err);
+    }
+
+    let warning = (total_bytes >= ARCHIVE_SIZE_WARN_BYTES).then(|| {
+        format!(
+            "实例数据较大（{}），导入时请确认目标磁盘空间充足",
+            format_size(total_bytes)
+        )
+    });
+    let path_str = path.to_string_lossy().to_string();
+    modules::logger::log_info(&format!(
+        "[Instance] 完整导出实例: platform={}, id={}, files={}, size={}, path={}",
+        platform,
+        instance_id,
+        files.len(),
+        format_size(total_bytes),
+        path_str
+    ));
+
+    Ok(InstanceFullExportResult {
+        path: path_str,
+        file_count: files.len() as u64,
+        total_bytes,
+        excluded_dirs,
+        warning,
+    })
+}
+
+/// 完整导入实例：在平台实例根目录下选择新的数据目录解包，并按账号标识重新绑定
+/// 名称或目录冲突时自动追加序号
+pub fn import_instance_full(
+    platform: &str,
+    archive: &str,
+) -> Result<InstanceFullImportResult, String> {
+    let file = fs::File::open(archive.trim()).map_err(|e| format!("打开归档文件失败: {}", e))?;
+    let mut reader = BufReader::new(file);
+    let manifest = read_manifest(&mut reader)?;
+    if manifest.platform != platform {
+        return Err(format!(
+            "实例归档平台不匹配: 文件为 {}, 目标为 {}",
+            manifest.platform, platform
+        ));
+    }
+
+    let base_name = instance_store::normalize_name(&manifest.definition.name)?;
+    instance_store::normalize_env_vars(manifest.definition.env_vars.clone())?;
+    let root_dir = instance_registry::get_default_instances_root_dir(platform)?;
+    let base_dir = resolve_target_dir(&root_dir, &manifest.definition)?;
+    let mut store = instance_registry::load_instance_store(platform)?;
+
+    let mut candidate = None;
+    for index in 1..=100 {
+        let (name, dir) = if index == 1 {
+            (base_name.clone(), base_dir.clone())
+        } else {
+            let dir_name = base_dir
+                .file_name()
+                .map(|value| value.to_string_lossy().to_string())
+                .unwrap_or_default();
+            (
+                format!("{} ({})", base_name, index),
+                base_dir.with_file_name(format!("{}_{}", dir_name, index)),
+            )
+        };
+        let dir_str = dir.to_string_lossy().to_string();
+        if instance_store::ensure_unique(&store, &name, &dir_str, None).is_ok()
+            && !is_dir_non_empty(&dir)
+        {
+            candidate = Some((name, dir));
+            break;
+        }
+    }
+    let (name, target_dir) = candidate.ok_or("无法为导入的实例分配名称或目录")?;
+    instance_store::check_instance_dir_length(&target_dir)?;
+
+    fs::create_dir_all(&target_dir).map_err(|e| format!("创建实例目录失败: {}", e))?;
+    if let Err(err) = extract_archive_files(&mut reader, &manifest, &target_dir) {
+        let _ = fs::remove_dir_all(&target_dir);
+        return Err(err);
+    }
+
+    let mut unresolved_binding = None;
+    let bind_account_id = manifest
+        .definition
+        .bind_account_identity
+        .as_deref()
+        .and_then(|identity| {
+            let resolved = instance_registry::find_account_id_by_identity(platform, identity);
+            if resolved.is_none() {
+                unresolved_binding = Some(identity.to_string());
+            }
+            resolved
+        });
+
+    let instance = instance_from_definition(
+        &manifest.definition,
+        name,
+        target_dir.to_string_lossy().to_string(),
+        bind_account_id,
+    )?;
+    store.instances.push(instance.clone());
+    instance_registry::save_instance_store(platform, &store)?;
+
+    modules::logger::log_info(&format!(
+        "[Instance] 完整导入实例: platform={}, id={}, files={}, size={}, dir={}",
+        platform,
+        instance.id,
+        manifest.file_count,
+        format_size(manifest.total_bytes),
+        instance.user_data_dir
+    ));
+
+    Ok(InstanceFullImportResult {
+        instance,
+        file_count: manifest.file_count,
+        total_bytes: manifest.total_bytes,
+        unresolved_binding,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_archive_entry_path_rejects_traversal() {
+        let root = Path::new("/tmp/cockpit-test/instance");
+        assert_eq!(
+            resolve_archive_entry_path(root, "User/settings.json").unwrap(),
+            root.join("User").join("settings.json")
+        );
+        for bad in [
+            "",
+            "../secret",
+            "User/../../x",
+            "/etc/passwd",
+            "C:/x",
+            "a\\b",
+        ] {
+            assert!(resolve_archive_entry_path(root, bad).is_err(), "{}", bad);
+        }
+    }
+
+    fn definition(relative_dir: Option<&str>) -> InstanceDefinition {
+        InstanceDefinition {
+            name: "Work Profile".to_string(),
+            relative_dir: relative_dir.map(|value| value.to_string()),
+            extra_args: String::new(),
+            bind_account_identity: None,
+            launch_args_template: None,
+            env_vars: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_resolve_target_dir_stays_under_root() {
+        let root = Path::new("/tmp/cockpit-test/instances");
+        assert_eq!(
+            resolve_target_dir(root, &definition(Some("team\\alpha"))).unwrap(),
+            root.join("team").join("alpha")
+        );
+        assert_eq!(
+            resolve_target_dir(root, &definition(None)).unwrap(),
+            root.join("Work_Profile")
+        );
+        for bad in [
+            "..",
+            "../x",
+            "..\\..\\x",
+            "a/./b",
+            "/etc/evil",
+            "\\evil",
+            "C:\\evil",
+            "C:evil",
+        ] {
+            assert!(
+                resolve_target_dir(root, &definition(Some(bad))).is_err(),
+                "{}",
+                bad
+            );
+        }
+    }
+
+    fn manifest_bytes(relative_dir: &str) -> Vec<u8> {
+        let manifest = InstanceArchiveManifest {
+            version: ARCHIVE_VERSION,
+            platform: "antigravity".to_string(),
+            exported_at: 0,
+            definition: definition(Some(relative_dir)),
+            file_count: 0,
+            total_bytes: 0,
+            include_caches: false,
+        };
+        let json = serde_json::to_vec(&manifest).unwrap();
+        let mut bytes = ARCHIVE_MAGIC.to_vec();
+        bytes.extend_from_slice(&(json.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&json);
+        bytes
+    }
+
+    #[test]
+    fn test_read_manifest_rejects_escaping_relative_dir() {
+        let ok = read_manifest(&mut io::Cursor::new(manifest_bytes("team/alpha"))).unwrap();
+        assert_eq!(ok.definition.relative_dir.as_deref(), Some("team/alpha"));
+
+        for bad in ["..\\..\\evil", "/etc/evil", "C:\\Users\\evil"] {
+            assert!(
+                read_manifest(&mut io::Cursor::new(manifest_bytes(bad))).is_err(),
+                "{}",
+                bad
+            );
+        }
+    }
+    #[test]
+    fn test_definition_round_trip_keeps_env_vars_and_template() {
+        let root = Path::new("/tmp/cockpit-test/instances");
+        let mut source = instance_from_definition(
+            &definition(None),
+            "Work".to_string(),
+            root.join("work").to_string_lossy().to_string(),
+            None,
+        )
+        .unwrap();
+        source.launch_args_template = Some("proxy".to_string());
+        source.env_vars.insert(
+            "HTTP_PROXY".to_string(),
+            "http://127.0.0.1:7890".to_string(),
+        );
+
+        let exported = definition_from_instance("antigravity", &source, root);
+        let json = serde_json::to_string(&exported).unwrap();
+        let imported: InstanceDefinition = serde_json::from_str(&json).unwrap();
+        let instance = instance_from_definition(
+            &imported,
+            "Work".to_string(),
+            root.join("work").to_string_lossy().to_string(),
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(instance.launch_args_template.as_deref(), Some("proxy"));
+        assert_eq!(instance.env_vars, source.env_vars);
+
+        // 旧版导出文件没有这两个字段时使用默认值
+        let legacy: InstanceDefinition = serde_json::from_str(r#"{"name":"Old"}"#).unwrap();
+        assert!(legacy.launch_args_template.is_none());
+        assert!(legacy.env_vars.is_empty());
+
+        let mut invalid = imported;
+        invalid
+            .env_vars
+            .insert("CODEX_HOME".to_string(), "/tmp".to_string());
+        assert!(
+            instance_from_definition(&invalid, "Work".to_string(), String::new(), None).is_err()
+        );
+    }
+}
@@ -0,0 +1,365 @@
+//! 数据完整性扫描
+//! 启动时（或手动调用）检查账号索引、实例配置、分组配置与指纹存储，
+//! 对可安全修复的问题先备份原文件再自动修复，最后发送一次 `integrity:report` 事件汇总结果
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+use serde::Serialize;
+use tauri::Emitter;
+
+use crate::modules;
+use crate::modules::instance_registry::{self, INSTANCE_PLATFORM_IDS};
+
+pub const INTEGRITY_REPORT_EVENT: &str = "integrity:report";
+
+const TARGET_ACCOUNT_INDEX: &str = "account_index";
+const TARGET_GROUP_SETTINGS: &str = "group_settings";
+const TARGET_FINGERPRINTS: &str = "fingerprints";
+
+const SEVERITY_ERROR: &str = "error";
+const SEVERITY_WARNING: &str = "warning";
+
+/// 单条扫描发现
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IntegrityFinding {
+    /// 检查对象：account_index / instances:<platform> / group_settings / fingerprints
+    pub target: String,
+    /// error：文件损坏无法读取；warning：数据不一致
+    pub severity: String,
+    pub message: String,
+    /// 是否已自动修复
+    pub repaired: bool,
+    /// 修复前备份的文件路径
+    pub backup_path: Option<String>,
+}
+
+/// 完整性扫描报告
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IntegrityReport {
+    pub scanned_at: i64,
+    pub findings: Vec<IntegrityFinding>,
+    pub repaired_count: usize,
+    pub unresolved_count: usize,
+}
+
+fn finding(target: &str, severity: &str, message: String) -> IntegrityFinding {
+    IntegrityFinding {
+        target: target.to_string(),
+        severity: severity.to_string(),
+        message,
+        repaired: false,
+        backup_path: None,
+    }
+}
+
+/// 修复前备份文件（先复制到临时文件再重命名，避免留下不完整的备份）
+fn backup_file(path: &Path) -> Result<Option<String>, String> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let timestamp = chrono::Utc::now().timestamp();
+    let backup_path = path.with_file_name(format!(
+        "{}.bak.{}",
+        path.file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default(),
+        timestamp
+    ));
+    let temp_path = backup_path.with_extension("tmp");
+    fs::copy(path, &temp_path).map_err(|e| format!("备份文件失败: {}", e))?;
+    fs::rename(&temp_path, &backup_path).map_err(|e| format!("备份文件失败: {}", e))?;
+    Ok(Some(backup_path.to_string_lossy().to_string()))
+}
+
+/// 备份后执行修复，修复结果写回 finding
+fn repair_with_backup<F>(item: &mut IntegrityFinding, path: &Path, repair: F)
+where
+    F: FnOnce() -> Result<(), String>,
+{
+    let result = backup_file(path).and_then(|backup_path| {
+        item.backup_path = backup_path;
+        repair()
+    });
+    match result {
+        Ok(()) => item.repaired = true,
+        Err(err) => item.message = format!("{}（自动修复失败: {}）", item.message, err),
+    }
+}
+
+fn scan_account_index(findings: &mut Vec<IntegrityFinding>) {
+    let index_path = match modules::account::get_account_index_path() {
+        Ok(path) => path,
+        Err(err) => {
+            findings.push(finding(TARGET_ACCOUNT_INDEX, SEVERITY_ERROR, err));
+            return;
+        }
+    };
+
+    let index = match modules::account::load_account_index() {
+        Ok(index) => index,
+        Err(err) => {
+            let mut item = finding(TARGET_ACCOUNT_INDEX, SEVERITY_ERROR, err);
+            repair_with_backup(&mut item, &index_path, || {
+                modules::account::rebuild_account_index(None).map(|_| ())
+            });
+            findings.push(item);
+            return;
+        }
+    };
+
+    let accounts_dir = match modules::account::get_accounts_dir() {
+        Ok(dir) => dir,
+        Err(err) => {
+            findings.push(finding(TARGET_ACCOUNT_INDEX, SEVERITY_ERROR, err));
+            return;
+        }
+    };
+    let indexed: HashSet<&str> = index.accounts.iter().map(|item| item.id.as_str()).collect();
+    let missing = indexed
+        .iter()
+        .filter(|id| !accounts_dir.join(format!("{}.json", id)).exists())
+        .count();
+    let unindexed = fs::read_dir(&accounts_dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter_map(|entry| {
+                    let path = entry.path();
+                    if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+                        return None;
+                    }
+                    path.file_stem()
+                        .and_then(|stem| stem.to_str())
+                        .map(|stem| stem.to_string())
+                })
+                .filter(|id| !indexed.contains(id.as_str()))
+                .count()
+        })
+        .unwrap_or(0);
+
+    if missing > 0 || unindexed > 0 {
+        let mut item = finding(
+            TARGET_ACCOUNT_INDEX,
+            SEVERITY_WARNING,
+            format!(
+                "账号索引与账号文件不一致: {} 个索引项缺少文件, {} 个账号文件未被索引",
+                missing, unindexed
+            ),
+        );
+        let current_account_id = index.current_account_id.clone();
+        repair_with_backup(&mut item, &index_path, || {
+            modules::account::rebuild_account_index(current_account_id).map(|_| ())
+        });
+        findings.push(item);
+    }
+
+    let dangling_current = index
+        .current_account_id
+        .as_deref()
+        .map(|id| !accounts_dir.join(format!("{}.json", id)).exists())
+        .unwrap_or(false);
+    if dangling_current && missing == 0 && unindexed == 0 {
+        let mut item = finding(
+            TARGET_ACCOUNT_INDEX,
+            SEVERITY_WARNING,
+            "当前账号指向已删除的账号".to_string(),
+        );
+        repair_with_backup(&mut item, &index_path, || {
+            modules::account::repair_current_account_id().map(|_| ())
+        });
+        findings.push(item);
+    }
+}
+
+fn scan_instance_stores(findings: &mut Vec<IntegrityFinding>) {
+    for platform in INSTANCE_PLATFORM_IDS {
+        let target = format!("instances:{}", platform);
+        let store = match instance_registry::load_instance_store(platform) {
+            Ok(store) => store,
+            Err(err) => {
+                findings.push(finding(&target, SEVERITY_ERROR, err));
+                continue;
+            }
+        };
+
+        let mut seen_ids = HashSet::new();
+        let duplicated = store
+            .instances
+            .iter()
+            .filter(|instance| !seen_ids.insert(instance.id.as_str()))
+            .count();
+        if duplicated > 0 {
+            findings.push(finding(
+                &target,
+                SEVERITY_WARNING,
+                format!("存在 {} 个重复的实例 ID", duplicated),
+            ));
+        }
+
+        let dangling_bindings: Vec<&str> = store
+            .instances
+            .iter()
+            .filter(|instance| {
+                instance
+                    .bind_account_id
+                    .as_deref()
+                    .filter(|id| !id.trim().is_empty())
+                    .map(|id| instance_registry::account_identity(platform, id).is_none())
+                    .unwrap_or(false)
+            })
+            .map(|instance| instance.name.as_str())
+            .collect();
+        if !dangling_bindings.is_empty() {
+            findings.push(finding(
+                &target,
+                SEVERITY_WARNING,
+                format!(
+                    "以下实例绑定的账号已不存在: {}",
+                    dangling_bindings.join(", ")
+                ),
+            ));
+        }
+    }
+}
+
+fn scan_group_settings(findings: &mut Vec<IntegrityFinding>) {
+    let path = modules::group_settings::get_group_settings_path();
+    if path.exists() {
+        let parse_error = fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|content| {
+                serde_json::from_str::<modules::group_settings::GroupSettings>(&content)
+                    .map(|_| ())
+                    .map_err(|e| e.to_string())
+            })
+            .err();
+        if let Some(err) = parse_error {
+            let mut item = finding(
+                TARGET_GROUP_SETTINGS,
+                SEVERITY_ERROR,
+                format!("分组配置无法解析，已回退为默认分组: {}", err),
+            );
+            repair_with_backup(&mut item, &path, || {
+                modules::group_settings::save_group_settings(
+                    &modules::group_settings::GroupSettings::default(),
+                )
+            });
+            findings.push(item);
+            return;
+        }
+    }
+
+    let settings = modules::group_settings::load_group_settings();
+    let mut unnamed: Vec<&str> = settings
+        .group_mappings
+        .values()
+        .filter(|group_id| !settings.group_names.contains_key(*group_id))
+        .map(|group_id| group_id.as_str())
+        .collect();
+    unnamed.sort_unstable();
+    unnamed.dedup();
+    if !unnamed.is_empty() {
+        findings.push(finding(
+            TARGET_GROUP_SETTINGS,
+            SEVERITY_WARNING,
+            format!("以下分组缺少名称: {}", unnamed.join(", ")),
+        ));
+    }
+}
+
+fn scan_fingerprints(findings: &mut Vec<IntegrityFinding>) {
+    let mut store = match modules::fingerprint::load_fingerprint_store() {
+        Ok(store) => store,
+        Err(err) => {
+            findings.push(finding(TARGET_FINGERPRINTS, SEVERITY_ERROR, err));
+            return;
+        }
+    };
+
+    let mut known_ids: HashSet<String> = store
+        .fingerprints
+        .iter()
+        .map(|item| item.id.clone())
+        .collect();
+    if let Some(baseline) = store.original_baseline.as_ref() {
+        known_ids.insert(baseline.id.clone());
+    }
+
+    let dangling_current = store
+        .current_fingerprint_id
+        .as_ref()
+        .map(|id| !known_ids.contains(id))
+        .unwrap_or(false);
+    if dangling_current {
+        let mut item = finding(
+            TARGET_FINGERPRINTS,
+            SEVERITY_WARNING,
+            "当前指纹指向不存在的指纹，已重置为原始指纹".to_string(),
+        );
+        match modules::fingerprint::get_fingerprints_path() {
+            Ok(path) => repair_with_backup(&mut item, &path, || {
+                store.current_fingerprint_id = store
+                    .original_baseline
+                    .as_ref()
+                    .map(|baseline| baseline.id.clone());
+                modules::fingerprint::save_fingerprint_store(&store)
+            }),
+            Err(err) => item.message = format!("{}（自动修复失败: {}）", item.message, err),
+        }
+        findings.push(item);
+    }
+
+    if let Ok(accounts) = modules::list_accounts() {
+        let unbound = accounts
+            .iter()
+            .filter(|account| {
+                account
+                    .fingerprint_id
+                    .as_ref()
+                    .map(|id| !known_ids.contains(id))
+                    .unwrap_or(false)
+            })
+            .count();
+        if unbound > 0 {
+            findings.push(finding(
+                TARGET_FINGERPRINTS,
+                SEVERITY_WARNING,
+                format!("{} 个账号绑定的指纹不存在", unbound),
+            ));
+        }
+    }
+}
+
+/// 执行完整性扫描并发送 `integrity:report` 事件
+pub fn run_integrity_scan() -> IntegrityReport {
+    let mut findings = Vec::new();
+    scan_account_index(&mut findings);
+    scan_instance_stores(&mut findings);
+    scan_group_settings(&mut findings);
+    scan_fingerprints(&mut findings);
+
+    let repaired_count = findings.iter().filter(|item| item.repaired).count();
+    let report = IntegrityReport {
+        scanned_at: chrono::Utc::now().timestamp(),
+        unresolved_count: findings.len() - repaired_count,
+        repaired_count,
+        findings,
+    };
+
+    if report.findings.is_empty() {
+        modules::logger::log_info("[Integrity] 完整性扫描完成，未发现问题");
+    } else {
+        modules::logger::log_warn(&format!(
+            "[Integrity] 完整性扫描完成: 已修复 {} 项, 未解决 {} 项",
+            report.repaired_count, report.unresolved_count
+        ));
+    }
+    if let Some(app_handle) = crate::get_app_handle() {
+        let _ = app_handle.emit(INTEGRITY_REPORT_EVENT, &report);
+    }
+    report
+}
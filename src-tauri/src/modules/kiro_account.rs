@@ -1,4 +1,4 @@
-use rusqlite::{Connection, OptionalExtension};
+use rusqlite::OptionalExtension;
 use serde_json::Value;
 use std::collections::{HashMap, HashSet};
 use std::fs;
@@ -6,7 +6,9 @@ use std::path::PathBuf;
 use std::sync::Mutex;
 use std::time::Instant;
 
-use crate::models::kiro::{KiroAccount, KiroAccountIndex, KiroOAuthCompletePayload};
+use crate::models::kiro::{
+    KiroAccount, KiroAccountIndex, KiroOAuthCompletePayload, KiroRefreshProgress,
+};
 use crate::modules::{account, kiro_oauth, logger};
 
 const ACCOUNTS_INDEX_FILE: &str = "kiro_accounts.json";
@@ -14,6 +16,12 @@ const ACCOUNTS_DIR: &str = "kiro_accounts";
 const LOCAL_AUTH_TOKEN_FILE_NAME: &str = "kiro-auth-token.json";
 const LOCAL_USAGE_DB_KEY: &str = "kiro.kiroAgent";
 const KIRO_QUOTA_ALERT_COOLDOWN_SECONDS: i64 = 10 * 60;
+/// refresh_all_tokens（托盘/定时刷新）使用的并发数
+const LEGACY_REFRESH_CONCURRENCY: usize = 5;
+/// 批量刷新命令默认并发数（AWS 对并发请求较敏感，保守取 3）
+pub const DEFAULT_BATCH_REFRESH_CONCURRENCY: usize = 3;
+/// 批量刷新命令允许的最大并发数
+const MAX_BATCH_REFRESH_CONCURRENCY: usize = 10;
 
 lazy_static::lazy_static! {
     static ref KIRO_ACCOUNT_INDEX_LOCK: Mutex<()> = Mutex::new(());
@@ -160,7 +168,7 @@ fn refresh_summary(index: &mut KiroAccountIndex, account: &KiroAccount) {
     index.accounts.push(account.summary());
 }
 
-fn upsert_account_record(account: KiroAccount) -> Result<KiroAccount, String> {
+pub fn upsert_account_record(account: KiroAccount) -> Result<KiroAccount, String> {
     let _lock = KIRO_ACCOUNT_INDEX_LOCK
         .lock()
         .map_err(|_| "获取 Kiro 账号锁失败".to_string())?;
@@ -732,18 +740,37 @@ pub async fn refresh_account_token(account_id: &str) -> Result<KiroAccount, Stri
 }
 
 pub async fn refresh_all_tokens() -> Result<Vec<(String, Result<KiroAccount, String>)>, String> {
+    let (results, _) =
+        refresh_all_tokens_with_progress(LEGACY_REFRESH_CONCURRENCY, |_| {}).await?;
+    Ok(results)
+}
+
+/// 解析批量刷新命令的并发数：未指定时取默认值，并限制在 1..=10
+pub fn resolve_batch_refresh_concurrency(requested: Option<usize>) -> usize {
+    requested
+        .unwrap_or(DEFAULT_BATCH_REFRESH_CONCURRENCY)
+        .clamp(1, MAX_BATCH_REFRESH_CONCURRENCY)
+}
+
+/// 有限并发批量刷新，逐个账号回调进度；单个账号失败不影响其余账号。
+/// 返回刷新结果与跳过的封禁账号数量。
+pub async fn refresh_all_tokens_with_progress<F>(
+    max_concurrent: usize,
+    on_progress: F,
+) -> Result<(Vec<(String, Result<KiroAccount, String>)>, usize), String>
+where
+    F: Fn(KiroRefreshProgress) + Send + Sync,
+{
     use futures::future::join_all;
+    use std::sync::atomic::{AtomicUsize, Ordering};
     use std::sync::Arc;
     use tokio::sync::Semaphore;
 
-    const MAX_CONCURRENT: usize = 5;
     let accounts = list_accounts();
     let total = accounts.len();
-    let active_accounts: Vec<KiroAccount> = accounts
-        .into_iter()
-        .filter(|account| !is_banned_account(account))
-        .collect();
-    let skipped_banned = total.saturating_sub(active_accounts.len());
+    let (banned_accounts, active_accounts): (Vec<KiroAccount>, Vec<KiroAccount>) =
+        accounts.into_iter().partition(is_banned_account);
+    let skipped_banned = banned_accounts.len();
     if skipped_banned > 0 {
         logger::log_info(&format!(
             "[Kiro Refresh] 跳过封禁账号: skipped={}, total={}",
@@ -751,19 +778,40 @@ pub async fn refresh_all_tokens() -> Result<Vec<(String, Result<KiroAccount, Str
         ));
     }
 
-    let semaphore = Arc::new(Semaphore::new(MAX_CONCURRENT));
+    let completed = AtomicUsize::new(0);
+    for account in banned_accounts {
+        on_progress(KiroRefreshProgress {
+            account_id: account.id,
+            email: account.email,
+            status: "skipped".to_string(),
+            error: account.status_reason,
+            completed: completed.fetch_add(1, Ordering::SeqCst) + 1,
+            total,
+        });
+    }
+
+    let semaphore = Arc::new(Semaphore::new(max_concurrent.max(1)));
     let tasks: Vec<_> = active_accounts
         .into_iter()
         .map(|account| {
-            let id = account.id;
             let semaphore = semaphore.clone();
+            let completed = &completed;
+            let on_progress = &on_progress;
             async move {
                 let _permit = semaphore
                     .acquire_owned()
                     .await
                     .map_err(|e| format!("获取 Kiro 刷新并发许可失败: {}", e))?;
-                let result = refresh_account_token(&id).await;
-                Ok::<(String, Result<KiroAccount, String>), String>((id, result))
+                let result = refresh_account_token(&account.id).await;
+                on_progress(KiroRefreshProgress {
+                    account_id: account.id.clone(),
+                    email: account.email,
+                    status: if result.is_ok() { "success" } else { "failed" }.to_string(),
+                    error: result.as_ref().err().cloned(),
+                    completed: completed.fetch_add(1, Ordering::SeqCst) + 1,
+                    total,
+                });
+                Ok::<(String, Result<KiroAccount, String>), String>((account.id, result))
             }
         })
         .collect();
@@ -776,7 +824,7 @@ pub async fn refresh_all_tokens() -> Result<Vec<(String, Result<KiroAccount, Str
         }
     }
 
-    Ok(results)
+    Ok((results, skipped_banned))
 }
 
 pub fn remove_account(account_id: &str) -> Result<(), String> {
@@ -1066,7 +1114,8 @@ fn average_quota_percentage(metrics: &[(String, i32)]) -> f64 {
     sum as f64 / metrics.len() as f64
 }
 
-fn resolve_current_account_id(accounts: &[KiroAccount]) -> Option<String> {
+/// 解析当前账号：优先默认实例绑定的账号，否则取最近使用的账号
+pub fn resolve_current_account_id(accounts: &[KiroAccount]) -> Option<String> {
     if let Ok(settings) = crate::modules::kiro_instance::load_default_settings() {
         if let Some(bind_id) = settings.bind_account_id {
             let trimmed = bind_id.trim();
@@ -1281,7 +1330,7 @@ pub fn read_local_usage_snapshot() -> Result<Option<Value>, String> {
         return Ok(None);
     }
 
-    let conn = Connection::open(&path)
+    let conn = crate::modules::db::open_readonly(&path)
         .map_err(|e| format!("打开 Kiro 本地数据库失败({}): {}", path.display(), e))?;
 
     let raw = conn
@@ -1302,3 +1351,16 @@ pub fn read_local_usage_snapshot() -> Result<Option<Value>, String> {
         None => Ok(None),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_batch_refresh_concurrency() {
+        assert_eq!(resolve_batch_refresh_concurrency(None), 3);
+        assert_eq!(resolve_batch_refresh_concurrency(Some(0)), 1);
+        assert_eq!(resolve_batch_refresh_concurrency(Some(4)), 4);
+        assert_eq!(resolve_batch_refresh_concurrency(Some(50)), 10);
+    }
+}
@@ -117,6 +117,8 @@ pub fn create_instance(params: CreateInstanceParams) -> Result<InstanceProfile,
     let _lock = KIRO_INSTANCE_STORE_LOCK
         .lock()
         .map_err(|_| "无法获取实例锁")?;
+    let env_vars = instance_store::normalize_env_vars(params.env_vars.clone())?;
+    instance_store::reject_fingerprint_binding("Kiro", params.bind_fingerprint_id.as_deref())?;
     let mut store = load_instance_store()?;
 
     let name = instance_store::normalize_name(&params.name)?;
@@ -128,6 +130,7 @@ pub fn create_instance(params: CreateInstanceParams) -> Result<InstanceProfile,
     instance_store::ensure_unique(&store, &name, &user_data_dir, None)?;
 
     let user_dir_path = PathBuf::from(&user_data_dir);
+    instance_store::check_instance_dir_length(&user_dir_path)?;
     let init_mode = params
         .init_mode
         .as_deref()
@@ -195,6 +198,12 @@ pub fn create_instance(params: CreateInstanceParams) -> Result<InstanceProfile,
         created_at: Utc::now().timestamp_millis(),
         last_launched_at: None,
         last_pid: None,
+        minimize_after_launch: false,
+        low_power: false,
+        launch_args_template: params.launch_args_template,
+        env_vars,
+        bind_fingerprint_id: None,
+        follow_local_account: false,
     };
 
     store.instances.push(instance.clone());
@@ -206,6 +215,10 @@ pub fn update_instance(params: UpdateInstanceParams) -> Result<InstanceProfile,
     let _lock = KIRO_INSTANCE_STORE_LOCK
         .lock()
         .map_err(|_| "无法获取实例锁")?;
+    instance_store::reject_fingerprint_binding(
+        "Kiro",
+        params.bind_fingerprint_id.clone().flatten().as_deref(),
+    )?;
     let mut store = load_instance_store()?;
     let index = store
         .instances
@@ -232,9 +245,18 @@ pub fn update_instance(params: UpdateInstanceParams) -> Result<InstanceProfile,
     if let Some(ref extra_args) = params.extra_args {
         instance.extra_args = extra_args.trim().to_string();
     }
+    if let Some(template) = params.launch_args_template.clone() {
+        instance.launch_args_template = template;
+    }
+    if let Some(env_vars) = params.env_vars.clone() {
+        instance.env_vars = instance_store::normalize_env_vars(env_vars)?;
+    }
     if let Some(bind) = params.bind_account_id.clone() {
         instance.bind_account_id = bind;
     }
+    if let Some(minimize) = params.minimize_after_launch {
+        instance.minimize_after_launch = minimize;
+    }
 
     let updated = instance.clone();
     save_instance_store(&store)?;
@@ -480,17 +502,8 @@ fn extract_user_data_dir_from_command_line(command_line: &str) -> Option<String>
     None
 }
 
-fn is_helper_process(name: &str, args_line: &str) -> bool {
-    args_line.contains("--type=")
-        || name.contains("helper")
-        || name.contains("renderer")
-        || name.contains("gpu")
-        || name.contains("utility")
-        || name.contains("crashpad")
-        || name.contains("sandbox")
-}
-
 pub fn collect_kiro_process_entries() -> Vec<(u32, Option<String>)> {
+    let helper_keywords = modules::process::HelperKeywords::load();
     let mut entries: HashMap<u32, Option<String>> = HashMap::new();
     let mut system = System::new();
     system.refresh_processes(sysinfo::ProcessesToUpdate::All, true);
@@ -524,7 +537,7 @@ pub fn collect_kiro_process_entries() -> Vec<(u32, Option<String>)> {
         #[cfg(target_os = "linux")]
         let is_kiro = name.contains("kiro") || exe_path.contains("/kiro");
 
-        if !is_kiro || is_helper_process(&name, &args_line) {
+        if !is_kiro || modules::process::is_helper_process(&helper_keywords, &name, &args_line) {
             continue;
         }
 
@@ -870,10 +883,10 @@ fn resolve_kiro_launch_path() -> Result<PathBuf, String> {
                 exec.to_string_lossy()
             ));
         }
-        return Err("APP_PATH_NOT_FOUND:kiro".to_string());
+        return Err(modules::process::app_path_stale_error("kiro"));
     }
 
-    Err("APP_PATH_NOT_FOUND:kiro".to_string())
+    Err(modules::process::app_path_missing_error("kiro"))
 }
 
 #[cfg(target_os = "windows")]
@@ -5,7 +5,11 @@ use sha2::{Digest, Sha256};
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 
-use crate::models::kiro::{KiroAccount, KiroOAuthCompletePayload, KiroOAuthStartResponse};
+use crate::error::{parse_retry_after, ApiError};
+use crate::models::kiro::{
+    KiroAccount, KiroLocalFileStatus, KiroLocalFilesReport, KiroOAuthCompletePayload,
+    KiroOAuthStartResponse, KiroUsageSummary,
+};
 use crate::modules::{kiro_account, logger};
 
 const KIRO_AUTH_PORTAL_URL: &str = "https://app.kiro.dev/signin";
@@ -17,6 +21,10 @@ const KIRO_ACCOUNT_STATUS_BANNED: &str = "banned";
 const KIRO_ACCOUNT_STATUS_ERROR: &str = "error";
 const OAUTH_TIMEOUT_SECONDS: u64 = 600;
 const OAUTH_POLL_INTERVAL_MS: u64 = 250;
+const TOKEN_EXCHANGE_MAX_RETRIES: u32 = 2;
+const TOKEN_EXCHANGE_RETRY_BASE_MS: u64 = 500;
+const RUNTIME_USAGE_MAX_RETRIES: u32 = 3;
+const RUNTIME_USAGE_RETRY_BASE_MS: u64 = 250;
 const CALLBACK_PORT_CANDIDATES: [u16; 10] = [
     3128, 4649, 6588, 8008, 9091, 49153, 50153, 51153, 52153, 53153,
 ];
@@ -49,6 +57,9 @@ struct PendingOAuthState {
 
 lazy_static::lazy_static! {
     static ref PENDING_OAUTH_STATE: Arc<Mutex<Option<PendingOAuthState>>> = Arc::new(Mutex::new(None));
+    static ref CALLBACK_SERVER_TASK: Mutex<Option<tokio::task::JoinHandle<()>>> = Mutex::new(None);
+    /// 取消登录时通知回调服务立即退出并释放端口
+    static ref CALLBACK_SERVER_SHUTDOWN: Mutex<Option<Arc<tokio::sync::Notify>>> = Mutex::new(None);
 }
 
 fn now_timestamp() -> i64 {
@@ -125,9 +136,33 @@ fn parse_runtime_error_reason(body: &str) -> Option<String> {
     None
 }
 
-fn parse_banned_reason_from_error(err: &str) -> Option<String> {
-    err.strip_prefix("BANNED:")
-        .and_then(|raw| normalize_non_empty(Some(raw)))
+/// 发送请求并读取状态码、Retry-After 与响应体；仅发送阶段的失败在此转换为 ApiError
+async fn send_api_request(
+    context: &str,
+    request: reqwest::RequestBuilder,
+) -> Result<(reqwest::StatusCode, Option<u64>, String), ApiError> {
+    let response = request
+        .send()
+        .await
+        .map_err(|e| ApiError::from_reqwest(context, e))?;
+    let status = response.status();
+    let retry_after = parse_retry_after(response.headers());
+    let body = response
+        .text()
+        .await
+        .unwrap_or_else(|_| "<no-body>".to_string());
+    Ok((status, retry_after, body))
+}
+
+fn parse_api_json(
+    status: reqwest::StatusCode,
+    body: &str,
+    context: &str,
+) -> Result<Value, ApiError> {
+    serde_json::from_str::<Value>(body).map_err(|e| ApiError::Unexpected {
+        status: status.as_u16(),
+        body: format!("{}: {} (body={})", context, e, body),
+    })
 }
 
 fn get_path_value<'a>(root: &'a Value, path: &[&str]) -> Option<&'a Value> {
@@ -367,14 +402,36 @@ fn build_portal_auth_url(
     url
 }
 
-fn find_available_callback_port() -> Result<u16, String> {
+/// 本地回调服务候选端口（依次尝试）
+/// 优先使用用户配置的端口，之后再尝试内置端口
+pub fn callback_port_candidates() -> Vec<u16> {
+    let configured = crate::modules::config::get_user_config().kiro_oauth_ports;
+    merge_callback_port_candidates(configured)
+}
+
+fn merge_callback_port_candidates(configured: Vec<u16>) -> Vec<u16> {
+    let mut ports = crate::modules::config::normalize_kiro_oauth_ports(configured);
     for port in CALLBACK_PORT_CANDIDATES {
-        if let Ok(listener) = std::net::TcpListener::bind(("127.0.0.1", port)) {
+        if !ports.contains(&port) {
+            ports.push(port);
+        }
+    }
+    ports
+}
+
+fn find_available_callback_port() -> Result<u16, String> {
+    let candidates = callback_port_candidates();
+    for port in &candidates {
+        if let Ok(listener) = std::net::TcpListener::bind(("127.0.0.1", *port)) {
             drop(listener);
-            return Ok(port);
+            return Ok(*port);
         }
     }
-    Err("本地回调端口已被占用，请关闭占用进程后重试".to_string())
+    let tried: Vec<String> = candidates.iter().map(|port| port.to_string()).collect();
+    Err(format!(
+        "本地回调端口已被占用（已尝试: {}），请关闭占用进程或在设置中配置其他端口后重试",
+        tried.join(", ")
+    ))
 }
 
 fn set_callback_result_for_login(
@@ -526,33 +583,52 @@ async fn exchange_code_for_token(
         .and_then(|value| normalize_non_empty(Some(value)))
         .ok_or_else(|| "Kiro 回调缺少 code，无法完成登录".to_string())?;
 
-    let response = reqwest::Client::new()
-        .post(KIRO_TOKEN_ENDPOINT)
-        .header("Content-Type", "application/json")
-        .json(&json!({
-            "code": code,
-            "code_verifier": code_verifier,
-            "redirect_uri": redirect_uri
-        }))
-        .send()
-        .await
-        .map_err(|e| format!("请求 Kiro oauth/token 接口失败: {}", e))?;
-
-    let status = response.status();
-    let body = response
-        .text()
-        .await
-        .unwrap_or_else(|_| "<no-body>".to_string());
-    if !status.is_success() {
-        return Err(format!(
-            "Kiro oauth/token 接口返回异常: status={}, body={}",
-            status, body
-        ));
-    }
+    // 用户已完成浏览器授权，网络抖动或 5xx 时重试换取，避免整个登录流程作废；4xx（如 code 无效）直接失败
+    let client = crate::modules::http::default_client();
+    let mut attempt = 0;
+    let (status, body) = loop {
+        let request = client
+            .post(KIRO_TOKEN_ENDPOINT)
+            .header("Content-Type", "application/json")
+            .json(&json!({
+                "code": code,
+                "code_verifier": code_verifier,
+                "redirect_uri": redirect_uri
+            }));
+        let result = match send_api_request("请求 Kiro oauth/token 接口失败", request).await {
+            Ok((status, _, body)) if status.is_success() => Ok((status, body)),
+            Ok((status, retry_after, body)) => {
+                logger::log_warn(&format!(
+                    "[Kiro OAuth] oauth/token 接口返回异常: status={}, body={}",
+                    status, body
+                ));
+                Err(ApiError::from_status(status, retry_after, body))
+            }
+            Err(err) => Err(err),
+        };
+        match result {
+            Ok(value) => break value,
+            Err(err) if err.is_transient() && attempt < TOKEN_EXCHANGE_MAX_RETRIES => {
+                attempt += 1;
+                logger::log_warn(&format!(
+                    "[Kiro OAuth] oauth/token 换取失败，{}ms 后重试 ({}/{}): {}",
+                    TOKEN_EXCHANGE_RETRY_BASE_MS * attempt as u64,
+                    attempt,
+                    TOKEN_EXCHANGE_MAX_RETRIES,
+                    err
+                ));
+                tokio::time::sleep(std::time::Duration::from_millis(
+                    TOKEN_EXCHANGE_RETRY_BASE_MS * attempt as u64,
+                ))
+                .await;
+            }
+            Err(err) => return Err(err.to_command_error()),
+        }
+    };
 
     let mut token = unwrap_token_response(
-        serde_json::from_str::<Value>(&body)
-            .map_err(|e| format!("解析 Kiro oauth/token 响应失败: {} (body={})", e, body))?,
+        parse_api_json(status, &body, "解析 Kiro oauth/token 响应失败")
+            .map_err(|e| e.to_command_error())?,
     );
     inject_callback_context_into_token(&mut token, callback);
     Ok(token)
@@ -568,6 +644,10 @@ async fn start_callback_server(
     let server = Server::http(format!("127.0.0.1:{}", callback_port))
         .map_err(|e| format!("启动 Kiro OAuth 回调服务失败: {}", e))?;
     let started = std::time::Instant::now();
+    let shutdown = Arc::new(tokio::sync::Notify::new());
+    if let Ok(mut guard) = CALLBACK_SERVER_SHUTDOWN.lock() {
+        *guard = Some(shutdown.clone());
+    }
 
     logger::log_info(&format!(
         "[Kiro OAuth] 本地回调服务启动: login_id={}, port={}",
@@ -724,7 +804,28 @@ async fn start_callback_server(
             break;
         }
 
-        tokio::time::sleep(tokio::time::Duration::from_millis(120)).await;
+        tokio::select! {
+            _ = shutdown.notified() => {
+                logger::log_info(&format!(
+                    "[Kiro OAuth] 登录已取消，关闭本地回调服务: login_id={}, port={}",
+                    expected_login_id, callback_port
+                ));
+                break;
+            }
+            _ = tokio::time::sleep(tokio::time::Duration::from_millis(120)) => {}
+        }
+    }
+
+    // 立即释放回调端口，不等待任务句柄被回收
+    drop(server);
+    if let Ok(mut guard) = CALLBACK_SERVER_SHUTDOWN.lock() {
+        if guard
+            .as_ref()
+            .map(|current| Arc::ptr_eq(current, &shutdown))
+            .unwrap_or(false)
+        {
+            *guard = None;
+        }
     }
 
     Ok(())
@@ -966,6 +1067,30 @@ fn extract_usage_payload(
     )
 }
 
+/// 解析任意一份用量 JSON（纯计算，不读写磁盘、不发请求）
+pub fn parse_usage_summary(raw: &Value) -> KiroUsageSummary {
+    let (
+        plan_name,
+        plan_tier,
+        credits_total,
+        credits_used,
+        bonus_total,
+        bonus_used,
+        usage_reset_at,
+        bonus_expire_days,
+    ) = extract_usage_payload(Some(raw));
+    KiroUsageSummary {
+        plan_name,
+        plan_tier,
+        credits_total,
+        credits_used,
+        bonus_total,
+        bonus_used,
+        usage_reset_at,
+        bonus_expire_days,
+    }
+}
+
 fn extract_profile_arn(auth_token: Option<&Value>, profile: Option<&Value>) -> Option<String> {
     pick_string(
         profile,
@@ -1251,39 +1376,143 @@ pub fn build_payload_from_local_files() -> Result<KiroOAuthCompletePayload, Stri
     build_payload_from_snapshot(auth_token, profile, usage)
 }
 
-async fn refresh_token_via_remote(refresh_token: &str) -> Result<Value, String> {
-    let response = reqwest::Client::new()
+const LOCAL_LOGIN_INCOMPLETE_MESSAGE: &str =
+    "本机 Kiro 登录信息不完整，请先在 Kiro 中完成登录后再导入";
+
+fn check_local_file(
+    path: Result<std::path::PathBuf, String>,
+    read: fn() -> Result<Option<Value>, String>,
+) -> (KiroLocalFileStatus, Option<Value>) {
+    let path = match path {
+        Ok(path) => path,
+        Err(err) => {
+            return (
+                KiroLocalFileStatus {
+                    path: None,
+                    exists: false,
+                    parsed: false,
+                    error: Some(err),
+                },
+                None,
+            )
+        }
+    };
+    let exists = path.exists();
+    let (value, error) = if exists {
+        match read() {
+            Ok(value) => (value, None),
+            Err(err) => (None, Some(err)),
+        }
+    } else {
+        (None, None)
+    };
+    (
+        KiroLocalFileStatus {
+            path: Some(path.to_string_lossy().to_string()),
+            exists,
+            parsed: value.is_some(),
+            error,
+        },
+        value,
+    )
+}
+
+/// 导入前检查本机 Kiro 登录文件：是否存在、能否解析、是否包含 access token 与 profile arn
+pub fn validate_local_files() -> KiroLocalFilesReport {
+    let (auth_token_status, auth_token) = check_local_file(
+        kiro_account::get_default_kiro_auth_token_path(),
+        kiro_account::read_local_auth_token_json,
+    );
+    let (profile_status, profile) = check_local_file(
+        kiro_account::get_default_kiro_profile_path(),
+        kiro_account::read_local_profile_json,
+    );
+    let (usage_status, _) = check_local_file(
+        kiro_account::get_default_kiro_state_db_path(),
+        kiro_account::read_local_usage_snapshot,
+    );
+
+    let has_access_token = pick_string(
+        auth_token.as_ref(),
+        &[
+            &["accessToken"],
+            &["access_token"],
+            &["token"],
+            &["idToken"],
+            &["id_token"],
+            &["accessTokenJwt"],
+        ],
+    )
+    .is_some();
+    let has_refresh_token = pick_string(
+        auth_token.as_ref(),
+        &[&["refreshToken"], &["refresh_token"], &["refreshTokenJwt"]],
+    )
+    .is_some();
+    let has_profile_arn = extract_profile_arn(auth_token.as_ref(), profile.as_ref()).is_some();
+
+    let mut issues = Vec::new();
+    if !auth_token_status.exists {
+        issues.push("未找到 Kiro 授权文件（~/.aws/sso/cache/kiro-auth-token.json）".to_string());
+    } else if let Some(err) = auth_token_status.error.as_ref() {
+        issues.push(err.clone());
+    } else if !has_access_token {
+        issues.push("Kiro 授权文件缺少 access token".to_string());
+    }
+    if let Some(err) = profile_status.error.as_ref() {
+        issues.push(err.clone());
+    }
+    if auth_token_status.parsed && !has_profile_arn {
+        issues.push("未找到 profile arn（授权文件与 profile.json 均缺失）".to_string());
+    }
+
+    let ready = auth_token_status.parsed && has_access_token && has_profile_arn;
+    if !ready {
+        logger::log_warn(&format!(
+            "[Kiro Import] 本机登录文件未就绪: {}",
+            issues.join("; ")
+        ));
+    }
+
+    KiroLocalFilesReport {
+        ready,
+        auth_token: auth_token_status,
+        profile: profile_status,
+        usage_snapshot: usage_status,
+        has_access_token,
+        has_refresh_token,
+        has_profile_arn,
+        issues,
+        message: if ready {
+            None
+        } else {
+            Some(LOCAL_LOGIN_INCOMPLETE_MESSAGE.to_string())
+        },
+    }
+}
+
+async fn refresh_token_via_remote(refresh_token: &str) -> Result<Value, ApiError> {
+    let request = crate::modules::http::default_client()
         .post(KIRO_REFRESH_ENDPOINT)
         .header("Content-Type", "application/json")
         .json(&json!({
             "refreshToken": refresh_token
-        }))
-        .send()
-        .await
-        .map_err(|e| format!("请求 Kiro refreshToken 接口失败: {}", e))?;
-
-    let status = response.status();
-    let body = response
-        .text()
-        .await
-        .unwrap_or_else(|_| "<no-body>".to_string());
+        }));
+    let (status, retry_after, body) =
+        send_api_request("请求 Kiro refreshToken 接口失败", request).await?;
 
     if !status.is_success() {
-        return Err(format!(
-            "Kiro refreshToken 接口返回异常: status={}, body={}",
-            status, body
-        ));
+        return Err(ApiError::from_status(status, retry_after, body));
     }
 
-    serde_json::from_str::<Value>(&body)
-        .map_err(|e| format!("解析 Kiro refreshToken 响应失败: {}", e))
+    parse_api_json(status, &body, "解析 Kiro refreshToken 响应失败")
 }
 
 async fn fetch_usage_limits_via_runtime(
     access_token: &str,
     profile_arn: &str,
     is_email_required: bool,
-) -> Result<Value, String> {
+) -> Result<Value, ApiError> {
     let region = parse_profile_arn_region(profile_arn);
     let endpoint = runtime_endpoint_for_region(region.as_deref());
     let mut url = format!(
@@ -1295,33 +1524,69 @@ async fn fetch_usage_limits_via_runtime(
         url.push_str("&isEmailRequired=true");
     }
 
-    let response = reqwest::Client::new()
-        .get(&url)
-        .header("Authorization", format!("Bearer {}", access_token.trim()))
-        .send()
-        .await
-        .map_err(|e| format!("请求 Kiro runtime usage 接口失败: {}", e))?;
+    request_runtime_usage_with_retry(&url, access_token).await
+}
 
-    let status = response.status();
-    let body = response
-        .text()
-        .await
-        .unwrap_or_else(|_| "<no-body>".to_string());
+/// runtime usage 接口仅对 429 与 5xx 重试，403（视为封禁）等其他错误立即返回
+fn is_runtime_usage_retryable(err: &ApiError) -> bool {
+    match err {
+        ApiError::RateLimited { .. } => true,
+        ApiError::Unexpected { status, .. } => *status >= 500,
+        _ => false,
+    }
+}
+
+/// 带指数退避（250ms、500ms、1000ms）的 runtime usage 请求
+/// 等待使用 tokio sleep，调用方丢弃该 future 即可随时取消重试
+async fn request_runtime_usage_with_retry(
+    url: &str,
+    access_token: &str,
+) -> Result<Value, ApiError> {
+    let client = crate::modules::http::default_client();
+    let mut attempt: u32 = 0;
+    loop {
+        match request_runtime_usage_once(&client, url, access_token).await {
+            Err(err) if is_runtime_usage_retryable(&err) && attempt < RUNTIME_USAGE_MAX_RETRIES => {
+                let delay_ms = RUNTIME_USAGE_RETRY_BASE_MS << attempt;
+                attempt += 1;
+                logger::log_warn(&format!(
+                    "[Kiro] runtime usage 请求失败，{}ms 后重试 ({}/{}): {}",
+                    delay_ms, attempt, RUNTIME_USAGE_MAX_RETRIES, err
+                ));
+                tokio::time::sleep(std::time::Duration::from_millis(delay_ms)).await;
+            }
+            result => return result,
+        }
+    }
+}
+
+async fn request_runtime_usage_once(
+    client: &reqwest::Client,
+    url: &str,
+    access_token: &str,
+) -> Result<Value, ApiError> {
+    let request = client
+        .get(url)
+        .header("Authorization", format!("Bearer {}", access_token.trim()));
+    let (status, retry_after, body) =
+        send_api_request("请求 Kiro runtime usage 接口失败", request).await?;
 
     if !status.is_success() {
+        if status == reqwest::StatusCode::UNAUTHORIZED
+            || status == reqwest::StatusCode::TOO_MANY_REQUESTS
+            || status.is_server_error()
+        {
+            return Err(ApiError::from_status(status, retry_after, body));
+        }
         let reason = parse_runtime_error_reason(&body)
             .or_else(|| (status == reqwest::StatusCode::FORBIDDEN).then(|| body.clone()));
         if let Some(reason) = reason {
-            return Err(format!("BANNED:{}", reason));
+            return Err(ApiError::Banned { reason });
         }
-        return Err(format!(
-            "Kiro runtime usage 接口返回异常: status={}, body={}",
-            status, body
-        ));
+        return Err(ApiError::from_status(status, retry_after, body));
     }
 
-    serde_json::from_str::<Value>(&body)
-        .map_err(|e| format!("解析 Kiro runtime usage 响应失败: {}", e))
+    parse_api_json(status, &body, "解析 Kiro runtime usage 响应失败")
 }
 
 fn merge_refreshed_auth_token_into_payload(
@@ -1549,12 +1814,16 @@ pub async fn enrich_payload_with_runtime_usage(
             set_payload_status(&mut payload, KIRO_ACCOUNT_STATUS_NORMAL, None);
             return payload;
         }
+        Err(ApiError::Banned { reason }) => {
+            set_payload_status(&mut payload, KIRO_ACCOUNT_STATUS_BANNED, Some(reason));
+            return payload;
+        }
         Err(err) => {
-            if let Some(reason) = parse_banned_reason_from_error(&err) {
-                set_payload_status(&mut payload, KIRO_ACCOUNT_STATUS_BANNED, Some(reason));
-                return payload;
-            }
-            set_payload_status(&mut payload, KIRO_ACCOUNT_STATUS_ERROR, Some(err.clone()));
+            set_payload_status(
+                &mut payload,
+                KIRO_ACCOUNT_STATUS_ERROR,
+                Some(err.to_string()),
+            );
             logger::log_warn(&format!(
                 "[Kiro Refresh] runtime usage 首次请求失败，准备尝试 refresh token: {}",
                 err
@@ -1575,7 +1844,11 @@ pub async fn enrich_payload_with_runtime_usage(
             merge_refreshed_auth_token_into_payload(&mut payload, auth_token);
         }
         Err(err) => {
-            set_payload_status(&mut payload, KIRO_ACCOUNT_STATUS_ERROR, Some(err.clone()));
+            set_payload_status(
+                &mut payload,
+                KIRO_ACCOUNT_STATUS_ERROR,
+                Some(err.to_string()),
+            );
             logger::log_warn(&format!(
                 "[Kiro Refresh] refresh token 失败，跳过 runtime usage 回填: {}",
                 err
@@ -1593,10 +1866,18 @@ pub async fn enrich_payload_with_runtime_usage(
             set_payload_status(&mut payload, KIRO_ACCOUNT_STATUS_NORMAL, None);
         }
         Err(err) => {
-            if let Some(reason) = parse_banned_reason_from_error(&err) {
-                set_payload_status(&mut payload, KIRO_ACCOUNT_STATUS_BANNED, Some(reason));
+            if let ApiError::Banned { reason } = &err {
+                set_payload_status(
+                    &mut payload,
+                    KIRO_ACCOUNT_STATUS_BANNED,
+                    Some(reason.clone()),
+                );
             } else {
-                set_payload_status(&mut payload, KIRO_ACCOUNT_STATUS_ERROR, Some(err.clone()));
+                set_payload_status(
+                    &mut payload,
+                    KIRO_ACCOUNT_STATUS_ERROR,
+                    Some(err.to_string()),
+                );
             }
             logger::log_warn(&format!(
                 "[Kiro Refresh] runtime usage 二次请求失败: {}",
@@ -1834,7 +2115,7 @@ pub async fn start_login() -> Result<KiroOAuthStartResponse, String> {
     let expected_login_id = pending.login_id.clone();
     let expected_state = state_token.clone();
     let callback_port = pending.callback_port;
-    tokio::spawn(async move {
+    let task = tokio::spawn(async move {
         if let Err(err) = start_callback_server(
             callback_port,
             expected_login_id.clone(),
@@ -1853,6 +2134,9 @@ pub async fn start_login() -> Result<KiroOAuthStartResponse, String> {
             );
         }
     });
+    if let Ok(mut guard) = CALLBACK_SERVER_TASK.lock() {
+        *guard = Some(task);
+    }
 
     logger::log_info(&format!(
         "[Kiro OAuth] 登录会话已创建: login_id={}, callback_url={}, expires_in={}s",
@@ -1931,9 +2215,58 @@ pub fn cancel_login(login_id: Option<&str>) -> Result<(), String> {
         }
         (None, _) => {}
     }
+    if let Some(shutdown) = CALLBACK_SERVER_SHUTDOWN
+        .lock()
+        .ok()
+        .and_then(|mut guard| guard.take())
+    {
+        shutdown.notify_one();
+    }
     Ok(())
 }
 
+fn callback_server_running() -> bool {
+    CALLBACK_SERVER_TASK
+        .lock()
+        .ok()
+        .and_then(|guard| guard.as_ref().map(|task| !task.is_finished()))
+        .unwrap_or(false)
+}
+
+/// 清理失效的登录会话（已过期，或仍在等待回调但回调服务已退出），返回是否有会话被清理
+pub fn clear_stale_login() -> bool {
+    let server_running = callback_server_running();
+    let stale_login_id = PENDING_OAUTH_STATE.lock().ok().and_then(|guard| {
+        guard
+            .as_ref()
+            .filter(|state| {
+                state.expires_at <= now_timestamp()
+                    || (state.callback_result.is_none() && !server_running)
+            })
+            .map(|state| state.login_id.clone())
+    });
+    let Some(login_id) = stale_login_id else {
+        return false;
+    };
+    if cancel_login(Some(&login_id)).is_err() {
+        return false;
+    }
+    logger::log_info(&format!(
+        "[Kiro OAuth] 已清理失效的登录会话: login_id={}",
+        login_id
+    ));
+    true
+}
+
+/// 取消挂起的登录并取出回调服务任务（应用退出时调用，由调用方等待任务结束）
+pub fn shutdown_callback_server() -> Option<tokio::task::JoinHandle<()>> {
+    let _ = cancel_login(None);
+    CALLBACK_SERVER_TASK
+        .lock()
+        .ok()
+        .and_then(|mut guard| guard.take())
+}
+
 pub async fn build_payload_from_token(token: &str) -> Result<KiroOAuthCompletePayload, String> {
     let trimmed = token.trim();
     if trimmed.is_empty() {
@@ -1972,6 +2305,124 @@ mod tests {
     use super::*;
     use serde_json::json;
 
+    #[test]
+    fn parse_usage_summary_reads_breakdown_list() {
+        let summary = parse_usage_summary(&json!({
+            "subscriptionInfo": {
+                "subscriptionTitle": "KIRO PRO",
+                "type": "Q_DEVELOPER_STANDALONE_PRO"
+            },
+            "usageBreakdownList": [{
+                "usageLimitWithPrecision": 1000.0,
+                "currentUsageWithPrecision": 250.5,
+                "freeTrialInfo": { "usageLimit": 500, "currentUsage": 20, "daysRemaining": 12 }
+            }]
+        }));
+        assert_eq!(summary.plan_name.as_deref(), Some("KIRO PRO"));
+        assert_eq!(
+            summary.plan_tier.as_deref(),
+            Some("Q_DEVELOPER_STANDALONE_PRO")
+        );
+        assert_eq!(summary.credits_total, Some(1000.0));
+        assert_eq!(summary.credits_used, Some(250.5));
+        assert_eq!(summary.bonus_total, Some(500.0));
+        assert_eq!(summary.bonus_used, Some(20.0));
+        assert_eq!(summary.bonus_expire_days, Some(12));
+    }
+
+    #[test]
+    fn callback_ports_prefer_configured_and_skip_invalid() {
+        let ports = merge_callback_port_candidates(vec![18080, 80, 18080, 3128]);
+        assert_eq!(&ports[..2], &[18080, 3128]);
+        assert!(!ports.contains(&80));
+        assert_eq!(ports.len(), 1 + CALLBACK_PORT_CANDIDATES.len());
+
+        let defaults = merge_callback_port_candidates(Vec::new());
+        assert_eq!(defaults, CALLBACK_PORT_CANDIDATES.to_vec());
+    }
+
+    #[tokio::test]
+    async fn cancel_login_releases_callback_port() {
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .and_then(|listener| listener.local_addr())
+            .map(|addr| addr.port())
+            .expect("pick free port");
+        *PENDING_OAUTH_STATE.lock().unwrap() = Some(PendingOAuthState {
+            login_id: "cancel-test".to_string(),
+            expires_at: now_timestamp() + 60,
+            verification_uri: String::new(),
+            verification_uri_complete: String::new(),
+            callback_url: String::new(),
+            callback_port: port,
+            state_token: "cancel-state".to_string(),
+            code_verifier: String::new(),
+            callback_result: None,
+        });
+        let task = tokio::spawn(start_callback_server(
+            port,
+            "cancel-test".to_string(),
+            "cancel-state".to_string(),
+        ));
+        for _ in 0..100 {
+            if CALLBACK_SERVER_SHUTDOWN.lock().unwrap().is_some() {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+        }
+        assert!(std::net::TcpListener::bind(("127.0.0.1", port)).is_err());
+
+        cancel_login(Some("cancel-test")).expect("cancel login");
+        tokio::time::timeout(std::time::Duration::from_millis(100), task)
+            .await
+            .expect("callback server should stop without waiting for the poll interval")
+            .expect("join callback server")
+            .expect("callback server result");
+
+        // tiny_http 的监听线程在 Server 释放后退出，留出极短的收尾时间
+        let mut rebound = false;
+        for _ in 0..20 {
+            if std::net::TcpListener::bind(("127.0.0.1", port)).is_ok() {
+                rebound = true;
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+        }
+        assert!(rebound, "callback port should be re-bindable after cancel");
+    }
+
+    #[tokio::test]
+    async fn runtime_usage_retries_on_503_then_recovers() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Arc;
+
+        let server = tiny_http::Server::http("127.0.0.1:0").expect("start mock server");
+        let port = server.server_addr().to_ip().expect("ip addr").port();
+        let hits = Arc::new(AtomicUsize::new(0));
+        let hits_in_server = hits.clone();
+        std::thread::spawn(move || {
+            for request in server.incoming_requests() {
+                let count = hits_in_server.fetch_add(1, Ordering::SeqCst) + 1;
+                let response = if count <= 2 {
+                    tiny_http::Response::from_string("unavailable").with_status_code(503)
+                } else {
+                    tiny_http::Response::from_string(r#"{"usageBreakdownList":[]}"#)
+                        .with_status_code(200)
+                };
+                let _ = request.respond(response);
+                if count >= 3 {
+                    break;
+                }
+            }
+        });
+
+        let url = format!("http://127.0.0.1:{}/getUsageLimits", port);
+        let value = request_runtime_usage_with_retry(&url, "token")
+            .await
+            .expect("should recover after retries");
+        assert!(value.get("usageBreakdownList").is_some());
+        assert_eq!(hits.load(Ordering::SeqCst), 3);
+    }
+
     #[test]
     fn build_payload_from_snapshot_supports_kiro_raw_json_shape() {
         let auth_token = json!({
@@ -0,0 +1,303 @@
+//! 启动参数模板
+//! 保存常用的 extra_args 组合，实例创建 / 更新时可按名称引用；
+//! 模板参数合并进实例参数（同名选项以模板为准，其余实例参数保留），
+//! 实例选择跟随模板时，模板更新后会把新参数再次合并进这些实例的 extra_args
+//!
+//! 文件路径: ~/.antigravity_cockpit/launch_args_templates.json
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+use crate::error::file_corrupted_error;
+use crate::modules;
+use crate::modules::instance_registry::{self, DEFAULT_INSTANCE_ID, INSTANCE_PLATFORM_IDS};
+
+const TEMPLATES_FILE: &str = "launch_args_templates.json";
+const MAX_TEMPLATE_NAME_CHARS: usize = 40;
+
+static TEMPLATE_STORE_LOCK: std::sync::LazyLock<Mutex<()>> =
+    std::sync::LazyLock::new(|| Mutex::new(()));
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LaunchArgsTemplate {
+    pub name: String,
+    pub args: String,
+    pub updated_at: i64,
+}
+
+/// 保存模板的结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SaveLaunchArgsTemplateResult {
+    pub template: LaunchArgsTemplate,
+    /// 跟随该模板并已同步 extra_args 的实例数量
+    pub synced_instances: usize,
+}
+
+fn templates_path() -> Result<PathBuf, String> {
+    Ok(modules::account::get_data_dir()?.join(TEMPLATES_FILE))
+}
+
+fn load_templates() -> Result<Vec<LaunchArgsTemplate>, String> {
+    let path = templates_path()?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = fs::read_to_string(&path).map_err(|e| format!("读取启动参数模板失败: {}", e))?;
+    if content.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    serde_json::from_str(&content)
+        .map_err(|e| file_corrupted_error(TEMPLATES_FILE, &path.to_string_lossy(), &e.to_string()))
+}
+
+fn save_templates(templates: &[LaunchArgsTemplate]) -> Result<(), String> {
+    let path = templates_path()?;
+    let temp_path = path.with_extension("json.tmp");
+    let content = serde_json::to_string_pretty(templates)
+        .map_err(|e| format!("序列化启动参数模板失败: {}", e))?;
+    fs::write(&temp_path, content).map_err(|e| format!("写入启动参数模板失败: {}", e))?;
+    fs::rename(&temp_path, &path).map_err(|e| format!("保存启动参数模板失败: {}", e))
+}
+
+fn normalize_template_name(name: &str) -> Result<String, String> {
+    let trimmed = name.trim();
+    if trimmed.is_empty() {
+        return Err("模板名称不能为空".to_string());
+    }
+    if trimmed.chars().count() > MAX_TEMPLATE_NAME_CHARS {
+        return Err(format!(
+            "模板名称长度不能超过 {} 个字符",
+            MAX_TEMPLATE_NAME_CHARS
+        ));
+    }
+    Ok(trimmed.to_string())
+}
+
+/// 列出所有启动参数模板（按名称排序）
+pub fn list_templates() -> Result<Vec<LaunchArgsTemplate>, String> {
+    let mut templates = load_templates()?;
+    templates.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(templates)
+}
+
+/// 按名称获取模板参数
+pub fn get_template_args(name: &str) -> Result<String, String> {
+    let name = normalize_template_name(name)?;
+    load_templates()?
+        .into_iter()
+        .find(|template| template.name == name)
+        .map(|template| template.args)
+        .ok_or_else(|| format!("启动参数模板不存在: {}", name))
+}
+
+/// 将参数切分为单元：不带 '=' 的选项与紧随其后的取值视为同一单元
+fn split_arg_units(raw: &str) -> Vec<Vec<String>> {
+    let mut units: Vec<Vec<String>> = Vec::new();
+    for arg in modules::process::parse_extra_args(raw) {
+        let attach_to_option = !arg.starts_with('-')
+            && units.last().is_some_and(|unit| {
+                unit.len() == 1 && unit[0].starts_with('-') && !unit[0].contains('=')
+            });
+        match units.last_mut() {
+            Some(unit) if attach_to_option => unit.push(arg),
+            _ => units.push(vec![arg]),
+        }
+    }
+    units
+}
+
+/// 选项单元的名称（`--name=value` 取 `--name`），位置参数返回 None
+fn option_name(unit: &[String]) -> Option<&str> {
+    let first = unit.first()?;
+    if !first.starts_with('-') {
+        return None;
+    }
+    first.split('=').next()
+}
+
+fn quote_arg(arg: &str) -> String {
+    if !arg.is_empty()
+        && !arg
+            .chars()
+            .any(|ch| ch.is_whitespace() || ch == '"' || ch == '\'')
+    {
+        return arg.to_string();
+    }
+    if arg.contains('"') {
+        format!("'{}'", arg)
+    } else {
+        format!("\"{}\"", arg)
+    }
+}
+
+/// 将模板参数合并进实例参数：同名选项以模板为准，模板未涉及的实例参数保留
+pub fn merge_template_args(current: &str, template_args: &str) -> String {
+    let template_units = split_arg_units(template_args);
+    let template_options: HashSet<String> = template_units
+        .iter()
+        .filter_map(|unit| option_name(unit).map(str::to_string))
+        .collect();
+
+    let mut merged: Vec<Vec<String>> = split_arg_units(current)
+        .into_iter()
+        .filter(|unit| option_name(unit).is_none_or(|name| !template_options.contains(name)))
+        .collect();
+    for unit in template_units {
+        if !merged.contains(&unit) {
+            merged.push(unit);
+        }
+    }
+
+    merged
+        .iter()
+        .flatten()
+        .map(|arg| quote_arg(arg))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// 读取实例当前保存的 extra_args（实例不存在时为空）
+fn current_extra_args(platform: &str, instance_id: &str) -> Result<String, String> {
+    if instance_id == DEFAULT_INSTANCE_ID {
+        return Ok(instance_registry::load_default_settings(platform)?.extra_args);
+    }
+    Ok(instance_registry::load_instance_store(platform)?
+        .instances
+        .into_iter()
+        .find(|instance| instance.id == instance_id)
+        .map(|instance| instance.extra_args)
+        .unwrap_or_default())
+}
+
+/// 将模板参数合并进跟随模板的实例的 extra_args，返回更新的实例数量
+fn sync_instances(name: &str, args: &str) -> usize {
+    let mut synced = 0;
+    for platform in INSTANCE_PLATFORM_IDS {
+        let mut store = match instance_registry::load_instance_store(platform) {
+            Ok(store) => store,
+            Err(err) => {
+                modules::logger::log_warn(&format!(
+                    "[LaunchArgs] 读取实例配置失败，跳过同步: platform={}, err={}",
+                    platform, err
+                ));
+                continue;
+            }
+        };
+        let mut changed = 0;
+        for instance in &mut store.instances {
+            if instance.launch_args_template.as_deref() != Some(name) {
+                continue;
+            }
+            let merged = merge_template_args(&instance.extra_args, args);
+            if instance.extra_args != merged {
+                instance.extra_args = merged;
+                changed += 1;
+            }
+        }
+        if changed == 0 {
+            continue;
+        }
+        match instance_registry::save_instance_store(platform, &store) {
+            Ok(()) => synced += changed,
+            Err(err) => modules::logger::log_warn(&format!(
+                "[LaunchArgs] 同步模板参数失败: platform={}, err={}",
+                platform, err
+            )),
+        }
+    }
+    synced
+}
+
+/// 新建或覆盖启动参数模板，并同步跟随该模板的实例
+pub fn save_template(name: &str, args: &str) -> Result<SaveLaunchArgsTemplateResult, String> {
+    let _lock = TEMPLATE_STORE_LOCK
+        .lock()
+        .map_err(|_| "无法获取启动参数模板锁")?;
+    let name = normalize_template_name(name)?;
+    let args = args.trim().to_string();
+    let template = LaunchArgsTemplate {
+        name: name.clone(),
+        args: args.clone(),
+        updated_at: Utc::now().timestamp_millis(),
+    };
+
+    let mut templates = load_templates()?;
+    match templates.iter_mut().find(|item| item.name == name) {
+        Some(existing) => *existing = template.clone(),
+        None => templates.push(template.clone()),
+    }
+    save_templates(&templates)?;
+
+    let synced_instances = sync_instances(&name, &args);
+    modules::logger::log_info(&format!(
+        "[LaunchArgs] 已保存启动参数模板: name={}, synced_instances={}",
+        name, synced_instances
+    ));
+    Ok(SaveLaunchArgsTemplateResult {
+        template,
+        synced_instances,
+    })
+}
+
+/// 解析实例对模板的引用，返回 (要写入的 extra_args, 跟随模板的变更)
+/// 引用模板时把模板参数合并进本次提交的 extra_args（未提交时取实例当前参数），
+/// follow 为 false 时只应用一次参数并解除跟随；未引用模板时保持原有的跟随状态
+/// instance_id 为 None 表示新建实例
+pub fn resolve_template_reference(
+    platform: &str,
+    instance_id: Option<&str>,
+    extra_args: Option<String>,
+    template: Option<Option<String>>,
+    follow: bool,
+) -> Result<(Option<String>, Option<Option<String>>), String> {
+    match template {
+        Some(Some(name)) if !name.trim().is_empty() => {
+            let template_args = get_template_args(&name)?;
+            let current = match (extra_args, instance_id) {
+                (Some(args), _) => args,
+                (None, Some(instance_id)) => current_extra_args(platform, instance_id)?,
+                (None, None) => String::new(),
+            };
+            let merged = merge_template_args(&current, &template_args);
+            Ok((Some(merged), Some(follow.then(|| name.trim().to_string()))))
+        }
+        Some(_) => Ok((extra_args, Some(None))),
+        None => Ok((extra_args, None)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_template_args_keeps_hand_edited_args() {
+        let merged = merge_template_args(
+            "--disable-gpu --lang=en --log-level debug",
+            "--lang=zh-CN --log-level trace --disable-extensions",
+        );
+        assert_eq!(
+            merged,
+            "--disable-gpu --lang=zh-CN --log-level trace --disable-extensions"
+        );
+    }
+
+    #[test]
+    fn test_merge_template_args_is_idempotent_and_quotes_spaces() {
+        let template = "--extensions-dir \"/opt/my extensions\"";
+        let once = merge_template_args("--disable-gpu", template);
+        assert_eq!(
+            once,
+            "--disable-gpu --extensions-dir \"/opt/my extensions\""
+        );
+        assert_eq!(merge_template_args(&once, template), once);
+        assert_eq!(merge_template_args("", "--new-window"), "--new-window");
+    }
+}
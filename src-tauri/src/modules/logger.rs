@@ -4,11 +4,18 @@ use regex::{Captures, Regex};
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::sync::LazyLock;
-use tracing::{error, info, warn};
+use tracing::field::{Field, Visit};
+use tracing::{error, info, warn, Event, Subscriber};
+use tracing_log::NormalizeEvent;
+use tracing_subscriber::fmt::format::{FormatEvent, FormatFields, Writer};
+use tracing_subscriber::fmt::FmtContext;
+use tracing_subscriber::registry::LookupSpan;
 use tracing_subscriber::{fmt, layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
 
 const LOG_FILE_PREFIX: &str = "app.log";
 const LOG_RETENTION_DAYS: i64 = 3;
+/// 设置为 1 时以 JSON 行格式输出日志（便于用户提交日志后解析）
+const LOG_JSON_ENV: &str = "COCKPIT_LOG_JSON";
 static EMAIL_REGEX: LazyLock<Regex> = LazyLock::new(|| {
     Regex::new(r"(?i)\b[a-z0-9._%+\-]+@[a-z0-9.\-]+\.[a-z]{2,}\b")
         .expect("email regex should be valid")
@@ -23,6 +30,72 @@ impl tracing_subscriber::fmt::time::FormatTime for LocalTimer {
     }
 }
 
+/// JSON 行格式：每条日志输出一个 JSON 对象，字段为 ts / level / target / message
+struct JsonLineFormat;
+
+/// 提取事件的 message 字段，其余字段以 key=value 追加到消息末尾
+#[derive(Default)]
+struct MessageVisitor {
+    message: String,
+    fields: Vec<String>,
+}
+
+impl Visit for MessageVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message = value.to_string();
+        } else if !field.name().starts_with("log.") {
+            self.fields.push(format!("{}={}", field.name(), value));
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            self.message = format!("{:?}", value);
+        } else if !field.name().starts_with("log.") {
+            self.fields.push(format!("{}={:?}", field.name(), value));
+        }
+    }
+}
+
+impl<S, N> FormatEvent<S, N> for JsonLineFormat
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    fn format_event(
+        &self,
+        _ctx: &FmtContext<'_, S, N>,
+        mut writer: Writer<'_>,
+        event: &Event<'_>,
+    ) -> std::fmt::Result {
+        // 通过 log crate 转发的事件需要还原原始 target
+        let normalized = event.normalized_metadata();
+        let metadata = normalized.as_ref().unwrap_or_else(|| event.metadata());
+
+        let mut visitor = MessageVisitor::default();
+        event.record(&mut visitor);
+        let mut message = visitor.message;
+        if !visitor.fields.is_empty() {
+            message = format!("{} {}", message, visitor.fields.join(" "));
+        }
+
+        let line = serde_json::json!({
+            "ts": Local::now().to_rfc3339(),
+            "level": metadata.level().to_string(),
+            "target": metadata.target(),
+            "message": message.trim(),
+        });
+        writeln!(writer, "{}", line)
+    }
+}
+
+fn is_json_log_enabled() -> bool {
+    std::env::var(LOG_JSON_ENV)
+        .map(|value| value.trim() == "1")
+        .unwrap_or(false)
+}
+
 pub fn get_log_dir() -> Result<PathBuf, String> {
     let data_dir = get_data_dir()?;
     let log_dir = data_dir.join("logs");
@@ -107,6 +180,8 @@ fn cleanup_expired_logs(log_dir: &Path) {
 /// 初始化日志系统
 pub fn init_logger() {
     let _ = tracing_log::LogTracer::init();
+    // 记录 panic 到本地崩溃日志（包括后台任务中的 panic）
+    crate::modules::crash_log::install_panic_hook();
 
     let log_dir = match get_log_dir() {
         Ok(dir) => dir,
@@ -119,18 +194,33 @@ pub fn init_logger() {
     let file_appender = tracing_appender::rolling::daily(log_dir.clone(), "app.log");
     let (non_blocking, _guard) = tracing_appender::non_blocking(file_appender);
 
-    let console_layer = fmt::Layer::new()
-        .with_target(false)
-        .with_thread_ids(false)
-        .with_level(true)
-        .with_timer(LocalTimer);
-
-    let file_layer = fmt::Layer::new()
-        .with_writer(non_blocking)
-        .with_ansi(false)
-        .with_target(true)
-        .with_level(true)
-        .with_timer(LocalTimer);
+    let json_output = is_json_log_enabled();
+
+    let console_layer = (!json_output).then(|| {
+        fmt::Layer::new()
+            .with_target(false)
+            .with_thread_ids(false)
+            .with_level(true)
+            .with_timer(LocalTimer)
+    });
+
+    let file_layer = (!json_output).then(|| {
+        fmt::Layer::new()
+            .with_writer(non_blocking.clone())
+            .with_ansi(false)
+            .with_target(true)
+            .with_level(true)
+            .with_timer(LocalTimer)
+    });
+
+    // JSON 模式下控制台与文件均输出 JSON 行，log_info / log_warn / log_error 同样经由这里输出
+    let json_console_layer = json_output.then(|| fmt::Layer::new().event_format(JsonLineFormat));
+    let json_file_layer = json_output.then(|| {
+        fmt::Layer::new()
+            .with_writer(non_blocking)
+            .with_ansi(false)
+            .event_format(JsonLineFormat)
+    });
 
     let filter_layer = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
 
@@ -138,6 +228,8 @@ pub fn init_logger() {
         .with(filter_layer)
         .with(console_layer)
         .with(file_layer)
+        .with(json_console_layer)
+        .with(json_file_layer)
         .try_init();
 
     std::mem::forget(_guard);
@@ -159,7 +251,7 @@ pub fn log_error(message: &str) {
     error!("{}", sanitize_message(message));
 }
 
-fn sanitize_message(message: &str) -> String {
+pub fn sanitize_message(message: &str) -> String {
     EMAIL_REGEX
         .replace_all(message, |caps: &Captures| mask_email(&caps[0]))
         .to_string()
@@ -207,3 +299,50 @@ fn mask_domain_head(head: &str) -> String {
         _ => format!("{}***{}", chars[0], chars[chars.len() - 1]),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Clone, Default)]
+    struct BufferWriter(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for BufferWriter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_json_line_shape() {
+        let buffer = BufferWriter::default();
+        let writer = buffer.clone();
+        let subscriber = tracing_subscriber::registry().with(
+            fmt::Layer::new()
+                .with_writer(move || writer.clone())
+                .event_format(JsonLineFormat),
+        );
+        tracing::subscriber::with_default(subscriber, || {
+            log_warn("刷新失败: user@example.com");
+        });
+
+        let output = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), 1);
+
+        let value: serde_json::Value = serde_json::from_str(lines[0]).unwrap();
+        let object = value.as_object().unwrap();
+        assert_eq!(object.len(), 4);
+        assert!(object["ts"].as_str().is_some());
+        assert_eq!(object["level"], "WARN");
+        assert!(object["target"].as_str().unwrap().ends_with("logger"));
+        assert_eq!(object["message"], "刷新失败: us***r@e***e.com");
+    }
+}
@@ -1,30 +1,48 @@
 pub mod account;
+pub mod account_bundle;
+pub mod app_relaunch;
 pub mod codex_account;
 pub mod codex_instance;
 pub mod codex_oauth;
 pub mod codex_quota;
 pub mod config;
+pub mod crash_log;
+pub mod current_accounts;
+pub mod data_permissions;
 pub mod db;
 pub mod device;
+pub mod fault_injection;
 pub mod fingerprint;
 pub mod github_copilot_account;
 pub mod github_copilot_instance;
 pub mod github_copilot_oauth;
 pub mod group_settings;
+pub mod http;
 pub mod import;
 pub mod instance;
+pub mod instance_registry;
 pub mod instance_store;
+pub mod instance_transfer;
+pub mod integrity;
 pub mod kiro_account;
 pub mod kiro_instance;
 pub mod kiro_oauth;
+pub mod launch_args_template;
 pub mod logger;
 pub mod oauth;
 pub mod oauth_server;
 pub mod opencode_auth;
 pub mod process;
+pub mod providers;
 pub mod quota;
 pub mod quota_cache;
+pub mod quota_refresh_scheduler;
+pub mod quota_report;
+pub mod quota_tags;
+pub mod safe_mode;
+pub mod startup_report;
 pub mod sync_settings;
+pub mod token_probe;
 pub mod tray;
 pub mod tray_layout;
 pub mod update_checker;
@@ -33,6 +51,7 @@ pub mod wakeup;
 pub mod wakeup_history;
 pub mod wakeup_scheduler;
 pub mod websocket;
+pub mod window_state;
 pub mod windsurf_account;
 pub mod windsurf_instance;
 pub mod windsurf_oauth;
@@ -76,7 +76,7 @@ pub fn get_auth_url(redirect_uri: &str, state: Option<&str>) -> String {
 /// 使用 Authorization Code 交换 Token
 pub async fn exchange_code(code: &str, redirect_uri: &str) -> Result<TokenResponse, String> {
     crate::modules::logger::log_info(&format!("开始 Token 交换, redirect_uri: {}", redirect_uri));
-    let client = crate::utils::http::create_client(15);
+    let client = crate::modules::http::create_client(15);
 
     let params = [
         ("client_id", CLIENT_ID),
@@ -126,7 +126,7 @@ pub async fn exchange_code(code: &str, redirect_uri: &str) -> Result<TokenRespon
 
 /// 使用 refresh_token 刷新 access_token
 pub async fn refresh_access_token(refresh_token: &str) -> Result<TokenResponse, String> {
-    let client = crate::utils::http::create_client(15);
+    let client = crate::modules::http::create_client(15);
 
     let params = [
         ("client_id", CLIENT_ID),
@@ -157,7 +157,7 @@ pub async fn refresh_access_token(refresh_token: &str) -> Result<TokenResponse,
 
 /// 获取用户信息
 pub async fn get_user_info(access_token: &str) -> Result<UserInfo, String> {
-    let client = crate::utils::http::create_client(15);
+    let client = crate::modules::http::create_client(15);
 
     let response = client
         .get(USERINFO_URL)
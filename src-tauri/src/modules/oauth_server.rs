@@ -1,4 +1,10 @@
-use crate::modules::oauth;
+use crate::modules::tray_layout::{
+    PLATFORM_ANTIGRAVITY, PLATFORM_CODEX, PLATFORM_GITHUB_COPILOT, PLATFORM_KIRO, PLATFORM_WINDSURF,
+};
+use crate::modules::{
+    codex_oauth, github_copilot_oauth, kiro_oauth, logger, oauth, process, windsurf_oauth,
+};
+use serde::Serialize;
 use std::sync::{Mutex, OnceLock};
 use tauri::Url;
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
@@ -10,15 +16,90 @@ use tokio::time::{timeout, Duration};
 struct OAuthFlowState {
     auth_url: String,
     redirect_uri: String,
+    port: u16,
+    callback_paths: Vec<String>,
     cancel_tx: watch::Sender<bool>,
     code_rx: Option<oneshot::Receiver<Result<String, String>>>,
 }
 
 static OAUTH_FLOW_STATE: OnceLock<Mutex<Option<OAuthFlowState>>> = OnceLock::new();
-const OAUTH_CALLBACK_PATH: &str = "/oauth-callback";
+/// 未指定回调路径时使用的默认路径
+const DEFAULT_OAUTH_CALLBACK_PATH: &str = "/oauth-callback";
 const MAX_HTTP_REQUEST_BYTES: usize = 32 * 1024;
 const REQUEST_READ_TIMEOUT: Duration = Duration::from_secs(5);
 const OAUTH_FLOW_WAIT_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+const CALLBACK_SERVER_JOIN_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// 占用端口的进程
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PortOwner {
+    pub pid: u32,
+    pub name: Option<String>,
+}
+
+/// OAuth 回调端口预检结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OAuthPortStatus {
+    pub platform: String,
+    pub port: u16,
+    pub free: bool,
+    /// 占用端口的其他进程（不含本进程）
+    pub owners: Vec<PortOwner>,
+}
+
+/// OAuth 流程准备结果，前端据此展示实际配置的回调地址
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OAuthFlowStartResponse {
+    pub auth_url: String,
+    pub redirect_uri: String,
+    pub port: u16,
+    /// 授权链接中使用的回调路径（callback_paths 的第一个）
+    pub callback_path: String,
+    /// 本地回调服务接受的全部路径，其余路径返回 404
+    pub callback_paths: Vec<String>,
+}
+
+impl OAuthFlowState {
+    fn to_start_response(&self) -> OAuthFlowStartResponse {
+        OAuthFlowStartResponse {
+            auth_url: self.auth_url.clone(),
+            redirect_uri: self.redirect_uri.clone(),
+            port: self.port,
+            callback_path: self.callback_paths[0].clone(),
+            callback_paths: self.callback_paths.clone(),
+        }
+    }
+}
+
+/// 规范化调用方指定的回调路径：去除空白、补全前导 `/`、去重；
+/// 未指定时使用默认路径
+fn normalize_callback_paths(callback_paths: Option<Vec<String>>) -> Result<Vec<String>, String> {
+    let mut normalized: Vec<String> = Vec::new();
+    for raw in callback_paths.unwrap_or_default() {
+        let trimmed = raw.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+        if trimmed.contains(['?', '#']) || trimmed.chars().any(char::is_whitespace) {
+            return Err(format!("OAuth 回调路径无效: {}", trimmed));
+        }
+        let path = if trimmed.starts_with('/') {
+            trimmed.to_string()
+        } else {
+            format!("/{}", trimmed)
+        };
+        if !normalized.contains(&path) {
+            normalized.push(path);
+        }
+    }
+    if normalized.is_empty() {
+        normalized.push(DEFAULT_OAUTH_CALLBACK_PATH.to_string());
+    }
+    Ok(normalized)
+}
 
 fn get_oauth_flow_state() -> &'static Mutex<Option<OAuthFlowState>> {
     OAUTH_FLOW_STATE.get_or_init(|| Mutex::new(None))
@@ -114,6 +195,7 @@ fn parse_request_target(request: &str) -> Result<(String, String), String> {
 async fn process_callback_request(
     stream: &mut tokio::net::TcpStream,
     port: u16,
+    callback_paths: &[String],
     expected_state: &str,
 ) -> Option<Result<String, String>> {
     let request = match read_http_request(stream).await {
@@ -156,7 +238,10 @@ async fn process_callback_request(
         }
     };
 
-    if callback_url.path() != OAUTH_CALLBACK_PATH {
+    if !callback_paths
+        .iter()
+        .any(|path| path.as_str() == callback_url.path())
+    {
         let _ = stream
             .write_all(oauth_not_found_response().as_bytes())
             .await;
@@ -201,12 +286,26 @@ async fn process_callback_request(
     Some(Ok(code))
 }
 
-async fn ensure_oauth_flow_prepared(app_handle: &tauri::AppHandle) -> Result<String, String> {
+async fn ensure_oauth_flow_prepared(
+    app_handle: &tauri::AppHandle,
+    callback_paths: Option<Vec<String>>,
+) -> Result<OAuthFlowStartResponse, String> {
     use tauri::Emitter;
 
-    if let Ok(state) = get_oauth_flow_state().lock() {
+    let requested_paths = callback_paths.is_some();
+    let callback_paths = normalize_callback_paths(callback_paths)?;
+    if let Ok(mut state) = get_oauth_flow_state().lock() {
         if let Some(s) = state.as_ref() {
-            return Ok(s.auth_url.clone());
+            // 未指定路径时沿用已准备的流程；路径不同则重新准备，避免授权链接与回调路径不一致
+            if !requested_paths || s.callback_paths == callback_paths {
+                return Ok(s.to_start_response());
+            }
+            if s.code_rx.is_none() {
+                return Err("OAuth 授权已在进行中".to_string());
+            }
+            if let Some(previous) = state.take() {
+                let _ = previous.cancel_tx.send(true);
+            }
         }
     }
 
@@ -219,7 +318,7 @@ async fn ensure_oauth_flow_prepared(app_handle: &tauri::AppHandle) -> Result<Str
         .map_err(|e| format!("无法获取本地端口: {}", e))?
         .port();
 
-    let redirect_uri = format!("http://localhost:{}/oauth-callback", port);
+    let redirect_uri = format!("http://localhost:{}{}", port, callback_paths[0]);
     let state_token = uuid::Uuid::new_v4().to_string();
     let auth_url = oauth::get_auth_url(&redirect_uri, Some(&state_token));
 
@@ -232,6 +331,7 @@ async fn ensure_oauth_flow_prepared(app_handle: &tauri::AppHandle) -> Result<Str
     let tx = code_tx.clone();
     let mut rx = cancel_rx;
     let expected_state = state_token.clone();
+    let accepted_paths = callback_paths.clone();
     tokio::spawn(async move {
         loop {
             let accept_result = tokio::select! {
@@ -247,7 +347,8 @@ async fn ensure_oauth_flow_prepared(app_handle: &tauri::AppHandle) -> Result<Str
                 continue;
             };
 
-            let result = process_callback_request(&mut stream, port, &expected_state).await;
+            let result =
+                process_callback_request(&mut stream, port, &accepted_paths, &expected_state).await;
             if let Some(result) = result {
                 if let Some(sender) = tx.lock().await.take() {
                     let _ = app_handle_clone.emit("oauth-callback-received", ());
@@ -258,39 +359,170 @@ async fn ensure_oauth_flow_prepared(app_handle: &tauri::AppHandle) -> Result<Str
         }
     });
 
+    let flow_state = OAuthFlowState {
+        auth_url: auth_url.clone(),
+        redirect_uri,
+        port,
+        callback_paths,
+        cancel_tx,
+        code_rx: Some(code_rx),
+    };
+    let response = flow_state.to_start_response();
     if let Ok(mut state) = get_oauth_flow_state().lock() {
-        *state = Some(OAuthFlowState {
-            auth_url: auth_url.clone(),
-            redirect_uri,
-            cancel_tx,
-            code_rx: Some(code_rx),
-        });
+        *state = Some(flow_state);
     }
 
     let _ = app_handle.emit("oauth-url-generated", &auth_url);
 
-    Ok(auth_url)
+    Ok(response)
 }
 
-/// 预生成 OAuth URL
-pub async fn prepare_oauth_url(app_handle: tauri::AppHandle) -> Result<String, String> {
-    ensure_oauth_flow_prepared(&app_handle).await
+/// 预生成 OAuth URL；callback_paths 为空时使用默认回调路径
+pub async fn prepare_oauth_url(
+    app_handle: tauri::AppHandle,
+    callback_paths: Option<Vec<String>>,
+) -> Result<OAuthFlowStartResponse, String> {
+    ensure_oauth_flow_prepared(&app_handle, callback_paths).await
 }
 
-/// 取消当前的 OAuth 流程
-pub fn cancel_oauth_flow() {
+/// 取消当前的 OAuth 流程，返回是否存在进行中的流程
+pub fn cancel_oauth_flow() -> bool {
     if let Ok(mut state) = get_oauth_flow_state().lock() {
         if let Some(s) = state.take() {
             let _ = s.cancel_tx.send(true);
+            return true;
         }
     }
+    false
+}
+
+fn check_callback_port(platform: &str, port: u16) -> OAuthPortStatus {
+    let free = std::net::TcpListener::bind(("127.0.0.1", port)).is_ok();
+    let owners = if free {
+        Vec::new()
+    } else {
+        process::find_pids_by_port(port)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|pid| PortOwner {
+                pid,
+                name: process::get_process_name(pid),
+            })
+            .collect()
+    };
+    OAuthPortStatus {
+        platform: platform.to_string(),
+        port,
+        free,
+        owners,
+    }
+}
+
+/// 预检各平台固定的 OAuth 回调端口是否空闲（仅诊断，不会结束占用进程）
+/// Antigravity / Windsurf 使用系统分配的随机端口，无需检查
+pub fn precheck_oauth_ports() -> Vec<OAuthPortStatus> {
+    let mut result = vec![check_callback_port(
+        PLATFORM_CODEX,
+        codex_oauth::get_callback_port(),
+    )];
+    for port in kiro_oauth::callback_port_candidates() {
+        result.push(check_callback_port(PLATFORM_KIRO, port));
+    }
+
+    for status in result.iter().filter(|status| !status.free) {
+        let owners: Vec<String> = status
+            .owners
+            .iter()
+            .map(|owner| {
+                format!(
+                    "{}({})",
+                    owner.name.as_deref().unwrap_or("unknown"),
+                    owner.pid
+                )
+            })
+            .collect();
+        logger::log_warn(&format!(
+            "[OAuth] 回调端口已被占用: platform={}, port={}, owners=[{}]",
+            status.platform,
+            status.port,
+            owners.join(", ")
+        ));
+    }
+    result
+}
+
+/// 关闭所有平台残留的 OAuth 回调服务（应用退出时调用），返回已关闭的平台 ID
+/// 取消各平台挂起的登录后等待回调服务任务结束，确保端口在下次启动前释放
+pub async fn shutdown_all_oauth_servers() -> Vec<String> {
+    let mut closed = Vec::new();
+    if cancel_oauth_flow() {
+        closed.push(PLATFORM_ANTIGRAVITY.to_string());
+    }
+
+    let tasks = [
+        (PLATFORM_KIRO, kiro_oauth::shutdown_callback_server()),
+        (PLATFORM_CODEX, codex_oauth::shutdown_callback_server()),
+        (
+            PLATFORM_WINDSURF,
+            windsurf_oauth::shutdown_callback_server(),
+        ),
+    ];
+    for (platform, task) in tasks {
+        let Some(task) = task else {
+            continue;
+        };
+        if timeout(CALLBACK_SERVER_JOIN_TIMEOUT, task).await.is_err() {
+            logger::log_warn(&format!(
+                "[OAuth] 等待回调服务退出超时: platform={}",
+                platform
+            ));
+        }
+        closed.push(platform.to_string());
+    }
+
+    if !closed.is_empty() {
+        logger::log_info(&format!(
+            "[OAuth] 已关闭 OAuth 回调服务: {}",
+            closed.join(", ")
+        ));
+    }
+    closed
+}
+
+/// 清理各平台失效的挂起登录（已过期或回调服务已退出），返回有会话被清理的平台 ID
+/// 没有挂起的登录或会话仍有效时不做任何处理
+pub fn clear_stale_oauth_states() -> Vec<String> {
+    let results = [
+        (PLATFORM_KIRO, kiro_oauth::clear_stale_login()),
+        (PLATFORM_CODEX, codex_oauth::clear_stale_login()),
+        (
+            PLATFORM_GITHUB_COPILOT,
+            github_copilot_oauth::clear_stale_login(),
+        ),
+        (PLATFORM_WINDSURF, windsurf_oauth::clear_stale_login()),
+    ];
+    let cleared: Vec<String> = results
+        .into_iter()
+        .filter(|(_, cleared)| *cleared)
+        .map(|(platform, _)| platform.to_string())
+        .collect();
+    if !cleared.is_empty() {
+        logger::log_info(&format!(
+            "[OAuth] 已清理失效的登录会话: {}",
+            cleared.join(", ")
+        ));
+    }
+    cleared
 }
 
 /// 启动 OAuth 流程并等待回调
 pub async fn start_oauth_flow(
     app_handle: tauri::AppHandle,
+    callback_paths: Option<Vec<String>>,
 ) -> Result<oauth::TokenResponse, String> {
-    let auth_url = ensure_oauth_flow_prepared(&app_handle).await?;
+    let auth_url = ensure_oauth_flow_prepared(&app_handle, callback_paths)
+        .await?
+        .auth_url;
 
     use tauri_plugin_opener::OpenerExt;
     app_handle
@@ -341,8 +573,9 @@ pub async fn start_oauth_flow(
 
 pub async fn complete_oauth_flow(
     app_handle: tauri::AppHandle,
+    callback_paths: Option<Vec<String>>,
 ) -> Result<oauth::TokenResponse, String> {
-    let _ = ensure_oauth_flow_prepared(&app_handle).await?;
+    let _ = ensure_oauth_flow_prepared(&app_handle, callback_paths).await?;
 
     let (code_rx, redirect_uri) = {
         let mut lock = get_oauth_flow_state()
@@ -662,11 +662,173 @@ fn normalize_custom_path(value: Option<&str>) -> Option<String> {
 }
 
 const APP_PATH_NOT_FOUND_PREFIX: &str = "APP_PATH_NOT_FOUND:";
+/// 已配置路径但路径已失效（应用被卸载或移动）
+const APP_PATH_STALE_PREFIX: &str = "APP_PATH_STALE:";
 
-fn app_path_missing_error(app: &str) -> String {
+pub fn app_path_missing_error(app: &str) -> String {
     format!("{}{}", APP_PATH_NOT_FOUND_PREFIX, app)
 }
 
+pub fn app_path_stale_error(app: &str) -> String {
+    format!("{}{}", APP_PATH_STALE_PREFIX, app)
+}
+
+/// 读取配置中的应用路径（未配置时返回 None）
+pub fn configured_app_path(app: &str) -> Option<String> {
+    let current = config::get_user_config();
+    let raw = match app {
+        "antigravity" => current.antigravity_app_path,
+        "codex" => current.codex_app_path,
+        "vscode" => current.vscode_app_path,
+        "windsurf" => current.windsurf_app_path,
+        "kiro" => current.kiro_app_path,
+        "opencode" => current.opencode_app_path,
+        _ => String::new(),
+    };
+    normalize_custom_path(Some(&raw))
+}
+
+/// 根据是否已配置路径，区分“配置的路径已失效”与“未找到应用”
+#[cfg(target_os = "macos")]
+fn app_path_unresolved_error(app: &str) -> String {
+    if configured_app_path(app).is_some() {
+        app_path_stale_error(app)
+    } else {
+        app_path_missing_error(app)
+    }
+}
+
+/// 应用路径检查结果
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AppPathAudit {
+    pub app: String,
+    pub configured_path: Option<String>,
+    /// "unset"：未配置；"ok"：路径存在；"stale"：已配置但路径不存在（应用被卸载或移动）
+    pub status: String,
+}
+
+const AUDITED_APPS: [&str; 6] = [
+    "antigravity",
+    "codex",
+    "vscode",
+    "windsurf",
+    "kiro",
+    "opencode",
+];
+
+/// 检查各应用已配置的启动路径是否仍然有效
+pub fn audit_app_paths() -> Vec<AppPathAudit> {
+    AUDITED_APPS
+        .iter()
+        .map(|app| {
+            let configured_path = configured_app_path(app);
+            let status = match configured_path.as_deref() {
+                None => "unset",
+                Some(path) if Path::new(path).exists() => "ok",
+                Some(path) => {
+                    crate::modules::logger::log_warn(&format!(
+                        "[AppPath] 已配置的 {} 路径不存在（可能已卸载或移动）: {}",
+                        app, path
+                    ));
+                    "stale"
+                }
+            };
+            AppPathAudit {
+                app: app.to_string(),
+                configured_path,
+                status: status.to_string(),
+            }
+        })
+        .collect()
+}
+
+/// 是否为应用路径缺失/失效错误（需要引导用户重新设置路径）
+pub fn is_app_path_error(err: &str) -> bool {
+    err.starts_with(APP_PATH_NOT_FOUND_PREFIX) || err.starts_with(APP_PATH_STALE_PREFIX)
+}
+
+/// 应用路径校验结果（保存前预检）
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AppPathInfo {
+    pub app: String,
+    /// 路径是否存在
+    pub exists: bool,
+    /// 是否能解析为可执行文件
+    pub executable: bool,
+    /// 解析出的可执行文件路径
+    pub exec_path: Option<String>,
+    /// 保存到配置中的路径（macOS 下为 .app 根目录）
+    pub normalized_path: String,
+}
+
+/// macOS 下各应用 `Contents/MacOS` 中的可执行文件名（依次尝试）
+#[cfg(target_os = "macos")]
+fn macos_binary_candidates(app: &str) -> &'static [&'static str] {
+    match app {
+        "codex" => &["Codex"],
+        "kiro" => &["Kiro", "Electron"],
+        "opencode" => &[OPENCODE_APP_NAME],
+        _ => &["Electron"],
+    }
+}
+
+#[cfg(unix)]
+fn is_executable_file(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::metadata(path)
+        .map(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+#[cfg(windows)]
+fn is_executable_file(path: &Path) -> bool {
+    path.is_file()
+        && path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| ext.eq_ignore_ascii_case("exe"))
+            .unwrap_or(false)
+}
+
+/// 校验应用启动路径是否有效（不写入配置）
+pub fn validate_app_path(app: &str, path: &str) -> Result<AppPathInfo, String> {
+    if !AUDITED_APPS.contains(&app) {
+        return Err("未知应用类型".to_string());
+    }
+    let trimmed = path.trim();
+    if trimmed.is_empty() {
+        return Err("路径不能为空".to_string());
+    }
+    let raw_path = Path::new(trimmed);
+
+    #[cfg(target_os = "macos")]
+    let (normalized_path, exec_path) = {
+        let normalized = normalize_macos_app_root(raw_path).unwrap_or_else(|| trimmed.to_string());
+        let exec_path = if normalized.ends_with(".app") {
+            macos_binary_candidates(app)
+                .iter()
+                .filter_map(|binary| resolve_macos_exec_path(trimmed, binary))
+                .find(|exec| exec.is_file())
+        } else {
+            Some(raw_path.to_path_buf())
+        };
+        (normalized, exec_path)
+    };
+    #[cfg(not(target_os = "macos"))]
+    let (normalized_path, exec_path) = (trimmed.to_string(), resolve_macos_exec_path(trimmed, ""));
+
+    let exec_path = exec_path.filter(|exec| is_executable_file(exec));
+    Ok(AppPathInfo {
+        app: app.to_string(),
+        exists: raw_path.exists(),
+        executable: exec_path.is_some(),
+        exec_path: exec_path.map(|exec| exec.to_string_lossy().to_string()),
+        normalized_path,
+    })
+}
+
 #[cfg(target_os = "macos")]
 fn normalize_macos_app_root(path: &Path) -> Option<String> {
     let path_str = path.to_string_lossy();
@@ -788,6 +950,12 @@ fn spawn_open_app(app_root: &str, args: &[String]) -> Result<u32, String> {
 }
 
 fn find_antigravity_process_exe() -> Option<std::path::PathBuf> {
+    find_antigravity_process().map(|(_, exe)| exe)
+}
+
+/// 查找正在运行的 Antigravity 主进程，返回 (pid, 可执行文件路径)
+fn find_antigravity_process() -> Option<(u32, std::path::PathBuf)> {
+    let helper_keywords = HelperKeywords::load();
     let mut system = System::new();
     system.refresh_processes(sysinfo::ProcessesToUpdate::All, true);
 
@@ -813,16 +981,7 @@ fn find_antigravity_process_exe() -> Option<std::path::PathBuf> {
             .collect::<Vec<String>>()
             .join(" ");
 
-        let is_helper = args_str.contains("--type=")
-            || name.contains("helper")
-            || name.contains("plugin")
-            || name.contains("renderer")
-            || name.contains("gpu")
-            || name.contains("crashpad")
-            || name.contains("utility")
-            || name.contains("audio")
-            || name.contains("sandbox")
-            || exe_path.contains("crashpad");
+        let is_helper = is_helper_process(&helper_keywords, &name, &args_str);
 
         #[cfg(target_os = "macos")]
         let is_antigravity =
@@ -836,7 +995,7 @@ fn find_antigravity_process_exe() -> Option<std::path::PathBuf> {
 
         if is_antigravity && !is_helper {
             if let Some(exe) = process.exe() {
-                return Some(exe.to_path_buf());
+                return Some((pid_u32, exe.to_path_buf()));
             }
         }
     }
@@ -844,7 +1003,99 @@ fn find_antigravity_process_exe() -> Option<std::path::PathBuf> {
     None
 }
 
+/// Rosetta 转译进程在 p_flag 中的标记（sys/proc.h: P_TRANSLATED）
+#[cfg(target_os = "macos")]
+const P_TRANSLATED: u32 = 0x0002_0000;
+
+/// 当前机器是否为 Apple Silicon（本程序自身可能运行在 Rosetta 下，不能用编译目标判断）
+#[cfg(target_os = "macos")]
+fn is_apple_silicon_host() -> bool {
+    static APPLE_SILICON_HOST: std::sync::OnceLock<bool> = std::sync::OnceLock::new();
+    *APPLE_SILICON_HOST.get_or_init(|| {
+        Command::new("sysctl")
+            .args(["-n", "hw.optional.arm64"])
+            .output()
+            .map(|output| {
+                output.status.success() && String::from_utf8_lossy(&output.stdout).trim() == "1"
+            })
+            .unwrap_or(false)
+    })
+}
+
+fn host_arch() -> String {
+    #[cfg(target_os = "macos")]
+    {
+        if is_apple_silicon_host() {
+            return "arm64".to_string();
+        }
+        "x86_64".to_string()
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    {
+        std::env::consts::ARCH.to_string()
+    }
+}
+
+/// 检测进程架构，返回 (arm64 / x86_64, 是否经 Rosetta 转译)
+#[cfg(target_os = "macos")]
+fn detect_process_arch(pid: u32) -> Option<(String, bool)> {
+    let output = Command::new("ps")
+        .args(["-o", "flags=", "-p", &pid.to_string()])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let flags = u32::from_str_radix(String::from_utf8_lossy(&output.stdout).trim(), 16).ok()?;
+    let translated = is_apple_silicon_host() && flags & P_TRANSLATED != 0;
+    let arch = if is_apple_silicon_host() && !translated {
+        "arm64"
+    } else {
+        "x86_64"
+    };
+    Some((arch.to_string(), translated))
+}
+
+#[cfg(not(target_os = "macos"))]
+fn detect_process_arch(_pid: u32) -> Option<(String, bool)> {
+    None
+}
+
+/// 正在运行的 Antigravity 进程信息（用于诊断混合架构下的窗口定位问题）
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AntigravityRuntimeInfo {
+    pub pid: Option<u32>,
+    pub exe_path: Option<String>,
+    /// 进程架构：arm64 / x86_64，未运行或无法检测时为 None（目前仅 macOS 支持检测）
+    pub arch: Option<String>,
+    /// 是否经 Rosetta 转译运行
+    pub translated: bool,
+    pub host_arch: String,
+}
+
+/// 获取正在运行的 Antigravity 主进程的可执行文件路径与架构
+pub fn get_antigravity_runtime_info() -> AntigravityRuntimeInfo {
+    let process = find_antigravity_process();
+    let pid = process.as_ref().map(|(pid, _)| *pid);
+    let detected = pid.and_then(detect_process_arch);
+    let info = AntigravityRuntimeInfo {
+        pid,
+        exe_path: process.map(|(_, exe)| exe.to_string_lossy().to_string()),
+        translated: detected.as_ref().is_some_and(|(_, translated)| *translated),
+        arch: detected.map(|(arch, _)| arch),
+        host_arch: host_arch(),
+    };
+    crate::modules::logger::log_info(&format!(
+        "[AG Runtime] pid={:?}, exe={:?}, arch={:?}, translated={}, host_arch={}",
+        info.pid, info.exe_path, info.arch, info.translated, info.host_arch
+    ));
+    info
+}
+
 fn find_vscode_process_exe() -> Option<std::path::PathBuf> {
+    let helper_keywords = HelperKeywords::load();
     let mut system = System::new();
     system.refresh_processes(sysinfo::ProcessesToUpdate::All, true);
 
@@ -870,14 +1121,7 @@ fn find_vscode_process_exe() -> Option<std::path::PathBuf> {
             .collect::<Vec<String>>()
             .join(" ");
 
-        let is_helper = args_str.contains("--type=")
-            || name.contains("helper")
-            || name.contains("renderer")
-            || name.contains("gpu")
-            || name.contains("crashpad")
-            || name.contains("utility")
-            || name.contains("audio")
-            || name.contains("sandbox");
+        let is_helper = is_helper_process(&helper_keywords, &name, &args_str);
 
         #[cfg(target_os = "macos")]
         let is_vscode = exe_path.contains("visual studio code.app/contents/") && !is_helper;
@@ -898,6 +1142,7 @@ fn find_vscode_process_exe() -> Option<std::path::PathBuf> {
 
 #[cfg(target_os = "macos")]
 fn find_codex_process_exe() -> Option<std::path::PathBuf> {
+    let helper_keywords = HelperKeywords::load();
     let mut system = System::new();
     system.refresh_processes(sysinfo::ProcessesToUpdate::All, true);
 
@@ -923,14 +1168,7 @@ fn find_codex_process_exe() -> Option<std::path::PathBuf> {
             .collect::<Vec<String>>()
             .join(" ");
 
-        let is_helper = args_str.contains("--type=")
-            || name.contains("helper")
-            || name.contains("renderer")
-            || name.contains("gpu")
-            || name.contains("crashpad")
-            || name.contains("utility")
-            || name.contains("audio")
-            || name.contains("sandbox");
+        let is_helper = is_helper_process(&helper_keywords, &name, &args_str);
 
         let is_codex = exe_path.contains("codex.app/contents/macos/codex");
 
@@ -1202,7 +1440,7 @@ fn resolve_antigravity_launch_path() -> Result<std::path::PathBuf, String> {
         if let Some(exec) = resolve_macos_exec_path(&custom, "Electron") {
             return Ok(exec);
         }
-        return Err(app_path_missing_error("antigravity"));
+        return Err(app_path_stale_error("antigravity"));
     }
 
     Err(app_path_missing_error("antigravity"))
@@ -1222,7 +1460,7 @@ fn resolve_vscode_launch_path() -> Result<std::path::PathBuf, String> {
                 return Ok(exec);
             }
         }
-        return Err(app_path_missing_error("vscode"));
+        return Err(app_path_stale_error("vscode"));
     }
 
     Err(app_path_missing_error("vscode"))
@@ -1234,7 +1472,7 @@ fn resolve_codex_launch_path() -> Result<std::path::PathBuf, String> {
         if let Some(exec) = resolve_macos_exec_path(&custom, "Codex") {
             return Ok(exec);
         }
-        return Err(app_path_missing_error("codex"));
+        return Err(app_path_stale_error("codex"));
     }
 
     Err(app_path_missing_error("codex"))
@@ -1287,6 +1525,7 @@ pub fn detect_and_save_app_path(app: &str, force: bool) -> Option<String> {
 /// 检查 Antigravity 是否在运行
 #[allow(dead_code)]
 pub fn is_antigravity_running() -> bool {
+    let helper_keywords = HelperKeywords::load();
     let mut system = System::new();
     system.refresh_processes(sysinfo::ProcessesToUpdate::All, true);
 
@@ -1313,16 +1552,7 @@ pub fn is_antigravity_running() -> bool {
             .collect::<Vec<String>>()
             .join(" ");
 
-        let is_helper = args_str.contains("--type=")
-            || name.contains("helper")
-            || name.contains("plugin")
-            || name.contains("renderer")
-            || name.contains("gpu")
-            || name.contains("crashpad")
-            || name.contains("utility")
-            || name.contains("audio")
-            || name.contains("sandbox")
-            || exe_path.contains("crashpad");
+        let is_helper = is_helper_process(&helper_keywords, &name, &args_str);
 
         #[cfg(target_os = "macos")]
         {
@@ -1424,7 +1654,6 @@ fn parse_user_data_dir_value(raw: &str) -> Option<String> {
     }
 }
 
-#[allow(dead_code)]
 fn extract_user_data_dir_from_command_line(command_line: &str) -> Option<String> {
     let tokens = split_command_tokens(command_line);
     let mut index = 0;
@@ -1579,7 +1808,7 @@ fn is_env_token(token: &str) -> bool {
     chars.all(|ch| ch.is_ascii_alphanumeric() || ch == '_')
 }
 
-#[cfg(target_os = "macos")]
+#[cfg(any(target_os = "macos", target_os = "windows"))]
 fn extract_env_value(command_line: &str, key: &str) -> Option<String> {
     let needle = format!("{}=", key);
     let pos = command_line.find(&needle)?;
@@ -1587,8 +1816,7 @@ fn extract_env_value(command_line: &str, key: &str) -> Option<String> {
     parse_env_value(rest)
 }
 
-#[allow(dead_code)]
-fn normalize_path_for_compare(raw: &str) -> String {
+pub fn normalize_path_for_compare(raw: &str) -> String {
     let trimmed = raw.trim();
     if trimmed.is_empty() {
         return String::new();
@@ -1607,24 +1835,126 @@ fn normalize_path_for_compare(raw: &str) -> String {
     }
 }
 
-fn is_helper_command_line(cmdline_lower: &str) -> bool {
-    cmdline_lower.contains("--type=")
-        || cmdline_lower.contains("helper")
-        || cmdline_lower.contains("plugin")
-        || cmdline_lower.contains("renderer")
-        || cmdline_lower.contains("gpu")
-        || cmdline_lower.contains("crashpad")
-        || cmdline_lower.contains("utility")
-        || cmdline_lower.contains("audio")
-        || cmdline_lower.contains("sandbox")
-        || cmdline_lower.contains("--node-ipc")
-        || cmdline_lower.contains("--clientprocessid=")
-        || cmdline_lower.contains("\\resources\\app\\extensions\\")
-        || cmdline_lower.contains("/resources/app/extensions/")
+/// 内置的辅助进程（非主窗口进程）命令行标记，只在参数中匹配
+const DEFAULT_HELPER_ARG_KEYWORDS: [&str; 5] = [
+    "--type=",
+    "--node-ipc",
+    "--clientprocessid=",
+    "\\resources\\app\\extensions\\",
+    "/resources/app/extensions/",
+];
+
+/// 内置的辅助进程名关键字，只在进程名中匹配
+/// （安装路径或 --no-sandbox 等主进程参数中出现这些字样不影响判定）
+const DEFAULT_HELPER_NAME_KEYWORDS: [&str; 8] = [
+    "helper", "plugin", "renderer", "gpu", "crashpad", "utility", "audio", "sandbox",
+];
+
+/// 一次进程扫描使用的辅助进程关键字，扫描开始时读取一次配置后传入各判定函数
+/// 配置项 `process_helper_exclude_keywords` 非空时整体替换内置列表：
+/// 以 `-` 开头或包含路径分隔符的关键字匹配命令行参数，其余匹配进程名
+#[derive(Debug, Clone)]
+pub struct HelperKeywords {
+    args: Vec<String>,
+    names: Vec<String>,
+}
+
+impl HelperKeywords {
+    pub fn load() -> Self {
+        Self::from_configured(config::get_process_helper_exclude_keywords())
+    }
+
+    fn from_configured(configured: Vec<String>) -> Self {
+        if configured.is_empty() {
+            return Self {
+                args: DEFAULT_HELPER_ARG_KEYWORDS
+                    .iter()
+                    .map(|keyword| keyword.to_string())
+                    .collect(),
+                names: DEFAULT_HELPER_NAME_KEYWORDS
+                    .iter()
+                    .map(|keyword| keyword.to_string())
+                    .collect(),
+            };
+        }
+        let (args, names) = configured.into_iter().partition(|keyword| {
+            keyword.starts_with('-') || keyword.contains('/') || keyword.contains('\\')
+        });
+        Self { args, names }
+    }
+}
+
+/// 去掉命令行中不应参与关键字匹配的部分：
+/// user-data-dir 的值（目录名可能恰好包含 gpu 等字样）以及 --disable-* / --enable-* 开关
+/// （如低功耗启动的 --disable-gpu）
+fn strip_non_matching_args(command_line_lower: &str) -> String {
+    let mut remaining = command_line_lower.to_string();
+    if let Some(dir) = extract_user_data_dir_from_command_line(command_line_lower) {
+        if !dir.is_empty() {
+            remaining = remaining.replace(&dir, " ");
+        }
+    }
+    remaining
+        .split_whitespace()
+        .filter(|token| !token.starts_with("--disable-") && !token.starts_with("--enable-"))
+        .collect::<Vec<&str>>()
+        .join(" ")
+}
+
+/// 判定为辅助进程的原因（`cmdline:<关键字>` / `name:<关键字>`），主进程返回 None
+/// 参数标记只在命令行中匹配，进程名关键字只在进程名中匹配；
+/// name_lower 为小写进程名，command_line_lower 为小写命令行（可含可执行路径）
+pub fn helper_process_reason(
+    keywords: &HelperKeywords,
+    name_lower: &str,
+    command_line_lower: &str,
+) -> Option<String> {
+    let args = strip_non_matching_args(command_line_lower);
+    if let Some(keyword) = keywords
+        .args
+        .iter()
+        .find(|keyword| args.contains(keyword.as_str()))
+    {
+        return Some(format!("cmdline:{}", keyword));
+    }
+    keywords
+        .names
+        .iter()
+        .find(|keyword| name_lower.contains(keyword.as_str()))
+        .map(|keyword| format!("name:{}", keyword))
+}
+
+/// 所有平台共用的辅助进程判定
+pub fn is_helper_process(
+    keywords: &HelperKeywords,
+    name_lower: &str,
+    command_line_lower: &str,
+) -> bool {
+    helper_process_reason(keywords, name_lower, command_line_lower).is_some()
+}
+
+/// 从命令行中取出可执行文件名（第一个 ` --` 之前路径的最后一段），用于只有命令行的场景
+fn command_line_program_name(cmdline_lower: &str) -> &str {
+    let program = cmdline_lower
+        .split(" --")
+        .next()
+        .unwrap_or(cmdline_lower)
+        .trim()
+        .trim_matches('"');
+    program.rsplit(['/', '\\']).next().unwrap_or(program)
+}
+
+fn is_helper_command_line(keywords: &HelperKeywords, cmdline_lower: &str) -> bool {
+    is_helper_process(
+        keywords,
+        command_line_program_name(cmdline_lower),
+        cmdline_lower,
+    )
 }
 
 #[cfg(target_os = "macos")]
 fn collect_antigravity_process_entries_from_ps() -> Vec<(u32, Option<String>)> {
+    let helper_keywords = HelperKeywords::load();
     let mut result = Vec::new();
     let output = Command::new("ps").args(["-axo", "pid,command"]).output();
     let output = match output {
@@ -1650,7 +1980,7 @@ fn collect_antigravity_process_entries_from_ps() -> Vec<(u32, Option<String>)> {
         }
         if lower.contains("antigravity tools.app/contents/")
             || lower.contains("crashpad_handler")
-            || is_helper_command_line(&lower)
+            || is_helper_command_line(&helper_keywords, &lower)
         {
             continue;
         }
@@ -1662,6 +1992,7 @@ fn collect_antigravity_process_entries_from_ps() -> Vec<(u32, Option<String>)> {
 
 #[cfg(target_os = "windows")]
 fn collect_antigravity_process_entries_from_powershell() -> Vec<(u32, Option<String>)> {
+    let helper_keywords = HelperKeywords::load();
     let mut result = Vec::new();
     let output = powershell_output(&[
         "-NoProfile",
@@ -1686,7 +2017,7 @@ fn collect_antigravity_process_entries_from_powershell() -> Vec<(u32, Option<Str
             Err(_) => continue,
         };
         let lower = cmdline.to_lowercase();
-        if lower.contains("antigravity tools") || is_helper_command_line(&lower) {
+        if lower.contains("antigravity tools") || is_helper_command_line(&helper_keywords, &lower) {
             continue;
         }
         let dir = extract_user_data_dir_from_command_line(cmdline);
@@ -1697,6 +2028,7 @@ fn collect_antigravity_process_entries_from_powershell() -> Vec<(u32, Option<Str
 
 #[cfg(target_os = "linux")]
 fn collect_antigravity_process_entries_from_proc() -> Vec<(u32, Option<String>)> {
+    let helper_keywords = HelperKeywords::load();
     let mut result = Vec::new();
     let entries = match std::fs::read_dir("/proc") {
         Ok(value) => value,
@@ -1732,7 +2064,7 @@ fn collect_antigravity_process_entries_from_proc() -> Vec<(u32, Option<String>)>
         if cmd_lower.contains("tools") || exe_path.contains("tools") {
             continue;
         }
-        if is_helper_command_line(&cmd_lower) {
+        if is_helper_command_line(&helper_keywords, &cmd_lower) {
             continue;
         }
         let dir = extract_user_data_dir_from_command_line(&cmdline_str);
@@ -1744,7 +2076,10 @@ fn collect_antigravity_process_entries_from_proc() -> Vec<(u32, Option<String>)>
 pub fn collect_antigravity_process_entries() -> Vec<(u32, Option<String>)> {
     #[cfg(target_os = "macos")]
     {
-        let entries = collect_antigravity_process_entries_macos();
+        let entries: Vec<(u32, Option<String>)> = collect_antigravity_process_entries_macos()
+            .into_iter()
+            .map(|(pid, dir, _)| (pid, dir))
+            .collect();
         if !entries.is_empty() {
             return entries;
         }
@@ -1933,8 +2268,38 @@ pub fn resolve_antigravity_pid(last_pid: Option<u32>, user_data_dir: Option<&str
     resolve_antigravity_pid_from_entries(last_pid, user_data_dir, &entries)
 }
 
+/// 通过 NSRunningApplication 直接激活应用，不需要启动子进程，也不依赖辅助功能权限
+#[cfg(target_os = "macos")]
+fn activate_app_by_pid_native(pid: u32) -> Result<(), String> {
+    use objc2_app_kit::{NSApplicationActivationOptions, NSRunningApplication};
+
+    let native_pid = libc::pid_t::try_from(pid).map_err(|_| format!("PID 超出范围: {}", pid))?;
+    let app = NSRunningApplication::runningApplicationWithProcessIdentifier(native_pid)
+        .ok_or_else(|| format!("未找到 PID {} 对应的应用", pid))?;
+    if app.activateWithOptions(NSApplicationActivationOptions::ActivateAllWindows) {
+        Ok(())
+    } else {
+        Err(format!("激活应用失败: pid={}", pid))
+    }
+}
+
 #[cfg(target_os = "macos")]
 fn focus_window_by_pid(pid: u32) -> Result<(), String> {
+    match activate_app_by_pid_native(pid) {
+        Ok(()) => {
+            crate::modules::logger::log_info(&format!("[Focus] macOS native success pid={}", pid));
+            return Ok(());
+        }
+        Err(err) => crate::modules::logger::log_warn(&format!(
+            "[Focus] macOS native failed pid={} err={}, fallback to osascript",
+            pid, err
+        )),
+    }
+    focus_window_by_osascript(pid)
+}
+
+#[cfg(target_os = "macos")]
+fn focus_window_by_osascript(pid: u32) -> Result<(), String> {
     let script = format!(
         "tell application \"System Events\" to set frontmost of (first process whose unix id is {}) to true",
         pid
@@ -1978,6 +2343,64 @@ public class Win32 {{
         return Ok(());
     }
     let stderr = String::from_utf8_lossy(&output.stderr);
+    if stderr.contains("MAIN_WINDOW_HANDLE_EMPTY") {
+        return focus_window_by_enum_windows(pid);
+    }
+    Err(format!("窗口聚焦失败: {}", stderr.trim()))
+}
+
+/// MainWindowHandle 为 0 时（窗口由子进程创建或主窗口尚未登记），
+/// 枚举顶层窗口，聚焦第一个属于目标进程或其子进程的可见窗口
+#[cfg(target_os = "windows")]
+fn focus_window_by_enum_windows(pid: u32) -> Result<(), String> {
+    let pids = collect_pid_with_descendants(pid)
+        .iter()
+        .map(|value| value.to_string())
+        .collect::<Vec<_>>()
+        .join(",");
+    let command = format!(
+        r#"$targetPids=[uint32[]]@({pids});Add-Type @' 
+using System; 
+using System.Runtime.InteropServices; 
+public class Win32EnumFocus {{ 
+  public delegate bool EnumWindowsProc(IntPtr hWnd, IntPtr lParam); 
+  [DllImport("user32.dll")] public static extern bool EnumWindows(EnumWindowsProc lpEnumFunc, IntPtr lParam); 
+  [DllImport("user32.dll")] public static extern uint GetWindowThreadProcessId(IntPtr hWnd, out uint lpdwProcessId); 
+  [DllImport("user32.dll")] public static extern bool IsWindowVisible(IntPtr hWnd); 
+  [DllImport("user32.dll")] public static extern bool SetForegroundWindow(IntPtr hWnd); 
+  [DllImport("user32.dll")] public static extern bool ShowWindowAsync(IntPtr hWnd, int nCmdShow); 
+  public static IntPtr FindVisibleWindow(uint[] pids) {{ 
+    IntPtr found = IntPtr.Zero; 
+    EnumWindows(delegate (IntPtr hWnd, IntPtr lParam) {{ 
+      if (!IsWindowVisible(hWnd)) {{ return true; }} 
+      uint owner; 
+      GetWindowThreadProcessId(hWnd, out owner); 
+      if (Array.IndexOf(pids, owner) < 0) {{ return true; }} 
+      found = hWnd; 
+      return false; 
+    }}, IntPtr.Zero); 
+    return found; 
+  }} 
+}} 
+'@;$h=[Win32EnumFocus]::FindVisibleWindow($targetPids);if ($h -eq [IntPtr]::Zero) {{ throw 'NO_VISIBLE_WINDOW' }};[Win32EnumFocus]::ShowWindowAsync($h, 9) | Out-Null;[Win32EnumFocus]::SetForegroundWindow($h) | Out-Null;"#
+    );
+    crate::modules::logger::log_info(&format!(
+        "[Focus] Windows MainWindowHandle empty, fallback to EnumWindows pid={} pids={}",
+        pid, pids
+    ));
+    let output = powershell_output(&["-NoProfile", "-Command", &command])
+        .map_err(|e| format!("调用 PowerShell 失败: {}", e))?;
+    if output.status.success() {
+        crate::modules::logger::log_info(&format!(
+            "[Focus] Windows EnumWindows success pid={}",
+            pid
+        ));
+        return Ok(());
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if stderr.contains("NO_VISIBLE_WINDOW") {
+        return Err("窗口聚焦失败: 未找到该进程的可见窗口".to_string());
+    }
     Err(format!("窗口聚焦失败: {}", stderr.trim()))
 }
 
@@ -2025,89 +2448,451 @@ fn focus_window_by_pid(pid: u32) -> Result<(), String> {
     Err(format!("窗口聚焦失败: {}", stderr.trim()))
 }
 
-pub fn focus_antigravity_instance(
-    last_pid: Option<u32>,
-    user_data_dir: Option<&str>,
-) -> Result<u32, String> {
-    let resolve_start = Instant::now();
-    let pid = resolve_antigravity_pid(last_pid, user_data_dir)
-        .ok_or_else(|| "实例未运行，无法定位窗口".to_string())?;
-    crate::modules::logger::log_info(&format!(
-        "[Focus] Antigravity resolve pid={} elapsed={}ms",
-        pid,
-        resolve_start.elapsed().as_millis()
-    ));
-    let focus_start = Instant::now();
-    focus_window_by_pid(pid)?;
-    crate::modules::logger::log_info(&format!(
-        "[Focus] Antigravity focus pid={} elapsed={}ms",
-        pid,
-        focus_start.elapsed().as_millis()
-    ));
-    Ok(pid)
+/// 获取当前前台窗口所属进程的 PID
+#[cfg(target_os = "macos")]
+pub fn get_foreground_window_pid() -> Result<u32, String> {
+    let output = Command::new("osascript")
+        .args([
+            "-e",
+            "tell application \"System Events\" to get unix id of first process whose frontmost is true",
+        ])
+        .output()
+        .map_err(|e| format!("调用 osascript 失败: {}", e))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!(
+            "获取前台窗口失败，请检查系统辅助功能权限: {}",
+            stderr.trim()
+        ));
+    }
+    String::from_utf8_lossy(&output.stdout)
+        .trim()
+        .parse::<u32>()
+        .map_err(|_| "无法解析前台窗口进程 PID".to_string())
 }
 
-#[cfg(target_os = "macos")]
-pub fn resolve_codex_pid_from_entries(
-    last_pid: Option<u32>,
-    codex_home: Option<&str>,
-    entries: &[(u32, Option<String>)],
-) -> Option<u32> {
-    let target = codex_home
-        .map(|value| normalize_path_for_compare(value))
-        .filter(|value| !value.is_empty());
+/// 获取当前前台窗口所属进程的 PID
+#[cfg(target_os = "windows")]
+pub fn get_foreground_window_pid() -> Result<u32, String> {
+    let command = r#"Add-Type @'
+using System;
+using System.Runtime.InteropServices;
+public class Win32Foreground {
+  [DllImport("user32.dll")] public static extern IntPtr GetForegroundWindow();
+  [DllImport("user32.dll")] public static extern uint GetWindowThreadProcessId(IntPtr hWnd, out uint pid);
+}
+'@;$p=0;[Win32Foreground]::GetWindowThreadProcessId([Win32Foreground]::GetForegroundWindow(), [ref]$p) | Out-Null;$p"#;
+    let output = powershell_output(&["-NoProfile", "-Command", command])
+        .map_err(|e| format!("调用 PowerShell 失败: {}", e))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("获取前台窗口失败: {}", stderr.trim()));
+    }
+    String::from_utf8_lossy(&output.stdout)
+        .trim()
+        .parse::<u32>()
+        .ok()
+        .filter(|pid| *pid != 0)
+        .ok_or_else(|| "无法解析前台窗口进程 PID".to_string())
+}
 
-    if let Some(pid) = last_pid {
-        if is_pid_running(pid) {
-            return Some(pid);
+/// 获取当前前台窗口所属进程的 PID
+#[cfg(target_os = "linux")]
+pub fn get_foreground_window_pid() -> Result<u32, String> {
+    let output = Command::new("xdotool")
+        .args(["getactivewindow", "getwindowpid"])
+        .output()
+        .map_err(|e| format!("调用 xdotool 失败: {}", e))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("获取前台窗口失败: {}", stderr.trim()));
+    }
+    String::from_utf8_lossy(&output.stdout)
+        .trim()
+        .parse::<u32>()
+        .map_err(|_| "无法解析前台窗口进程 PID".to_string())
+}
+
+/// 返回 PID 本身及其所有祖先进程 PID（由近及远）
+pub fn collect_pid_with_ancestors(pid: u32) -> Vec<u32> {
+    let mut system = System::new();
+    system.refresh_processes(sysinfo::ProcessesToUpdate::All, true);
+    let mut chain = vec![pid];
+    let mut current = Pid::from(pid as usize);
+    while let Some(parent) = system.process(current).and_then(|process| process.parent()) {
+        let parent_pid = parent.as_u32();
+        if parent_pid == 0 || chain.contains(&parent_pid) {
+            break;
         }
+        chain.push(parent_pid);
+        current = parent;
     }
+    chain
+}
 
-    let mut matches = Vec::new();
-    for (pid, home) in entries {
-        match (&target, home.as_ref()) {
-            (Some(target_home), Some(home)) => {
-                let normalized = normalize_path_for_compare(home);
-                if !normalized.is_empty() && &normalized == target_home {
-                    matches.push(*pid);
-                }
-            }
-            (None, None) => {
-                matches.push(*pid);
-            }
-            (None, Some(home)) => {
-                let normalized = normalize_path_for_compare(home);
-                if normalized.is_empty() {
-                    matches.push(*pid);
+/// 返回 PID 本身及其所有子孙进程 PID（广度优先）
+#[cfg(target_os = "windows")]
+pub fn collect_pid_with_descendants(pid: u32) -> Vec<u32> {
+    let mut system = System::new();
+    system.refresh_processes(sysinfo::ProcessesToUpdate::All, true);
+    let mut children: HashMap<u32, Vec<u32>> = HashMap::new();
+    for (child_pid, process) in system.processes() {
+        if let Some(parent) = process.parent() {
+            children
+                .entry(parent.as_u32())
+                .or_default()
+                .push(child_pid.as_u32());
+        }
+    }
+    let mut result = vec![pid];
+    let mut index = 0;
+    while index < result.len() {
+        if let Some(items) = children.get(&result[index]) {
+            for child in items {
+                if !result.contains(child) {
+                    result.push(*child);
                 }
             }
-            _ => {}
         }
+        index += 1;
     }
+    result
+}
 
-    pick_preferred_pid(matches)
+/// 启动后最小化窗口：首次等待时间（窗口通常尚未创建）
+const MINIMIZE_AFTER_LAUNCH_INITIAL_DELAY_MS: u64 = 1500;
+/// 启动后最小化窗口：重试次数与间隔
+const MINIMIZE_AFTER_LAUNCH_ATTEMPTS: u32 = 5;
+const MINIMIZE_AFTER_LAUNCH_RETRY_INTERVAL_MS: u64 = 1000;
+
+#[cfg(target_os = "macos")]
+fn minimize_window_by_pid(pid: u32) -> Result<(), String> {
+    let script = format!(
+        "tell application \"System Events\" to tell (first process whose unix id is {}) to set value of attribute \"AXMinimized\" of window 1 to true",
+        pid
+    );
+    let output = Command::new("osascript")
+        .args(["-e", &script])
+        .output()
+        .map_err(|e| format!("调用 osascript 失败: {}", e))?;
+    if output.status.success() {
+        return Ok(());
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    Err(format!(
+        "窗口最小化失败，请检查系统辅助功能权限: {}",
+        stderr.trim()
+    ))
+}
+
+#[cfg(target_os = "windows")]
+fn minimize_window_by_pid(pid: u32) -> Result<(), String> {
+    let command = format!(
+        r#"$pid={pid};$p=Get-Process -Id $pid -ErrorAction Stop;$h=$p.MainWindowHandle;if ($h -eq 0) {{ throw 'MAIN_WINDOW_HANDLE_EMPTY' }};Add-Type @' 
+using System; 
+using System.Runtime.InteropServices; 
+public class Win32Minimize {{ 
+  [DllImport("user32.dll")] public static extern bool ShowWindowAsync(IntPtr hWnd, int nCmdShow); 
+}} 
+'@;[Win32Minimize]::ShowWindowAsync($h, 6) | Out-Null;"#
+    );
+    let output = powershell_output(&["-NoProfile", "-Command", &command])
+        .map_err(|e| format!("调用 PowerShell 失败: {}", e))?;
+    if output.status.success() {
+        return Ok(());
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    Err(format!("窗口最小化失败: {}", stderr.trim()))
+}
+
+/// wmctrl 的 `-b add,hidden` 只设置状态提示，并不会真正最小化，因此只使用 xdotool；
+/// 不加 `--sync`：窗口尚未出现时立即失败，由调用方的有限次重试等待
+#[cfg(target_os = "linux")]
+fn minimize_window_by_pid(pid: u32) -> Result<(), String> {
+    let output = Command::new("xdotool")
+        .args(["search", "--pid", &pid.to_string(), "windowminimize", "%@"])
+        .output()
+        .map_err(|e| format!("调用 xdotool 失败: {}", e))?;
+    if output.status.success() {
+        return Ok(());
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    Err(format!("窗口最小化失败: {}", stderr.trim()))
+}
+
+/// 启动成功后在后台线程中最小化实例窗口
+/// 窗口可能尚未创建，失败时短暂重试
+pub fn minimize_window_after_launch(pid: u32) {
+    thread::spawn(move || {
+        thread::sleep(Duration::from_millis(
+            MINIMIZE_AFTER_LAUNCH_INITIAL_DELAY_MS,
+        ));
+        let mut last_error = String::new();
+        for attempt in 1..=MINIMIZE_AFTER_LAUNCH_ATTEMPTS {
+            if !is_pid_running(pid) {
+                crate::modules::logger::log_warn(&format!(
+                    "[Minimize] 进程已退出，放弃最小化: pid={}",
+                    pid
+                ));
+                return;
+            }
+            match minimize_window_by_pid(pid) {
+                Ok(()) => {
+                    crate::modules::logger::log_info(&format!(
+                        "[Minimize] 启动后已最小化窗口: pid={}, attempt={}",
+                        pid, attempt
+                    ));
+                    return;
+                }
+                Err(err) => last_error = err,
+            }
+            thread::sleep(Duration::from_millis(
+                MINIMIZE_AFTER_LAUNCH_RETRY_INTERVAL_MS,
+            ));
+        }
+        crate::modules::logger::log_warn(&format!(
+            "[Minimize] 启动后最小化窗口失败: pid={}, err={}",
+            pid, last_error
+        ));
+    });
+}
+
+#[cfg(target_os = "macos")]
+fn move_window_by_pid(
+    pid: u32,
+    monitor: &crate::modules::window_state::MonitorRect,
+    scale_factor: f64,
+) -> Result<(), String> {
+    // System Events 使用逻辑坐标
+    let scale = if scale_factor > 0.0 {
+        scale_factor
+    } else {
+        1.0
+    };
+    let x = (f64::from(monitor.x) / scale).round() as i64;
+    let y = (f64::from(monitor.y) / scale).round() as i64;
+    let width = (f64::from(monitor.width) / scale).round() as i64;
+    let height = (f64::from(monitor.height) / scale).round() as i64;
+    let script = format!(
+        "tell application \"System Events\"\ntell (first process whose unix id is {})\nset position of window 1 to {{{}, {}}}\nset size of window 1 to {{{}, {}}}\nend tell\nend tell",
+        pid, x, y, width, height
+    );
+    let output = Command::new("osascript")
+        .args(["-e", &script])
+        .output()
+        .map_err(|e| format!("调用 osascript 失败: {}", e))?;
+    if output.status.success() {
+        return Ok(());
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    Err(format!(
+        "窗口移动失败，请检查系统辅助功能权限: {}",
+        stderr.trim()
+    ))
+}
+
+#[cfg(target_os = "windows")]
+fn move_window_by_pid(
+    pid: u32,
+    monitor: &crate::modules::window_state::MonitorRect,
+    _scale_factor: f64,
+) -> Result<(), String> {
+    let (x, y, width, height) = (monitor.x, monitor.y, monitor.width, monitor.height);
+    let command = format!(
+        r#"$targetPid={pid};$p=Get-Process -Id $targetPid -ErrorAction Stop;$h=$p.MainWindowHandle;if ($h -eq 0) {{ throw 'MAIN_WINDOW_HANDLE_EMPTY' }};Add-Type @' 
+using System; 
+using System.Runtime.InteropServices; 
+public class Win32Place {{ 
+  [DllImport("user32.dll")] public static extern bool ShowWindowAsync(IntPtr hWnd, int nCmdShow); 
+  [DllImport("user32.dll")] public static extern bool MoveWindow(IntPtr hWnd, int X, int Y, int nWidth, int nHeight, bool bRepaint); 
+}} 
+'@;[Win32Place]::ShowWindowAsync($h, 9) | Out-Null;if (-not [Win32Place]::MoveWindow($h, {x}, {y}, {width}, {height}, $true)) {{ throw 'MOVE_WINDOW_FAILED' }}"#
+    );
+    let output = powershell_output(&["-NoProfile", "-Command", &command])
+        .map_err(|e| format!("调用 PowerShell 失败: {}", e))?;
+    if output.status.success() {
+        return Ok(());
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    Err(format!("窗口移动失败: {}", stderr.trim()))
+}
+
+#[cfg(target_os = "linux")]
+fn move_window_by_pid(
+    pid: u32,
+    monitor: &crate::modules::window_state::MonitorRect,
+    _scale_factor: f64,
+) -> Result<(), String> {
+    let geometry = format!(
+        "0,{},{},{},{}",
+        monitor.x, monitor.y, monitor.width, monitor.height
+    );
+    if let Ok(output) = Command::new("wmctrl").arg("-lp").output() {
+        if output.status.success() {
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            for line in stdout.lines() {
+                let mut parts = line.split_whitespace();
+                let win_id = parts.next();
+                let _desktop = parts.next();
+                let pid_str = parts.next();
+                if let (Some(win_id), Some(pid_str)) = (win_id, pid_str) {
+                    if pid_str == pid.to_string() {
+                        // 最大化的窗口无法移动，先取消最大化
+                        let _ = Command::new("wmctrl")
+                            .args(["-ir", win_id, "-b", "remove,maximized_vert,maximized_horz"])
+                            .output();
+                        let moved = Command::new("wmctrl")
+                            .args(["-ir", win_id, "-e", &geometry])
+                            .output();
+                        if let Ok(moved) = moved {
+                            if moved.status.success() {
+                                return Ok(());
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    // 不加 `--sync`：窗口尚未出现时立即失败，由调用方的有限次重试等待
+    let output = Command::new("xdotool")
+        .args([
+            "search",
+            "--pid",
+            &pid.to_string(),
+            "windowmove",
+            &monitor.x.to_string(),
+            &monitor.y.to_string(),
+        ])
+        .output()
+        .map_err(|e| format!("调用 xdotool 失败: {}", e))?;
+    if output.status.success() {
+        return Ok(());
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    Err(format!("窗口移动失败: {}", stderr.trim()))
+}
+
+/// 启动成功后在后台线程中将实例窗口移动到指定显示器（尽力而为，失败只记录日志）
+/// 等待与重试策略与启动后最小化相同
+pub fn place_window_on_monitor_after_launch(pid: u32, monitor_index: usize) {
+    let monitor = match crate::get_app_handle()
+        .ok_or_else(|| "应用尚未初始化".to_string())
+        .and_then(|app| crate::modules::window_state::monitor_at(app, monitor_index))
+    {
+        Ok(monitor) => monitor,
+        Err(err) => {
+            crate::modules::logger::log_warn(&format!(
+                "[Placement] 无法获取目标显示器，跳过窗口移动: pid={}, monitor={}, err={}",
+                pid, monitor_index, err
+            ));
+            return;
+        }
+    };
+
+    thread::spawn(move || {
+        let (rect, scale_factor) = monitor;
+        thread::sleep(Duration::from_millis(
+            MINIMIZE_AFTER_LAUNCH_INITIAL_DELAY_MS,
+        ));
+        let mut last_error = String::new();
+        for attempt in 1..=MINIMIZE_AFTER_LAUNCH_ATTEMPTS {
+            if !is_pid_running(pid) {
+                crate::modules::logger::log_warn(&format!(
+                    "[Placement] 进程已退出，放弃移动窗口: pid={}",
+                    pid
+                ));
+                return;
+            }
+            match move_window_by_pid(pid, &rect, scale_factor) {
+                Ok(()) => {
+                    crate::modules::logger::log_info(&format!(
+                        "[Placement] 已将窗口移动到显示器 {}: pid={}, attempt={}",
+                        monitor_index, pid, attempt
+                    ));
+                    return;
+                }
+                Err(err) => last_error = err,
+            }
+            thread::sleep(Duration::from_millis(
+                MINIMIZE_AFTER_LAUNCH_RETRY_INTERVAL_MS,
+            ));
+        }
+        crate::modules::logger::log_warn(&format!(
+            "[Placement] 移动窗口到显示器 {} 失败: pid={}, err={}",
+            monitor_index, pid, last_error
+        ));
+    });
+}
+
+pub fn focus_antigravity_instance(
+    last_pid: Option<u32>,
+    user_data_dir: Option<&str>,
+) -> Result<u32, String> {
+    let resolve_start = Instant::now();
+    let pid = resolve_antigravity_pid(last_pid, user_data_dir)
+        .ok_or_else(|| "实例未运行，无法定位窗口".to_string())?;
+    crate::modules::logger::log_info(&format!(
+        "[Focus] Antigravity resolve pid={} elapsed={}ms",
+        pid,
+        resolve_start.elapsed().as_millis()
+    ));
+    let focus_start = Instant::now();
+    focus_window_by_pid(pid)?;
+    crate::modules::logger::log_info(&format!(
+        "[Focus] Antigravity focus pid={} elapsed={}ms",
+        pid,
+        focus_start.elapsed().as_millis()
+    ));
+    Ok(pid)
 }
 
-#[cfg(not(target_os = "macos"))]
 pub fn resolve_codex_pid_from_entries(
-    _last_pid: Option<u32>,
-    _codex_home: Option<&str>,
-    _entries: &[(u32, Option<String>)],
+    last_pid: Option<u32>,
+    codex_home: Option<&str>,
+    entries: &[(u32, Option<String>)],
 ) -> Option<u32> {
-    None
+    let target = codex_home
+        .map(|value| normalize_path_for_compare(value))
+        .filter(|value| !value.is_empty());
+
+    if let Some(pid) = last_pid {
+        if is_pid_running(pid) {
+            return Some(pid);
+        }
+    }
+
+    let mut matches = Vec::new();
+    for (pid, home) in entries {
+        match (&target, home.as_ref()) {
+            (Some(target_home), Some(home)) => {
+                let normalized = normalize_path_for_compare(home);
+                if !normalized.is_empty() && &normalized == target_home {
+                    matches.push(*pid);
+                }
+            }
+            (None, None) => {
+                matches.push(*pid);
+            }
+            (None, Some(home)) => {
+                let normalized = normalize_path_for_compare(home);
+                if normalized.is_empty() {
+                    matches.push(*pid);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    pick_preferred_pid(matches)
 }
 
-#[cfg(target_os = "macos")]
 pub fn resolve_codex_pid(last_pid: Option<u32>, codex_home: Option<&str>) -> Option<u32> {
     let entries = collect_codex_process_entries();
     resolve_codex_pid_from_entries(last_pid, codex_home, &entries)
 }
 
-#[cfg(not(target_os = "macos"))]
-pub fn resolve_codex_pid(_last_pid: Option<u32>, _codex_home: Option<&str>) -> Option<u32> {
-    None
-}
-
 pub fn focus_codex_instance(
     last_pid: Option<u32>,
     codex_home: Option<&str>,
@@ -2132,6 +2917,7 @@ pub fn focus_codex_instance(
 
 #[cfg(target_os = "windows")]
 fn collect_vscode_process_entries_from_powershell() -> Vec<(u32, Option<String>)> {
+    let helper_keywords = HelperKeywords::load();
     let mut entries: Vec<(u32, Option<String>)> = Vec::new();
     let output = powershell_output(&[
         "-Command",
@@ -2155,7 +2941,7 @@ fn collect_vscode_process_entries_from_powershell() -> Vec<(u32, Option<String>)
             Err(_) => continue,
         };
         let lower = cmdline.to_lowercase();
-        if is_helper_command_line(&lower) || lower.contains("crashpad_handler") {
+        if is_helper_command_line(&helper_keywords, &lower) || lower.contains("crashpad_handler") {
             continue;
         }
         let dir = extract_user_data_dir_from_command_line(cmdline).and_then(|value| {
@@ -2174,6 +2960,7 @@ fn collect_vscode_process_entries_from_powershell() -> Vec<(u32, Option<String>)
 }
 
 pub fn collect_vscode_process_entries() -> Vec<(u32, Option<String>)> {
+    let helper_keywords = HelperKeywords::load();
     #[cfg(target_os = "windows")]
     {
         let entries = collect_vscode_process_entries_from_powershell();
@@ -2208,7 +2995,7 @@ pub fn collect_vscode_process_entries() -> Vec<(u32, Option<String>)> {
             .map(|arg| arg.to_string_lossy().to_lowercase())
             .collect::<Vec<String>>()
             .join(" ");
-        let is_helper = is_helper_command_line(&args_str) || args_str.contains("crashpad");
+        let is_helper = is_helper_command_line(&helper_keywords, &args_str);
 
         #[cfg(target_os = "macos")]
         let is_vscode = exe_path.contains("visual studio code.app/contents/macos/");
@@ -2246,7 +3033,9 @@ pub fn collect_vscode_process_entries() -> Vec<(u32, Option<String>)> {
                 if !lower.contains("visual studio code.app/contents/macos/") {
                     continue;
                 }
-                if lower.contains("crashpad_handler") || is_helper_command_line(&lower) {
+                if lower.contains("crashpad_handler")
+                    || is_helper_command_line(&helper_keywords, &lower)
+                {
                     continue;
                 }
                 let dir = extract_user_data_dir_from_command_line(cmdline);
@@ -2285,7 +3074,7 @@ pub fn collect_vscode_process_entries() -> Vec<(u32, Option<String>)> {
                 if !cmd_lower.contains("code") && !exe_path.contains("/code") {
                     continue;
                 }
-                if is_helper_command_line(&cmd_lower) {
+                if is_helper_command_line(&helper_keywords, &cmd_lower) {
                     continue;
                 }
                 let dir = extract_user_data_dir_from_command_line(&cmdline_str);
@@ -2435,9 +3224,10 @@ fn list_user_data_dirs_from_ps() -> Vec<String> {
     result
 }
 
+/// 返回 (pid, user-data-dir, 进程架构)
 #[cfg(target_os = "macos")]
 #[allow(dead_code)]
-fn collect_antigravity_process_entries_macos() -> Vec<(u32, Option<String>)> {
+fn collect_antigravity_process_entries_macos() -> Vec<(u32, Option<String>, Option<String>)> {
     let mut pids = Vec::new();
     if let Ok(output) = Command::new("pgrep")
         .args(["-f", ANTIGRAVITY_APP_PATH])
@@ -2484,7 +3274,8 @@ fn collect_antigravity_process_entries_macos() -> Vec<(u32, Option<String>)> {
                 continue;
             }
             let dir = extract_user_data_dir_from_command_line(cmdline);
-            result.push((pid, dir));
+            let arch = detect_process_arch(pid).map(|(arch, _)| arch);
+            result.push((pid, dir, arch));
         }
     }
 
@@ -2618,7 +3409,7 @@ fn collect_antigravity_pids_by_user_data_dir(user_data_dir: &str) -> Vec<u32> {
     {
         let entries = collect_antigravity_process_entries_macos();
         if !entries.is_empty() {
-            for (pid, dir) in entries {
+            for (pid, dir, _) in entries {
                 if let Some(dir) = dir {
                     let normalized = normalize_path_for_compare(&dir);
                     if normalized == target {
@@ -2865,7 +3656,7 @@ pub fn list_antigravity_user_data_dirs() -> Vec<String> {
     {
         let mut pid_dirs: Vec<String> = collect_antigravity_process_entries_macos()
             .into_iter()
-            .filter_map(|(_, dir)| dir)
+            .filter_map(|(_, dir, _)| dir)
             .map(|dir| normalize_path_for_compare(&dir))
             .filter(|dir| !dir.is_empty())
             .collect();
@@ -2909,6 +3700,7 @@ pub fn list_antigravity_user_data_dirs() -> Vec<String> {
 /// 获取所有 Antigravity 进程的 PID（包括主进程和Helper进程）
 #[allow(dead_code)]
 fn get_antigravity_pids() -> Vec<u32> {
+    let helper_keywords = HelperKeywords::load();
     let mut system = System::new();
     system.refresh_processes(sysinfo::ProcessesToUpdate::All, true);
 
@@ -2938,16 +3730,7 @@ fn get_antigravity_pids() -> Vec<u32> {
             .collect::<Vec<String>>()
             .join(" ");
 
-        let is_helper = args_str.contains("--type=")
-            || name.contains("helper")
-            || name.contains("plugin")
-            || name.contains("renderer")
-            || name.contains("gpu")
-            || name.contains("crashpad")
-            || name.contains("utility")
-            || name.contains("audio")
-            || name.contains("sandbox")
-            || exe_path.contains("crashpad");
+        let is_helper = is_helper_process(&helper_keywords, &name, &args_str);
 
         #[cfg(target_os = "macos")]
         {
@@ -3087,6 +3870,85 @@ fn filter_entries_by_target_dirs(
         .collect()
 }
 
+pub const ANTIGRAVITY_CLOSE_PROGRESS_EVENT: &str = "instance:close_progress";
+pub const VSCODE_CLOSE_PROGRESS_EVENT: &str = "github_copilot:instance_close_progress";
+pub const CODEX_CLOSE_PROGRESS_EVENT: &str = "codex:instance_close_progress";
+
+/// 批量关闭时单个实例确认退出的进度
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstanceCloseProgress {
+    pub instance_id: String,
+    /// 仍未退出的实例数量
+    pub remaining: usize,
+}
+
+/// 按实例目录跟踪主进程，目录下的主进程全部退出后回调一次 (目录原始值, 剩余实例数)
+struct CloseProgressTracker<'a> {
+    pending: Vec<(String, Vec<u32>)>,
+    on_progress: &'a mut dyn FnMut(&str, usize),
+}
+
+impl<'a> CloseProgressTracker<'a> {
+    fn new<ResolveDir>(
+        user_data_dirs: &[String],
+        entries: &[(u32, Option<String>)],
+        pids: &[u32],
+        resolve_dir: ResolveDir,
+        on_progress: &'a mut dyn FnMut(&str, usize),
+    ) -> Self
+    where
+        ResolveDir: Fn(Option<&String>) -> Option<String>,
+    {
+        let mut pending: Vec<(String, Vec<u32>)> = Vec::new();
+        for (pid, dir) in entries {
+            if !pids.contains(pid) {
+                continue;
+            }
+            let Some(normalized) = resolve_dir(dir.as_ref()) else {
+                continue;
+            };
+            let Some(original) = user_data_dirs
+                .iter()
+                .find(|value| normalize_path_for_compare(value) == normalized)
+            else {
+                continue;
+            };
+            match pending.iter_mut().find(|(dir, _)| dir == original) {
+                Some((_, dir_pids)) => dir_pids.push(*pid),
+                None => pending.push((original.clone(), vec![*pid])),
+            }
+        }
+        Self {
+            pending,
+            on_progress,
+        }
+    }
+
+    fn poll(&mut self) {
+        if self.pending.is_empty() {
+            return;
+        }
+        let mut system = System::new();
+        system.refresh_processes(sysinfo::ProcessesToUpdate::All, true);
+        let mut stopped = Vec::new();
+        self.pending.retain(|(dir, pids)| {
+            let alive = pids
+                .iter()
+                .any(|pid| system.process(Pid::from(*pid as usize)).is_some());
+            if !alive {
+                stopped.push(dir.clone());
+            }
+            alive
+        });
+        let mut remaining = self.pending.len() + stopped.len();
+        for dir in stopped {
+            remaining -= 1;
+            (self.on_progress)(&dir, remaining);
+        }
+    }
+}
+
 fn close_managed_instances_common<CollectEntries, SelectMainPids, CollectRemainingEntries>(
     log_prefix: &str,
     start_message: &str,
@@ -3096,12 +3958,15 @@ fn close_managed_instances_common<CollectEntries, SelectMainPids, CollectRemaini
     failure_message: &str,
     user_data_dirs: &[String],
     timeout_secs: u64,
+    default_dir: Option<&str>,
     collect_entries: CollectEntries,
     select_main_pids: SelectMainPids,
     collect_remaining_entries: CollectRemainingEntries,
     graceful_close: Option<fn(u32)>,
     graceful_wait_secs: Option<u64>,
+    has_open_window: Option<fn(u32) -> bool>,
     detail_logger: Option<fn(&[u32])>,
+    on_progress: &mut dyn FnMut(&str, usize),
 ) -> Result<(), String>
 where
     CollectEntries: Fn() -> Vec<(u32, Option<String>)>,
@@ -3142,22 +4007,61 @@ where
         process_display_name
     ));
 
+    let mut tracker = CloseProgressTracker::new(
+        user_data_dirs,
+        &entries,
+        &pids,
+        |dir| resolve_entry_user_data_dir_for_matching(dir, default_dir),
+        on_progress,
+    );
+
     if let Some(graceful_close_fn) = graceful_close {
         for pid in &pids {
             graceful_close_fn(*pid);
         }
         if let Some(wait_secs) = graceful_wait_secs {
-            if wait_pids_exit(&pids, wait_secs) {
+            let mut exited = wait_pids_exit_with_tick(&pids, wait_secs, &mut || tracker.poll());
+            if !exited {
+                // 进程仍在且还有窗口，多半是「是否保存更改」之类的对话框在等待用户操作，再等一轮
+                let dialog_pids: Vec<u32> = has_open_window
+                    .map(|check| {
+                        pids.iter()
+                            .copied()
+                            .filter(|pid| is_pid_running(*pid) && check(*pid))
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                if dialog_pids.is_empty() {
+                    crate::modules::logger::log_info(&format!(
+                        "[{}] graceful close timed out after {}s, no open window, force closing",
+                        log_prefix, wait_secs
+                    ));
+                } else {
+                    crate::modules::logger::log_info(&format!(
+                        "[{}] window still open after {}s (possible unsaved-changes dialog), extending wait once, pids={:?}",
+                        log_prefix, wait_secs, dialog_pids
+                    ));
+                    exited = wait_pids_exit_with_tick(&pids, wait_secs, &mut || tracker.poll());
+                    if !exited {
+                        crate::modules::logger::log_warn(&format!(
+                            "[{}] still running after extended wait, force closing",
+                            log_prefix
+                        ));
+                    }
+                }
+            }
+            if exited {
                 crate::modules::logger::log_info(&format!(
                     "[{}] graceful close finished, targets={:?}",
                     log_prefix, pids
                 ));
+                tracker.poll();
                 return Ok(());
             }
         }
     }
 
-    if let Err(err) = close_pids(&pids, timeout_secs) {
+    if let Err(err) = close_pids_with_tick(&pids, timeout_secs, &mut || tracker.poll()) {
         crate::modules::logger::log_warn(&format!(
             "[{}] close_pids returned error: {}",
             log_prefix, err
@@ -3179,7 +4083,7 @@ where
                 "[{}] retry force close for remaining pids={:?}",
                 log_prefix, remaining_pids
             ));
-            if let Err(err) = close_pids(&remaining_pids, 6) {
+            if let Err(err) = close_pids_with_tick(&remaining_pids, 6, &mut || tracker.poll()) {
                 crate::modules::logger::log_warn(&format!(
                     "[{}] retry close_pids returned error: {}",
                     log_prefix, err
@@ -3188,6 +4092,7 @@ where
             remaining_entries = collect_remaining_entries(&target_dirs);
         }
     }
+    tracker.poll();
 
     if !remaining_entries.is_empty() {
         let remaining_pids = collect_remaining_pids(&remaining_entries);
@@ -3204,13 +4109,19 @@ where
         ));
     }
 
+    crate::modules::logger::log_info(&format!(
+        "[{}] force close finished, targets={:?}",
+        log_prefix, pids
+    ));
     Ok(())
 }
 
 /// 关闭受管 Antigravity 实例（按 user-data-dir 匹配，包含默认实例目录）
-pub fn close_antigravity_instances(
+/// 每个实例目录确认退出后回调 (目录, 剩余实例数)
+pub fn close_antigravity_instances_with_progress(
     user_data_dirs: &[String],
     timeout_secs: u64,
+    on_progress: &mut dyn FnMut(&str, usize),
 ) -> Result<(), String> {
     let default_dir = crate::modules::instance::get_default_user_data_dir()
         .ok()
@@ -3226,6 +4137,7 @@ pub fn close_antigravity_instances(
         "无法关闭受管 Antigravity 实例进程，请手动关闭后重试",
         user_data_dirs,
         timeout_secs,
+        default_dir.as_deref(),
         collect_antigravity_process_entries,
         |entries, target_dirs| {
             select_main_pids_by_target_dirs(entries, target_dirs, default_dir.as_deref())
@@ -3237,15 +4149,97 @@ pub fn close_antigravity_instances(
                 default_dir.as_deref(),
             )
         },
+        #[cfg(any(target_os = "macos", target_os = "windows"))]
+        Some(request_antigravity_graceful_close as fn(u32)),
+        #[cfg(not(any(target_os = "macos", target_os = "windows")))]
         None,
+        Some(crate::modules::config::get_antigravity_graceful_wait_secs()),
         None,
         #[cfg(target_os = "windows")]
         Some(log_antigravity_process_details_for_pids as fn(&[u32])),
         #[cfg(not(target_os = "windows"))]
         None,
+        on_progress,
     )
 }
 
+/// 请求 Antigravity 主进程优雅退出，给编辑器保存状态的机会
+/// macOS 激活窗口后发送 Cmd+Q，Windows 向主窗口投递 WM_CLOSE
+#[cfg(any(target_os = "macos", target_os = "windows"))]
+fn request_antigravity_graceful_close(pid: u32) {
+    if pid == 0 || !is_pid_running(pid) {
+        return;
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        let script = format!(
+            "tell application \"System Events\" to set frontmost of (first process whose unix id is {}) to true\n\
+tell application \"System Events\" to keystroke \"q\" using command down",
+            pid
+        );
+        match Command::new("osascript").args(["-e", &script]).output() {
+            Ok(output) => {
+                if output.status.success() {
+                    crate::modules::logger::log_info(&format!(
+                        "[AG Close] 已发送优雅退出请求 pid={}",
+                        pid
+                    ));
+                } else {
+                    let stderr = String::from_utf8_lossy(&output.stderr);
+                    crate::modules::logger::log_warn(&format!(
+                        "[AG Close] 优雅退出失败 pid={} err={}",
+                        pid,
+                        stderr.trim()
+                    ));
+                }
+            }
+            Err(e) => {
+                crate::modules::logger::log_warn(&format!(
+                    "[AG Close] 调用 osascript 失败 pid={} err={}",
+                    pid, e
+                ));
+            }
+        }
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        let command = format!(
+            r#"$pid={pid};$p=Get-Process -Id $pid -ErrorAction Stop;$h=$p.MainWindowHandle;if ($h -eq 0) {{ throw 'MAIN_WINDOW_HANDLE_EMPTY' }};Add-Type @' 
+using System; 
+using System.Runtime.InteropServices; 
+public class Win32Close {{ 
+  [DllImport("user32.dll")] public static extern bool PostMessage(IntPtr hWnd, uint Msg, IntPtr wParam, IntPtr lParam); 
+}} 
+'@;[Win32Close]::PostMessage($h, 0x0010, [IntPtr]::Zero, [IntPtr]::Zero) | Out-Null;"#
+        );
+        match powershell_output(&["-NoProfile", "-Command", &command]) {
+            Ok(output) => {
+                if output.status.success() {
+                    crate::modules::logger::log_info(&format!(
+                        "[AG Close] 已发送 WM_CLOSE pid={}",
+                        pid
+                    ));
+                } else {
+                    let stderr = String::from_utf8_lossy(&output.stderr);
+                    crate::modules::logger::log_warn(&format!(
+                        "[AG Close] 发送 WM_CLOSE 失败 pid={} err={}",
+                        pid,
+                        stderr.trim()
+                    ));
+                }
+            }
+            Err(e) => {
+                crate::modules::logger::log_warn(&format!(
+                    "[AG Close] 调用 PowerShell 失败 pid={} err={}",
+                    pid, e
+                ));
+            }
+        }
+    }
+}
+
 /// 关闭指定实例（按 user-data-dir 匹配）
 
 #[allow(dead_code)]
@@ -3278,13 +4272,63 @@ pub fn close_pid(pid: u32, timeout_secs: u64) -> Result<(), String> {
     }
 
     send_close_signal(pid);
-    if wait_pids_exit(&[pid], timeout_secs) {
+    if wait_pids_exit(&[pid], timeout_secs) || force_kill_remaining(&[pid]) {
         Ok(())
     } else {
         Err("无法关闭实例进程，请手动关闭后重试".to_string())
     }
 }
 
+/// SIGKILL 后额外等待进程退出的时间
+#[cfg(any(target_os = "macos", target_os = "linux"))]
+const SIGKILL_WAIT_SECS: u64 = 2;
+
+/// 设置 COCKPIT_NO_SIGKILL 后只做优雅关闭，不补发 SIGKILL
+#[cfg(any(target_os = "macos", target_os = "linux"))]
+fn is_sigkill_disabled() -> bool {
+    std::env::var("COCKPIT_NO_SIGKILL")
+        .map(|value| {
+            matches!(
+                value.trim().to_lowercase().as_str(),
+                "1" | "true" | "yes" | "on"
+            )
+        })
+        .unwrap_or(false)
+}
+
+/// SIGTERM 超时后对仍存活的进程发送 SIGKILL，返回是否已全部退出
+/// Windows 关闭时已使用 taskkill /F，无需兜底
+fn force_kill_remaining(pids: &[u32]) -> bool {
+    #[cfg(any(target_os = "macos", target_os = "linux"))]
+    {
+        if is_sigkill_disabled() {
+            return false;
+        }
+        let remaining: Vec<u32> = pids
+            .iter()
+            .copied()
+            .filter(|pid| *pid != 0 && is_pid_running(*pid))
+            .collect();
+        if remaining.is_empty() {
+            return true;
+        }
+        crate::modules::logger::log_warn(&format!(
+            "[ClosePids] SIGTERM timeout, sending SIGKILL to {:?}",
+            remaining
+        ));
+        for pid in &remaining {
+            let _ = Command::new("kill").args(["-9", &pid.to_string()]).output();
+        }
+        wait_pids_exit(&remaining, SIGKILL_WAIT_SECS)
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "linux")))]
+    {
+        let _ = pids;
+        false
+    }
+}
+
 fn send_close_signal(pid: u32) {
     if pid == 0 || !is_pid_running(pid) {
         return;
@@ -3423,11 +4467,17 @@ fn log_vscode_process_details_for_pids(pids: &[u32]) {
 }
 
 fn wait_pids_exit(pids: &[u32], timeout_secs: u64) -> bool {
+    wait_pids_exit_with_tick(pids, timeout_secs, &mut || {})
+}
+
+/// 等待进程退出，每轮检查后调用一次 tick（用于上报关闭进度）
+fn wait_pids_exit_with_tick(pids: &[u32], timeout_secs: u64, tick: &mut dyn FnMut()) -> bool {
     if pids.is_empty() {
         return true;
     }
     let start = std::time::Instant::now();
     loop {
+        tick();
         let mut any_alive = false;
         for pid in pids {
             if *pid != 0 && is_pid_running(*pid) {
@@ -3446,6 +4496,14 @@ fn wait_pids_exit(pids: &[u32], timeout_secs: u64) -> bool {
 }
 
 fn close_pids(pids: &[u32], timeout_secs: u64) -> Result<(), String> {
+    close_pids_with_tick(pids, timeout_secs, &mut || {})
+}
+
+fn close_pids_with_tick(
+    pids: &[u32],
+    timeout_secs: u64,
+    tick: &mut dyn FnMut(),
+) -> Result<(), String> {
     if pids.is_empty() {
         return Ok(());
     }
@@ -3468,9 +4526,15 @@ fn close_pids(pids: &[u32], timeout_secs: u64) -> Result<(), String> {
         send_close_signal(*pid);
     }
 
-    if wait_pids_exit(&targets, timeout_secs) {
+    if wait_pids_exit_with_tick(&targets, timeout_secs, tick) {
         crate::modules::logger::log_info(&format!("[ClosePids] all exited, targets={:?}", targets));
         Ok(())
+    } else if force_kill_remaining(&targets) {
+        crate::modules::logger::log_warn(&format!(
+            "[ClosePids] all exited after SIGKILL, targets={:?}",
+            targets
+        ));
+        Ok(())
     } else {
         let remaining: Vec<u32> = targets
             .iter()
@@ -3483,19 +4547,212 @@ fn close_pids(pids: &[u32], timeout_secs: u64) -> Result<(), String> {
         ));
         Err("无法关闭实例进程，请手动关闭后重试".to_string())
     }
-}
+}
+
+/// 启动 Antigravity
+pub fn start_antigravity() -> Result<u32, String> {
+    start_antigravity_with_args("", &[], &HashMap::new())
+}
+
+/// 低功耗启动附加的编辑器参数（禁用 GPU 加速以降低耗电）
+const LOW_POWER_LAUNCH_ARGS: [&str; 1] = ["--disable-gpu"];
+
+/// 追加低功耗启动参数（已存在的参数不重复添加）
+pub fn apply_low_power_args(args: &mut Vec<String>) {
+    for flag in LOW_POWER_LAUNCH_ARGS {
+        if !args.iter().any(|arg| arg.trim() == flag) {
+            args.push(flag.to_string());
+        }
+    }
+}
+
+/// 解析启动时是否打开新窗口
+/// 优先级：实例附加参数中显式的 --new-window / --reuse-window > 应用默认窗口模式配置 > 内置默认值
+pub fn resolve_use_new_window(app: &str, extra_args: &[String], fallback: bool) -> bool {
+    for arg in extra_args.iter().rev() {
+        match arg.trim() {
+            "--new-window" => return true,
+            "--reuse-window" => return false,
+            _ => {}
+        }
+    }
+    crate::modules::config::get_default_new_window(app).unwrap_or(fallback)
+}
+
+/// 被移除的附加参数及原因
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StrippedLaunchArg {
+    pub arg: String,
+    pub reason: String,
+}
+
+/// 实例最终的启动参数
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EffectiveLaunchArgs {
+    pub args: Vec<String>,
+    pub stripped: Vec<StrippedLaunchArg>,
+}
+
+/// 过滤附加参数中由启动流程自行注入的参数
+/// --user-data-dir 由实例目录决定；--new-window / --reuse-window 已用于解析窗口模式
+fn strip_reserved_launch_args(extra_args: &[String]) -> (Vec<String>, Vec<StrippedLaunchArg>) {
+    let mut kept = Vec::new();
+    let mut stripped = Vec::new();
+    let mut iter = extra_args.iter().peekable();
+    while let Some(raw) = iter.next() {
+        let arg = raw.trim();
+        if arg.is_empty() {
+            continue;
+        }
+        if arg == "--user-data-dir" || arg.starts_with("--user-data-dir=") {
+            let mut removed = arg.to_string();
+            if arg == "--user-data-dir" {
+                if let Some(value) = iter.next_if(|next| !next.trim().starts_with("--")) {
+                    removed = format!("{} {}", removed, value);
+                }
+            }
+            stripped.push(StrippedLaunchArg {
+                arg: removed,
+                reason: "用户数据目录由实例配置决定".to_string(),
+            });
+            continue;
+        }
+        if arg == "--new-window" || arg == "--reuse-window" {
+            stripped.push(StrippedLaunchArg {
+                arg: arg.to_string(),
+                reason: "已作为窗口模式设置注入".to_string(),
+            });
+            continue;
+        }
+        kept.push(raw.to_string());
+    }
+    (kept, stripped)
+}
+
+/// 解析 Antigravity 最终的启动参数（注入目录与窗口模式，并移除冲突的附加参数）
+pub fn resolve_antigravity_launch_args(
+    user_data_dir: &str,
+    extra_args: &[String],
+) -> EffectiveLaunchArgs {
+    let mut args: Vec<String> = Vec::new();
+    if !user_data_dir.trim().is_empty() {
+        args.push("--user-data-dir".to_string());
+        args.push(user_data_dir.trim().to_string());
+    }
+    if resolve_use_new_window("antigravity", extra_args, false) {
+        args.push("--new-window".to_string());
+    } else {
+        args.push("--reuse-window".to_string());
+    }
+    let (kept, stripped) = strip_reserved_launch_args(extra_args);
+    args.extend(kept);
+    EffectiveLaunchArgs { args, stripped }
+}
+
+fn build_antigravity_launch_args(user_data_dir: &str, extra_args: &[String]) -> Vec<String> {
+    resolve_antigravity_launch_args(user_data_dir, extra_args).args
+}
+
+fn quote_command_arg(arg: &str) -> String {
+    if !arg.is_empty()
+        && !arg
+            .chars()
+            .any(|ch| ch.is_whitespace() || ch == '"' || ch == '\'')
+    {
+        return arg.to_string();
+    }
+    format!("\"{}\"", arg.replace('"', "\\\""))
+}
+
+fn format_command_line(program: &str, args: &[String]) -> String {
+    std::iter::once(program)
+        .chain(args.iter().map(|arg| arg.as_str()))
+        .map(quote_command_arg)
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+/// 启动命令预览（仅解析，不执行）
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LaunchCommandPreview {
+    pub program: String,
+    pub args: Vec<String>,
+    /// 额外设置的环境变量
+    pub env: HashMap<String, String>,
+    pub command_line: String,
+    /// macOS 直接执行失败时回退的 `open -a ... --args` 形式
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fallback_command_line: Option<String>,
+}
+
+/// 预览 Antigravity 启动命令（与 start_antigravity_with_args 的解析保持一致）
+pub fn preview_antigravity_launch_command(
+    user_data_dir: &str,
+    extra_args: &[String],
+    env_vars: &HashMap<String, String>,
+) -> Result<LaunchCommandPreview, String> {
+    let args = build_antigravity_launch_args(user_data_dir, extra_args);
+
+    #[cfg(target_os = "macos")]
+    {
+        let open_args = resolve_macos_app_root_from_config("antigravity").map(|app_root| {
+            let mut open_args = vec!["-a".to_string(), app_root];
+            if !args.is_empty() {
+                open_args.push("--args".to_string());
+                open_args.extend(args.iter().cloned());
+            }
+            open_args
+        });
+        if let Ok(path) = resolve_antigravity_launch_path() {
+            let program = path.to_string_lossy().to_string();
+            return Ok(LaunchCommandPreview {
+                command_line: format_command_line(&program, &args),
+                program,
+                args,
+                env: env_vars.clone(),
+                fallback_command_line: open_args
+                    .map(|open_args| format_command_line("open", &open_args)),
+            });
+        }
+        if let Some(open_args) = open_args {
+            return Ok(LaunchCommandPreview {
+                command_line: format_command_line("open", &open_args),
+                program: "open".to_string(),
+                args: open_args,
+                env: HashMap::new(),
+                fallback_command_line: None,
+            });
+        }
+        return Err(app_path_unresolved_error("antigravity"));
+    }
 
-/// 启动 Antigravity
-pub fn start_antigravity() -> Result<u32, String> {
-    start_antigravity_with_args("", &[])
+    #[cfg(not(target_os = "macos"))]
+    {
+        let program = resolve_antigravity_launch_path()?
+            .to_string_lossy()
+            .to_string();
+        Ok(LaunchCommandPreview {
+            command_line: format_command_line(&program, &args),
+            program,
+            args,
+            env: env_vars.clone(),
+            fallback_command_line: None,
+        })
+    }
 }
 
 /// 启动 Antigravity（支持 user-data-dir 与附加参数）
+/// env_vars 为实例级环境变量，macOS 回退为 `open -a` 启动时无法传递
 pub fn start_antigravity_with_args(
     user_data_dir: &str,
     extra_args: &[String],
+    env_vars: &HashMap<String, String>,
 ) -> Result<u32, String> {
     crate::modules::logger::log_info("正在启动 Antigravity...");
+    let launch_args = build_antigravity_launch_args(user_data_dir, extra_args);
 
     #[cfg(target_os = "macos")]
     let launch_path = resolve_antigravity_launch_path().ok();
@@ -3507,16 +4764,7 @@ pub fn start_antigravity_with_args(
         let app_root = resolve_macos_app_root_from_config("antigravity");
         if let Some(path) = launch_path {
             let mut cmd = Command::new(&path);
-            if !user_data_dir.trim().is_empty() {
-                cmd.arg("--user-data-dir");
-                cmd.arg(user_data_dir.trim());
-            }
-            cmd.arg("--reuse-window");
-            for arg in extra_args {
-                if !arg.trim().is_empty() {
-                    cmd.arg(arg);
-                }
-            }
+            cmd.args(&launch_args).envs(env_vars);
             match spawn_detached_unix(&mut cmd) {
                 Ok(child) => {
                     crate::modules::logger::log_info("Antigravity 启动命令已发送");
@@ -3524,18 +4772,7 @@ pub fn start_antigravity_with_args(
                 }
                 Err(e) => {
                     if let Some(app_root) = app_root {
-                        let mut args: Vec<String> = Vec::new();
-                        if !user_data_dir.trim().is_empty() {
-                            args.push("--user-data-dir".to_string());
-                            args.push(user_data_dir.trim().to_string());
-                        }
-                        args.push("--reuse-window".to_string());
-                        for arg in extra_args {
-                            if !arg.trim().is_empty() {
-                                args.push(arg.to_string());
-                            }
-                        }
-                        let pid = spawn_open_app(&app_root, &args)
+                        let pid = spawn_open_app(&app_root, &launch_args)
                             .map_err(|open_err| format!("启动 Antigravity 失败: {}", open_err))?;
                         crate::modules::logger::log_info("Antigravity 启动命令已发送");
                         return Ok(pid);
@@ -3545,23 +4782,17 @@ pub fn start_antigravity_with_args(
             }
         }
         if let Some(app_root) = app_root {
-            let mut args: Vec<String> = Vec::new();
-            if !user_data_dir.trim().is_empty() {
-                args.push("--user-data-dir".to_string());
-                args.push(user_data_dir.trim().to_string());
-            }
-            args.push("--reuse-window".to_string());
-            for arg in extra_args {
-                if !arg.trim().is_empty() {
-                    args.push(arg.to_string());
-                }
+            if !env_vars.is_empty() {
+                crate::modules::logger::log_warn(
+                    "通过 open -a 启动 Antigravity 时无法设置实例环境变量",
+                );
             }
-            let pid = spawn_open_app(&app_root, &args)
+            let pid = spawn_open_app(&app_root, &launch_args)
                 .map_err(|e| format!("启动 Antigravity 失败: {}", e))?;
             crate::modules::logger::log_info("Antigravity 启动命令已发送");
             return Ok(pid);
         }
-        return Err(app_path_missing_error("antigravity"));
+        return Err(app_path_unresolved_error("antigravity"));
     }
 
     #[cfg(target_os = "windows")]
@@ -3577,16 +4808,7 @@ pub fn start_antigravity_with_args(
         } else {
             cmd.creation_flags(0x08000000);
         }
-        if !user_data_dir.trim().is_empty() {
-            cmd.arg("--user-data-dir");
-            cmd.arg(user_data_dir.trim());
-        }
-        cmd.arg("--reuse-window");
-        for arg in extra_args {
-            if !arg.trim().is_empty() {
-                cmd.arg(arg);
-            }
-        }
+        cmd.args(&launch_args).envs(env_vars);
         let child = cmd
             .spawn()
             .map_err(|e| format!("启动 Antigravity 失败: {}", e))?;
@@ -3605,16 +4827,7 @@ pub fn start_antigravity_with_args(
                 .stdout(Stdio::null())
                 .stderr(Stdio::null());
         }
-        if !user_data_dir.trim().is_empty() {
-            cmd.arg("--user-data-dir");
-            cmd.arg(user_data_dir.trim());
-        }
-        cmd.arg("--reuse-window");
-        for arg in extra_args {
-            if !arg.trim().is_empty() {
-                cmd.arg(arg);
-            }
-        }
+        cmd.args(&launch_args).envs(env_vars);
         let child =
             spawn_detached_unix(&mut cmd).map_err(|e| format!("启动 Antigravity 失败: {}", e))?;
         crate::modules::logger::log_info(&format!(
@@ -3630,6 +4843,7 @@ pub fn start_antigravity_with_args(
 
 #[cfg(target_os = "macos")]
 pub fn collect_codex_process_entries() -> Vec<(u32, Option<String>)> {
+    let helper_keywords = HelperKeywords::load();
     let mut result = Vec::new();
     let mut pids: Vec<u32> = Vec::new();
     if let Ok(output) = Command::new("pgrep")
@@ -3719,15 +4933,7 @@ pub fn collect_codex_process_entries() -> Vec<(u32, Option<String>)> {
             }
         }
         let args_lower = args.join(" ").to_lowercase();
-        let is_helper = args_lower.contains("--type=")
-            || args_lower.contains("helper")
-            || args_lower.contains("renderer")
-            || args_lower.contains("gpu")
-            || args_lower.contains("crashpad")
-            || args_lower.contains("utility")
-            || args_lower.contains("audio")
-            || args_lower.contains("sandbox");
-        if is_helper {
+        if is_helper_command_line(&helper_keywords, &args_lower) {
             continue;
         }
         let mut codex_home = extract_env_value_from_tokens(&env_tokens, "CODEX_HOME");
@@ -3740,23 +4946,120 @@ pub fn collect_codex_process_entries() -> Vec<(u32, Option<String>)> {
         if codex_home.is_none() {
             codex_home = extract_env_value(&cmdline, "CODEX_HOME");
         }
-        if let Some(ref home) = codex_home {
-            crate::modules::logger::log_info(&format!(
-                "[Codex Instances] pid={} CODEX_HOME={}",
-                pid, home
-            ));
-        } else {
-            crate::modules::logger::log_info(&format!(
-                "[Codex Instances] pid={} CODEX_HOME not found",
-                pid
-            ));
+        log_codex_home(pid, codex_home.as_ref());
+        result.push((pid, codex_home));
+    }
+    result
+}
+
+/// 从 `/proc/<pid>/environ` 格式（以 \0 分隔的 KEY=VALUE）的内容中读取指定环境变量
+#[cfg(any(target_os = "linux", test))]
+fn parse_environ_value(environ: &[u8], key: &str) -> Option<String> {
+    let prefix = format!("{}=", key);
+    environ
+        .split(|byte| *byte == 0)
+        .map(String::from_utf8_lossy)
+        .find_map(|entry| entry.strip_prefix(prefix.as_str()).map(str::to_string))
+        .filter(|value| !value.trim().is_empty())
+}
+
+fn log_codex_home(pid: u32, codex_home: Option<&String>) {
+    match codex_home {
+        Some(home) => crate::modules::logger::log_info(&format!(
+            "[Codex Instances] pid={} CODEX_HOME={}",
+            pid, home
+        )),
+        None => crate::modules::logger::log_info(&format!(
+            "[Codex Instances] pid={} CODEX_HOME not found",
+            pid
+        )),
+    }
+}
+
+/// Windows：CIM 无法读取其他进程的环境变量，只能识别命令行中显式写出的 CODEX_HOME，
+/// 其余进程视为使用默认 CODEX_HOME
+#[cfg(target_os = "windows")]
+pub fn collect_codex_process_entries() -> Vec<(u32, Option<String>)> {
+    let helper_keywords = HelperKeywords::load();
+    let mut result = Vec::new();
+    let output = powershell_output(&[
+        "-NoProfile",
+        "-Command",
+        "Get-CimInstance Win32_Process -Filter \"Name='Codex.exe'\" | ForEach-Object { \"$($_.ProcessId)|$($_.CommandLine)\" }",
+    ]);
+    let output = match output {
+        Ok(value) => value,
+        Err(_) => return result,
+    };
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    for line in stdout.lines() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let mut parts = line.splitn(2, '|');
+        let pid_str = parts.next().unwrap_or("").trim();
+        let cmdline = parts.next().unwrap_or("").trim();
+        let pid = match pid_str.parse::<u32>() {
+            Ok(value) => value,
+            Err(_) => continue,
+        };
+        if is_helper_command_line(&helper_keywords, &cmdline.to_lowercase()) {
+            continue;
         }
+        let codex_home = extract_env_value(cmdline, "CODEX_HOME");
+        log_codex_home(pid, codex_home.as_ref());
         result.push((pid, codex_home));
     }
     result
 }
 
-#[cfg(not(target_os = "macos"))]
+/// Linux：从 `/proc/<pid>/environ` 读取 CODEX_HOME（需与目标进程同一用户）
+#[cfg(target_os = "linux")]
+pub fn collect_codex_process_entries() -> Vec<(u32, Option<String>)> {
+    let helper_keywords = HelperKeywords::load();
+    let mut result = Vec::new();
+    let entries = match std::fs::read_dir("/proc") {
+        Ok(value) => value,
+        Err(_) => return result,
+    };
+    for entry in entries.flatten() {
+        let file_name = entry.file_name();
+        let pid_str = file_name.to_string_lossy();
+        if !pid_str.chars().all(|ch| ch.is_ascii_digit()) {
+            continue;
+        }
+        let pid = match pid_str.parse::<u32>() {
+            Ok(value) => value,
+            Err(_) => continue,
+        };
+        let is_codex = std::fs::read_link(format!("/proc/{}/exe", pid))
+            .ok()
+            .and_then(|path| {
+                path.file_name()
+                    .map(|name| name.to_string_lossy().eq_ignore_ascii_case("codex"))
+            })
+            .unwrap_or(false);
+        if !is_codex {
+            continue;
+        }
+        let cmdline = match std::fs::read(format!("/proc/{}/cmdline", pid)) {
+            Ok(value) => String::from_utf8_lossy(&value).replace('\0', " "),
+            Err(_) => continue,
+        };
+        if is_helper_command_line(&helper_keywords, &cmdline.to_lowercase()) {
+            continue;
+        }
+        let codex_home = std::fs::read(format!("/proc/{}/environ", pid))
+            .ok()
+            .and_then(|environ| parse_environ_value(&environ, "CODEX_HOME"));
+        log_codex_home(pid, codex_home.as_ref());
+        result.push((pid, codex_home));
+    }
+    result
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
 pub fn collect_codex_process_entries() -> Vec<(u32, Option<String>)> {
     Vec::new()
 }
@@ -3888,7 +5191,7 @@ pub fn start_codex_with_args(codex_home: &str, extra_args: &[String]) -> Result<
                 return Ok(pid);
             }
         }
-        return Err(app_path_missing_error("codex"));
+        return Err(app_path_unresolved_error("codex"));
     }
 
     #[cfg(not(target_os = "macos"))]
@@ -3927,7 +5230,7 @@ pub fn start_codex_default() -> Result<u32, String> {
             crate::modules::logger::log_info("Codex 启动命令已发送");
             return Ok(pid);
         }
-        return Err(app_path_missing_error("codex"));
+        return Err(app_path_unresolved_error("codex"));
     }
 
     #[cfg(not(target_os = "macos"))]
@@ -3964,7 +5267,12 @@ pub fn close_codex(timeout_secs: u64) -> Result<(), String> {
 }
 
 /// 关闭受管 Codex 实例（按 CODEX_HOME 匹配，包含默认实例目录）
-pub fn close_codex_instances(codex_homes: &[String], timeout_secs: u64) -> Result<(), String> {
+/// 每个实例目录确认退出后回调 (CODEX_HOME, 剩余实例数)
+pub fn close_codex_instances_with_progress(
+    codex_homes: &[String],
+    timeout_secs: u64,
+    on_progress: &mut dyn FnMut(&str, usize),
+) -> Result<(), String> {
     #[cfg(target_os = "macos")]
     {
         crate::modules::logger::log_info("正在关闭受管 Codex 实例...");
@@ -4011,7 +5319,21 @@ pub fn close_codex_instances(codex_homes: &[String], timeout_secs: u64) -> Resul
             "准备关闭 {} 个受管 Codex 主进程...",
             pids.len()
         ));
-        let _ = close_pids(&pids, timeout_secs);
+        let mut tracker = CloseProgressTracker::new(
+            codex_homes,
+            &entries,
+            &pids,
+            |home| {
+                let resolved_home = home
+                    .map(|value| normalize_path_for_compare(value))
+                    .filter(|value| !value.is_empty())
+                    .unwrap_or_else(|| default_home.clone());
+                (!resolved_home.is_empty()).then_some(resolved_home)
+            },
+            on_progress,
+        );
+        let _ = close_pids_with_tick(&pids, timeout_secs, &mut || tracker.poll());
+        tracker.poll();
 
         let still_running = collect_codex_process_entries()
             .into_iter()
@@ -4031,7 +5353,7 @@ pub fn close_codex_instances(codex_homes: &[String], timeout_secs: u64) -> Resul
 
     #[cfg(not(target_os = "macos"))]
     {
-        let _ = (codex_homes, timeout_secs);
+        let _ = (codex_homes, timeout_secs, on_progress);
         Err("Codex 多开实例仅支持 macOS".to_string())
     }
 }
@@ -4074,6 +5396,7 @@ pub fn close_codex_instance(codex_home: &str, timeout_secs: u64) -> Result<(), S
 
 /// 检查 OpenCode（桌面端）是否在运行
 pub fn is_opencode_running() -> bool {
+    let helper_keywords = HelperKeywords::load();
     let mut system = System::new();
     system.refresh_processes(sysinfo::ProcessesToUpdate::All, true);
 
@@ -4101,16 +5424,7 @@ pub fn is_opencode_running() -> bool {
             .collect::<Vec<String>>()
             .join(" ");
 
-        let is_helper = args_str.contains("--type=")
-            || name.contains("helper")
-            || name.contains("plugin")
-            || name.contains("renderer")
-            || name.contains("gpu")
-            || name.contains("crashpad")
-            || name.contains("utility")
-            || name.contains("audio")
-            || name.contains("sandbox")
-            || exe_path.contains("crashpad");
+        let is_helper = is_helper_process(&helper_keywords, &name, &args_str);
 
         #[cfg(target_os = "macos")]
         {
@@ -4144,6 +5458,7 @@ pub fn is_opencode_running() -> bool {
 }
 
 fn get_opencode_pids() -> Vec<u32> {
+    let helper_keywords = HelperKeywords::load();
     let mut system = System::new();
     system.refresh_processes(sysinfo::ProcessesToUpdate::All, true);
 
@@ -4172,16 +5487,7 @@ fn get_opencode_pids() -> Vec<u32> {
             .collect::<Vec<String>>()
             .join(" ");
 
-        let is_helper = args_str.contains("--type=")
-            || name.contains("helper")
-            || name.contains("plugin")
-            || name.contains("renderer")
-            || name.contains("gpu")
-            || name.contains("crashpad")
-            || name.contains("utility")
-            || name.contains("audio")
-            || name.contains("sandbox")
-            || exe_path.contains("crashpad");
+        let is_helper = is_helper_process(&helper_keywords, &name, &args_str);
 
         #[cfg(target_os = "macos")]
         {
@@ -4389,6 +5695,18 @@ pub fn find_pids_by_port(port: u16) -> Result<Vec<u32>, String> {
     Ok(pids.into_iter().collect())
 }
 
+/// 获取进程名称（用于展示端口占用者等诊断信息）
+pub fn get_process_name(pid: u32) -> Option<String> {
+    let mut system = System::new();
+    system.refresh_processes(
+        sysinfo::ProcessesToUpdate::Some(&[Pid::from(pid as usize)]),
+        true,
+    );
+    system
+        .process(Pid::from(pid as usize))
+        .map(|process| process.name().to_string_lossy().to_string())
+}
+
 pub fn is_port_in_use(port: u16) -> Result<bool, String> {
     Ok(!find_pids_by_port(port)?.is_empty())
 }
@@ -4444,6 +5762,7 @@ pub fn kill_port_processes(port: u16) -> Result<usize, String> {
 
 #[allow(dead_code)]
 fn get_vscode_pids() -> Vec<u32> {
+    let helper_keywords = HelperKeywords::load();
     let mut result = Vec::new();
     let mut system = System::new();
     system.refresh_processes(sysinfo::ProcessesToUpdate::All, true);
@@ -4470,7 +5789,7 @@ fn get_vscode_pids() -> Vec<u32> {
             .map(|arg| arg.to_string_lossy().to_lowercase())
             .collect::<Vec<String>>()
             .join(" ");
-        let is_helper = is_helper_command_line(&args_str) || args_str.contains("crashpad");
+        let is_helper = is_helper_command_line(&helper_keywords, &args_str);
 
         #[cfg(target_os = "macos")]
         let is_vscode = exe_path.contains("visual studio code.app");
@@ -4505,7 +5824,9 @@ fn get_vscode_pids() -> Vec<u32> {
                 if !lower.contains("visual studio code.app/contents/") {
                     continue;
                 }
-                if lower.contains("crashpad_handler") || is_helper_command_line(&lower) {
+                if lower.contains("crashpad_handler")
+                    || is_helper_command_line(&helper_keywords, &lower)
+                {
                     continue;
                 }
                 result.push(pid);
@@ -4535,7 +5856,7 @@ fn get_vscode_pids() -> Vec<u32> {
                     Err(_) => continue,
                 };
                 let lower = cmdline.to_lowercase();
-                if is_helper_command_line(&lower) {
+                if is_helper_command_line(&helper_keywords, &lower) {
                     continue;
                 }
                 result.push(pid);
@@ -4552,6 +5873,7 @@ pub fn start_vscode_with_args_with_new_window(
     user_data_dir: &str,
     extra_args: &[String],
     use_new_window: bool,
+    env_vars: &HashMap<String, String>,
 ) -> Result<u32, String> {
     #[cfg(target_os = "macos")]
     {
@@ -4562,6 +5884,7 @@ pub fn start_vscode_with_args_with_new_window(
         let launch_path = resolve_vscode_launch_path()?;
 
         let mut cmd = Command::new(&launch_path);
+        cmd.envs(env_vars);
         cmd.arg("--user-data-dir").arg(target);
         if use_new_window {
             cmd.arg("--new-window");
@@ -4592,6 +5915,7 @@ pub fn start_vscode_with_args_with_new_window(
         let launch_path = resolve_vscode_launch_path()?;
 
         let mut cmd = Command::new(&launch_path);
+        cmd.envs(env_vars);
         if should_detach_child() {
             cmd.creation_flags(0x08000000 | CREATE_NEW_PROCESS_GROUP | DETACHED_PROCESS);
             cmd.stdin(Stdio::null())
@@ -4629,6 +5953,7 @@ pub fn start_vscode_with_args_with_new_window(
         let launch_path = resolve_vscode_launch_path()?;
 
         let mut cmd = Command::new(&launch_path);
+        cmd.envs(env_vars);
         if should_detach_child() {
             cmd.stdin(Stdio::null())
                 .stdout(Stdio::null())
@@ -4655,14 +5980,14 @@ pub fn start_vscode_with_args_with_new_window(
 
     #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
     {
-        let _ = (user_data_dir, extra_args, use_new_window);
+        let _ = (user_data_dir, extra_args, use_new_window, env_vars);
         Err("GitHub Copilot 多开实例仅支持 macOS、Windows 和 Linux".to_string())
     }
 }
 
 #[allow(dead_code)]
 pub fn start_vscode_with_args(user_data_dir: &str, extra_args: &[String]) -> Result<u32, String> {
-    start_vscode_with_args_with_new_window(user_data_dir, extra_args, false)
+    start_vscode_with_args_with_new_window(user_data_dir, extra_args, false, &HashMap::new())
 }
 
 pub fn start_vscode_default_with_args_with_new_window(
@@ -4756,6 +6081,15 @@ pub fn start_vscode_default_with_args_with_new_window(
 }
 
 pub fn close_vscode(user_data_dirs: &[String], timeout_secs: u64) -> Result<(), String> {
+    close_vscode_with_progress(user_data_dirs, timeout_secs, &mut |_, _| {})
+}
+
+/// 关闭受管 VS Code 实例，每个实例目录确认退出后回调 (目录, 剩余实例数)
+pub fn close_vscode_with_progress(
+    user_data_dirs: &[String],
+    timeout_secs: u64,
+    on_progress: &mut dyn FnMut(&str, usize),
+) -> Result<(), String> {
     #[cfg(target_os = "windows")]
     let _ = timeout_secs;
     let default_dir = get_default_vscode_user_data_dir_for_os()
@@ -4771,6 +6105,7 @@ pub fn close_vscode(user_data_dirs: &[String], timeout_secs: u64) -> Result<(),
         "无法关闭受管 VS Code 实例进程，请手动关闭后重试",
         user_data_dirs,
         timeout_secs,
+        default_dir.as_deref(),
         collect_vscode_process_entries,
         |entries, target_dirs| {
             select_main_pids_by_target_dirs(entries, target_dirs, default_dir.as_deref())
@@ -4783,11 +6118,13 @@ pub fn close_vscode(user_data_dirs: &[String], timeout_secs: u64) -> Result<(),
             )
         },
         Some(request_vscode_graceful_close as fn(u32)),
-        Some(2),
+        Some(crate::modules::config::get_vscode_graceful_wait_secs()),
+        Some(vscode_has_open_window as fn(u32) -> bool),
         #[cfg(target_os = "windows")]
         Some(log_vscode_process_details_for_pids as fn(&[u32])),
         #[cfg(not(target_os = "windows"))]
         None,
+        on_progress,
     )
 }
 
@@ -4833,3 +6170,403 @@ tell application \"System Events\" to keystroke \"q\" using command down",
         let _ = pid;
     }
 }
+
+/// 进程是否仍有窗口（优雅退出超时后用于判断是否有对话框在等待用户操作）
+fn vscode_has_open_window(pid: u32) -> bool {
+    #[cfg(target_os = "macos")]
+    {
+        let script = format!(
+            "tell application \"System Events\" to count windows of (first process whose unix id is {})",
+            pid
+        );
+        match Command::new("osascript").args(["-e", &script]).output() {
+            Ok(output) if output.status.success() => String::from_utf8_lossy(&output.stdout)
+                .trim()
+                .parse::<u32>()
+                .map(|count| count > 0)
+                .unwrap_or(false),
+            _ => false,
+        }
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    {
+        let _ = pid;
+        false
+    }
+}
+
+/// 外部命令行工具检测结果
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExternalToolStatus {
+    pub name: String,
+    pub present: bool,
+    /// 缺失时受影响的功能
+    pub purpose: String,
+}
+
+fn is_external_tool_available(name: &str) -> bool {
+    #[cfg(target_os = "windows")]
+    let checker = "where.exe";
+    #[cfg(not(target_os = "windows"))]
+    let checker = "which";
+
+    let mut cmd = Command::new(checker);
+    cmd.arg(name).stdout(Stdio::null()).stderr(Stdio::null());
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::process::CommandExt;
+        cmd.creation_flags(0x08000000);
+    }
+    cmd.status().map(|status| status.success()).unwrap_or(false)
+}
+
+/// 检测当前平台各功能依赖的外部工具是否已安装
+pub fn check_external_tools() -> Vec<ExternalToolStatus> {
+    #[cfg(target_os = "macos")]
+    let tools: &[(&str, &str)] = &[
+        ("osascript", "窗口聚焦、最小化与优雅关闭"),
+        ("security", "读取钥匙串中的编辑器加密密钥"),
+        ("lsof", "检测端口占用进程"),
+        ("pgrep", "查找应用进程"),
+        ("ps", "读取进程命令行"),
+        ("open", "启动应用与打开文件夹"),
+    ];
+    #[cfg(target_os = "windows")]
+    let tools: &[(&str, &str)] = &[
+        ("powershell", "窗口聚焦、最小化与进程查询"),
+        ("taskkill", "关闭实例进程"),
+        ("netstat", "检测端口占用进程"),
+        ("explorer", "打开文件夹"),
+    ];
+    #[cfg(target_os = "linux")]
+    let tools: &[(&str, &str)] = &[
+        ("wmctrl", "窗口聚焦与最小化"),
+        ("xdotool", "窗口聚焦与最小化（wmctrl 不可用时的备选）"),
+        ("secret-tool", "读取系统密钥环中的编辑器加密密钥"),
+        ("lsof", "检测端口占用进程"),
+        ("pgrep", "查找应用进程"),
+        ("ps", "读取进程命令行"),
+        ("xdg-open", "打开文件夹与链接"),
+    ];
+    #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+    let tools: &[(&str, &str)] = &[];
+
+    let result: Vec<ExternalToolStatus> = tools
+        .iter()
+        .map(|(name, purpose)| ExternalToolStatus {
+            name: name.to_string(),
+            present: is_external_tool_available(name),
+            purpose: purpose.to_string(),
+        })
+        .collect();
+
+    let missing: Vec<&str> = result
+        .iter()
+        .filter(|tool| !tool.present)
+        .map(|tool| tool.name.as_str())
+        .collect();
+    if !missing.is_empty() {
+        crate::modules::logger::log_warn(&format!("[ExternalTools] 缺少外部工具: {:?}", missing));
+    }
+    result
+}
+
+/// 进程命令行中 user-data-dir 的编码诊断结果
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PathEncodingDiagnosis {
+    pub pid: u32,
+    /// 从进程命令行解析出的原始目录
+    pub raw_dir: String,
+    /// 经 normalize_path_for_compare 处理后的目录
+    pub normalized_dir: String,
+    pub has_non_ascii: bool,
+    /// 是否与某个已配置实例目录匹配
+    pub matched_instance: bool,
+    /// 未通过往返校验的原因；None 表示正常
+    pub issue: Option<String>,
+}
+
+/// 校验目录在解析与规范化过程中能否完整往返，返回问题描述
+fn check_path_round_trip(raw: &str, normalized: &str) -> Option<String> {
+    if raw.contains('\u{FFFD}') {
+        return Some("命令行解码时出现替换字符，原始编码已丢失".to_string());
+    }
+    if !Path::new(raw.trim()).exists() {
+        return Some("目录在文件系统中不存在，可能是编码不一致导致".to_string());
+    }
+    if normalize_path_for_compare(normalized) != normalized {
+        return Some("规范化结果不稳定，二次规范化后发生变化".to_string());
+    }
+    None
+}
+
+/// 诊断进程条目中的目录编码，known_dirs 为已配置的实例目录
+pub fn diagnose_path_encoding_entries(
+    entries: &[(u32, Option<String>)],
+    known_dirs: &[String],
+) -> Vec<PathEncodingDiagnosis> {
+    let known: HashSet<String> = known_dirs
+        .iter()
+        .map(|dir| normalize_path_for_compare(dir))
+        .filter(|dir| !dir.is_empty())
+        .collect();
+    entries
+        .iter()
+        .filter_map(|(pid, dir)| {
+            let raw_dir = dir.as_ref()?.clone();
+            let normalized_dir = normalize_path_for_compare(&raw_dir);
+            let issue = check_path_round_trip(&raw_dir, &normalized_dir);
+            Some(PathEncodingDiagnosis {
+                pid: *pid,
+                has_non_ascii: !raw_dir.is_ascii(),
+                matched_instance: known.contains(&normalized_dir),
+                issue,
+                raw_dir,
+                normalized_dir,
+            })
+        })
+        .collect()
+}
+
+/// 诊断用进程条目（排查实例无法关闭 / 聚焦问题）
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProcDebugEntry {
+    pub pid: u32,
+    pub parent_pid: Option<u32>,
+    pub name: String,
+    pub exe_path: Option<String>,
+    pub is_helper: bool,
+    /// 判定为辅助进程的原因，如 `cmdline:--type=` / `name:helper`
+    pub helper_reason: Option<String>,
+    pub user_data_dir: Option<String>,
+    pub codex_home: Option<String>,
+    /// 是否被实例管理识别为主进程（出现在对应平台的进程条目中）
+    pub tracked: bool,
+}
+
+/// 列出进程名 / 可执行路径满足 matches 的全部进程（含辅助进程），
+/// matches 的参数均为小写的进程名与可执行路径
+pub fn collect_debug_process_entries(matches: impl Fn(&str, &str) -> bool) -> Vec<ProcDebugEntry> {
+    let helper_keywords = HelperKeywords::load();
+    let mut system = System::new();
+    system.refresh_processes_specifics(
+        sysinfo::ProcessesToUpdate::All,
+        true,
+        sysinfo::ProcessRefreshKind::nothing()
+            .with_exe(sysinfo::UpdateKind::Always)
+            .with_cmd(sysinfo::UpdateKind::Always)
+            .with_environ(sysinfo::UpdateKind::Always),
+    );
+    let current_pid = std::process::id();
+
+    let mut result: Vec<ProcDebugEntry> = system
+        .processes()
+        .iter()
+        .filter_map(|(pid, process)| {
+            let pid_u32 = pid.as_u32();
+            if pid_u32 == current_pid {
+                return None;
+            }
+            let name = process.name().to_string_lossy().to_string();
+            let exe_path = process.exe().map(|path| path.to_string_lossy().to_string());
+            let name_lower = name.to_lowercase();
+            let exe_lower = exe_path.as_deref().unwrap_or("").to_lowercase();
+            if !matches(&name_lower, &exe_lower) {
+                return None;
+            }
+            let args_lower = process
+                .cmd()
+                .iter()
+                .map(|arg| arg.to_string_lossy().to_lowercase())
+                .collect::<Vec<String>>()
+                .join(" ");
+            let helper_reason = helper_process_reason(&helper_keywords, &name_lower, &args_lower);
+            let codex_home = process.environ().iter().find_map(|entry| {
+                entry
+                    .to_string_lossy()
+                    .strip_prefix("CODEX_HOME=")
+                    .map(str::to_string)
+            });
+            Some(ProcDebugEntry {
+                pid: pid_u32,
+                parent_pid: process.parent().map(|parent| parent.as_u32()),
+                name,
+                exe_path,
+                is_helper: helper_reason.is_some(),
+                helper_reason,
+                user_data_dir: extract_user_data_dir(process.cmd()),
+                codex_home,
+                tracked: false,
+            })
+        })
+        .collect();
+    result.sort_by_key(|entry| entry.pid);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn default_keywords() -> HelperKeywords {
+        HelperKeywords::from_configured(Vec::new())
+    }
+
+    #[test]
+    fn test_helper_process_reason_reports_matched_keyword() {
+        let keywords = default_keywords();
+        assert_eq!(
+            helper_process_reason(&keywords, "antigravity", "antigravity --type=renderer")
+                .as_deref(),
+            Some("cmdline:--type=")
+        );
+        assert_eq!(
+            helper_process_reason(&keywords, "antigravity helper (gpu)", "").as_deref(),
+            Some("name:helper")
+        );
+        assert_eq!(
+            helper_process_reason(
+                &keywords,
+                "antigravity",
+                "antigravity --user-data-dir=/tmp/a"
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_helper_keywords_ignore_user_data_dir_and_switches() {
+        let keywords = default_keywords();
+        assert!(!is_helper_process(
+            &keywords,
+            "antigravity",
+            "/opt/antigravity/antigravity --user-data-dir=/home/u/gpu-profiles/a --disable-gpu"
+        ));
+        assert!(is_helper_process(
+            &keywords,
+            "antigravity",
+            "/opt/antigravity/antigravity --type=gpu-process --user-data-dir=/home/u/a"
+        ));
+    }
+
+    #[test]
+    fn test_main_process_with_no_sandbox_or_keyword_path_is_not_helper() {
+        let keywords = default_keywords();
+        assert!(!is_helper_process(
+            &keywords,
+            "antigravity",
+            "/usr/share/antigravity/antigravity --no-sandbox"
+        ));
+        assert!(!is_helper_process(
+            &keywords,
+            "antigravity",
+            "/opt/plugin-tools/antigravity/antigravity --user-data-dir=/home/u/a"
+        ));
+        assert!(!is_helper_command_line(
+            &keywords,
+            "/opt/plugin-tools/antigravity/antigravity --no-sandbox"
+        ));
+        assert!(is_helper_command_line(
+            &keywords,
+            "/applications/antigravity.app/contents/frameworks/antigravity helper (gpu).app/contents/macos/antigravity helper (gpu)"
+        ));
+    }
+
+    #[test]
+    fn test_configured_helper_keywords_split_by_source() {
+        let keywords = HelperKeywords::from_configured(vec![
+            "--type=".to_string(),
+            "/extensions/".to_string(),
+            "helper".to_string(),
+        ]);
+        assert!(is_helper_process(&keywords, "code", "code --type=renderer"));
+        assert!(is_helper_process(&keywords, "code helper", "code"));
+        assert!(!is_helper_process(&keywords, "code", "/opt/helper/code"));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_validate_app_path_requires_exec_bit() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let path = std::env::temp_dir().join(format!("cockpit-app-path-{}", std::process::id()));
+        std::fs::write(&path, "#!/bin/sh\n").unwrap();
+        let raw = path.to_string_lossy().to_string();
+
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o644)).unwrap();
+        let info = validate_app_path("antigravity", &raw).unwrap();
+        assert!(info.exists);
+        assert!(!info.executable);
+        assert_eq!(info.normalized_path, raw);
+
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        let info = validate_app_path("antigravity", &raw).unwrap();
+        assert!(info.executable);
+        assert_eq!(info.exec_path.as_deref(), Some(raw.as_str()));
+
+        assert!(validate_app_path("unknown", &raw).is_err());
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_non_ascii_dir_round_trips() {
+        let dir = std::env::temp_dir().join("cockpit-编码测试-用户");
+        std::fs::create_dir_all(&dir).unwrap();
+        let raw = dir.to_string_lossy().to_string();
+
+        let result = diagnose_path_encoding_entries(&[(42, Some(raw.clone()))], &[raw]);
+        let _ = std::fs::remove_dir_all(&dir);
+
+        assert_eq!(result.len(), 1);
+        assert!(result[0].has_non_ascii);
+        assert!(result[0].matched_instance);
+        assert_eq!(result[0].issue, None);
+    }
+
+    #[test]
+    fn test_parse_environ_value() {
+        let environ = b"PATH=/usr/bin\0CODEX_HOME=/home/u/.codex-work\0LANG=C.UTF-8\0";
+        assert_eq!(
+            parse_environ_value(environ, "CODEX_HOME"),
+            Some("/home/u/.codex-work".to_string())
+        );
+        assert_eq!(parse_environ_value(environ, "HOME"), None);
+        assert_eq!(parse_environ_value(b"CODEX_HOME=\0", "CODEX_HOME"), None);
+        assert_eq!(
+            parse_environ_value(b"XCODEX_HOME=/a\0CODEX_HOME=/b", "CODEX_HOME"),
+            Some("/b".to_string())
+        );
+    }
+
+    #[test]
+    fn test_strip_reserved_launch_args() {
+        let extra_args = parse_extra_args(
+            "--disable-extensions --user-data-dir /tmp/other --new-window --user-data-dir=/tmp/x --log trace",
+        );
+        let (kept, stripped) = strip_reserved_launch_args(&extra_args);
+        assert_eq!(kept, vec!["--disable-extensions", "--log", "trace"]);
+        let removed: Vec<&str> = stripped.iter().map(|item| item.arg.as_str()).collect();
+        assert_eq!(
+            removed,
+            vec![
+                "--user-data-dir /tmp/other",
+                "--new-window",
+                "--user-data-dir=/tmp/x"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_lossy_decoded_dir_is_flagged() {
+        let raw = "/tmp/cockpit-\u{FFFD}\u{FFFD}".to_string();
+        let result = diagnose_path_encoding_entries(&[(7, Some(raw)), (8, None)], &[]);
+
+        assert_eq!(result.len(), 1);
+        assert!(!result[0].matched_instance);
+        assert!(result[0].issue.is_some());
+    }
+}
@@ -0,0 +1,116 @@
+//! 平台能力矩阵
+//! 集中描述各平台的 ID、显示名称与支持的功能，前端据此驱动导航与功能开关；
+//! 新增平台时在此注册即可
+
+use serde::Serialize;
+
+use crate::modules::tray_layout::{
+    PLATFORM_ANTIGRAVITY, PLATFORM_CODEX, PLATFORM_GITHUB_COPILOT, PLATFORM_KIRO, PLATFORM_WINDSURF,
+};
+
+/// OpenCode 不管理账号，仅在切换 Codex 账号时同步其 auth.json
+pub const PROVIDER_OPENCODE: &str = "opencode";
+
+/// 平台支持的功能
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderCapabilities {
+    /// 管理多个账号并切换
+    pub accounts: bool,
+    /// 多开实例（当前系统可用）
+    pub multi_instance: bool,
+    /// 默认实例可“跟随当前账号”
+    pub follow_local_account: bool,
+    /// 非默认实例可开启“跟随当前账号”（未绑定账号时启动前注入当前账号）
+    pub instance_follow_local_account: bool,
+    /// 浏览器 OAuth 登录
+    pub oauth_login: bool,
+    /// 粘贴 Token 添加账号
+    pub token_import: bool,
+    /// 从本机客户端导入已登录账号
+    pub local_import: bool,
+    /// 配额查询
+    pub quota: bool,
+    /// 设备指纹管理
+    pub device_fingerprint: bool,
+    /// 显示在系统托盘
+    pub tray: bool,
+    /// 仅作为其他平台凭据的同步目标
+    pub sync_target: bool,
+}
+
+/// 平台描述
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderInfo {
+    pub id: String,
+    pub display_name: String,
+    pub capabilities: ProviderCapabilities,
+}
+
+fn account_provider(id: &str, display_name: &str) -> ProviderInfo {
+    ProviderInfo {
+        id: id.to_string(),
+        display_name: display_name.to_string(),
+        capabilities: ProviderCapabilities {
+            accounts: true,
+            multi_instance: true,
+            oauth_login: true,
+            token_import: true,
+            local_import: true,
+            quota: true,
+            tray: true,
+            ..ProviderCapabilities::default()
+        },
+    }
+}
+
+/// 获取所有平台及其功能矩阵
+pub fn get_providers() -> Vec<ProviderInfo> {
+    let mut antigravity = account_provider(PLATFORM_ANTIGRAVITY, "Antigravity");
+    antigravity.capabilities.follow_local_account = true;
+    antigravity.capabilities.device_fingerprint = true;
+
+    let mut codex = account_provider(PLATFORM_CODEX, "Codex");
+    codex.capabilities.follow_local_account = true;
+    codex.capabilities.instance_follow_local_account = true;
+    // Codex 多开实例仅支持 macOS
+    codex.capabilities.multi_instance = cfg!(target_os = "macos");
+
+    let mut github_copilot = account_provider(PLATFORM_GITHUB_COPILOT, "GitHub Copilot");
+    github_copilot.capabilities.local_import = false;
+
+    vec![
+        antigravity,
+        codex,
+        github_copilot,
+        account_provider(PLATFORM_WINDSURF, "Windsurf"),
+        account_provider(PLATFORM_KIRO, "Kiro"),
+        ProviderInfo {
+            id: PROVIDER_OPENCODE.to_string(),
+            display_name: "OpenCode".to_string(),
+            capabilities: ProviderCapabilities {
+                sync_target: true,
+                ..ProviderCapabilities::default()
+            },
+        },
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::tray_layout::SUPPORTED_PLATFORM_IDS;
+
+    #[test]
+    fn test_all_tray_platforms_registered() {
+        let providers = get_providers();
+        for platform in SUPPORTED_PLATFORM_IDS {
+            let provider = providers
+                .iter()
+                .find(|item| item.id == platform)
+                .unwrap_or_else(|| panic!("未注册平台: {}", platform));
+            assert!(provider.capabilities.tray);
+        }
+    }
+}
@@ -22,6 +22,8 @@ const ONBOARD_DELAY_MS: u64 = 2000;
 const API_CACHE_DIR: &str = "cache/quota_api_v1_desktop";
 const API_CACHE_VERSION: u8 = 1;
 const API_CACHE_TTL_MS: i64 = 60_000;
+/// 单个账号配额查询的默认超时
+pub const DEFAULT_QUOTA_TIMEOUT_MS: u64 = 15_000;
 
 fn truncate_log_text(text: &str, max_len: usize) -> String {
     if text.chars().count() <= max_len {
@@ -203,7 +205,30 @@ struct OnboardResponse {
 }
 
 fn create_client() -> reqwest::Client {
-    crate::utils::http::create_client(15)
+    crate::modules::http::create_client(15)
+}
+
+fn create_client_with_timeout(timeout_ms: u64) -> reqwest::Client {
+    crate::modules::http::create_client_with_timeout(std::time::Duration::from_millis(timeout_ms))
+}
+
+fn quota_timeout_error(email: &str, timeout_ms: u64) -> crate::error::AppError {
+    crate::error::AppError::QuotaTimeout(format!("配额查询超时 ({}ms): {}", timeout_ms, email))
+}
+
+/// 为整个配额查询（含项目 ID 查询与重试）设置总超时
+async fn with_quota_deadline<T, F>(
+    email: &str,
+    timeout_ms: u64,
+    future: F,
+) -> crate::error::AppResult<T>
+where
+    F: std::future::Future<Output = crate::error::AppResult<T>>,
+{
+    match tokio::time::timeout(std::time::Duration::from_millis(timeout_ms), future).await {
+        Ok(result) => result,
+        Err(_) => Err(quota_timeout_error(email, timeout_ms)),
+    }
 }
 
 fn build_metadata_payload() -> serde_json::Value {
@@ -529,12 +554,37 @@ pub async fn fetch_project_id(access_token: &str, email: &str) -> (Option<String
     (None, subscription_tier)
 }
 
-/// 查询账号配额
+/// 查询账号配额（使用默认超时）
 /// skip_cache: 是否跳过缓存，单个账号刷新应传 true，批量刷新传 false
 pub async fn fetch_quota(
     access_token: &str,
     email: &str,
     skip_cache: bool,
+) -> crate::error::AppResult<QuotaFetchResult> {
+    fetch_quota_with_timeout(access_token, email, skip_cache, DEFAULT_QUOTA_TIMEOUT_MS).await
+}
+
+/// 查询账号配额，超过 timeout_ms 仍未完成时返回 `QUOTA_TIMEOUT:` 错误
+pub async fn fetch_quota_with_timeout(
+    access_token: &str,
+    email: &str,
+    skip_cache: bool,
+    timeout_ms: u64,
+) -> crate::error::AppResult<QuotaFetchResult> {
+    let timeout_ms = timeout_ms.max(1);
+    with_quota_deadline(
+        email,
+        timeout_ms,
+        fetch_quota_inner(access_token, email, skip_cache, timeout_ms),
+    )
+    .await
+}
+
+async fn fetch_quota_inner(
+    access_token: &str,
+    email: &str,
+    skip_cache: bool,
+    timeout_ms: u64,
 ) -> crate::error::AppResult<QuotaFetchResult> {
     use crate::error::AppError;
 
@@ -587,7 +637,7 @@ pub async fn fetch_quota(
         }
     }
 
-    let client = create_client();
+    let client = create_client_with_timeout(timeout_ms);
     let payload = project_id
         .as_ref()
         .map(|id| json!({ "project": id }))
@@ -690,6 +740,10 @@ pub async fn fetch_quota(
                 });
             }
             Err(e) => {
+                // 超时不再重试，避免慢接口成倍拉长等待时间
+                if e.is_timeout() {
+                    return Err(quota_timeout_error(email, timeout_ms));
+                }
                 if attempt < max_retries {
                     tokio::time::sleep(std::time::Duration::from_secs(1)).await;
                 } else {
@@ -701,3 +755,51 @@ pub async fn fetch_quota(
 
     Err(AppError::Unknown("配额查询失败".to_string()))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+    use std::net::TcpListener;
+    use std::time::Duration;
+
+    /// 接受连接后迟迟不返回响应的本地服务
+    fn spawn_delayed_server(delay: Duration) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            if let Ok((mut stream, _)) = listener.accept() {
+                let mut buf = [0u8; 1024];
+                let _ = stream.read(&mut buf);
+                std::thread::sleep(delay);
+            }
+        });
+        format!("http://{}", addr)
+    }
+
+    #[tokio::test]
+    async fn test_quota_deadline_returns_timeout_error() {
+        let url = spawn_delayed_server(Duration::from_secs(3));
+        let client = create_client_with_timeout(10_000);
+        let result = with_quota_deadline("a@example.com", 200, async {
+            client
+                .get(&url)
+                .send()
+                .await
+                .map_err(crate::error::AppError::Network)
+        })
+        .await;
+
+        let err = result.err().expect("应当超时");
+        assert!(matches!(err, crate::error::AppError::QuotaTimeout(_)));
+        assert!(err.to_string().starts_with("QUOTA_TIMEOUT:"));
+    }
+
+    #[tokio::test]
+    async fn test_client_timeout_is_detected() {
+        let url = spawn_delayed_server(Duration::from_secs(3));
+        let client = create_client_with_timeout(200);
+        let err = client.get(&url).send().await.err().expect("应当超时");
+        assert!(err.is_timeout());
+    }
+}
@@ -1,5 +1,6 @@
 use std::fs;
 use std::path::PathBuf;
+use std::sync::{LazyLock, Mutex};
 
 use serde::{Deserialize, Serialize};
 use serde_json::Value as JsonValue;
@@ -10,6 +11,19 @@ use crate::modules;
 
 const CACHE_DIR: &str = "cache/quota_api_v1_desktop";
 const CACHE_VERSION: u8 = 1;
+const HISTORY_DIR: &str = "cache/quota_history";
+/// 每个账号保留的配额快照数量上限
+const MAX_HISTORY_SNAPSHOTS: usize = 90;
+
+static HISTORY_LOCK: LazyLock<Mutex<()>> = LazyLock::new(|| Mutex::new(()));
+
+/// 配额历史快照
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuotaSnapshot {
+    pub ts: i64,
+    pub quota: QuotaData,
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -153,3 +167,122 @@ pub fn apply_cached_quota(account: &mut Account, source: &str) -> Result<bool, S
     account.update_quota(quota);
     Ok(true)
 }
+
+fn history_path(account_id: &str) -> Result<PathBuf, String> {
+    if account_id.trim().is_empty() || account_id.contains(['/', '\\']) || account_id.contains("..")
+    {
+        return Err(format!("账号 ID 无效: {}", account_id));
+    }
+    let dir = modules::account::get_data_dir()?.join(HISTORY_DIR);
+    if !dir.exists() {
+        fs::create_dir_all(&dir).map_err(|e| format!("创建配额历史目录失败: {}", e))?;
+    }
+    Ok(dir.join(format!("{}.json", account_id)))
+}
+
+/// 解析配额历史，文件损坏时丢弃旧数据重新开始
+fn parse_history(account_id: &str, content: &str) -> Vec<QuotaSnapshot> {
+    if content.trim().is_empty() {
+        return Vec::new();
+    }
+    serde_json::from_str(content).unwrap_or_else(|e| {
+        modules::logger::log_warn(&format!(
+            "[QuotaHistory] 配额历史文件损坏，已重新开始记录: account_id={}, err={}",
+            account_id, e
+        ));
+        Vec::new()
+    })
+}
+
+fn load_history(account_id: &str) -> Result<Vec<QuotaSnapshot>, String> {
+    let path = history_path(account_id)?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = fs::read_to_string(&path).map_err(|e| format!("读取配额历史失败: {}", e))?;
+    Ok(parse_history(account_id, &content))
+}
+
+fn push_snapshot(history: &mut Vec<QuotaSnapshot>, snapshot: QuotaSnapshot) {
+    history.push(snapshot);
+    if history.len() > MAX_HISTORY_SNAPSHOTS {
+        let overflow = history.len() - MAX_HISTORY_SNAPSHOTS;
+        history.drain(..overflow);
+    }
+}
+
+/// 追加一条配额快照（超出上限时丢弃最旧的记录）
+pub fn append_quota_history(account_id: &str, quota: &QuotaData) -> Result<(), String> {
+    let _lock = HISTORY_LOCK
+        .lock()
+        .map_err(|_| "无法获取配额历史锁".to_string())?;
+    let mut history = load_history(account_id)?;
+    push_snapshot(
+        &mut history,
+        QuotaSnapshot {
+            ts: chrono::Utc::now().timestamp(),
+            quota: quota.clone(),
+        },
+    );
+
+    let path = history_path(account_id)?;
+    let temp_path = path.with_extension("json.tmp");
+    let content =
+        serde_json::to_string(&history).map_err(|e| format!("序列化配额历史失败: {}", e))?;
+    fs::write(&temp_path, content).map_err(|e| format!("写入配额历史失败: {}", e))?;
+    fs::rename(&temp_path, &path).map_err(|e| format!("保存配额历史失败: {}", e))
+}
+
+/// 获取账号最近的配额快照（按时间正序），limit 为空时返回全部
+pub fn get_quota_history(
+    account_id: &str,
+    limit: Option<usize>,
+) -> Result<Vec<QuotaSnapshot>, String> {
+    let _lock = HISTORY_LOCK
+        .lock()
+        .map_err(|_| "无法获取配额历史锁".to_string())?;
+    let mut history = load_history(account_id)?;
+    if let Some(limit) = limit {
+        let skip = history.len().saturating_sub(limit);
+        history.drain(..skip);
+    }
+    Ok(history)
+}
+
+/// 删除账号的配额历史
+pub fn remove_quota_history(account_id: &str) -> Result<(), String> {
+    let _lock = HISTORY_LOCK
+        .lock()
+        .map_err(|_| "无法获取配额历史锁".to_string())?;
+    let path = history_path(account_id)?;
+    if path.exists() {
+        fs::remove_file(&path).map_err(|e| format!("删除配额历史失败: {}", e))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_history_caps_and_recovers_from_corruption() {
+        assert!(parse_history("a", "{not json").is_empty());
+
+        let mut history = Vec::new();
+        for ts in 0..(MAX_HISTORY_SNAPSHOTS as i64 + 5) {
+            push_snapshot(
+                &mut history,
+                QuotaSnapshot {
+                    ts,
+                    quota: QuotaData::new(),
+                },
+            );
+        }
+        assert_eq!(history.len(), MAX_HISTORY_SNAPSHOTS);
+        assert_eq!(history.first().map(|item| item.ts), Some(5));
+
+        let content = serde_json::to_string(&history).unwrap();
+        assert_eq!(parse_history("a", &content).len(), MAX_HISTORY_SNAPSHOTS);
+    }
+}
@@ -0,0 +1,151 @@
+//! 配额定时刷新
+//! 按 `quota_auto_refresh_minutes` 在后台定时批量刷新 Antigravity 账号配额，
+//! 每轮重新读取配置，修改间隔后无需重启；系统刚唤醒或网络不可用时跳过本轮；
+//! 与手动批量刷新共用运行标记，两者不会同时进行
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use chrono::Utc;
+use serde::Serialize;
+use tauri::Emitter;
+use tokio::time::sleep;
+
+use crate::modules;
+
+pub const QUOTA_AUTO_REFRESH_EVENT: &str = "quota:auto_refresh_completed";
+
+/// 调度检查间隔
+const TICK_SECS: u64 = 30;
+/// 两次检查的实际间隔超过该值时视为系统刚从睡眠中唤醒
+const WAKE_GAP_SECS: i64 = 120;
+/// 用于判断网络是否可用的地址（经 modules::http 客户端发送 HEAD 请求，遵循代理设置）
+const ONLINE_PROBE_URL: &str = "https://oauth2.googleapis.com";
+const ONLINE_PROBE_TIMEOUT_SECS: u64 = 5;
+
+static STARTED: AtomicBool = AtomicBool::new(false);
+static REFRESH_RUNNING: AtomicBool = AtomicBool::new(false);
+
+/// 定时刷新完成事件
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuotaAutoRefreshResult {
+    pub started_at: i64,
+    pub finished_at: i64,
+    pub stats: Option<modules::account::RefreshStats>,
+    pub error: Option<String>,
+}
+
+/// 批量刷新运行标记，离开作用域时释放
+pub struct RefreshRunningGuard;
+
+impl Drop for RefreshRunningGuard {
+    fn drop(&mut self) {
+        REFRESH_RUNNING.store(false, Ordering::SeqCst);
+    }
+}
+
+/// 占用批量刷新运行标记；已有刷新（定时或手动）在进行时返回 None
+pub fn try_begin_refresh() -> Option<RefreshRunningGuard> {
+    if REFRESH_RUNNING.swap(true, Ordering::SeqCst) {
+        None
+    } else {
+        Some(RefreshRunningGuard)
+    }
+}
+
+/// 任意 HTTP 响应（包括错误状态码）都说明网络可达
+async fn is_online() -> bool {
+    modules::http::create_client(ONLINE_PROBE_TIMEOUT_SECS)
+        .head(ONLINE_PROBE_URL)
+        .send()
+        .await
+        .is_ok()
+}
+
+async fn run_scheduled_refresh(interval_minutes: u32, _guard: RefreshRunningGuard) {
+    let started_at = Utc::now().timestamp();
+    modules::logger::log_info(&format!(
+        "[QuotaScheduler] 开始定时刷新配额: interval_minutes={}",
+        interval_minutes
+    ));
+
+    let result = modules::account::refresh_all_quotas_logic().await;
+    if result.is_ok() {
+        modules::account::run_post_batch_refresh_hooks().await;
+    }
+    let payload = match result {
+        Ok(stats) => {
+            modules::logger::log_info(&format!(
+                "[QuotaScheduler] 定时刷新完成: success={}, failed={}",
+                stats.success, stats.failed
+            ));
+            QuotaAutoRefreshResult {
+                started_at,
+                finished_at: Utc::now().timestamp(),
+                stats: Some(stats),
+                error: None,
+            }
+        }
+        Err(err) => {
+            modules::logger::log_warn(&format!("[QuotaScheduler] 定时刷新失败: {}", err));
+            QuotaAutoRefreshResult {
+                started_at,
+                finished_at: Utc::now().timestamp(),
+                stats: None,
+                error: Some(err),
+            }
+        }
+    };
+
+    if let Some(app_handle) = crate::get_app_handle() {
+        let _ = modules::tray::update_tray_menu(app_handle);
+        let _ = app_handle.emit(QUOTA_AUTO_REFRESH_EVENT, &payload);
+    }
+}
+
+/// 启动后台调度（重复调用无副作用）
+pub fn ensure_started() {
+    if STARTED.swap(true, Ordering::SeqCst) {
+        return;
+    }
+
+    tauri::async_runtime::spawn(async {
+        let mut last_tick_at = Utc::now().timestamp();
+        // 启动后先等待一个完整间隔，避免与前端启动时的刷新重复
+        let mut last_run_at = last_tick_at;
+        loop {
+            sleep(Duration::from_secs(TICK_SECS)).await;
+            let now = Utc::now().timestamp();
+            let gap = now - last_tick_at;
+            last_tick_at = now;
+
+            let interval_minutes = modules::config::get_quota_auto_refresh_minutes();
+            if interval_minutes == 0 {
+                last_run_at = now;
+                continue;
+            }
+            if gap > WAKE_GAP_SECS {
+                modules::logger::log_info(&format!(
+                    "[QuotaScheduler] 检测到系统从睡眠中恢复（{}s 未运行），本轮跳过",
+                    gap
+                ));
+                continue;
+            }
+            if now - last_run_at < i64::from(interval_minutes) * 60 {
+                continue;
+            }
+            if !is_online().await {
+                modules::logger::log_info("[QuotaScheduler] 网络不可用，跳过本轮定时刷新");
+                continue;
+            }
+            let Some(guard) = try_begin_refresh() else {
+                modules::logger::log_info("[QuotaScheduler] 已有刷新正在进行，跳过本轮");
+                continue;
+            };
+
+            last_run_at = now;
+            tauri::async_runtime::spawn(run_scheduled_refresh(interval_minutes, guard));
+        }
+    });
+}
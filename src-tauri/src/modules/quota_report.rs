@@ -0,0 +1,341 @@
+//! 配额报告导出
+//! 汇总所有平台账号的缓存配额，导出为 JSON / CSV 文件（默认写入下载目录）
+//! 各平台都提供剩余百分比；used / total / last_refreshed 取决于平台缓存了哪些数据，
+//! 缺失时 JSON 中为 null、CSV 中为空
+
+use std::fs;
+use std::path::PathBuf;
+
+use chrono::{Local, TimeZone, Utc};
+use serde::Serialize;
+
+use crate::modules;
+use crate::modules::tray_layout::{
+    PLATFORM_ANTIGRAVITY, PLATFORM_CODEX, PLATFORM_GITHUB_COPILOT, PLATFORM_KIRO, PLATFORM_WINDSURF,
+};
+
+const CSV_HEADER: &str =
+    "provider,account,metric,remaining_percent,used,total,reset_at,last_refreshed";
+
+/// 配额报告中的一行（单个账号的单项配额）
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuotaReportRow {
+    pub provider: String,
+    /// 本机账号 ID（仅内部使用，不写入报告）
+    #[serde(skip)]
+    pub account_id: String,
+    /// 邮箱或登录名
+    pub account: String,
+    pub metric: String,
+    pub remaining_percent: i32,
+    /// 已用额度（仅 Kiro 按额度计量，其他平台只有百分比，为空）
+    pub used: Option<f64>,
+    /// 总额度（同 used，仅 Kiro 提供）
+    pub total: Option<f64>,
+    pub reset_at: Option<String>,
+    /// 配额最近刷新时间（Unix 秒；仅 Antigravity 缓存了刷新时间，其他平台为空）
+    pub last_refreshed: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct QuotaReport {
+    generated_at: i64,
+    rows: Vec<QuotaReportRow>,
+}
+
+pub fn format_ts(ts: Option<i64>) -> Option<String> {
+    ts.and_then(|value| Local.timestamp_opt(value, 0).single())
+        .map(|time| time.format("%Y-%m-%d %H:%M:%S").to_string())
+}
+
+fn row(
+    provider: &str,
+    account_id: &str,
+    account: &str,
+    metric: String,
+    remaining_percent: i32,
+) -> QuotaReportRow {
+    QuotaReportRow {
+        provider: provider.to_string(),
+        account_id: account_id.to_string(),
+        account: account.to_string(),
+        metric,
+        remaining_percent: remaining_percent.clamp(0, 100),
+        used: None,
+        total: None,
+        reset_at: None,
+        last_refreshed: None,
+    }
+}
+
+fn collect_antigravity_rows(rows: &mut Vec<QuotaReportRow>) {
+    let accounts = match modules::list_accounts() {
+        Ok(accounts) => accounts,
+        Err(err) => {
+            modules::logger::log_warn(&format!("[QuotaReport] 读取 Antigravity 账号失败: {}", err));
+            return;
+        }
+    };
+    for account in accounts {
+        let Some(quota) = account.quota.as_ref() else {
+            continue;
+        };
+        for model in &quota.models {
+            let metric = model
+                .display_name
+                .clone()
+                .unwrap_or_else(|| model.name.clone());
+            let mut item = row(
+                PLATFORM_ANTIGRAVITY,
+                &account.id,
+                &account.email,
+                metric,
+                model.percentage,
+            );
+            if !model.reset_time.trim().is_empty() {
+                item.reset_at = Some(model.reset_time.clone());
+            }
+            item.last_refreshed = Some(quota.last_updated);
+            rows.push(item);
+        }
+    }
+}
+
+fn collect_codex_rows(rows: &mut Vec<QuotaReportRow>) {
+    for account in modules::codex_account::list_accounts() {
+        let Some(quota) = account.quota.as_ref() else {
+            continue;
+        };
+        if quota.hourly_window_present != Some(false) {
+            let mut item = row(
+                PLATFORM_CODEX,
+                &account.id,
+                &account.email,
+                "5h".to_string(),
+                quota.hourly_percentage,
+            );
+            item.reset_at = format_ts(quota.hourly_reset_time);
+            rows.push(item);
+        }
+        if quota.weekly_window_present != Some(false) {
+            let mut item = row(
+                PLATFORM_CODEX,
+                &account.id,
+                &account.email,
+                "Weekly".to_string(),
+                quota.weekly_percentage,
+            );
+            item.reset_at = format_ts(quota.weekly_reset_time);
+            rows.push(item);
+        }
+    }
+}
+
+fn collect_github_copilot_rows(rows: &mut Vec<QuotaReportRow>) {
+    for account in modules::github_copilot_account::list_accounts() {
+        for (metric, percent) in modules::github_copilot_account::extract_quota_metrics(&account) {
+            let mut item = row(
+                PLATFORM_GITHUB_COPILOT,
+                &account.id,
+                &account.github_login,
+                metric,
+                percent,
+            );
+            item.reset_at = account.copilot_quota_reset_date.clone();
+            rows.push(item);
+        }
+    }
+}
+
+fn collect_windsurf_rows(rows: &mut Vec<QuotaReportRow>) {
+    for account in modules::windsurf_account::list_accounts() {
+        for (metric, percent) in modules::windsurf_account::extract_quota_metrics(&account) {
+            let mut item = row(
+                PLATFORM_WINDSURF,
+                &account.id,
+                &account.github_login,
+                metric,
+                percent,
+            );
+            item.reset_at = account.copilot_quota_reset_date.clone();
+            rows.push(item);
+        }
+    }
+}
+
+fn collect_kiro_rows(rows: &mut Vec<QuotaReportRow>) {
+    for account in modules::kiro_account::list_accounts() {
+        let credits = [
+            (
+                "Prompt Credits",
+                account.credits_total,
+                account.credits_used,
+            ),
+            ("Add-on Credits", account.bonus_total, account.bonus_used),
+        ];
+        for (metric, total, used) in credits {
+            let Some(total_value) = total.filter(|value| value.is_finite() && *value > 0.0) else {
+                continue;
+            };
+            let used_value = used.unwrap_or(0.0);
+            let remaining = (total_value - used_value).max(0.0);
+            let percent = ((remaining / total_value) * 100.0).round() as i32;
+            let mut item = row(
+                PLATFORM_KIRO,
+                &account.id,
+                &account.email,
+                metric.to_string(),
+                percent,
+            );
+            item.used = Some(used_value);
+            item.total = Some(total_value);
+            item.reset_at = format_ts(account.usage_reset_at);
+            rows.push(item);
+        }
+    }
+}
+
+/// 汇总所有平台账号的缓存配额
+pub fn collect_quota_report_rows() -> Vec<QuotaReportRow> {
+    let mut rows = Vec::new();
+    collect_antigravity_rows(&mut rows);
+    collect_codex_rows(&mut rows);
+    collect_github_copilot_rows(&mut rows);
+    collect_windsurf_rows(&mut rows);
+    collect_kiro_rows(&mut rows);
+    rows
+}
+
+/// 刷新所有平台的配额（单个平台失败不影响其他平台）
+pub async fn refresh_all_platform_quotas() {
+    match modules::quota_refresh_scheduler::try_begin_refresh() {
+        Some(_guard) => {
+            if let Err(err) = modules::account::refresh_all_quotas_logic().await {
+                modules::logger::log_warn(&format!(
+                    "[QuotaReport] 刷新 Antigravity 配额失败: {}",
+                    err
+                ));
+            }
+        }
+        None => {
+            modules::logger::log_info("[QuotaReport] Antigravity 配额刷新正在进行，跳过本次刷新")
+        }
+    }
+    if let Err(err) = modules::codex_quota::refresh_all_quotas().await {
+        modules::logger::log_warn(&format!("[QuotaReport] 刷新 Codex 配额失败: {}", err));
+    }
+    if let Err(err) = modules::github_copilot_account::refresh_all_tokens().await {
+        modules::logger::log_warn(&format!(
+            "[QuotaReport] 刷新 GitHub Copilot 配额失败: {}",
+            err
+        ));
+    }
+    if let Err(err) = modules::windsurf_account::refresh_all_tokens().await {
+        modules::logger::log_warn(&format!("[QuotaReport] 刷新 Windsurf 配额失败: {}", err));
+    }
+    if let Err(err) = modules::kiro_account::refresh_all_tokens().await {
+        modules::logger::log_warn(&format!("[QuotaReport] 刷新 Kiro 配额失败: {}", err));
+    }
+}
+
+pub fn escape_csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn format_optional_number(value: Option<f64>) -> String {
+    value.map(|number| number.to_string()).unwrap_or_default()
+}
+
+fn render_csv(rows: &[QuotaReportRow]) -> String {
+    let mut lines = vec![CSV_HEADER.to_string()];
+    for item in rows {
+        let fields = [
+            item.provider.clone(),
+            item.account.clone(),
+            item.metric.clone(),
+            item.remaining_percent.to_string(),
+            format_optional_number(item.used),
+            format_optional_number(item.total),
+            item.reset_at.clone().unwrap_or_default(),
+            format_ts(item.last_refreshed).unwrap_or_default(),
+        ];
+        lines.push(
+            fields
+                .iter()
+                .map(|field| escape_csv_field(field))
+                .collect::<Vec<String>>()
+                .join(","),
+        );
+    }
+    lines.join("\n")
+}
+
+fn get_report_dir() -> Result<PathBuf, String> {
+    dirs::download_dir()
+        .or_else(|| dirs::home_dir().map(|home| home.join("Downloads")))
+        .ok_or_else(|| "无法获取下载目录".to_string())
+}
+
+/// 导出配额报告，返回写入的文件路径
+pub fn export_quota_report(format: &str) -> Result<String, String> {
+    let rows = collect_quota_report_rows();
+    let (content, extension) = match format.trim().to_ascii_lowercase().as_str() {
+        "json" => {
+            let report = QuotaReport {
+                generated_at: Utc::now().timestamp(),
+                rows,
+            };
+            let content = serde_json::to_string_pretty(&report)
+                .map_err(|e| format!("序列化配额报告失败: {}", e))?;
+            (content, "json")
+        }
+        "csv" => (render_csv(&rows), "csv"),
+        other => return Err(format!("不支持的导出格式: {}", other)),
+    };
+
+    let dir = get_report_dir()?;
+    fs::create_dir_all(&dir).map_err(|e| format!("创建下载目录失败: {}", e))?;
+    let file_name = format!(
+        "quota_report_{}.{}",
+        Local::now().format("%Y%m%d_%H%M%S"),
+        extension
+    );
+    let path = dir.join(file_name);
+    fs::write(&path, content).map_err(|e| format!("写入配额报告失败: {}", e))?;
+
+    let path_str = path.to_string_lossy().to_string();
+    modules::logger::log_info(&format!("[QuotaReport] 已导出配额报告: {}", path_str));
+    Ok(path_str)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_csv_escapes_fields() {
+        let mut item = row(
+            PLATFORM_KIRO,
+            "kiro_1",
+            "a@example.com",
+            "Prompt, \"Credits\"".to_string(),
+            120,
+        );
+        item.used = Some(25.0);
+        item.total = Some(100.0);
+
+        let csv = render_csv(&[item]);
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], CSV_HEADER);
+        assert_eq!(
+            lines[1],
+            "kiro,a@example.com,\"Prompt, \"\"Credits\"\"\",100,25,100,,"
+        );
+    }
+}
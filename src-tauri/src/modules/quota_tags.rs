@@ -0,0 +1,162 @@
+//! 按配额状态自动打标签
+//! 受管理的标签统一使用 `auto:` 前缀，与用户标签互不干扰，每次执行都会完整重算
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use crate::modules;
+use crate::modules::tray_layout::{
+    PLATFORM_ANTIGRAVITY, PLATFORM_CODEX, PLATFORM_GITHUB_COPILOT, PLATFORM_KIRO,
+    PLATFORM_WINDSURF, SUPPORTED_PLATFORM_IDS,
+};
+
+/// 受管理标签前缀
+pub const AUTO_TAG_PREFIX: &str = "auto:";
+const TAG_LOW_QUOTA: &str = "auto:low-quota";
+const TAG_EXHAUSTED: &str = "auto:exhausted";
+
+/// 自动打标签结果
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AutoTagResult {
+    /// 标签发生变化的账号数
+    pub updated: usize,
+    pub low_quota: usize,
+    pub exhausted: usize,
+    /// 更新失败的账号及原因
+    pub errors: Vec<String>,
+}
+
+fn list_account_tags(platform: &str) -> Vec<(String, Vec<String>)> {
+    match platform {
+        PLATFORM_ANTIGRAVITY => modules::list_accounts()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|account| (account.id, account.tags))
+            .collect(),
+        PLATFORM_CODEX => modules::codex_account::list_accounts()
+            .into_iter()
+            .map(|account| (account.id, account.tags.unwrap_or_default()))
+            .collect(),
+        PLATFORM_GITHUB_COPILOT => modules::github_copilot_account::list_accounts()
+            .into_iter()
+            .map(|account| (account.id, account.tags.unwrap_or_default()))
+            .collect(),
+        PLATFORM_WINDSURF => modules::windsurf_account::list_accounts()
+            .into_iter()
+            .map(|account| (account.id, account.tags.unwrap_or_default()))
+            .collect(),
+        PLATFORM_KIRO => modules::kiro_account::list_accounts()
+            .into_iter()
+            .map(|account| (account.id, account.tags.unwrap_or_default()))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+fn save_account_tags(platform: &str, account_id: &str, tags: Vec<String>) -> Result<(), String> {
+    match platform {
+        PLATFORM_ANTIGRAVITY => modules::update_account_tags(account_id, tags).map(|_| ()),
+        PLATFORM_CODEX => modules::codex_account::update_account_tags(account_id, tags).map(|_| ()),
+        PLATFORM_GITHUB_COPILOT => {
+            modules::github_copilot_account::update_account_tags(account_id, tags).map(|_| ())
+        }
+        PLATFORM_WINDSURF => {
+            modules::windsurf_account::update_account_tags(account_id, tags).map(|_| ())
+        }
+        PLATFORM_KIRO => modules::kiro_account::update_account_tags(account_id, tags).map(|_| ()),
+        _ => Err(format!("未知平台: {}", platform)),
+    }
+}
+
+/// 根据最低剩余百分比计算受管理标签
+fn managed_tag_for(min_percent: Option<i32>, threshold_percent: i32) -> Option<&'static str> {
+    match min_percent? {
+        percent if percent <= 0 => Some(TAG_EXHAUSTED),
+        percent if percent < threshold_percent => Some(TAG_LOW_QUOTA),
+        _ => None,
+    }
+}
+
+/// 保留用户标签，替换受管理标签
+fn merge_managed_tag(tags: &[String], managed: Option<&str>) -> Vec<String> {
+    let mut result: Vec<String> = tags
+        .iter()
+        .filter(|tag| !tag.trim().to_lowercase().starts_with(AUTO_TAG_PREFIX))
+        .cloned()
+        .collect();
+    if let Some(tag) = managed {
+        result.push(tag.to_string());
+    }
+    result
+}
+
+/// 按缓存配额为所有平台账号重算受管理标签：
+/// 配额耗尽标记 auto:exhausted，低于阈值标记 auto:low-quota，其余移除受管理标签
+pub fn auto_tag_by_quota(threshold_percent: i32) -> AutoTagResult {
+    let threshold_percent = threshold_percent.clamp(0, 100);
+    let mut min_percents: HashMap<(String, String), i32> = HashMap::new();
+    for row in modules::quota_report::collect_quota_report_rows() {
+        min_percents
+            .entry((row.provider, row.account_id))
+            .and_modify(|value| *value = (*value).min(row.remaining_percent))
+            .or_insert(row.remaining_percent);
+    }
+
+    let mut result = AutoTagResult::default();
+    for platform in SUPPORTED_PLATFORM_IDS {
+        for (account_id, tags) in list_account_tags(platform) {
+            let min_percent = min_percents
+                .get(&(platform.to_string(), account_id.clone()))
+                .copied();
+            let managed = managed_tag_for(min_percent, threshold_percent);
+            match managed {
+                Some(TAG_EXHAUSTED) => result.exhausted += 1,
+                Some(_) => result.low_quota += 1,
+                None => {}
+            }
+
+            let next = merge_managed_tag(&tags, managed);
+            if next == tags {
+                continue;
+            }
+            match save_account_tags(platform, &account_id, next) {
+                Ok(()) => result.updated += 1,
+                Err(err) => result
+                    .errors
+                    .push(format!("{}/{}: {}", platform, account_id, err)),
+            }
+        }
+    }
+
+    modules::logger::log_info(&format!(
+        "[QuotaTags] 自动标签完成: threshold={}%, updated={}, low_quota={}, exhausted={}, errors={}",
+        threshold_percent,
+        result.updated,
+        result.low_quota,
+        result.exhausted,
+        result.errors.len()
+    ));
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_managed_tag_keeps_user_tags() {
+        let tags = vec!["work".to_string(), "auto:low-quota".to_string()];
+        assert_eq!(
+            merge_managed_tag(&tags, managed_tag_for(Some(0), 20)),
+            vec!["work".to_string(), TAG_EXHAUSTED.to_string()]
+        );
+        assert_eq!(
+            merge_managed_tag(&tags, managed_tag_for(Some(50), 20)),
+            vec!["work".to_string()]
+        );
+        assert_eq!(managed_tag_for(Some(10), 20), Some(TAG_LOW_QUOTA));
+        assert_eq!(managed_tag_for(None, 20), None);
+    }
+}
@@ -0,0 +1,49 @@
+//! 安全模式
+//! 以 `COCKPIT_SAFE_MODE=1` 启动时跳过启动时的配置合并与 WebSocket 自动启动，
+//! 便于应用启动异常时排查问题；其余启动流程保持不变（托盘创建失败本就不会中断启动）
+
+use std::sync::OnceLock;
+
+use crate::modules::logger;
+
+const SAFE_MODE_ENV: &str = "COCKPIT_SAFE_MODE";
+
+static SAFE_MODE: OnceLock<bool> = OnceLock::new();
+
+fn parse_flag(value: &str) -> bool {
+    matches!(
+        value.trim().to_lowercase().as_str(),
+        "1" | "true" | "yes" | "on"
+    )
+}
+
+/// 当前进程是否以安全模式启动（首次调用时读取环境变量，之后保持不变）
+pub fn is_safe_mode() -> bool {
+    *SAFE_MODE.get_or_init(|| {
+        let enabled = std::env::var(SAFE_MODE_ENV)
+            .map(|value| parse_flag(&value))
+            .unwrap_or(false);
+        if enabled {
+            logger::log_warn(&format!(
+                "[SafeMode] 检测到 {}，已进入安全模式：跳过启动时配置合并与 WebSocket 自动启动",
+                SAFE_MODE_ENV
+            ));
+        }
+        enabled
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_flag() {
+        assert!(parse_flag("1"));
+        assert!(parse_flag(" true "));
+        assert!(parse_flag("ON"));
+        assert!(!parse_flag("0"));
+        assert!(!parse_flag(""));
+        assert!(!parse_flag("false"));
+    }
+}
@@ -0,0 +1,99 @@
+//! 启动报告
+//! 汇总 setup 阶段各初始化步骤的结果，全部步骤完成后发送 `app:startup_report` 事件，
+//! 前端也可通过 `get_startup_report` 主动查询（事件发送前查询时 completed 为 false）
+
+use std::sync::{LazyLock, Mutex};
+
+use serde::Serialize;
+use tauri::Emitter;
+
+use crate::modules;
+
+pub const STARTUP_REPORT_EVENT: &str = "app:startup_report";
+
+pub const STEP_CONFIG: &str = "config";
+pub const STEP_SYNC_MERGE: &str = "sync_merge";
+pub const STEP_WEBSOCKET: &str = "websocket";
+pub const STEP_TRAY: &str = "tray";
+
+/// 需要全部记录后才发送事件的步骤
+const EXPECTED_STEPS: [&str; 4] = [STEP_CONFIG, STEP_SYNC_MERGE, STEP_WEBSOCKET, STEP_TRAY];
+
+/// 单个初始化步骤的结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StartupStep {
+    pub name: String,
+    pub ok: bool,
+    pub detail: Option<String>,
+}
+
+/// 启动报告
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StartupReport {
+    /// 所有步骤是否已记录完毕（已发送事件）
+    pub completed: bool,
+    /// 是否存在失败的步骤
+    pub has_failures: bool,
+    pub websocket_port: Option<u16>,
+    pub steps: Vec<StartupStep>,
+}
+
+static STARTUP_REPORT: LazyLock<Mutex<StartupReport>> =
+    LazyLock::new(|| Mutex::new(StartupReport::default()));
+
+/// 记录一个初始化步骤的结果；全部步骤记录完毕后发送启动报告事件
+pub fn record_step(name: &str, ok: bool, detail: Option<String>) {
+    if !ok {
+        modules::logger::log_warn(&format!(
+            "[Startup] 初始化步骤失败: {} ({})",
+            name,
+            detail.as_deref().unwrap_or("-")
+        ));
+    }
+
+    let report = {
+        let Ok(mut report) = STARTUP_REPORT.lock() else {
+            return;
+        };
+        report.steps.retain(|step| step.name != name);
+        report.steps.push(StartupStep {
+            name: name.to_string(),
+            ok,
+            detail,
+        });
+        report.has_failures = report.steps.iter().any(|step| !step.ok);
+
+        let all_recorded = EXPECTED_STEPS
+            .iter()
+            .all(|expected| report.steps.iter().any(|step| step.name == *expected));
+        if !all_recorded || report.completed {
+            return;
+        }
+        report.completed = true;
+        report.websocket_port = modules::config::get_actual_port();
+        report.clone()
+    };
+
+    modules::logger::log_info(&format!(
+        "[Startup] 初始化完成: failures={}, websocket_port={:?}",
+        report.steps.iter().filter(|step| !step.ok).count(),
+        report.websocket_port
+    ));
+    if let Some(app_handle) = crate::get_app_handle() {
+        let _ = app_handle.emit(STARTUP_REPORT_EVENT, &report);
+    }
+}
+
+/// 获取当前的启动报告
+pub fn get_startup_report() -> StartupReport {
+    let mut report = STARTUP_REPORT
+        .lock()
+        .map(|report| report.clone())
+        .unwrap_or_default();
+    if report.websocket_port.is_none() {
+        report.websocket_port = modules::config::get_actual_port();
+    }
+    report
+}
@@ -6,12 +6,14 @@
 //! - 在线时: 通过 WebSocket 实时同步，不写入共享文件
 //! - 离线时: 写入共享文件，等对方启动时读取合并
 //! - 启动时: 读取共享文件，与本地配置比较时间戳后合并
+//! - 本地修改时间单独记录在数据目录的 sync_settings_local.json 中
 //!
 //! 可扩展性:
 //! - 目前支持 language 配置
 //! - 可扩展支持 theme、accounts 等其他配置
 
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
 
@@ -20,6 +22,15 @@ use super::config::get_shared_dir;
 /// 同步配置文件名
 const SYNC_SETTINGS_FILE: &str = "sync_settings.json";
 
+/// 本地修改时间记录文件名
+const LOCAL_TIMESTAMPS_FILE: &str = "sync_settings_local.json";
+
+/// 支持同步的配置项
+const SYNC_KEYS: [&str; 2] = ["language", "theme"];
+
+/// 时钟偏差容忍度（毫秒）：共享文件只有比本地新超过该值才覆盖本地，避免两端时钟不一致时误覆盖
+const CLOCK_SKEW_TOLERANCE_MS: i64 = 5_000;
+
 /// 配置来源
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "snake_case")]
@@ -34,6 +45,9 @@ pub struct SyncSettingValue {
     pub value: String,
     pub updated_at: i64,
     pub updated_by: ConfigSource,
+    /// 写入该配置的机器标识（见 device::get_cockpit_machine_id）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub machine_id: Option<String>,
 }
 
 /// 同步配置文件结构
@@ -47,6 +61,67 @@ pub struct SyncSettings {
     // 可扩展其他配置项...
 }
 
+/// 本地配置项的最后修改时间（毫秒时间戳）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LocalSyncTimestamps {
+    #[serde(default)]
+    last_modified: HashMap<String, i64>,
+}
+
+fn get_local_timestamps_path() -> Result<PathBuf, String> {
+    Ok(crate::modules::account::get_data_dir()?.join(LOCAL_TIMESTAMPS_FILE))
+}
+
+fn read_local_timestamps() -> LocalSyncTimestamps {
+    get_local_timestamps_path()
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn set_local_modified_at(key: &str, updated_at: i64) {
+    if !SYNC_KEYS.contains(&key) {
+        crate::modules::logger::log_warn(&format!("[SyncSettings] 未知配置项: {}", key));
+        return;
+    }
+
+    let mut timestamps = read_local_timestamps();
+    timestamps.last_modified.insert(key.to_string(), updated_at);
+
+    let result = get_local_timestamps_path().and_then(|path| {
+        let content =
+            serde_json::to_string_pretty(&timestamps).map_err(|e| format!("序列化失败: {}", e))?;
+        fs::write(&path, content).map_err(|e| format!("写入文件失败: {}", e))
+    });
+    if let Err(e) = result {
+        crate::modules::logger::log_warn(&format!(
+            "[SyncSettings] 记录本地修改时间失败: {} ({})",
+            key, e
+        ));
+    }
+}
+
+/// 记录本地配置项刚被修改（本地修改配置后调用）
+pub fn record_local_modified(key: &str) {
+    set_local_modified_at(key, chrono::Utc::now().timestamp_millis());
+}
+
+/// 获取本地配置项的最后修改时间；没有记录时返回 None
+pub fn get_local_modified(key: &str) -> Option<i64> {
+    read_local_timestamps().last_modified.get(key).copied()
+}
+
+/// 共享文件的值是否应覆盖本地：本地无记录时以共享文件为准；
+/// 否则共享文件必须比本地新超过时钟偏差容忍度，相同或相近时保留本地
+fn shared_value_wins(local_updated_at: Option<i64>, shared_updated_at: i64) -> bool {
+    match local_updated_at {
+        None => true,
+        Some(local) => shared_updated_at > local.saturating_add(CLOCK_SKEW_TOLERANCE_MS),
+    }
+}
+
 /// 获取同步配置文件路径
 fn get_sync_settings_path() -> PathBuf {
     get_shared_dir().join(SYNC_SETTINGS_FILE)
@@ -105,6 +180,7 @@ pub fn write_sync_setting(key: &str, value: &str) {
         value: value.to_string(),
         updated_at: chrono::Utc::now().timestamp_millis(),
         updated_by: ConfigSource::Desktop,
+        machine_id: crate::modules::device::get_cockpit_machine_id().ok(),
     };
 
     match key {
@@ -181,17 +257,51 @@ pub fn merge_setting_on_startup(
         return None;
     }
 
-    // 如果共享文件更新时间更晚，或者本地没有更新时间记录，使用共享文件的值
-    if local_updated_at.is_none() || sync_setting.updated_at > local_updated_at.unwrap_or(0) {
+    // 如果共享文件明显更新，或者本地没有更新时间记录，使用共享文件的值
+    if shared_value_wins(local_updated_at, sync_setting.updated_at) {
         crate::modules::logger::log_info(&format!(
             "[SyncSettings] 合并配置 {}: 共享文件 \"{}\" > 本地 \"{}\"",
             key, sync_setting.value, local_value
         ));
+        // 本地采用共享文件的值，修改时间与共享文件保持一致
+        set_local_modified_at(key, sync_setting.updated_at);
         // 清除共享文件中的配置（已合并）
         clear_sync_setting(key);
         return Some(sync_setting.value);
     }
 
-    // 本地更新时间更晚，不需要更新本地
+    // 本地更新时间更晚（或与共享文件相近），保留本地
+    crate::modules::logger::log_info(&format!(
+        "[SyncSettings] 保留本地配置 {}: 本地 \"{}\" (updated_at={:?}) 不早于共享文件 \"{}\" (updated_at={})",
+        key, local_value, local_updated_at, sync_setting.value, sync_setting.updated_at
+    ));
     None
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shared_value_wins_when_shared_is_newer() {
+        let local = 1_700_000_000_000;
+        assert!(shared_value_wins(
+            Some(local),
+            local + CLOCK_SKEW_TOLERANCE_MS + 1
+        ));
+        // 本地没有修改记录时以共享文件为准
+        assert!(shared_value_wins(None, 0));
+    }
+
+    #[test]
+    fn test_local_value_kept_when_local_is_newer_or_tied() {
+        let local = 1_700_000_000_000;
+        assert!(!shared_value_wins(Some(local), local - 60_000));
+        assert!(!shared_value_wins(Some(local), local));
+        // 时钟偏差范围内视为相同，保留本地
+        assert!(!shared_value_wins(
+            Some(local),
+            local + CLOCK_SKEW_TOLERANCE_MS
+        ));
+    }
+}
@@ -0,0 +1,359 @@
+//! 添加账号前的 Token 预检
+//! 先按格式判断 Token 类型（识别明显贴错平台 / 已过期的 Token），再做一次最小化的鉴权请求，
+//! 返回账号标识与配额预览，不写入任何账号数据
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::models::codex::{CodexAccount, CodexTokens};
+use crate::modules;
+use crate::modules::tray_layout::{
+    PLATFORM_ANTIGRAVITY, PLATFORM_CODEX, PLATFORM_GITHUB_COPILOT, PLATFORM_KIRO, PLATFORM_WINDSURF,
+};
+
+pub const PROBE_REASON_MALFORMED: &str = "malformed";
+pub const PROBE_REASON_EXPIRED: &str = "expired";
+pub const PROBE_REASON_WRONG_PROVIDER: &str = "wrong_provider";
+pub const PROBE_REASON_INVALID: &str = "invalid";
+pub const PROBE_REASON_NETWORK: &str = "network";
+
+/// Token 预检结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenProbeResult {
+    pub provider: String,
+    pub valid: bool,
+    /// 邮箱或登录名
+    pub identity: Option<String>,
+    /// 配额/套餐预览文本
+    pub quota_preview: Option<String>,
+    /// 失败原因：malformed / expired / wrong_provider / invalid / network
+    pub reason: Option<String>,
+    pub message: Option<String>,
+}
+
+/// 按格式识别出的 Token 类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TokenKind {
+    GoogleRefreshToken,
+    GoogleAccessToken,
+    GitHubToken,
+    WindsurfApiKey,
+    FirebaseIdToken,
+    OpenAiJwt,
+    OtherJwt,
+    Unknown,
+}
+
+impl TokenKind {
+    /// 该类型 Token 明确归属的平台（无法确定时为 None）
+    fn owner(self) -> Option<&'static str> {
+        match self {
+            TokenKind::GoogleRefreshToken | TokenKind::GoogleAccessToken => {
+                Some(PLATFORM_ANTIGRAVITY)
+            }
+            TokenKind::GitHubToken => Some(PLATFORM_GITHUB_COPILOT),
+            TokenKind::WindsurfApiKey | TokenKind::FirebaseIdToken => Some(PLATFORM_WINDSURF),
+            TokenKind::OpenAiJwt => Some(PLATFORM_CODEX),
+            TokenKind::OtherJwt | TokenKind::Unknown => None,
+        }
+    }
+}
+
+fn decode_jwt_claims(token: &str) -> Option<Value> {
+    let parts: Vec<&str> = token.split('.').collect();
+    if parts.len() != 3 {
+        return None;
+    }
+    let bytes = URL_SAFE_NO_PAD
+        .decode(parts[1].trim_end_matches('='))
+        .ok()?;
+    serde_json::from_slice(&bytes).ok()
+}
+
+fn detect_token_kind(token: &str) -> TokenKind {
+    const GITHUB_PREFIXES: [&str; 5] = ["gho_", "ghu_", "ghp_", "ghs_", "github_pat_"];
+    if token.starts_with("1//") {
+        return TokenKind::GoogleRefreshToken;
+    }
+    if token.starts_with("ya29.") {
+        return TokenKind::GoogleAccessToken;
+    }
+    if GITHUB_PREFIXES
+        .iter()
+        .any(|prefix| token.starts_with(prefix))
+    {
+        return TokenKind::GitHubToken;
+    }
+    if token.starts_with("sk-ws-") {
+        return TokenKind::WindsurfApiKey;
+    }
+    let Some(claims) = decode_jwt_claims(token) else {
+        return TokenKind::Unknown;
+    };
+    let issuer = claims
+        .get("iss")
+        .and_then(Value::as_str)
+        .unwrap_or_default();
+    if issuer.contains("openai.com") {
+        TokenKind::OpenAiJwt
+    } else if issuer.contains("securetoken.google.com") {
+        TokenKind::FirebaseIdToken
+    } else {
+        TokenKind::OtherJwt
+    }
+}
+
+/// Windsurf 也支持用 GitHub 登录，GitHub Token 对其不算贴错平台
+fn is_wrong_provider(provider: &str, kind: TokenKind) -> bool {
+    match kind.owner() {
+        Some(owner) => {
+            owner != provider && !(provider == PLATFORM_WINDSURF && kind == TokenKind::GitHubToken)
+        }
+        None => false,
+    }
+}
+
+fn is_jwt_expired(token: &str, now: i64) -> bool {
+    decode_jwt_claims(token)
+        .and_then(|claims| claims.get("exp").and_then(Value::as_i64))
+        .map(|exp| exp <= now)
+        .unwrap_or(false)
+}
+
+/// 根据请求错误信息归类失败原因
+fn classify_probe_error(message: &str) -> &'static str {
+    let lower = message.to_ascii_lowercase();
+    if ["401", "invalid_grant", "expired", "unauthorized", "过期"]
+        .iter()
+        .any(|keyword| lower.contains(keyword))
+    {
+        PROBE_REASON_EXPIRED
+    } else if ["timeout", "timed out", "connect", "dns", "网络", "请求失败"]
+        .iter()
+        .any(|keyword| lower.contains(keyword))
+    {
+        PROBE_REASON_NETWORK
+    } else {
+        PROBE_REASON_INVALID
+    }
+}
+
+fn format_percent_preview(label: &str, percent: i32) -> String {
+    format!("{} 剩余 {}%", label, percent.clamp(0, 100))
+}
+
+async fn probe_antigravity(token: &str) -> Result<(String, Option<String>), String> {
+    let token_res = modules::oauth::refresh_access_token(token).await?;
+    let user_info = modules::oauth::get_user_info(&token_res.access_token).await?;
+    let preview = match modules::quota::fetch_quota(&token_res.access_token, &user_info.email, true)
+        .await
+    {
+        Ok(result) => result
+            .quota
+            .models
+            .iter()
+            .map(|model| model.percentage)
+            .min()
+            .map(|percent| format_percent_preview("最低模型", percent)),
+        Err(err) => {
+            modules::logger::log_warn(&format!("[TokenProbe] 获取 Antigravity 配额失败: {}", err));
+            None
+        }
+    };
+    Ok((user_info.email, preview))
+}
+
+async fn probe_codex(token: &str) -> Result<(String, Option<String>), String> {
+    let claims = decode_jwt_claims(token).ok_or("无效的 JWT Token 格式")?;
+    let email = claims
+        .get("email")
+        .or_else(|| {
+            claims
+                .get("https://api.openai.com/profile")
+                .and_then(|profile| profile.get("email"))
+        })
+        .and_then(Value::as_str)
+        .map(|value| value.to_string())
+        .ok_or("Token 中缺少邮箱信息")?;
+
+    let mut account = CodexAccount::new(
+        String::new(),
+        email.clone(),
+        CodexTokens {
+            id_token: String::new(),
+            access_token: token.to_string(),
+            refresh_token: None,
+        },
+    );
+    account.account_id =
+        modules::codex_account::extract_chatgpt_account_id_from_access_token(token);
+    let quota = modules::codex_quota::fetch_quota(&account).await?;
+    let preview = format!(
+        "{} / {}",
+        format_percent_preview("5h", quota.hourly_percentage),
+        format_percent_preview("Weekly", quota.weekly_percentage)
+    );
+    Ok((email, Some(preview)))
+}
+
+async fn probe_github_copilot(token: &str) -> Result<(String, Option<String>), String> {
+    let payload =
+        modules::github_copilot_oauth::build_payload_from_github_access_token(token).await?;
+    let preview = payload.copilot_plan.map(|plan| format!("套餐: {}", plan));
+    Ok((payload.github_login, preview))
+}
+
+async fn probe_windsurf(token: &str) -> Result<(String, Option<String>), String> {
+    let payload = modules::windsurf_oauth::build_payload_from_token(token).await?;
+    let preview = payload.copilot_plan.map(|plan| format!("套餐: {}", plan));
+    Ok((payload.github_login, preview))
+}
+
+async fn probe_kiro(token: &str) -> Result<(String, Option<String>), String> {
+    let payload = modules::kiro_oauth::build_payload_from_token(token).await?;
+    let preview = payload
+        .credits_total
+        .filter(|total| total.is_finite() && *total > 0.0)
+        .map(|total| {
+            let used = payload.credits_used.unwrap_or(0.0);
+            let percent = (((total - used).max(0.0) / total) * 100.0).round() as i32;
+            format_percent_preview("Prompt Credits", percent)
+        })
+        .or_else(|| {
+            payload
+                .plan_name
+                .clone()
+                .map(|plan| format!("套餐: {}", plan))
+        });
+    Ok((payload.email, preview))
+}
+
+fn fail(result: &mut TokenProbeResult, reason: &str, message: String) {
+    result.reason = Some(reason.to_string());
+    result.message = Some(message);
+}
+
+/// 预检待添加的 Token：返回是否可用、所属账号及配额预览，失败时给出分类原因
+pub async fn probe_token(provider: &str, token: &str) -> TokenProbeResult {
+    let mut result = TokenProbeResult {
+        provider: provider.to_string(),
+        valid: false,
+        identity: None,
+        quota_preview: None,
+        reason: None,
+        message: None,
+    };
+    let token = token.trim();
+    if token.is_empty() || token.chars().any(char::is_whitespace) {
+        fail(
+            &mut result,
+            PROBE_REASON_MALFORMED,
+            "Token 为空或包含空白字符".to_string(),
+        );
+        return result;
+    }
+
+    let kind = detect_token_kind(token);
+    if is_wrong_provider(provider, kind) {
+        fail(
+            &mut result,
+            PROBE_REASON_WRONG_PROVIDER,
+            format!("该 Token 看起来属于 {}", kind.owner().unwrap_or_default()),
+        );
+        return result;
+    }
+    if provider == PLATFORM_ANTIGRAVITY && kind == TokenKind::GoogleAccessToken {
+        fail(
+            &mut result,
+            PROBE_REASON_MALFORMED,
+            "请提供 refresh_token（以 1// 开头），而不是 access_token".to_string(),
+        );
+        return result;
+    }
+    if is_jwt_expired(token, chrono::Utc::now().timestamp()) {
+        fail(
+            &mut result,
+            PROBE_REASON_EXPIRED,
+            "Token 已过期".to_string(),
+        );
+        return result;
+    }
+
+    let outcome = match provider {
+        PLATFORM_ANTIGRAVITY => probe_antigravity(token).await,
+        PLATFORM_CODEX => probe_codex(token).await,
+        PLATFORM_GITHUB_COPILOT => probe_github_copilot(token).await,
+        PLATFORM_WINDSURF => probe_windsurf(token).await,
+        PLATFORM_KIRO => probe_kiro(token).await,
+        _ => {
+            fail(
+                &mut result,
+                PROBE_REASON_INVALID,
+                format!("未知平台: {}", provider),
+            );
+            return result;
+        }
+    };
+
+    match outcome {
+        Ok((identity, quota_preview)) => {
+            result.valid = true;
+            result.identity = Some(identity);
+            result.quota_preview = quota_preview;
+        }
+        Err(err) => {
+            modules::logger::log_warn(&format!(
+                "[TokenProbe] Token 预检失败: provider={}, err={}",
+                provider, err
+            ));
+            fail(&mut result, classify_probe_error(&err), err);
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fake_jwt(claims: Value) -> String {
+        let body = URL_SAFE_NO_PAD.encode(claims.to_string());
+        format!("eyJhbGciOiJub25lIn0.{}.sig", body)
+    }
+
+    #[test]
+    fn test_detect_token_kind_and_wrong_provider() {
+        let openai = fake_jwt(serde_json::json!({ "iss": "https://auth.openai.com", "exp": 10 }));
+        assert_eq!(detect_token_kind(&openai), TokenKind::OpenAiJwt);
+        assert_eq!(detect_token_kind("1//0abc"), TokenKind::GoogleRefreshToken);
+        assert_eq!(detect_token_kind("gho_abc"), TokenKind::GitHubToken);
+        assert_eq!(detect_token_kind("opaque-token"), TokenKind::Unknown);
+
+        assert!(is_wrong_provider(
+            PLATFORM_GITHUB_COPILOT,
+            TokenKind::OpenAiJwt
+        ));
+        assert!(!is_wrong_provider(
+            PLATFORM_WINDSURF,
+            TokenKind::GitHubToken
+        ));
+        assert!(!is_wrong_provider(PLATFORM_KIRO, TokenKind::Unknown));
+        assert!(is_jwt_expired(&openai, 11));
+        assert!(!is_jwt_expired("gho_abc", 11));
+    }
+
+    #[test]
+    fn test_classify_probe_error() {
+        assert_eq!(
+            classify_probe_error("HTTP 401 Unauthorized"),
+            PROBE_REASON_EXPIRED
+        );
+        assert_eq!(
+            classify_probe_error("请求失败: connect error"),
+            PROBE_REASON_NETWORK
+        );
+        assert_eq!(classify_probe_error("返回数据异常"), PROBE_REASON_INVALID);
+    }
+}
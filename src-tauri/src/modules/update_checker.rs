@@ -51,7 +51,7 @@ struct GitHubRelease {
 
 /// Check for updates from GitHub releases
 pub async fn check_for_updates() -> Result<UpdateInfo, String> {
-    let client = reqwest::Client::builder()
+    let client = crate::modules::http::client_builder()
         .user_agent("Antigravity-Cockpit-Tools")
         .timeout(std::time::Duration::from_secs(10))
         .build()
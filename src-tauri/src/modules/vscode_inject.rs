@@ -46,6 +46,10 @@ type Aes128CbcEnc = cbc::Encryptor<Aes128>;
 #[cfg(not(target_os = "windows"))]
 type Aes128CbcDec = cbc::Decryptor<Aes128>;
 
+const GITHUB_AUTH_SECRET_KEY: &str =
+    r#"secret://{"extensionId":"vscode.github-authentication","key":"github.auth"}"#;
+const COPILOT_GITHUB_KEY: &str = "github.copilot-github";
+
 const V10_PREFIX: &[u8] = b"v10";
 const V11_PREFIX: &[u8] = b"v11";
 #[cfg(not(target_os = "windows"))]
@@ -483,11 +487,11 @@ fn inject_copilot_token_with_data_root(
     github_user_id: Option<&str>,
 ) -> Result<String, String> {
     let db_path = get_vscode_db_path_from_data_root(data_root)?;
+    crate::modules::db::ensure_db_writable(&db_path, "VS Code")?;
     let conn = Connection::open(&db_path)
         .map_err(|e| format!("Failed to open VS Code database: {}", e))?;
 
-    let secret_key =
-        r#"secret://{"extensionId":"vscode.github-authentication","key":"github.auth"}"#;
+    let secret_key = GITHUB_AUTH_SECRET_KEY;
     let existing: Option<String> = match conn.query_row(
         "SELECT value FROM ItemTable WHERE key = ?",
         [secret_key],
@@ -533,7 +537,7 @@ fn inject_copilot_token_with_data_root(
 
     tx.execute(
         "INSERT OR REPLACE INTO ItemTable (key, value) VALUES (?, ?)",
-        ["github.copilot-github", username],
+        [COPILOT_GITHUB_KEY, username],
     )
     .map_err(|e| format!("Failed to write github.copilot-github: {}", e))?;
 
@@ -641,3 +645,104 @@ pub fn inject_copilot_token_for_user_data_dir(
     let data_root = resolve_vscode_data_root(Some(user_data_dir))?;
     inject_copilot_token_with_data_root(&data_root, username, token, github_user_id)
 }
+
+/// GitHub session that Copilot uses in a VS Code profile.
+#[derive(Debug, Clone)]
+pub struct VscodeCopilotSession {
+    pub login: String,
+    pub access_token: String,
+}
+
+fn is_db_busy(err: &rusqlite::Error) -> bool {
+    matches!(
+        err,
+        rusqlite::Error::SqliteFailure(e, _)
+            if matches!(
+                e.code,
+                rusqlite::ErrorCode::DatabaseBusy | rusqlite::ErrorCode::DatabaseLocked
+            )
+    )
+}
+
+fn query_item(conn: &Connection, key: &str) -> Result<Option<String>, String> {
+    match conn.query_row("SELECT value FROM ItemTable WHERE key = ?", [key], |row| {
+        row.get(0)
+    }) {
+        Ok(value) => Ok(Some(value)),
+        Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+        Err(e) if is_db_busy(&e) => Err(
+            "VS Code database is locked by another process, please close VS Code and retry"
+                .to_string(),
+        ),
+        Err(e) => Err(format!("Failed to query {} from database: {}", key, e)),
+    }
+}
+
+/// Pick the session Copilot signed in with: the account recorded in
+/// `github.copilot-github` first, then any session with the `user:email` scope.
+fn select_copilot_session(
+    sessions: &[serde_json::Value],
+    copilot_login: Option<&str>,
+) -> Option<VscodeCopilotSession> {
+    let candidates: Vec<(bool, VscodeCopilotSession)> = sessions
+        .iter()
+        .filter_map(|session| {
+            let access_token = session["accessToken"].as_str()?.trim();
+            let login = session["account"]["label"].as_str()?.trim();
+            if access_token.is_empty() || login.is_empty() {
+                return None;
+            }
+            let has_user_email = session["scopes"]
+                .as_array()
+                .map(|scopes| scopes.iter().any(|s| s.as_str() == Some("user:email")))
+                .unwrap_or(false);
+            Some((
+                has_user_email,
+                VscodeCopilotSession {
+                    login: login.to_string(),
+                    access_token: access_token.to_string(),
+                },
+            ))
+        })
+        .collect();
+
+    let preferred = copilot_login.map(str::trim).filter(|s| !s.is_empty());
+    let matched = preferred.and_then(|login| {
+        candidates
+            .iter()
+            .find(|(_, session)| session.login.eq_ignore_ascii_case(login))
+    });
+    matched
+        .or_else(|| {
+            candidates
+                .iter()
+                .find(|(has_user_email, _)| *has_user_email)
+        })
+        .or_else(|| candidates.first())
+        .map(|(_, session)| session.clone())
+}
+
+/// Read the GitHub session used by Copilot from a VS Code user-data-dir.
+pub fn read_copilot_session_for_user_data_dir(
+    user_data_dir: &str,
+) -> Result<VscodeCopilotSession, String> {
+    let data_root = resolve_vscode_data_root(Some(user_data_dir))?;
+    let db_path = get_vscode_db_path_from_data_root(&data_root)?;
+    let conn = crate::modules::db::open_readonly(&db_path)?;
+
+    let secret = query_item(&conn, GITHUB_AUTH_SECRET_KEY)?.ok_or_else(|| {
+        format!(
+            "No GitHub Copilot sign-in found in VS Code profile: {}",
+            data_root.display()
+        )
+    })?;
+    let copilot_login = query_item(&conn, COPILOT_GITHUB_KEY)?;
+
+    let (sessions, _) = load_existing_sessions(Some(&secret), Some(&data_root))?;
+    select_copilot_session(&sessions, copilot_login.as_deref()).ok_or_else(|| {
+        format!(
+            "No GitHub Copilot sign-in found in VS Code profile: {}",
+            data_root.display()
+        )
+    })
+}
@@ -19,6 +19,8 @@ const DEFAULT_ATTEMPTS: usize = 2;
 const BACKOFF_BASE_MS: u64 = 500;
 const BACKOFF_MAX_MS: u64 = 4000;
 static BASE_URL_ORDER: OnceLock<Mutex<Vec<&'static str>>> = OnceLock::new();
+/// 模型列表内存缓存（按账号 ID 区分）
+static MODELS_CACHE: OnceLock<Mutex<HashMap<String, CachedModels>>> = OnceLock::new();
 
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -471,7 +473,7 @@ pub async fn trigger_wakeup(
         let _ = modules::save_account(&account);
     }
 
-    let client = crate::utils::http::create_client(15);
+    let client = crate::modules::http::create_client(15);
     let body = build_request_body(&final_project_id, model, prompt, max_output_tokens);
     let started = std::time::Instant::now();
 
@@ -499,7 +501,7 @@ pub async fn trigger_wakeup(
     }
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct AvailableModel {
     pub id: String,
@@ -523,8 +525,38 @@ struct AvailableModelMeta {
     recommended: Option<bool>,
 }
 
+/// 可用模型列表结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AvailableModelsResult {
+    pub models: Vec<AvailableModel>,
+    /// 模型列表的获取时间（秒级时间戳）
+    pub fetched_at: i64,
+    /// 本次请求失败，返回的是上次缓存的列表
+    pub stale: bool,
+}
+
+#[derive(Debug, Clone)]
+struct CachedModels {
+    models: Vec<AvailableModel>,
+    fetched_at: i64,
+}
+
+fn get_cached_models(account_id: &str) -> Option<CachedModels> {
+    let lock = MODELS_CACHE.get_or_init(|| Mutex::new(HashMap::new()));
+    lock.lock().ok()?.get(account_id).cloned()
+}
+
+fn store_cached_models(account_id: &str, cached: CachedModels) {
+    let lock = MODELS_CACHE.get_or_init(|| Mutex::new(HashMap::new()));
+    if let Ok(mut cache) = lock.lock() {
+        cache.insert(account_id.to_string(), cached);
+    }
+}
+
 /// 获取可用模型列表（用于唤醒配置）
-pub async fn fetch_available_models() -> Result<Vec<AvailableModel>, String> {
+/// 缓存未过期时直接返回缓存，`force` 为 true 时跳过缓存；请求失败但有缓存时返回缓存并标记 stale
+pub async fn fetch_available_models(force: bool) -> Result<AvailableModelsResult, String> {
     let current = modules::get_current_account()?;
     let account = if let Some(account) = current {
         account
@@ -536,6 +568,57 @@ pub async fn fetch_available_models() -> Result<Vec<AvailableModel>, String> {
             .ok_or_else(|| "未找到可用账号".to_string())?
     };
 
+    let now = chrono::Utc::now().timestamp();
+    let ttl_secs = i64::from(modules::config::get_wakeup_models_cache_minutes()) * 60;
+    let cached = get_cached_models(&account.id);
+    if !force {
+        if let Some(cached) = cached.as_ref() {
+            let age = now - cached.fetched_at;
+            if ttl_secs > 0 && (0..ttl_secs).contains(&age) {
+                return Ok(AvailableModelsResult {
+                    models: cached.models.clone(),
+                    fetched_at: cached.fetched_at,
+                    stale: false,
+                });
+            }
+        }
+    }
+
+    match request_available_models(&account).await {
+        Ok(models) => {
+            store_cached_models(
+                &account.id,
+                CachedModels {
+                    models: models.clone(),
+                    fetched_at: now,
+                },
+            );
+            Ok(AvailableModelsResult {
+                models,
+                fetched_at: now,
+                stale: false,
+            })
+        }
+        Err(err) => match cached {
+            Some(cached) => {
+                crate::modules::logger::log_warn(&format!(
+                    "[Wakeup] 获取模型列表失败，返回缓存列表 (fetched_at={}): {}",
+                    cached.fetched_at, err
+                ));
+                Ok(AvailableModelsResult {
+                    models: cached.models,
+                    fetched_at: cached.fetched_at,
+                    stale: true,
+                })
+            }
+            None => Err(err),
+        },
+    }
+}
+
+async fn request_available_models(
+    account: &crate::models::Account,
+) -> Result<Vec<AvailableModel>, String> {
     let token = modules::oauth::ensure_fresh_token(&account.token).await?;
     if token.access_token != account.token.access_token
         || token.expiry_timestamp != account.token.expiry_timestamp
@@ -547,7 +630,7 @@ pub async fn fetch_available_models() -> Result<Vec<AvailableModel>, String> {
 
     let payload = json!({});
 
-    let client = crate::utils::http::create_client(15);
+    let client = crate::modules::http::create_client(15);
     let mut last_error: Option<String> = None;
     let mut data: Option<AvailableModelsResponse> = None;
     'outer: for base in CLOUD_CODE_BASE_URLS {
@@ -5,12 +5,14 @@ use futures_util::{SinkExt, StreamExt};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::net::SocketAddr;
-use std::sync::Arc;
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::Duration;
 use tokio::net::{TcpListener, TcpStream};
-use tokio::sync::{broadcast, RwLock};
+use tokio::sync::{broadcast, oneshot, RwLock};
 use tokio_tungstenite::tungstenite::Message;
 
 use super::config::{get_preferred_port, init_server_status, PORT_RANGE};
+use crate::models::QuotaData;
 
 /// 消息类型
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -41,6 +43,13 @@ pub enum WsMessage {
     #[serde(rename = "event.wakeup_override")]
     WakeupOverride { enabled: bool },
 
+    /// 账号配额已更新
+    #[serde(rename = "event.quota_updated")]
+    QuotaUpdated {
+        account_id: String,
+        quota: QuotaData,
+    },
+
     // ============ 请求（扩展 -> Tools） ============
     /// 请求获取账号列表
     #[serde(rename = "request.get_accounts")]
@@ -189,6 +198,43 @@ impl WsServer {
     }
 }
 
+/// 端口段全部绑定失败后的重试次数
+const BIND_RETRY_ATTEMPTS: u32 = 3;
+
+/// 首次重试前的等待时间，之后每次翻倍
+const BIND_RETRY_BASE_DELAY: Duration = Duration::from_secs(1);
+
+/// WebSocket 服务运行状态（供前端健康检查）
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WebSocketStatus {
+    pub running: bool,
+    pub port: Option<u16>,
+    pub connected_clients: usize,
+    pub started_at: Option<i64>,
+    pub last_error: Option<String>,
+}
+
+static WS_STATUS: LazyLock<Mutex<WebSocketStatus>> =
+    LazyLock::new(|| Mutex::new(WebSocketStatus::default()));
+
+fn update_status<F>(update: F)
+where
+    F: FnOnce(&mut WebSocketStatus),
+{
+    if let Ok(mut status) = WS_STATUS.lock() {
+        update(&mut status);
+    }
+}
+
+/// 获取 WebSocket 服务当前状态
+pub fn get_status() -> WebSocketStatus {
+    WS_STATUS
+        .lock()
+        .map(|status| status.clone())
+        .unwrap_or_default()
+}
+
 /// 全局 WebSocket 服务实例
 static WS_SERVER: std::sync::OnceLock<Arc<WsServer>> = std::sync::OnceLock::new();
 
@@ -240,6 +286,15 @@ pub fn broadcast_account_switched(account_id: &str, email: &str) {
     crate::modules::logger::log_info("[WS] 广播账号切换");
 }
 
+/// 广播任意事件，供其他模块推送（没有已连接的客户端时直接跳过，不阻塞）
+pub fn broadcast_event(event: WsMessage) {
+    let server = get_server();
+    if server.tx.receiver_count() == 0 {
+        return;
+    }
+    server.broadcast(event);
+}
+
 /// 广播唤醒互斥开关
 pub fn broadcast_wakeup_override(enabled: bool) {
     let server = get_server();
@@ -247,47 +302,129 @@ pub fn broadcast_wakeup_override(enabled: bool) {
     crate::modules::logger::log_info(&format!("[WS] 广播唤醒互斥: enabled={}", enabled));
 }
 
-/// 启动 WebSocket 服务（支持动态端口尝试）
-pub async fn start_server() {
-    // 从用户配置获取首选端口
-    let preferred_port = get_preferred_port();
-
-    // 尝试绑定端口，如果失败则尝试下一个
-    let mut port = preferred_port;
-    let mut listener = None;
-
-    for attempt in 0..PORT_RANGE {
-        let addr = format!("127.0.0.1:{}", port);
-        match TcpListener::bind(&addr).await {
-            Ok(l) => {
-                listener = Some(l);
-                if attempt > 0 {
+/// 从首选端口开始依次尝试绑定，直到端口段内有可用端口
+async fn bind_listener(preferred_port: u16) -> Result<(TcpListener, u16), String> {
+    let mut last_error = String::new();
+    for offset in 0..PORT_RANGE {
+        let Some(port) = preferred_port.checked_add(offset) else {
+            break;
+        };
+        match TcpListener::bind(("127.0.0.1", port)).await {
+            Ok(listener) => {
+                if offset > 0 {
                     crate::modules::logger::log_info(&format!(
                         "[WS] 配置端口 {} 被占用，使用端口: {}",
                         preferred_port, port
                     ));
                 }
-                break;
+                return Ok((listener, port));
             }
-            Err(e) => {
-                if attempt < PORT_RANGE - 1 {
-                    port += 1;
-                } else {
-                    crate::modules::logger::log_error(&format!(
-                        "[WS] 无法绑定端口 ({}-{})，最后错误: {}",
-                        preferred_port,
-                        preferred_port + PORT_RANGE - 1,
-                        e
-                    ));
-                    return;
+            Err(e) => last_error = e.to_string(),
+        }
+    }
+    Err(format!(
+        "无法绑定端口 ({}-{})，最后错误: {}",
+        preferred_port,
+        preferred_port.saturating_add(PORT_RANGE - 1),
+        last_error
+    ))
+}
+
+/// 绑定端口，失败时按退避间隔重试
+async fn bind_listener_with_retry(preferred_port: u16) -> Result<(TcpListener, u16), String> {
+    let mut delay = BIND_RETRY_BASE_DELAY;
+    let mut attempt = 0;
+    loop {
+        match bind_listener(preferred_port).await {
+            Ok(bound) => return Ok(bound),
+            Err(err) => {
+                update_status(|status| status.last_error = Some(err.clone()));
+                if attempt >= BIND_RETRY_ATTEMPTS {
+                    return Err(err);
                 }
+                attempt += 1;
+                crate::modules::logger::log_warn(&format!(
+                    "[WS] {}，{} 秒后重试 ({}/{})",
+                    err,
+                    delay.as_secs(),
+                    attempt,
+                    BIND_RETRY_ATTEMPTS
+                ));
+                tokio::time::sleep(delay).await;
+                delay *= 2;
             }
         }
     }
+}
+
+/// 停止服务后等待监听端口释放的最长时间
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(5);
 
-    let listener = match listener {
-        Some(l) => l,
-        None => return,
+/// 停止信号：携带一个回执通道，服务关闭监听后通过它通知调用方
+type ShutdownSignal = oneshot::Sender<()>;
+
+/// 当前运行中服务的停止信号发送端
+static SERVER_SHUTDOWN: LazyLock<Mutex<Option<oneshot::Sender<ShutdownSignal>>>> =
+    LazyLock::new(|| Mutex::new(None));
+
+/// 串行化启动 / 停止，避免并发重启时重复绑定
+static SERVER_LIFECYCLE_LOCK: LazyLock<tokio::sync::Mutex<()>> =
+    LazyLock::new(|| tokio::sync::Mutex::new(()));
+
+/// 绑定端口并在后台开始接受连接，返回实际绑定的端口
+async fn spawn_server(preferred_port: u16) -> Result<u16, String> {
+    let (listener, port) = bind_listener_with_retry(preferred_port).await?;
+    let (shutdown_tx, shutdown_rx) = oneshot::channel();
+    if let Ok(mut slot) = SERVER_SHUTDOWN.lock() {
+        *slot = Some(shutdown_tx);
+    }
+    tokio::spawn(serve(listener, port, shutdown_rx));
+    Ok(port)
+}
+
+/// 通知当前服务停止监听，并等待监听端口释放（未运行时直接返回）
+async fn shutdown_server() {
+    let shutdown_tx = SERVER_SHUTDOWN.lock().ok().and_then(|mut slot| slot.take());
+    let Some(shutdown_tx) = shutdown_tx else {
+        return;
+    };
+    let (ack_tx, ack_rx) = oneshot::channel();
+    if shutdown_tx.send(ack_tx).is_err() {
+        // 服务任务已经退出
+        return;
+    }
+    if tokio::time::timeout(SHUTDOWN_TIMEOUT, ack_rx)
+        .await
+        .is_err()
+    {
+        crate::modules::logger::log_warn("[WS] 等待旧服务停止超时");
+    }
+}
+
+/// 停止当前服务后在新端口重新启动（不写入服务状态文件）
+async fn restart_on(preferred_port: u16) -> Result<u16, String> {
+    let _lock = SERVER_LIFECYCLE_LOCK.lock().await;
+    shutdown_server().await;
+    spawn_server(preferred_port).await
+}
+
+/// 启动 WebSocket 服务（支持动态端口尝试），返回实际绑定的端口
+pub async fn start_server() -> Result<u16, String> {
+    let _lock = SERVER_LIFECYCLE_LOCK.lock().await;
+    // 从用户配置获取首选端口
+    let preferred_port = get_preferred_port();
+
+    let port = match spawn_server(preferred_port).await {
+        Ok(port) => port,
+        Err(err) => {
+            crate::modules::logger::log_error(&format!("[WS] {}", err));
+            crate::modules::startup_report::record_step(
+                crate::modules::startup_report::STEP_WEBSOCKET,
+                false,
+                Some(err.clone()),
+            );
+            return Err(err);
+        }
     };
 
     // 保存服务状态到共享文件（供 VS Code 扩展读取）
@@ -299,12 +436,91 @@ pub async fn start_server() {
         "[WS] WebSocket 服务已启动: ws://127.0.0.1:{}",
         port
     ));
+    crate::modules::startup_report::record_step(
+        crate::modules::startup_report::STEP_WEBSOCKET,
+        true,
+        Some(format!("port={}", port)),
+    );
+    Ok(port)
+}
 
-    let server = get_server();
+/// 停止当前服务并重新启动，返回实际绑定的端口
+/// 未指定端口时重新读取配置中的首选端口；指定的端口只在本次运行中生效，不写入配置
+pub async fn restart_server(port: Option<u16>) -> Result<u16, String> {
+    let preferred_port = port.unwrap_or_else(get_preferred_port);
+    crate::modules::logger::log_info(&format!(
+        "[WS] 重启 WebSocket 服务: preferred_port={}",
+        preferred_port
+    ));
+
+    let port = restart_on(preferred_port).await.map_err(|err| {
+        crate::modules::logger::log_error(&format!("[WS] 重启失败: {}", err));
+        err
+    })?;
+
+    if let Err(e) = init_server_status(port) {
+        crate::modules::logger::log_error(&format!("[WS] 保存服务状态失败: {}", e));
+    }
+    crate::modules::logger::log_info(&format!(
+        "[WS] WebSocket 服务已重启: ws://127.0.0.1:{}",
+        port
+    ));
+    Ok(port)
+}
+
+/// 在已绑定的端口上接受连接，直到收到停止信号或监听出错
+async fn serve(
+    listener: TcpListener,
+    port: u16,
+    mut shutdown_rx: oneshot::Receiver<ShutdownSignal>,
+) {
+    update_status(|status| {
+        *status = WebSocketStatus {
+            running: true,
+            port: Some(port),
+            connected_clients: 0,
+            started_at: Some(chrono::Utc::now().timestamp()),
+            last_error: None,
+        };
+    });
 
-    while let Ok((stream, addr)) = listener.accept().await {
-        let server_clone = Arc::clone(server);
-        tokio::spawn(handle_connection(server_clone, stream, addr));
+    let server = get_server();
+    loop {
+        let accept_result = tokio::select! {
+            res = listener.accept() => Ok(res),
+            signal = &mut shutdown_rx => Err(signal.ok()),
+        };
+        let accept_result = match accept_result {
+            Ok(res) => res,
+            Err(ack_tx) => {
+                // 先释放监听端口再回执，调用方收到回执后即可重新绑定
+                drop(listener);
+                update_status(|status| {
+                    if status.port == Some(port) {
+                        status.running = false;
+                    }
+                });
+                crate::modules::logger::log_info(&format!("[WS] 服务已停止监听端口: {}", port));
+                if let Some(ack_tx) = ack_tx {
+                    let _ = ack_tx.send(());
+                }
+                return;
+            }
+        };
+        match accept_result {
+            Ok((stream, addr)) => {
+                let server_clone = Arc::clone(server);
+                tokio::spawn(handle_connection(server_clone, stream, addr));
+            }
+            Err(e) => {
+                crate::modules::logger::log_error(&format!("[WS] 监听连接失败，服务已停止: {}", e));
+                update_status(|status| {
+                    status.running = false;
+                    status.last_error = Some(e.to_string());
+                });
+                break;
+            }
+        }
     }
 }
 
@@ -324,6 +540,8 @@ async fn handle_connection(server: Arc<WsServer>, stream: TcpStream, addr: Socke
     {
         let mut clients = server.clients.write().await;
         clients.insert(addr, Client { _addr: addr });
+        let connected = clients.len();
+        update_status(|status| status.connected_clients = connected);
     }
 
     let (mut ws_sender, mut ws_receiver) = ws_stream.split();
@@ -376,6 +594,8 @@ async fn handle_connection(server: Arc<WsServer>, stream: TcpStream, addr: Socke
     {
         let mut clients = server.clients.write().await;
         clients.remove(&addr);
+        let connected = clients.len();
+        update_status(|status| status.connected_clients = connected);
     }
 
     crate::modules::logger::log_info(&format!("[WS] 连接关闭: {}", addr));
@@ -475,23 +695,11 @@ async fn handle_client_message(
             // 异步执行切换
             let server_clone = server.tx.clone();
             tokio::spawn(async move {
-                match crate::modules::account::switch_account_internal(&account_id).await {
-                    Ok(account) => {
-                        let msg = WsMessage::AccountSwitched {
-                            account_id: account.id,
-                            email: account.email,
-                        };
-                        if let Ok(json) = serde_json::to_string(&msg) {
-                            let _ = server_clone.send(json);
-                        }
-                    }
-                    Err(e) => {
-                        let msg = WsMessage::SwitchError { message: e };
-                        if let Ok(json) = serde_json::to_string(&msg) {
-                            let _ = server_clone.send(json);
-                        }
-                    }
-                }
+                broadcast_switch_result(
+                    &server_clone,
+                    crate::modules::account::switch_account_internal(&account_id),
+                )
+                .await;
             });
         }
 
@@ -740,6 +948,27 @@ fn handle_delete_account_by_email(email: &str) -> Result<String, String> {
     }
 }
 
+/// 等待切换完成并把结果广播给所有客户端
+/// 成功发送 event.account_switched，失败发送 event.switch_error
+async fn broadcast_switch_result<F>(tx: &broadcast::Sender<String>, switch: F)
+where
+    F: std::future::Future<Output = Result<crate::models::Account, String>>,
+{
+    let msg = match switch.await {
+        Ok(account) => WsMessage::AccountSwitched {
+            account_id: account.id,
+            email: account.email,
+        },
+        Err(e) => {
+            crate::modules::logger::log_warn(&format!("[WS] 切换账号失败: {}", e));
+            WsMessage::SwitchError { message: e }
+        }
+    };
+    if let Ok(json) = serde_json::to_string(&msg) {
+        let _ = tx.send(json);
+    }
+}
+
 /// 处理语言设置请求
 fn handle_set_language(language: &str, source: Option<&str>) -> Result<String, String> {
     use crate::modules::config::{self, UserConfig};
@@ -787,11 +1016,195 @@ fn handle_set_language(language: &str, source: Option<&str>) -> Result<String, S
         windsurf_quota_alert_threshold: current.windsurf_quota_alert_threshold,
         kiro_quota_alert_enabled: current.kiro_quota_alert_enabled,
         kiro_quota_alert_threshold: current.kiro_quota_alert_threshold,
+        sync_on_startup_enabled: current.sync_on_startup_enabled,
+        auto_apply_fingerprint_on_switch: current.auto_apply_fingerprint_on_switch,
+        default_window_modes: current.default_window_modes,
+        kiro_oauth_ports: current.kiro_oauth_ports,
+        window_geometry: current.window_geometry,
+        vscode_graceful_wait_secs: current.vscode_graceful_wait_secs,
+        antigravity_graceful_wait_secs: current.antigravity_graceful_wait_secs,
+        quota_auto_refresh_minutes: current.quota_auto_refresh_minutes,
+        process_helper_exclude_keywords: current.process_helper_exclude_keywords,
+        wakeup_models_cache_minutes: current.wakeup_models_cache_minutes,
+        proxy: current.proxy,
     };
 
     config::save_user_config(&new_config)?;
+    crate::modules::sync_settings::record_local_modified("language");
 
     broadcast_language_changed(&normalized, source.unwrap_or("ws"));
 
     Ok(format!("语言已更新为 {}", normalized))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 服务状态是全局的，涉及服务启停的测试需串行执行
+    static SERVER_TEST_LOCK: LazyLock<tokio::sync::Mutex<()>> =
+        LazyLock::new(|| tokio::sync::Mutex::new(()));
+
+    fn free_port() -> u16 {
+        std::net::TcpListener::bind("127.0.0.1:0")
+            .and_then(|listener| listener.local_addr())
+            .map(|addr| addr.port())
+            .unwrap()
+    }
+
+    async fn wait_for_status<F>(predicate: F) -> WebSocketStatus
+    where
+        F: Fn(&WebSocketStatus) -> bool,
+    {
+        for _ in 0..50 {
+            let status = get_status();
+            if predicate(&status) {
+                return status;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        get_status()
+    }
+
+    #[tokio::test]
+    async fn test_status_tracks_bind_and_clients() {
+        let _lock = SERVER_TEST_LOCK.lock().await;
+        let (listener, port) = bind_listener_with_retry(free_port()).await.unwrap();
+        let (_shutdown_tx, shutdown_rx) = oneshot::channel();
+        tokio::spawn(serve(listener, port, shutdown_rx));
+
+        let status = wait_for_status(|status| status.running).await;
+        assert!(status.running);
+        assert_eq!(status.port, Some(port));
+        assert!(status.started_at.is_some());
+        assert!(status.last_error.is_none());
+
+        let (client, _) = tokio_tungstenite::connect_async(format!("ws://127.0.0.1:{}", port))
+            .await
+            .unwrap();
+        let status = wait_for_status(|status| status.connected_clients == 1).await;
+        assert_eq!(status.connected_clients, 1);
+
+        drop(client);
+        let status = wait_for_status(|status| status.connected_clients == 0).await;
+        assert_eq!(status.connected_clients, 0);
+    }
+
+    #[tokio::test]
+    async fn test_restart_releases_old_port() {
+        let _lock = SERVER_TEST_LOCK.lock().await;
+        let old_port = restart_on(free_port()).await.unwrap();
+        let status =
+            wait_for_status(|status| status.running && status.port == Some(old_port)).await;
+        assert_eq!(status.port, Some(old_port));
+
+        let mut new_port = free_port();
+        while new_port == old_port {
+            new_port = free_port();
+        }
+        let bound = restart_on(new_port).await.unwrap();
+        assert_ne!(bound, old_port);
+
+        // 旧端口已释放，可以重新绑定
+        assert!(std::net::TcpListener::bind(("127.0.0.1", old_port)).is_ok());
+        let status = wait_for_status(|status| status.running && status.port == Some(bound)).await;
+        assert_eq!(status.port, Some(bound));
+        let (client, _) = tokio_tungstenite::connect_async(format!("ws://127.0.0.1:{}", bound))
+            .await
+            .unwrap();
+        drop(client);
+
+        shutdown_server().await;
+        assert!(std::net::TcpListener::bind(("127.0.0.1", bound)).is_ok());
+    }
+
+    async fn next_message<S>(client: &mut S) -> serde_json::Value
+    where
+        S: StreamExt<Item = Result<Message, tokio_tungstenite::tungstenite::Error>> + Unpin,
+    {
+        loop {
+            let msg = tokio::time::timeout(Duration::from_secs(5), client.next())
+                .await
+                .expect("等待消息超时")
+                .expect("连接已关闭")
+                .unwrap();
+            if let Message::Text(text) = msg {
+                return serde_json::from_str(&text).unwrap();
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_switch_account_unknown_account_broadcasts_error() {
+        let _lock = SERVER_TEST_LOCK.lock().await;
+        let (listener, port) = bind_listener_with_retry(free_port()).await.unwrap();
+        let (_shutdown_tx, shutdown_rx) = oneshot::channel();
+        tokio::spawn(serve(listener, port, shutdown_rx));
+
+        let (mut client, _) = tokio_tungstenite::connect_async(format!("ws://127.0.0.1:{}", port))
+            .await
+            .unwrap();
+        assert_eq!(next_message(&mut client).await["type"], "event.ready");
+
+        let request = serde_json::json!({
+            "type": "request.switch_account",
+            "payload": { "account_id": "ws-test-missing-account" },
+        });
+        client
+            .send(Message::Text(request.to_string().into()))
+            .await
+            .unwrap();
+        assert_eq!(
+            next_message(&mut client).await["type"],
+            "event.switch_error"
+        );
+
+        // 出错后连接仍可继续使用
+        client
+            .send(Message::Text(r#"{"type":"ping"}"#.into()))
+            .await
+            .unwrap();
+        assert_eq!(next_message(&mut client).await["type"], "pong");
+    }
+
+    #[tokio::test]
+    async fn test_switch_success_broadcasts_account_switched() {
+        let (tx, mut rx) = broadcast::channel(4);
+        let account = crate::models::Account::new(
+            "ws-test-account".to_string(),
+            "ws@example.com".to_string(),
+            crate::models::TokenData::new(
+                "access".to_string(),
+                "refresh".to_string(),
+                3600,
+                None,
+                None,
+                None,
+            ),
+        );
+
+        broadcast_switch_result(&tx, async { Ok(account) }).await;
+
+        let msg: serde_json::Value = serde_json::from_str(&rx.recv().await.unwrap()).unwrap();
+        assert_eq!(msg["type"], "event.account_switched");
+        assert_eq!(msg["payload"]["account_id"], "ws-test-account");
+        assert_eq!(msg["payload"]["email"], "ws@example.com");
+    }
+
+    #[tokio::test]
+    async fn test_broadcast_event_reaches_subscribers() {
+        let _lock = SERVER_TEST_LOCK.lock().await;
+        // 没有订阅者时直接跳过
+        broadcast_event(WsMessage::WakeupOverride { enabled: true });
+
+        let mut rx = get_server().tx.subscribe();
+        broadcast_event(WsMessage::QuotaUpdated {
+            account_id: "ws-test-account".to_string(),
+            quota: QuotaData::new(),
+        });
+
+        let msg: serde_json::Value = serde_json::from_str(&rx.recv().await.unwrap()).unwrap();
+        assert_eq!(msg["type"], "event.quota_updated");
+        assert_eq!(msg["payload"]["account_id"], "ws-test-account");
+    }
+}
@@ -0,0 +1,206 @@
+//! 主窗口位置记忆
+//! 启动时恢复上次的位置与尺寸（保存的位置已不在任何显示器上时移回可见区域），
+//! 移动 / 缩放时延迟写入配置，关闭时立即写入
+
+use std::sync::Mutex;
+
+use tauri::{AppHandle, Manager, PhysicalPosition, PhysicalSize, Window};
+
+use crate::modules::config::{self, WindowGeometry};
+use crate::modules::logger;
+
+const MAIN_WINDOW_LABEL: &str = "main";
+
+/// 窗口至少有这么多像素落在显示器内才视为可见（物理像素）
+const MIN_VISIBLE_PX: i32 = 100;
+
+/// 最近一次非最大化时的位置与尺寸，最大化时沿用它作为还原尺寸
+static LAST_NORMAL_GEOMETRY: Mutex<Option<WindowGeometry>> = Mutex::new(None);
+
+/// 显示器区域（物理像素）
+#[derive(Debug, Clone, Copy)]
+pub struct MonitorRect {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// 标题栏附近的区域是否落在显示器内（保证用户能拖动窗口）
+fn is_visible_on(geometry: &WindowGeometry, monitor: &MonitorRect) -> bool {
+    let left = geometry.x.max(monitor.x);
+    let right = (geometry.x + geometry.width as i32).min(monitor.x + monitor.width as i32);
+    let top_in_range =
+        geometry.y >= monitor.y && geometry.y <= monitor.y + monitor.height as i32 - MIN_VISIBLE_PX;
+    right - left >= MIN_VISIBLE_PX && top_in_range
+}
+
+/// 保存的位置不在任何显示器上时，移到第一个显示器（主显示器）居中，并缩小到不超过显示器尺寸
+fn clamp_to_monitors(geometry: WindowGeometry, monitors: &[MonitorRect]) -> WindowGeometry {
+    if monitors.is_empty()
+        || monitors
+            .iter()
+            .any(|monitor| is_visible_on(&geometry, monitor))
+    {
+        return geometry;
+    }
+    let monitor = monitors[0];
+    let width = geometry.width.min(monitor.width);
+    let height = geometry.height.min(monitor.height);
+    WindowGeometry {
+        x: monitor.x + (monitor.width - width) as i32 / 2,
+        y: monitor.y + (monitor.height - height) as i32 / 2,
+        width,
+        height,
+        maximized: geometry.maximized,
+    }
+}
+
+fn collect_monitors(app: &AppHandle) -> Vec<MonitorRect> {
+    let primary = app.primary_monitor().ok().flatten();
+    let mut monitors: Vec<MonitorRect> = app
+        .available_monitors()
+        .unwrap_or_default()
+        .iter()
+        .chain(primary.iter())
+        .map(|monitor| MonitorRect {
+            x: monitor.position().x,
+            y: monitor.position().y,
+            width: monitor.size().width,
+            height: monitor.size().height,
+        })
+        .collect();
+    // 主显示器放在首位，作为移回可见区域时的目标
+    if primary.is_some() {
+        monitors.rotate_right(1);
+    }
+    monitors
+}
+
+/// 按序号获取显示器区域及缩放比例（序号与系统返回的显示器顺序一致，从 0 开始）
+pub fn monitor_at(app: &AppHandle, index: usize) -> Result<(MonitorRect, f64), String> {
+    let monitors = app
+        .available_monitors()
+        .map_err(|e| format!("获取显示器列表失败: {}", e))?;
+    let count = monitors.len();
+    let monitor = monitors
+        .get(index)
+        .ok_or_else(|| format!("显示器序号超出范围: {}（共 {} 个显示器）", index, count))?;
+    Ok((
+        MonitorRect {
+            x: monitor.position().x,
+            y: monitor.position().y,
+            width: monitor.size().width,
+            height: monitor.size().height,
+        },
+        monitor.scale_factor(),
+    ))
+}
+
+/// 启动时恢复主窗口的位置与尺寸
+pub fn restore_main_window(app: &AppHandle) {
+    let Some(window) = app.get_webview_window(MAIN_WINDOW_LABEL) else {
+        return;
+    };
+    let Some(saved) = config::get_window_geometry() else {
+        return;
+    };
+
+    let geometry = clamp_to_monitors(saved, &collect_monitors(app));
+    if geometry != saved {
+        logger::log_info(&format!(
+            "[Window] 保存的窗口位置不在可见区域，已移回显示器内: ({}, {}) -> ({}, {})",
+            saved.x, saved.y, geometry.x, geometry.y
+        ));
+    }
+    if let Ok(mut last) = LAST_NORMAL_GEOMETRY.lock() {
+        *last = Some(WindowGeometry {
+            maximized: false,
+            ..geometry
+        });
+    }
+
+    let _ = window.set_size(PhysicalSize::new(geometry.width, geometry.height));
+    let _ = window.set_position(PhysicalPosition::new(geometry.x, geometry.y));
+    if geometry.maximized {
+        let _ = window.maximize();
+    }
+}
+
+/// 主窗口移动 / 缩放后记录当前位置（延迟写入）
+pub fn track_main_window(window: &Window) {
+    if window.label() != MAIN_WINDOW_LABEL || window.is_minimized().unwrap_or(false) {
+        return;
+    }
+    let maximized = window.is_maximized().unwrap_or(false);
+    let geometry = if maximized {
+        let last = LAST_NORMAL_GEOMETRY.lock().ok().and_then(|last| *last);
+        match last.or_else(config::get_window_geometry) {
+            Some(last) => WindowGeometry {
+                maximized: true,
+                ..last
+            },
+            None => return,
+        }
+    } else {
+        let (Ok(position), Ok(size)) = (window.outer_position(), window.inner_size()) else {
+            return;
+        };
+        let geometry = WindowGeometry {
+            x: position.x,
+            y: position.y,
+            width: size.width,
+            height: size.height,
+            maximized: false,
+        };
+        if let Ok(mut last) = LAST_NORMAL_GEOMETRY.lock() {
+            *last = Some(geometry);
+        }
+        geometry
+    };
+    config::save_window_geometry(geometry);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn geometry(x: i32, y: i32) -> WindowGeometry {
+        WindowGeometry {
+            x,
+            y,
+            width: 1200,
+            height: 800,
+            maximized: false,
+        }
+    }
+
+    #[test]
+    fn test_clamp_keeps_visible_and_moves_offscreen() {
+        let monitors = [
+            MonitorRect {
+                x: 0,
+                y: 0,
+                width: 1920,
+                height: 1080,
+            },
+            MonitorRect {
+                x: 1920,
+                y: 0,
+                width: 1280,
+                height: 720,
+            },
+        ];
+
+        let on_second = geometry(2000, 100);
+        assert_eq!(clamp_to_monitors(on_second, &monitors), on_second);
+
+        // 副屏被拔掉后的位置
+        let clamped = clamp_to_monitors(geometry(4000, 100), &monitors);
+        assert_eq!((clamped.x, clamped.y), (360, 140));
+
+        // 标题栏在屏幕上方之外
+        let clamped = clamp_to_monitors(geometry(100, -500), &monitors);
+        assert!(clamped.y >= 0);
+    }
+}
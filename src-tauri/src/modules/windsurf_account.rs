@@ -1,4 +1,4 @@
-use rusqlite::{Connection, OptionalExtension};
+use rusqlite::OptionalExtension;
 use serde_json::Value;
 use std::collections::{HashMap, HashSet};
 use std::fs;
@@ -103,7 +103,7 @@ fn refresh_summary(index: &mut WindsurfAccountIndex, account: &WindsurfAccount)
     index.accounts.push(account.summary());
 }
 
-fn upsert_account_record(account: WindsurfAccount) -> Result<WindsurfAccount, String> {
+pub fn upsert_account_record(account: WindsurfAccount) -> Result<WindsurfAccount, String> {
     let _lock = WINDSURF_ACCOUNT_INDEX_LOCK
         .lock()
         .map_err(|_| "获取 Windsurf 账号锁失败".to_string())?;
@@ -956,8 +956,8 @@ pub fn read_local_auth_status() -> Result<Option<Value>, String> {
     if !db_path.exists() {
         return Ok(None);
     }
-    let conn =
-        Connection::open(&db_path).map_err(|e| format!("打开 Windsurf 本地数据库失败: {}", e))?;
+    let conn = crate::modules::db::open_readonly(&db_path)
+        .map_err(|e| format!("打开 Windsurf 本地数据库失败: {}", e))?;
     let value = conn
         .query_row(
             "SELECT value FROM ItemTable WHERE key = ?1",
@@ -982,7 +982,7 @@ pub fn read_local_login_hint() -> Option<String> {
     if !db_path.exists() {
         return None;
     }
-    let conn = Connection::open(&db_path).ok()?;
+    let conn = crate::modules::db::open_readonly(&db_path).ok()?;
     let key = conn
         .query_row(
             "SELECT key FROM ItemTable WHERE key LIKE 'windsurf_auth-%' LIMIT 1",
@@ -1094,7 +1094,7 @@ fn extract_premium_metric(account: &WindsurfAccount) -> Option<(String, i32)> {
     Some(("Premium Interactions".to_string(), percent_remaining))
 }
 
-fn extract_quota_metrics(account: &WindsurfAccount) -> Vec<(String, i32)> {
+pub fn extract_quota_metrics(account: &WindsurfAccount) -> Vec<(String, i32)> {
     let mut metrics = extract_limited_metrics(account);
     if let Some(premium) = extract_premium_metric(account) {
         metrics.push(premium);
@@ -1110,7 +1110,8 @@ fn average_quota_percentage(metrics: &[(String, i32)]) -> f64 {
     sum as f64 / metrics.len() as f64
 }
 
-fn resolve_current_account_id(accounts: &[WindsurfAccount]) -> Option<String> {
+/// 解析当前账号：优先默认实例绑定的账号，否则取最近使用的账号
+pub fn resolve_current_account_id(accounts: &[WindsurfAccount]) -> Option<String> {
     if let Ok(settings) = crate::modules::windsurf_instance::load_default_settings() {
         if let Some(bind_id) = settings.bind_account_id {
             let trimmed = bind_id.trim();
@@ -40,6 +40,7 @@ struct RegisterResult {
 
 lazy_static::lazy_static! {
     static ref PENDING_OAUTH_STATE: Arc<Mutex<Option<PendingOAuthState>>> = Arc::new(Mutex::new(None));
+    static ref CALLBACK_SERVER_TASK: Mutex<Option<tokio::task::JoinHandle<()>>> = Mutex::new(None);
 }
 
 fn now_timestamp() -> i64 {
@@ -488,7 +489,7 @@ async fn post_seat_management_json(
         "{}/exa.seat_management_pb.SeatManagementService/{}",
         base, method
     );
-    let client = reqwest::Client::new();
+    let client = crate::modules::http::default_client();
 
     let response = client
         .post(url.clone())
@@ -914,7 +915,7 @@ pub async fn start_login() -> Result<WindsurfOAuthStartResponse, String> {
 
     let callback_login_id = login_id.clone();
     let callback_state = state_token.clone();
-    tokio::spawn(async move {
+    let task = tokio::spawn(async move {
         if let Err(e) = start_callback_server(port, callback_login_id, callback_state).await {
             logger::log_error(&format!(
                 "[Windsurf OAuth] 回调服务异常: login_id={}, error={}",
@@ -922,6 +923,9 @@ pub async fn start_login() -> Result<WindsurfOAuthStartResponse, String> {
             ));
         }
     });
+    if let Ok(mut guard) = CALLBACK_SERVER_TASK.lock() {
+        *guard = Some(task);
+    }
 
     logger::log_info(&format!(
         "[Windsurf OAuth] 登录会话已创建: login_id={}, callback_url={}",
@@ -980,6 +984,48 @@ pub fn cancel_login(login_id: Option<&str>) -> Result<(), String> {
     Ok(())
 }
 
+fn callback_server_running() -> bool {
+    CALLBACK_SERVER_TASK
+        .lock()
+        .ok()
+        .and_then(|guard| guard.as_ref().map(|task| !task.is_finished()))
+        .unwrap_or(false)
+}
+
+/// 清理失效的登录会话（已过期，或仍在等待回调但回调服务已退出），返回是否有会话被清理
+pub fn clear_stale_login() -> bool {
+    let server_running = callback_server_running();
+    let stale_login_id = PENDING_OAUTH_STATE.lock().ok().and_then(|guard| {
+        guard
+            .as_ref()
+            .filter(|state| {
+                let waiting = state.access_token.is_none() && state.callback_error.is_none();
+                state.expires_at <= now_timestamp() || (waiting && !server_running)
+            })
+            .map(|state| state.login_id.clone())
+    });
+    let Some(login_id) = stale_login_id else {
+        return false;
+    };
+    if cancel_login(Some(&login_id)).is_err() {
+        return false;
+    }
+    logger::log_info(&format!(
+        "[Windsurf OAuth] 已清理失效的登录会话: login_id={}",
+        login_id
+    ));
+    true
+}
+
+/// 取消挂起的登录并取出回调服务任务（应用退出时调用，由调用方等待任务结束）
+pub fn shutdown_callback_server() -> Option<tokio::task::JoinHandle<()>> {
+    let _ = cancel_login(None);
+    CALLBACK_SERVER_TASK
+        .lock()
+        .ok()
+        .and_then(|mut guard| guard.take())
+}
+
 pub async fn build_payload_from_token(token: &str) -> Result<WindsurfOAuthCompletePayload, String> {
     let trimmed = token.trim();
     if trimmed.is_empty() {
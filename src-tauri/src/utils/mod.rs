@@ -1,2 +1 @@
-pub mod http;
 pub mod protobuf;
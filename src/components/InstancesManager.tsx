@@ -413,10 +413,13 @@ export function InstancesManager<TAccount extends AccountLike>({
 
   const handleMissingPathError = (error: unknown, instanceId?: string) => {
     const message = String(error ?? '');
-    if (!message.startsWith('APP_PATH_NOT_FOUND:')) {
+    const prefix = ['APP_PATH_NOT_FOUND:', 'APP_PATH_STALE:'].find((item) =>
+      message.startsWith(item),
+    );
+    if (!prefix) {
       return false;
     }
-    const rawApp = message.slice('APP_PATH_NOT_FOUND:'.length);
+    const rawApp = message.slice(prefix.length);
     const app =
       rawApp === 'codex' ||
       rawApp === 'antigravity' ||
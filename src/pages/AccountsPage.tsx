@@ -35,7 +35,7 @@ import {
 import { useTranslation, Trans } from 'react-i18next'
 import { useAccountStore } from '../stores/useAccountStore'
 import * as accountService from '../services/accountService'
-import { FingerprintWithStats, Account } from '../types/account'
+import { FingerprintWithStats, Account, getAccountDisplayName } from '../types/account'
 import { Page } from '../types/navigation'
 import {
   getQuotaClass,
@@ -749,9 +749,9 @@ export function AccountsPage({ onNavigate }: AccountsPageProps) {
     if (!showAddModal || addTab !== 'oauth' || oauthUrl) return
     accountService
       .prepareOAuthUrl()
-      .then((url) => {
-        if (typeof url === 'string' && url.length > 0) {
-          setOauthUrl(url)
+      .then(({ authUrl }) => {
+        if (typeof authUrl === 'string' && authUrl.length > 0) {
+          setOauthUrl(authUrl)
         }
       })
       .catch((e) => {
@@ -1403,7 +1403,7 @@ export function AccountsPage({ onNavigate }: AccountsPageProps) {
               />
             </div>
             <span className="account-email" title={maskAccountText(account.email)}>
-              {maskAccountText(account.email)}
+              {maskAccountText(getAccountDisplayName(account))}
             </span>
             {isCurrent && (
               <span className="current-tag">
@@ -1708,7 +1708,7 @@ export function AccountsPage({ onNavigate }: AccountsPageProps) {
                 </span>
               )}
               <span className={styles.emailText}>
-                {maskAccountText(account.email)}
+                {maskAccountText(getAccountDisplayName(account))}
               </span>
             </span>
             <div className={styles.quotas}>
@@ -1923,7 +1923,7 @@ export function AccountsPage({ onNavigate }: AccountsPageProps) {
             <div className="account-cell">
               <div className="account-main-line">
                 <span className="account-email-text" title={maskAccountText(account.email)}>
-                  {maskAccountText(account.email)}
+                  {maskAccountText(getAccountDisplayName(account))}
                 </span>
                 {isCurrent && (
                   <span className="mini-tag current">
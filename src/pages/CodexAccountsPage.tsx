@@ -29,6 +29,7 @@ import { useCodexAccountStore } from '../stores/useCodexAccountStore';
 import * as codexService from '../services/codexService';
 import { TagEditModal } from '../components/TagEditModal';
 import {
+  getCodexAccountDisplayName,
   getCodexPlanDisplayName,
   getCodexQuotaClass,
   getCodexQuotaWindows,
@@ -923,7 +924,7 @@ export function CodexAccountsPage() {
               />
             </div>
             <span className="account-email" title={maskAccountText(account.email)}>
-              {maskAccountText(account.email)}
+              {maskAccountText(getCodexAccountDisplayName(account))}
             </span>
             {isCurrent && <span className="current-tag">{t('codex.current', '当前')}</span>}
             {hasQuotaError && (
@@ -1053,7 +1054,7 @@ export function CodexAccountsPage() {
             <div className="account-cell">
               <div className="account-main-line">
                 <span className="account-email-text" title={maskAccountText(account.email)}>
-                  {maskAccountText(account.email)}
+                  {maskAccountText(getCodexAccountDisplayName(account))}
                 </span>
                 {isCurrent && <span className="mini-tag current">{t('codex.current', '当前')}</span>}
               </div>
@@ -22,6 +22,16 @@ interface NetworkConfig {
   ws_port: number;
   actual_port: number | null;
   default_port: number;
+  proxy: ProxyConfig;
+}
+
+/** 出站代理配置 */
+interface ProxyConfig {
+  mode: 'system' | 'direct' | 'custom';
+  url: string;
+  username: string;
+  password: string;
+  no_proxy: string;
 }
 
 /** 通用配置类型 */
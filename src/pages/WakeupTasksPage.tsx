@@ -691,8 +691,10 @@ export function WakeupTasksPage({ onNavigate }: WakeupPageProps) {
       setModelsLoading(true);
       const fallbackModels = filterAvailableModels(buildFallbackModels(accounts));
       try {
-        const models = await invoke<AvailableModel[]>('fetch_available_models');
-        const filtered = filterAvailableModels(models);
+        const result = await invoke<{ models: AvailableModel[]; fetchedAt: number; stale: boolean }>(
+          'fetch_available_models',
+        );
+        const filtered = filterAvailableModels(result.models);
         if (filtered.length > 0) {
           setAvailableModels(filtered);
         } else if (fallbackModels.length > 0) {
@@ -41,16 +41,24 @@ export async function refreshAllQuotas(): Promise<RefreshStats> {
     return await invoke('refresh_all_quotas');
 }
 
-export async function startOAuthLogin(): Promise<Account> {
-    return await invoke('start_oauth_login');
+export interface OAuthFlowStartResponse {
+    authUrl: string;
+    redirectUri: string;
+    port: number;
+    callbackPath: string;
+    callbackPaths: string[];
 }
 
-export async function prepareOAuthUrl(): Promise<string> {
-    return await invoke('prepare_oauth_url');
+export async function startOAuthLogin(callbackPaths?: string[]): Promise<Account> {
+    return await invoke('start_oauth_login', { callbackPaths });
 }
 
-export async function completeOAuthLogin(): Promise<Account> {
-    return await invoke('complete_oauth_login');
+export async function prepareOAuthUrl(callbackPaths?: string[]): Promise<OAuthFlowStartResponse> {
+    return await invoke('prepare_oauth_url', { callbackPaths });
+}
+
+export async function completeOAuthLogin(callbackPaths?: string[]): Promise<Account> {
+    return await invoke('complete_oauth_login', { callbackPaths });
 }
 
 export async function cancelOAuthLogin(): Promise<void> {
@@ -101,6 +109,19 @@ export async function updateAccountTags(accountId: string, tags: string[]): Prom
     return await invoke('update_account_tags', { accountId, tags });
 }
 
+/** 设置账号显示名称（空字符串清除） */
+export async function setAccountLabel(accountId: string, label: string): Promise<Account> {
+    return await invoke('set_account_label', { accountId, label });
+}
+
+export async function bulkUpdateAccountTags(
+    accountIds: string[],
+    addTags: string[],
+    removeTags: string[],
+): Promise<Account[]> {
+    return await invoke('bulk_update_account_tags', { accountIds, addTags, removeTags });
+}
+
 export async function syncFromExtension(): Promise<number> {
     return await invoke('sync_from_extension');
 }
@@ -117,6 +138,17 @@ export async function importFingerprintsFromJson(jsonContent: string): Promise<n
     return await invoke('import_fingerprints_from_json', { jsonContent });
 }
 
+export interface FingerprintZipImportReport {
+    imported: number;
+    duplicates: number;
+    skipped: number;
+}
+
+/** 从 zip 压缩包导入指纹 */
+export async function importFingerprintsFromZip(path: string): Promise<FingerprintZipImportReport> {
+    return await invoke('import_fingerprints_from_zip', { path });
+}
+
 export async function importFromLocal(): Promise<Account> {
     return await invoke('import_from_local');
 }
@@ -125,8 +157,8 @@ export async function importFromJson(jsonContent: string): Promise<Account[]> {
     return await invoke('import_from_json', { jsonContent });
 }
 
-export async function exportAccounts(accountIds: string[]): Promise<string> {
-    return await invoke('export_accounts', { accountIds });
+export async function exportAccounts(accountIds: string[], format?: 'json' | 'csv'): Promise<string> {
+    return await invoke('export_accounts', { accountIds, format });
 }
 
 export async function previewGenerateProfile(): Promise<DeviceProfile> {
@@ -6,6 +6,23 @@ export interface CodexOAuthLoginStartResponse {
   authUrl: string;
 }
 
+export interface CodexAccountPage {
+  accounts: CodexAccount[];
+  total: number;
+}
+
+export interface CodexQuotaRefreshSummary {
+  success: number;
+  failed: number;
+  /** 因限流被推迟、本轮未刷新的账号 */
+  deferred: { account_id: string; retry_after_secs: number }[];
+}
+
+/** 分页列出 Codex 账号（不传参数时返回全部） */
+export async function listCodexAccountsPage(offset?: number, limit?: number): Promise<CodexAccountPage> {
+  return await invoke('list_codex_accounts_page', { offset, limit });
+}
+
 /** 列出所有 Codex 账号 */
 export async function listCodexAccounts(): Promise<CodexAccount[]> {
   return await invoke('list_codex_accounts');
@@ -52,7 +69,7 @@ export async function refreshCodexQuota(accountId: string): Promise<CodexQuota>
 }
 
 /** 刷新所有账号配额 */
-export async function refreshAllCodexQuotas(): Promise<number> {
+export async function refreshAllCodexQuotas(): Promise<CodexQuotaRefreshSummary> {
   return await invoke('refresh_all_codex_quotas');
 }
 
@@ -97,3 +114,8 @@ export async function closeCodexOAuthPort(): Promise<number> {
 export async function updateCodexAccountTags(accountId: string, tags: string[]): Promise<CodexAccount> {
   return await invoke('update_codex_account_tags', { accountId, tags });
 }
+
+/** 设置账号显示名称（空字符串清除） */
+export async function setCodexAccountLabel(accountId: string, label: string): Promise<CodexAccount> {
+  return await invoke('set_codex_account_label', { accountId, label });
+}
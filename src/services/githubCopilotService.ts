@@ -69,6 +69,14 @@ export async function updateGitHubCopilotAccountTags(accountId: string, tags: st
   return await invoke('update_github_copilot_account_tags', { accountId, tags });
 }
 
+/** 设置账号显示名称（空字符串清除） */
+export async function setGitHubCopilotAccountLabel(
+  accountId: string,
+  label: string,
+): Promise<GitHubCopilotAccount> {
+  return await invoke('set_github_copilot_account_label', { accountId, label });
+}
+
 export async function getGitHubCopilotAccountsIndexPath(): Promise<string> {
   return await invoke('get_github_copilot_accounts_index_path');
 }
@@ -26,7 +26,8 @@ export type PlatformInstanceService = {
   createInstance: (payload: InstancePayload) => Promise<InstanceProfile>;
   updateInstance: (payload: UpdateInstancePayload) => Promise<InstanceProfile>;
   deleteInstance: (instanceId: string) => Promise<void>;
-  startInstance: (instanceId: string) => Promise<InstanceProfile>;
+  /** targetMonitor 仅 Antigravity 与 GitHub Copilot（VS Code）实例支持 */
+  startInstance: (instanceId: string, targetMonitor?: number) => Promise<InstanceProfile>;
   stopInstance: (instanceId: string) => Promise<InstanceProfile>;
   closeAllInstances: () => Promise<void>;
   openInstanceWindow: (instanceId: string) => Promise<void>;
@@ -81,8 +82,8 @@ export function createPlatformInstanceService(
       return await invoke(commandFor(prefix, 'delete_instance'), { instanceId });
     },
 
-    startInstance: async (instanceId) => {
-      return await invoke(commandFor(prefix, 'start_instance'), { instanceId });
+    startInstance: async (instanceId, targetMonitor) => {
+      return await invoke(commandFor(prefix, 'start_instance'), { instanceId, targetMonitor });
     },
 
     stopInstance: async (instanceId) => {
@@ -59,7 +59,7 @@ interface CodexAccountState {
   deleteAccount: (accountId: string) => Promise<void>;
   deleteAccounts: (accountIds: string[]) => Promise<void>;
   refreshQuota: (accountId: string) => Promise<CodexQuota>;
-  refreshAllQuotas: () => Promise<number>;
+  refreshAllQuotas: () => Promise<codexService.CodexQuotaRefreshSummary>;
   importFromLocal: () => Promise<CodexAccount>;
   importFromJson: (jsonContent: string) => Promise<CodexAccount[]>;
   updateAccountTags: (accountId: string, tags: string[]) => Promise<CodexAccount>;
@@ -118,9 +118,9 @@ export const useCodexAccountStore = create<CodexAccountState>((set, get) => ({
   },
   
   refreshAllQuotas: async () => {
-    const successCount = await codexService.refreshAllCodexQuotas();
+    const summary = await codexService.refreshAllCodexQuotas();
     await get().fetchAccounts();
-    return successCount;
+    return summary;
   },
   
   importFromLocal: async () => {
@@ -2,6 +2,8 @@ export interface Account {
     id: string;
     email: string;
     name?: string;
+    /** 自定义显示名称，为空时显示邮箱 */
+    label?: string;
     tags?: string[];
     token: TokenData;
     fingerprint_id?: string;
@@ -86,3 +88,8 @@ export interface FingerprintWithStats extends Fingerprint {
     is_current: boolean;
     bound_account_count: number;
 }
+
+/** 账号显示名称：优先自定义名称，否则为邮箱 */
+export function getAccountDisplayName(account: Pick<Account, 'email' | 'label'>): string {
+    return account.label?.trim() || account.email;
+}
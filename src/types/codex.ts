@@ -2,6 +2,8 @@
 export interface CodexAccount {
   id: string;
   email: string;
+  /** 自定义显示名称，为空时显示邮箱 */
+  label?: string;
   user_id?: string;
   plan_type?: string;
   account_id?: string;
@@ -48,6 +50,11 @@ export interface CodexQuota {
   raw_data?: unknown;
 }
 
+/** 账号显示名称：优先自定义名称，否则为邮箱 */
+export function getCodexAccountDisplayName(account: Pick<CodexAccount, 'email' | 'label'>): string {
+  return account.label?.trim() || account.email;
+}
+
 /** 获取订阅类型显示名称 */
 export function getCodexPlanDisplayName(planType?: string): string {
   if (!planType) return 'FREE';
@@ -5,6 +5,8 @@ export interface GitHubCopilotAccount {
   github_id: number;
   github_name?: string | null;
   github_email?: string | null;
+  /** 自定义显示名称，为空时显示邮箱或 GitHub 用户名 */
+  label?: string | null;
   tags?: string[] | null;
 
   // 注意：这里包含敏感信息。前端不应打印/上报。
@@ -21,6 +23,8 @@ export interface GitHubCopilotAccount {
   copilot_quota_reset_date?: string | null;
   copilot_limited_user_quotas?: unknown;
   copilot_limited_user_reset_date?: number | null;
+  /** GitHub 授权已失效，需要重新登录 */
+  reauth_required?: boolean;
 
   created_at: number;
   last_used: number;
@@ -89,7 +93,7 @@ export function getGitHubCopilotQuotaClass(percentage: number): GitHubCopilotQuo
 }
 
 export function getGitHubCopilotAccountDisplayEmail(account: GitHubCopilotAccount): string {
-  return account.github_email?.trim() || account.github_login;
+  return account.label?.trim() || account.github_email?.trim() || account.github_login;
 }
 
 type Translate = (key: string, options?: Record<string, unknown>) => string;